@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS torrust_torrent_file_types (
+    torrent_id INTEGER NOT NULL,
+    extension VARCHAR(16) NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    file_count INTEGER NOT NULL,
+    size BIGINT NOT NULL,
+    PRIMARY KEY(torrent_id, extension),
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE
+)
@@ -0,0 +1,8 @@
+-- BEP 52: BitTorrent v2 and hybrid torrents.
+ALTER TABLE torrust_torrents ADD COLUMN meta_version INTEGER DEFAULT NULL;
+ALTER TABLE torrust_torrents ADD COLUMN info_hash_v2 CHAR(64) DEFAULT NULL;
+ALTER TABLE torrust_torrents ADD COLUMN file_tree LONGTEXT DEFAULT NULL;
+ALTER TABLE torrust_torrents ADD COLUMN piece_layers LONGTEXT DEFAULT NULL;
+
+-- BEP 47: file attributes, for example, padding files in hybrid torrents.
+ALTER TABLE torrust_torrent_files ADD COLUMN attr VARCHAR(16) DEFAULT NULL;
@@ -0,0 +1,3 @@
+-- Torrents listed from a magnet link don't have their meta-info until someone
+-- uploads the matching torrent file.
+ALTER TABLE torrust_torrents ADD COLUMN magnet_only BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,15 @@
+-- Emails waiting to be delivered by the mailer worker. Delivered emails are
+-- deleted. Emails that keep failing are kept with the `failed` status.
+CREATE TABLE IF NOT EXISTS torrust_mail_outbox (
+    mail_id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    recipient VARCHAR(320) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    body_plain MEDIUMTEXT NOT NULL,
+    body_html MEDIUMTEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at BIGINT NOT NULL,
+    last_error TEXT DEFAULT NULL,
+    date_created DATETIME NOT NULL,
+    INDEX (status, next_attempt_at)
+);
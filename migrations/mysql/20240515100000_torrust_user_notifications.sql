@@ -0,0 +1,26 @@
+-- Email notifications users opted into. Users without a row don't get any
+-- notification.
+CREATE TABLE IF NOT EXISTS torrust_user_notification_preferences (
+    user_id INTEGER NOT NULL PRIMARY KEY,
+    new_torrents BOOLEAN NOT NULL DEFAULT FALSE,
+    torrent_moderation BOOLEAN NOT NULL DEFAULT FALSE,
+    weekly_digest BOOLEAN NOT NULL DEFAULT FALSE,
+    date_last_digest BIGINT DEFAULT NULL,
+    FOREIGN KEY(user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS torrust_user_followed_categories (
+    user_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES torrust_categories(category_id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, category_id)
+);
+
+CREATE TABLE IF NOT EXISTS torrust_user_followed_tags (
+    user_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES torrust_torrent_tags(tag_id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, tag_id)
+);
@@ -0,0 +1,28 @@
+-- Webhooks configured by the admins. `events` is a comma-separated list of
+-- the names of the events the webhook is subscribed to.
+CREATE TABLE IF NOT EXISTS torrust_webhooks (
+    webhook_id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    events VARCHAR(255) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    date_created DATETIME NOT NULL
+);
+
+-- Events sent to the webhooks. Deliveries are kept after they succeed or fail
+-- so they can be inspected and replayed.
+CREATE TABLE IF NOT EXISTS torrust_webhook_deliveries (
+    delivery_id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    webhook_id INTEGER NOT NULL,
+    event VARCHAR(64) NOT NULL,
+    payload MEDIUMTEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at BIGINT NOT NULL,
+    response_status INTEGER DEFAULT NULL,
+    last_error TEXT DEFAULT NULL,
+    date_created DATETIME NOT NULL,
+    date_delivered DATETIME DEFAULT NULL,
+    INDEX (status, next_attempt_at),
+    FOREIGN KEY(webhook_id) REFERENCES torrust_webhooks(webhook_id) ON DELETE CASCADE
+);
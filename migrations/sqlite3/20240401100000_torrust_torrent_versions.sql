@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS torrust_torrent_versions (
+    version_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    torrent_id INTEGER NOT NULL,
+    version INTEGER NOT NULL,
+    info_hash TEXT NOT NULL,
+    name TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    -- NULL for magnet-only listings, which have no meta-info.
+    torrent_file BLOB DEFAULT NULL,
+    replaced_by INTEGER,
+    date_replaced TEXT NOT NULL,
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE,
+    FOREIGN KEY(replaced_by) REFERENCES torrust_users(user_id) ON DELETE SET NULL,
+    UNIQUE(torrent_id, version)
+)
@@ -0,0 +1,11 @@
+-- Categories can serve the original uploaded torrent files instead of the
+-- canonical ones rebuilt from the database.
+ALTER TABLE torrust_categories ADD COLUMN torrent_file_format TEXT NOT NULL DEFAULT 'canonical';
+
+CREATE TABLE IF NOT EXISTS torrust_torrent_original_files (
+    torrent_id INTEGER NOT NULL PRIMARY KEY,
+    info_hash TEXT NOT NULL,
+    backend TEXT NOT NULL,
+    bytes BLOB DEFAULT NULL,
+    FOREIGN KEY(torrent_id) REFERENCES torrust_torrents(torrent_id) ON DELETE CASCADE
+);
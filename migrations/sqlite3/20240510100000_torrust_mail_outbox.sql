@@ -0,0 +1,16 @@
+-- Emails waiting to be delivered by the mailer worker. Delivered emails are
+-- deleted. Emails that keep failing are kept with the `failed` status.
+CREATE TABLE IF NOT EXISTS torrust_mail_outbox (
+    mail_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body_plain TEXT NOT NULL,
+    body_html TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT DEFAULT NULL,
+    date_created TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS torrust_mail_outbox_status_next_attempt_at ON torrust_mail_outbox (status, next_attempt_at);
@@ -0,0 +1,30 @@
+-- Webhooks configured by the admins. `events` is a comma-separated list of
+-- the names of the events the webhook is subscribed to.
+CREATE TABLE IF NOT EXISTS torrust_webhooks (
+    webhook_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    date_created TEXT NOT NULL
+);
+
+-- Events sent to the webhooks. Deliveries are kept after they succeed or fail
+-- so they can be inspected and replayed.
+CREATE TABLE IF NOT EXISTS torrust_webhook_deliveries (
+    delivery_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    webhook_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    response_status INTEGER DEFAULT NULL,
+    last_error TEXT DEFAULT NULL,
+    date_created TEXT NOT NULL,
+    date_delivered TEXT DEFAULT NULL,
+    FOREIGN KEY(webhook_id) REFERENCES torrust_webhooks(webhook_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS torrust_webhook_deliveries_status_next_attempt_at ON torrust_webhook_deliveries (status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS torrust_webhook_deliveries_webhook_id ON torrust_webhook_deliveries (webhook_id);
@@ -0,0 +1,9 @@
+-- Email addresses users asked to change to. The profile email is only
+-- updated when the user confirms the new address with the link sent to it.
+-- There is at most one pending change per user, the last one requested.
+CREATE TABLE IF NOT EXISTS torrust_user_pending_emails (
+    user_id INTEGER NOT NULL PRIMARY KEY,
+    email TEXT NOT NULL,
+    date_requested INTEGER NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES torrust_users(user_id) ON DELETE CASCADE
+);
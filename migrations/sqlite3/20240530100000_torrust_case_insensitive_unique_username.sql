@@ -0,0 +1,70 @@
+-- Usernames are unique regardless of their case.
+
+-- Step 1: Find a new name for the users whose username only differs in case
+-- from an older one.
+--
+-- The new name is the old one followed by `_<user_id>`, or by
+-- `_<user_id>_<n>` if that one is already taken, truncated so it still fits
+-- in the 20 characters allowed for usernames. The suffix always ends with
+-- the user ID (and the attempt number), so two renamed users never get the
+-- same name.
+CREATE TEMP TABLE renamed_usernames AS
+WITH RECURSIVE attempts(n) AS (
+    SELECT 0
+    UNION ALL
+    SELECT n + 1 FROM attempts WHERE n < 99
+),
+duplicates AS (
+    SELECT user_id, username
+    FROM torrust_user_profiles
+    WHERE EXISTS (
+        SELECT 1
+        FROM torrust_user_profiles older
+        WHERE LOWER(older.username) = LOWER(torrust_user_profiles.username)
+        AND older.user_id < torrust_user_profiles.user_id
+    )
+),
+suffixes AS (
+    SELECT duplicates.user_id, duplicates.username, attempts.n,
+        '_' || duplicates.user_id || CASE WHEN attempts.n = 0 THEN '' ELSE '_' || attempts.n END AS suffix
+    FROM duplicates
+    CROSS JOIN attempts
+),
+candidates AS (
+    SELECT user_id, n, SUBSTR(username, 1, 20 - LENGTH(suffix)) || suffix AS username
+    FROM suffixes
+),
+available AS (
+    SELECT user_id, n, username
+    FROM candidates
+    WHERE NOT EXISTS (
+        SELECT 1
+        FROM torrust_user_profiles taken
+        WHERE LOWER(taken.username) = LOWER(candidates.username)
+    )
+)
+SELECT user_id, username
+FROM available
+WHERE n = (SELECT MIN(earliest.n) FROM available earliest WHERE earliest.user_id = available.user_id);
+
+-- Step 2: Rename them
+UPDATE torrust_user_profiles
+SET username = (
+    SELECT renamed_usernames.username
+    FROM renamed_usernames
+    WHERE renamed_usernames.user_id = torrust_user_profiles.user_id
+)
+WHERE user_id IN (SELECT user_id FROM renamed_usernames);
+
+DROP TABLE renamed_usernames;
+
+-- Step 3: Create a UNIQUE index on the lowercased username.
+--
+-- It only fails if a user could not be renamed in the previous steps. The
+-- conflicting users have to be renamed by hand before upgrading. This query
+-- lists them:
+--
+-- SELECT user_id, username FROM torrust_user_profiles WHERE LOWER(username) IN (
+--     SELECT LOWER(username) FROM torrust_user_profiles GROUP BY LOWER(username) HAVING COUNT(*) > 1
+-- ) ORDER BY LOWER(username), user_id;
+CREATE UNIQUE INDEX IF NOT EXISTS torrust_user_profiles_lower_username ON torrust_user_profiles (LOWER(username));
@@ -13,13 +13,18 @@ use crate::databases::database;
 use crate::services::authentication::{DbUserAuthenticationRepository, JsonWebToken, Service};
 use crate::services::authorization::{CasbinConfiguration, CasbinEnforcer};
 use crate::services::category::{self, DbCategoryRepository};
+use crate::services::event_stream::{self, Broadcaster};
+use crate::services::notification::{self, DbNotificationRepository};
 use crate::services::tag::{self, DbTagRepository};
 use crate::services::torrent::{
     DbCanonicalInfoHashGroupRepository, DbTorrentAnnounceUrlRepository, DbTorrentFileRepository, DbTorrentInfoRepository,
-    DbTorrentListingGenerator, DbTorrentRepository, DbTorrentTagRepository,
+    DbTorrentListingGenerator, DbTorrentOriginalFileRepository, DbTorrentRepository, DbTorrentTagRepository,
+    DbTorrentWebSeedRepository,
 };
 use crate::services::user::{self, DbBannedUserList, DbUserProfileRepository, DbUserRepository, Repository};
-use crate::services::{about, authorization, proxy, settings, torrent};
+use crate::services::username_policy::UsernamePolicy;
+use crate::services::webhook::{self, DbWebhookRepository};
+use crate::services::{about, authorization, mail_outbox, proxy, settings, torrent};
 use crate::tracker::statistics_importer::StatisticsImporter;
 use crate::web::api::server::signals::Halted;
 use crate::web::api::server::v1::auth::Authentication;
@@ -31,13 +36,18 @@ pub struct Running {
     pub api_server: JoinHandle<std::result::Result<(), std::io::Error>>,
     pub api_server_halt_task: tokio::sync::oneshot::Sender<Halted>,
     pub tracker_data_importer_handle: tokio::task::JoinHandle<()>,
+    pub tracker_key_cleaner_handle: tokio::task::JoinHandle<()>,
+    pub mail_sender_handle: tokio::task::JoinHandle<()>,
+    pub weekly_digest_handle: tokio::task::JoinHandle<()>,
+    pub webhook_sender_handle: tokio::task::JoinHandle<()>,
 }
 
 /// Runs the application.
 ///
 /// # Panics
 ///
-/// It panics if there is an error connecting to the database.
+/// It panics if there is an error connecting to the database or reading the
+/// profanity words for the username policy.
 #[allow(clippy::too_many_lines)]
 pub async fn run(configuration: Configuration, api_version: &Version) -> Running {
     let threshold = configuration.settings.read().await.logging.threshold.clone();
@@ -65,6 +75,15 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
     let opt_net_tsl = settings.net.tsl.clone();
     // Unstable config
     let unstable = settings.unstable.clone();
+    // From [auth] config
+    let username_policy = Arc::new(
+        UsernamePolicy::new(&settings.auth.username_constraints).unwrap_or_else(|err| {
+            panic!(
+                "Failed to read the profanity words in {:?}: {err}",
+                settings.auth.username_constraints.profanity_words_path
+            )
+        }),
+    );
 
     // IMPORTANT: drop settings before starting server to avoid read locks that
     // leads to requests hanging.
@@ -87,9 +106,14 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
     let torrent_info_repository = Arc::new(DbTorrentInfoRepository::new(database.clone()));
     let torrent_file_repository = Arc::new(DbTorrentFileRepository::new(database.clone()));
     let torrent_announce_url_repository = Arc::new(DbTorrentAnnounceUrlRepository::new(database.clone()));
+    let torrent_web_seed_repository = Arc::new(DbTorrentWebSeedRepository::new(database.clone()));
+    let torrent_original_file_repository =
+        Arc::new(DbTorrentOriginalFileRepository::new(configuration.clone(), database.clone()));
     let torrent_tag_repository = Arc::new(DbTorrentTagRepository::new(database.clone()));
     let torrent_listing_generator = Arc::new(DbTorrentListingGenerator::new(database.clone()));
     let banned_user_list = Arc::new(DbBannedUserList::new(database.clone()));
+    let notification_repository = Arc::new(DbNotificationRepository::new(database.clone()));
+    let webhook_repository = Arc::new(DbWebhookRepository::new(database.clone()));
     let casbin_enforcer = Arc::new(
         if let Some(casbin) = unstable
             .as_ref()
@@ -105,15 +129,34 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
     // Services
     let authorization_service = Arc::new(authorization::Service::new(user_repository.clone(), casbin_enforcer.clone()));
     let tracker_service = Arc::new(tracker::service::Service::new(configuration.clone(), database.clone()).await);
-    let tracker_statistics_importer =
-        Arc::new(StatisticsImporter::new(configuration.clone(), tracker_service.clone(), database.clone()).await);
-    let mailer_service = Arc::new(mailer::Service::new(configuration.clone()).await);
+    let event_broadcaster = Arc::new(Broadcaster::new());
+    let tracker_statistics_importer = Arc::new(
+        StatisticsImporter::new(
+            configuration.clone(),
+            tracker_service.clone(),
+            database.clone(),
+            event_broadcaster.clone(),
+        )
+        .await,
+    );
+    let mailer_service = Arc::new(mailer::Service::new(configuration.clone(), database.clone()).await);
+    let notification_dispatcher = Arc::new(notification::Dispatcher::new(
+        configuration.clone(),
+        mailer_service.clone(),
+        notification_repository.clone(),
+    ));
+    let webhook_publisher = Arc::new(webhook::Publisher::new(webhook_repository.clone()));
     let image_cache_service: Arc<ImageCacheService> = Arc::new(ImageCacheService::new(configuration.clone()).await);
     let category_service = Arc::new(category::Service::new(
         category_repository.clone(),
         authorization_service.clone(),
     ));
-    let tag_service = Arc::new(tag::Service::new(tag_repository.clone(), authorization_service.clone()));
+    let tag_service = Arc::new(tag::Service::new(
+        tag_repository.clone(),
+        notification_repository.clone(),
+        notification_dispatcher.clone(),
+        authorization_service.clone(),
+    ));
     let proxy_service = Arc::new(proxy::Service::new(
         image_cache_service.clone(),
         authorization_service.clone(),
@@ -130,25 +173,43 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
         torrent_info_repository.clone(),
         torrent_file_repository.clone(),
         torrent_announce_url_repository.clone(),
+        torrent_web_seed_repository,
+        torrent_original_file_repository,
         torrent_tag_repository.clone(),
         torrent_listing_generator.clone(),
         authorization_service.clone(),
+        notification_dispatcher.clone(),
+        webhook_publisher.clone(),
+        event_broadcaster.clone(),
     ));
     let registration_service = Arc::new(user::RegistrationService::new(
         configuration.clone(),
         mailer_service.clone(),
         user_repository.clone(),
         user_profile_repository.clone(),
+        webhook_publisher.clone(),
+        username_policy.clone(),
     ));
     let profile_service = Arc::new(user::ProfileService::new(
         configuration.clone(),
+        mailer_service.clone(),
         user_authentication_repository.clone(),
+        user_repository.clone(),
+        user_profile_repository.clone(),
         authorization_service.clone(),
+        username_policy,
     ));
     let ban_service = Arc::new(user::BanService::new(
+        user_repository.clone(),
         user_profile_repository.clone(),
         banned_user_list.clone(),
         authorization_service.clone(),
+        webhook_publisher.clone(),
+    ));
+    let tracker_key_service = Arc::new(user::TrackerKeyService::new(
+        user_profile_repository.clone(),
+        tracker_service.clone(),
+        authorization_service.clone(),
     ));
     let authentication_service = Arc::new(Service::new(
         configuration.clone(),
@@ -158,6 +219,26 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
         user_authentication_repository.clone(),
     ));
 
+    let notification_service = Arc::new(notification::Service::new(
+        notification_repository,
+        authorization_service.clone(),
+    ));
+    let webhook_service = Arc::new(webhook::Service::new(
+        webhook_repository,
+        webhook_publisher.clone(),
+        authorization_service.clone(),
+    ));
+    let mail_outbox_service = Arc::new(mail_outbox::Service::new(
+        database.clone(),
+        mailer_service.clone(),
+        authorization_service.clone(),
+    ));
+    let event_stream_service = Arc::new(event_stream::Service::new(
+        event_broadcaster,
+        category_repository.clone(),
+        tag_repository.clone(),
+        authorization_service.clone(),
+    ));
     let about_service = Arc::new(about::Service::new(authorization_service.clone()));
 
     // Build app container
@@ -170,7 +251,7 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
         authentication_service,
         tracker_service.clone(),
         tracker_statistics_importer.clone(),
-        mailer_service,
+        mailer_service.clone(),
         image_cache_service,
         category_repository,
         tag_repository,
@@ -193,6 +274,11 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
         registration_service,
         profile_service,
         ban_service,
+        tracker_key_service,
+        notification_service,
+        webhook_service,
+        mail_outbox_service,
+        event_stream_service,
         about_service,
     ));
 
@@ -204,6 +290,18 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
         &tracker_statistics_importer,
     );
 
+    // Start cronjob to delete expired tracker keys.
+    let tracker_key_cleaner_handle = console::cronjobs::tracker_key_cleaner::start(&tracker_service);
+
+    // Start cronjob to deliver the emails in the outbox.
+    let mail_sender_handle = console::cronjobs::mail_sender::start(&mailer_service);
+
+    // Start cronjob to send the weekly digests.
+    let weekly_digest_handle = console::cronjobs::weekly_digest::start(&notification_dispatcher);
+
+    // Start cronjob to send the webhook deliveries.
+    let webhook_sender_handle = console::cronjobs::webhook_sender::start(&webhook_publisher);
+
     // Start API server
     let running_api = web::api::start(app_data, config_bind_address, opt_net_tsl, api_version).await;
 
@@ -213,6 +311,10 @@ pub async fn run(configuration: Configuration, api_version: &Version) -> Running
         api_server: running_api.task,
         api_server_halt_task: running_api.halt_task,
         tracker_data_importer_handle: tracker_statistics_importer_handle,
+        tracker_key_cleaner_handle,
+        mail_sender_handle,
+        weekly_digest_handle,
+        webhook_sender_handle,
     }
 }
 
@@ -0,0 +1,9 @@
+//! Program to classify the files of the torrents already in the Index.
+//!
+//! You can execute it with: `cargo run --bin classify_torrents -- --help`
+use torrust_index::console::commands::torrent_classifier::app;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    app::run().await
+}
@@ -40,16 +40,19 @@ fn main() {
                 path: vec![file_name.clone()], // Adjusted to include the actual file name
                 length: i64::try_from(file_contents.len()).expect("file contents size in bytes cannot exceed i64::MAX"),
                 md5sum: None, // DevSkim: ignore DS126858
+                attr: None,
             }],
         ),
         announce: Some("https://tracker.torrust-demo.com/announce".to_string()),
         nodes: Some(vec![("99.236.6.144".to_string(), 6881), ("91.109.195.156".to_string(), 1996)]),
         encoding: None,
         httpseeds: Some(vec!["https://seeder.torrust-demo.com/seed".to_string()]),
+        url_list: None,
         announce_list: Some(vec![vec!["https://tracker.torrust-demo.com/announce".to_string()]]),
         creation_date: None,
         comment: None,
         created_by: None,
+        piece_layers: None,
     };
 
     match parse_torrent::encode_torrent(&torrent) {
@@ -0,0 +1,9 @@
+//! Program to export the Index into a portable backup archive.
+//!
+//! You can execute it with: `cargo run --bin export_index -- --help`
+use torrust_index::console::commands::backup::export;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    export::run().await
+}
@@ -0,0 +1,9 @@
+//! Program to import the data of other torrent index applications.
+//!
+//! You can execute it with: `cargo run --bin import -- --help`
+use torrust_index::console::commands::external_importer::app;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    app::run().await
+}
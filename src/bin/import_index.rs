@@ -0,0 +1,9 @@
+//! Program to restore a backup archive generated with `export_index`.
+//!
+//! You can execute it with: `cargo run --bin import_index -- --help`
+use torrust_index::console::commands::backup::import;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    import::run().await
+}
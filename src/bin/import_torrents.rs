@@ -0,0 +1,9 @@
+//! Program to import a directory of torrent files into the Index.
+//!
+//! You can execute it with: `cargo run --bin import_torrents -- --help`
+use torrust_index::console::commands::torrent_importer::app;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    app::run().await
+}
@@ -11,7 +11,7 @@ use crate::services::torrent::{
     DbTorrentListingGenerator, DbTorrentRepository, DbTorrentTagRepository,
 };
 use crate::services::user::{self, DbBannedUserList, DbUserProfileRepository, Repository};
-use crate::services::{about, proxy, settings, torrent};
+use crate::services::{about, event_stream, mail_outbox, notification, proxy, settings, torrent, webhook};
 use crate::tracker::statistics_importer::StatisticsImporter;
 use crate::web::api::server::v1::auth::Authentication;
 use crate::{mailer, tracker};
@@ -51,6 +51,11 @@ pub struct AppData {
     pub registration_service: Arc<user::RegistrationService>,
     pub profile_service: Arc<user::ProfileService>,
     pub ban_service: Arc<user::BanService>,
+    pub tracker_key_service: Arc<user::TrackerKeyService>,
+    pub notification_service: Arc<notification::Service>,
+    pub webhook_service: Arc<webhook::Service>,
+    pub mail_outbox_service: Arc<mail_outbox::Service>,
+    pub event_stream_service: Arc<event_stream::Service>,
     pub about_service: Arc<about::Service>,
 }
 
@@ -89,6 +94,11 @@ impl AppData {
         registration_service: Arc<user::RegistrationService>,
         profile_service: Arc<user::ProfileService>,
         ban_service: Arc<user::BanService>,
+        tracker_key_service: Arc<user::TrackerKeyService>,
+        notification_service: Arc<notification::Service>,
+        webhook_service: Arc<webhook::Service>,
+        mail_outbox_service: Arc<mail_outbox::Service>,
+        event_stream_service: Arc<event_stream::Service>,
         about_service: Arc<about::Service>,
     ) -> AppData {
         AppData {
@@ -124,6 +134,11 @@ impl AppData {
             registration_service,
             profile_service,
             ban_service,
+            tracker_key_service,
+            notification_service,
+            webhook_service,
+            mail_outbox_service,
+            event_stream_service,
             about_service,
         }
     }
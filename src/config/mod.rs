@@ -27,12 +27,17 @@ pub type Email = v2::registration::Email;
 pub type Auth = v2::auth::Auth;
 pub type SecretKey = v2::auth::ClaimTokenPepper;
 pub type PasswordConstraints = v2::auth::PasswordConstraints;
+pub type UsernameConstraints = v2::auth::UsernameConstraints;
+pub type Argon2Params = v2::auth::Argon2Params;
 
 pub type Database = v2::database::Database;
 
 pub type ImageCache = v2::image_cache::ImageCache;
 
 pub type Mail = v2::mail::Mail;
+pub type MailTransport = v2::mail::Transport;
+pub type FileTransport = v2::mail::File;
+pub type Sendmail = v2::mail::Sendmail;
 pub type Smtp = v2::mail::Smtp;
 pub type Credentials = v2::mail::Credentials;
 
@@ -40,6 +45,13 @@ pub type Network = v2::net::Network;
 
 pub type TrackerStatisticsImporter = v2::tracker_statistics_importer::TrackerStatisticsImporter;
 
+pub type TorrentStorage = v2::torrent_storage::TorrentStorage;
+pub type TorrentStorageBackend = v2::torrent_storage::Backend;
+
+pub type UploadPolicy = v2::upload_policy::UploadPolicy;
+pub type PrivateFlag = v2::upload_policy::PrivateFlag;
+pub type AllowedCategories = v2::upload_policy::AllowedCategories;
+
 pub type Tracker = v2::tracker::Tracker;
 pub type ApiToken = v2::tracker::ApiToken;
 
@@ -458,10 +470,7 @@ mod tests {
 
     #[tokio::test]
     async fn configuration_could_be_loaded_from_a_toml_string() {
-        figment::Jail::expect_with(|jail| {
-            jail.create_dir("templates")?;
-            jail.create_file("templates/verify.html", "EMAIL TEMPLATE")?;
-
+        figment::Jail::expect_with(|_jail| {
             let info = Info {
                 config_toml: Some(default_config_toml()),
                 config_toml_path: String::new(),
@@ -542,9 +551,6 @@ mod tests {
     #[tokio::test]
     async fn configuration_should_allow_to_override_the_tracker_api_token_provided_in_the_toml_file() {
         figment::Jail::expect_with(|jail| {
-            jail.create_dir("templates")?;
-            jail.create_file("templates/verify.html", "EMAIL TEMPLATE")?;
-
             jail.set_env("TORRUST_INDEX_CONFIG_OVERRIDE_TRACKER__TOKEN", "OVERRIDDEN API TOKEN");
 
             let info = Info {
@@ -563,9 +569,6 @@ mod tests {
     #[tokio::test]
     async fn configuration_should_allow_to_override_the_authentication_user_claim_token_pepper_provided_in_the_toml_file() {
         figment::Jail::expect_with(|jail| {
-            jail.create_dir("templates")?;
-            jail.create_file("templates/verify.html", "EMAIL TEMPLATE")?;
-
             jail.set_env(
                 "TORRUST_INDEX_CONFIG_OVERRIDE_AUTH__USER_CLAIM_TOKEN_PEPPER",
                 "OVERRIDDEN AUTH SECRET KEY",
@@ -10,6 +10,10 @@ pub struct Api {
     /// The maximum page size for torrent lists.
     #[serde(default = "Api::default_max_torrent_page_size")]
     pub max_torrent_page_size: u8,
+
+    /// The maximum number of torrents in a batch download.
+    #[serde(default = "Api::default_max_torrents_per_batch_download")]
+    pub max_torrents_per_batch_download: u8,
 }
 
 impl Default for Api {
@@ -17,6 +21,7 @@ impl Default for Api {
         Self {
             default_torrent_page_size: Api::default_default_torrent_page_size(),
             max_torrent_page_size: Api::default_max_torrent_page_size(),
+            max_torrents_per_batch_download: Api::default_max_torrents_per_batch_download(),
         }
     }
 }
@@ -29,4 +34,8 @@ impl Api {
     fn default_max_torrent_page_size() -> u8 {
         30
     }
+
+    fn default_max_torrents_per_batch_download() -> u8 {
+        50
+    }
 }
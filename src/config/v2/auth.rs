@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
@@ -12,13 +13,24 @@ pub struct Auth {
     /// The password constraints
     #[serde(default = "Auth::default_password_constraints")]
     pub password_constraints: PasswordConstraints,
+
+    /// The username constraints
+    #[serde(default = "Auth::default_username_constraints")]
+    pub username_constraints: UsernameConstraints,
+
+    /// The cost parameters of the `Argon2id` password hashes. Passwords
+    /// hashed with other parameters are rehashed when the user logs in.
+    #[serde(default = "Auth::default_argon2")]
+    pub argon2: Argon2Params,
 }
 
 impl Default for Auth {
     fn default() -> Self {
         Self {
             password_constraints: Self::default_password_constraints(),
+            username_constraints: Self::default_username_constraints(),
             user_claim_token_pepper: Self::default_user_claim_token_pepper(),
+            argon2: Self::default_argon2(),
         }
     }
 }
@@ -35,6 +47,14 @@ impl Auth {
     fn default_password_constraints() -> PasswordConstraints {
         PasswordConstraints::default()
     }
+
+    fn default_username_constraints() -> UsernameConstraints {
+        UsernameConstraints::default()
+    }
+
+    fn default_argon2() -> Argon2Params {
+        Argon2Params::default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -63,6 +83,8 @@ impl fmt::Display for ClaimTokenPepper {
     }
 }
 
+/// The password policy checked when users register or change their password.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PasswordConstraints {
     /// The maximum password length.
@@ -71,6 +93,32 @@ pub struct PasswordConstraints {
     /// The minimum password length.
     #[serde(default = "PasswordConstraints::default_min_password_length")]
     pub min_password_length: usize,
+    /// The password must contain a lowercase letter.
+    #[serde(default = "PasswordConstraints::default_require_lowercase")]
+    pub require_lowercase: bool,
+    /// The password must contain an uppercase letter.
+    #[serde(default = "PasswordConstraints::default_require_uppercase")]
+    pub require_uppercase: bool,
+    /// The password must contain a digit.
+    #[serde(default = "PasswordConstraints::default_require_digit")]
+    pub require_digit: bool,
+    /// The password must contain a character that is not a letter or a
+    /// digit.
+    #[serde(default = "PasswordConstraints::default_require_special_character")]
+    pub require_special_character: bool,
+    /// The minimum strength score, from 0 (too guessable) to 4 (very
+    /// unguessable). 0 disables the check.
+    #[serde(default = "PasswordConstraints::default_min_strength_score")]
+    pub min_strength_score: u8,
+    /// Reject passwords containing the username or the email.
+    #[serde(default = "PasswordConstraints::default_reject_username_or_email")]
+    pub reject_username_or_email: bool,
+    /// The Pwned Passwords SHA-1 hashes used to reject breached passwords.
+    /// Either a directory with one range file per hash prefix, like
+    /// `21BD1.txt` with `<HASH SUFFIX>:<COUNT>` lines, or a single file with
+    /// `<HASH>:<COUNT>` lines. The check is disabled if it's not set.
+    #[serde(default = "PasswordConstraints::default_breached_passwords_path")]
+    pub breached_passwords_path: Option<PathBuf>,
 }
 
 impl Default for PasswordConstraints {
@@ -78,6 +126,13 @@ impl Default for PasswordConstraints {
         Self {
             max_password_length: Self::default_max_password_length(),
             min_password_length: Self::default_min_password_length(),
+            require_lowercase: Self::default_require_lowercase(),
+            require_uppercase: Self::default_require_uppercase(),
+            require_digit: Self::default_require_digit(),
+            require_special_character: Self::default_require_special_character(),
+            min_strength_score: Self::default_min_strength_score(),
+            reject_username_or_email: Self::default_reject_username_or_email(),
+            breached_passwords_path: Self::default_breached_passwords_path(),
         }
     }
 }
@@ -90,6 +145,143 @@ impl PasswordConstraints {
     fn default_max_password_length() -> usize {
         64
     }
+
+    fn default_require_lowercase() -> bool {
+        false
+    }
+
+    fn default_require_uppercase() -> bool {
+        false
+    }
+
+    fn default_require_digit() -> bool {
+        false
+    }
+
+    fn default_require_special_character() -> bool {
+        false
+    }
+
+    fn default_min_strength_score() -> u8 {
+        0
+    }
+
+    fn default_reject_username_or_email() -> bool {
+        false
+    }
+
+    fn default_breached_passwords_path() -> Option<PathBuf> {
+        None
+    }
+}
+
+/// The username policy checked when users register or are renamed.
+///
+/// Usernames are compared without case, dashes and underscores, so
+/// `Ad_min` is also a reserved username when `admin` is reserved.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsernameConstraints {
+    /// The usernames nobody can register, like `admin` or `support`.
+    #[serde(default = "UsernameConstraints::default_reserved_usernames")]
+    pub reserved_usernames: Vec<String>,
+    /// A file with the words usernames can't contain, one per line. Lines
+    /// starting with `#` are comments. The check is disabled if it's not set.
+    ///
+    /// The file is read when the Index starts, which fails if it can't be
+    /// read.
+    #[serde(default = "UsernameConstraints::default_profanity_words_path")]
+    pub profanity_words_path: Option<PathBuf>,
+}
+
+impl Default for UsernameConstraints {
+    fn default() -> Self {
+        Self {
+            reserved_usernames: Self::default_reserved_usernames(),
+            profanity_words_path: Self::default_profanity_words_path(),
+        }
+    }
+}
+
+impl UsernameConstraints {
+    fn default_reserved_usernames() -> Vec<String> {
+        [
+            "abuse",
+            "admin",
+            "administrator",
+            "anonymous",
+            "api",
+            "contact",
+            "guest",
+            "help",
+            "hostmaster",
+            "index",
+            "info",
+            "mail",
+            "mod",
+            "moderator",
+            "noreply",
+            "null",
+            "official",
+            "owner",
+            "postmaster",
+            "root",
+            "security",
+            "staff",
+            "support",
+            "sysadmin",
+            "system",
+            "torrust",
+            "tracker",
+            "undefined",
+            "webmaster",
+            "www",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+    }
+
+    fn default_profanity_words_path() -> Option<PathBuf> {
+        None
+    }
+}
+
+/// The defaults are the ones recommended by the `argon2` crate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Argon2Params {
+    /// Memory size in KiB.
+    #[serde(default = "Argon2Params::default_memory_cost")]
+    pub memory_cost: u32,
+    /// Number of iterations.
+    #[serde(default = "Argon2Params::default_time_cost")]
+    pub time_cost: u32,
+    /// Degree of parallelism.
+    #[serde(default = "Argon2Params::default_parallelism")]
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Self {
+            memory_cost: Self::default_memory_cost(),
+            time_cost: Self::default_time_cost(),
+            parallelism: Self::default_parallelism(),
+        }
+    }
+}
+
+impl Argon2Params {
+    fn default_memory_cost() -> u32 {
+        19 * 1024
+    }
+
+    fn default_time_cost() -> u32 {
+        2
+    }
+
+    fn default_parallelism() -> u32 {
+        1
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,14 @@
+use std::fmt;
+use std::path::PathBuf;
+
 use lettre::message::Mailbox;
 use serde::{Deserialize, Serialize};
 
-/// SMTP configuration.
+/// Mail configuration.
+///
+/// Emails are not sent while handling the request that triggers them. They
+/// are stored in an outbox in the database and a background worker delivers
+/// them with the configured transport, retrying when it fails.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Mail {
     /// The email address to send emails from.
@@ -12,6 +19,26 @@ pub struct Mail {
     #[serde(default = "Mail::default_reply_to")]
     pub reply_to: Mailbox,
 
+    /// The transport used to deliver emails. Possible values are: `smtp`,
+    /// `file` and `sendmail`.
+    #[serde(default = "Mail::default_transport")]
+    pub transport: Transport,
+
+    /// Optional directory with templates overriding the built-in ones. Every
+    /// message type has an HTML (`<name>.html`) and a plain text
+    /// (`<name>.txt`) template. Templates missing in the directory fall back
+    /// to the built-in ones.
+    #[serde(default = "Mail::default_templates_path")]
+    pub templates_path: Option<PathBuf>,
+
+    /// The file transport configuration.
+    #[serde(default = "Mail::default_file")]
+    pub file: File,
+
+    /// The sendmail transport configuration.
+    #[serde(default = "Mail::default_sendmail")]
+    pub sendmail: Sendmail,
+
     /// The SMTP server configuration.
     #[serde(default = "Mail::default_smtp")]
     pub smtp: Smtp,
@@ -22,6 +49,10 @@ impl Default for Mail {
         Self {
             from: Self::default_from(),
             reply_to: Self::default_reply_to(),
+            transport: Self::default_transport(),
+            templates_path: Self::default_templates_path(),
+            file: Self::default_file(),
+            sendmail: Self::default_sendmail(),
             smtp: Self::default_smtp(),
         }
     }
@@ -36,11 +67,95 @@ impl Mail {
         "noreply@email.com".parse().expect("valid mailbox")
     }
 
+    fn default_transport() -> Transport {
+        Transport::Smtp
+    }
+
+    fn default_templates_path() -> Option<PathBuf> {
+        None
+    }
+
+    fn default_file() -> File {
+        File::default()
+    }
+
+    fn default_sendmail() -> Sendmail {
+        Sendmail::default()
+    }
+
     fn default_smtp() -> Smtp {
         Smtp::default()
     }
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// Emails are sent to an SMTP server.
+    Smtp,
+    /// Emails are written as `.eml` files to a directory. It's meant for
+    /// testing and development.
+    File,
+    /// Emails are piped to a local `sendmail` binary.
+    Sendmail,
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let display_str = match self {
+            Transport::Smtp => "smtp",
+            Transport::File => "file",
+            Transport::Sendmail => "sendmail",
+        };
+        write!(f, "{display_str}")
+    }
+}
+
+/// File transport configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct File {
+    /// The directory where the emails are written.
+    #[serde(default = "File::default_path")]
+    pub path: PathBuf,
+}
+
+impl Default for File {
+    fn default() -> Self {
+        Self {
+            path: Self::default_path(),
+        }
+    }
+}
+
+impl File {
+    fn default_path() -> PathBuf {
+        PathBuf::from("./storage/index/lib/mail")
+    }
+}
+
+/// Sendmail transport configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Sendmail {
+    /// The `sendmail` compatible binary. It's looked up in the `PATH` when
+    /// it's not an absolute path.
+    #[serde(default = "Sendmail::default_command")]
+    pub command: String,
+}
+
+impl Default for Sendmail {
+    fn default() -> Self {
+        Self {
+            command: Self::default_command(),
+        }
+    }
+}
+
+impl Sendmail {
+    fn default_command() -> String {
+        "sendmail".to_string()
+    }
+}
+
 /// SMTP configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Smtp {
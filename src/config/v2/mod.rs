@@ -6,9 +6,11 @@ pub mod logging;
 pub mod mail;
 pub mod net;
 pub mod registration;
+pub mod torrent_storage;
 pub mod tracker;
 pub mod tracker_statistics_importer;
 pub mod unstable;
+pub mod upload_policy;
 pub mod website;
 
 use logging::Logging;
@@ -22,8 +24,10 @@ use self::database::Database;
 use self::image_cache::ImageCache;
 use self::mail::Mail;
 use self::net::Network;
+use self::torrent_storage::TorrentStorage;
 use self::tracker::{ApiToken, Tracker};
 use self::tracker_statistics_importer::TrackerStatisticsImporter;
+use self::upload_policy::UploadPolicy;
 use self::website::Website;
 use super::validator::{ValidationError, Validator};
 use super::Metadata;
@@ -79,6 +83,14 @@ pub struct Settings {
     #[serde(default = "Settings::default_tracker_statistics_importer")]
     pub tracker_statistics_importer: TrackerStatisticsImporter,
 
+    /// The storage for the original torrent files.
+    #[serde(default = "Settings::default_torrent_storage")]
+    pub torrent_storage: TorrentStorage,
+
+    /// The policy for the torrents users can upload.
+    #[serde(default = "Settings::default_upload_policy")]
+    pub upload_policy: UploadPolicy,
+
     /// The unstable configuration.
     #[serde(default = "Settings::default_unstable")]
     pub unstable: Option<Unstable>,
@@ -99,6 +111,8 @@ impl Default for Settings {
             api: Self::default_api(),
             registration: Self::default_registration(),
             tracker_statistics_importer: Self::default_tracker_statistics_importer(),
+            torrent_storage: Self::default_torrent_storage(),
+            upload_policy: Self::default_upload_policy(),
             unstable: Self::default_unstable(),
         }
     }
@@ -182,6 +196,14 @@ impl Settings {
         TrackerStatisticsImporter::default()
     }
 
+    fn default_torrent_storage() -> TorrentStorage {
+        TorrentStorage::default()
+    }
+
+    fn default_upload_policy() -> UploadPolicy {
+        UploadPolicy::default()
+    }
+
     fn default_unstable() -> Option<Unstable> {
         None
     }
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Storage for the original torrent files uploaded by users.
+///
+/// By default, the index rebuilds the torrent file from the database when it's
+/// downloaded, which drops non-standard keys from the `info` dictionary. The
+/// categories configured to serve the original torrent files keep the exact
+/// uploaded bytes in this storage.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TorrentStorage {
+    /// Where the original torrent files are stored. Possible values are:
+    /// `database` and `filesystem`.
+    #[serde(default = "TorrentStorage::default_backend")]
+    pub backend: Backend,
+
+    /// The directory for the original torrent files when the backend is
+    /// `filesystem`.
+    #[serde(default = "TorrentStorage::default_path")]
+    pub path: PathBuf,
+}
+
+impl Default for TorrentStorage {
+    fn default() -> Self {
+        Self {
+            backend: Self::default_backend(),
+            path: Self::default_path(),
+        }
+    }
+}
+
+impl TorrentStorage {
+    fn default_backend() -> Backend {
+        Backend::Database
+    }
+
+    fn default_path() -> PathBuf {
+        PathBuf::from("./storage/index/lib/torrents")
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The original bytes are stored in the database.
+    Database,
+    /// The original bytes are stored as files in the configured directory.
+    Filesystem,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let display_str = match self {
+            Backend::Database => "database",
+            Backend::Filesystem => "filesystem",
+        };
+        write!(f, "{display_str}")
+    }
+}
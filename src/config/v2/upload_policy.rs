@@ -0,0 +1,143 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Policy for the torrents users can upload.
+///
+/// All the limits are optional. By default, any valid torrent can be uploaded
+/// to any category.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UploadPolicy {
+    /// The maximum size of the uploaded torrent file in bytes.
+    #[serde(default = "UploadPolicy::default_max_torrent_file_size")]
+    pub max_torrent_file_size: u64,
+
+    /// The maximum total size of the files in the torrent, in bytes.
+    #[serde(default = "UploadPolicy::default_max_content_size")]
+    pub max_content_size: Option<u64>,
+
+    /// The maximum number of files in the torrent. Padding files are not
+    /// counted.
+    #[serde(default = "UploadPolicy::default_max_files")]
+    pub max_files: Option<u64>,
+
+    /// Whether the torrent must be private (`private=1`). Possible values
+    /// are: `any`, `required` and `forbidden`.
+    #[serde(default = "UploadPolicy::default_private_flag")]
+    pub private_flag: PrivateFlag,
+
+    /// The value the `source` key of the `info` dictionary must have. Private
+    /// trackers use it to make the info-hash unique to the tracker.
+    #[serde(default = "UploadPolicy::default_required_source")]
+    pub required_source: Option<String>,
+
+    /// Whether the `source` key of the `info` dictionary is rewritten to the
+    /// website name on upload. Cross-seeded torrents get a new info-hash
+    /// unique to this index. The uploaded info-hash is still recorded, so
+    /// re-uploads of the original torrent are detected.
+    #[serde(default = "UploadPolicy::default_rewrite_source")]
+    pub rewrite_source: bool,
+
+    /// If not empty, only files with these extensions can be uploaded. Files
+    /// without extension are not allowed either.
+    #[serde(default = "UploadPolicy::default_allowed_extensions")]
+    pub allowed_extensions: Vec<String>,
+
+    /// Files with these extensions can't be uploaded.
+    #[serde(default = "UploadPolicy::default_forbidden_extensions")]
+    pub forbidden_extensions: Vec<String>,
+
+    /// The categories each user role can upload torrents to.
+    #[serde(default = "UploadPolicy::default_allowed_categories")]
+    pub allowed_categories: AllowedCategories,
+}
+
+impl Default for UploadPolicy {
+    fn default() -> Self {
+        Self {
+            max_torrent_file_size: Self::default_max_torrent_file_size(),
+            max_content_size: Self::default_max_content_size(),
+            max_files: Self::default_max_files(),
+            private_flag: Self::default_private_flag(),
+            required_source: Self::default_required_source(),
+            rewrite_source: Self::default_rewrite_source(),
+            allowed_extensions: Self::default_allowed_extensions(),
+            forbidden_extensions: Self::default_forbidden_extensions(),
+            allowed_categories: Self::default_allowed_categories(),
+        }
+    }
+}
+
+impl UploadPolicy {
+    fn default_max_torrent_file_size() -> u64 {
+        10_485_760
+    }
+
+    fn default_max_content_size() -> Option<u64> {
+        None
+    }
+
+    fn default_max_files() -> Option<u64> {
+        None
+    }
+
+    fn default_private_flag() -> PrivateFlag {
+        PrivateFlag::Any
+    }
+
+    fn default_required_source() -> Option<String> {
+        None
+    }
+
+    fn default_rewrite_source() -> bool {
+        false
+    }
+
+    fn default_allowed_extensions() -> Vec<String> {
+        vec![]
+    }
+
+    fn default_forbidden_extensions() -> Vec<String> {
+        vec![]
+    }
+
+    fn default_allowed_categories() -> AllowedCategories {
+        AllowedCategories::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivateFlag {
+    /// Public and private torrents are allowed.
+    Any,
+    /// Only private torrents are allowed.
+    Required,
+    /// Only public torrents are allowed.
+    Forbidden,
+}
+
+impl fmt::Display for PrivateFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let display_str = match self {
+            PrivateFlag::Any => "any",
+            PrivateFlag::Required => "required",
+            PrivateFlag::Forbidden => "forbidden",
+        };
+        write!(f, "{display_str}")
+    }
+}
+
+/// The categories each user role can upload torrents to. If a role has no
+/// list, it can upload torrents to any category.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AllowedCategories {
+    /// The categories administrators can upload torrents to.
+    #[serde(default)]
+    pub admin: Option<Vec<String>>,
+
+    /// The categories registered users can upload torrents to.
+    #[serde(default)]
+    pub registered: Option<Vec<String>>,
+}
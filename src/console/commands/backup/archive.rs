@@ -0,0 +1,319 @@
+//! Portable Index backup archive.
+//!
+//! The archive is a directory with this layout:
+//!
+//! ```text
+//! manifest.json
+//! users.ndjson
+//! categories.ndjson
+//! tags.ndjson
+//! torrents.ndjson
+//! torrents/<CANONICAL_INFO_HASH>.torrent
+//! ```
+//!
+//! The `manifest.json` file contains the archive format version and the
+//! number of records in each file. The `.ndjson` files contain one JSON
+//! record per line. Torrent records point to the `.torrent` file with the
+//! meta-info stored in the Index (the info dictionary, files, announce URLs,
+//! HTTP seeds and DHT nodes). Torrents listed from a magnet link without
+//! meta-info don't have a `.torrent` file. Their record contains the magnet
+//! link instead.
+//!
+//! Records reference each other by name (usernames, category and tag names)
+//! instead of by database ID, so the archive can be imported into any
+//! database driver.
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use derive_more::{Display, Error};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Value of the `format` field in the manifest.
+pub const FORMAT: &str = "torrust-index-backup";
+
+/// Current archive format version.
+pub const VERSION: u32 = 1;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const USERS_FILE: &str = "users.ndjson";
+pub const CATEGORIES_FILE: &str = "categories.ndjson";
+pub const TAGS_FILE: &str = "tags.ndjson";
+pub const TORRENTS_FILE: &str = "torrents.ndjson";
+pub const TORRENTS_DIR: &str = "torrents";
+
+#[derive(Debug, Display, Error)]
+pub enum Error {
+    #[display(fmt = "cannot read or write `{file}`: {source}")]
+    Io { file: String, source: std::io::Error },
+
+    #[display(fmt = "invalid record in `{file}` at line {line}: {message}")]
+    InvalidRecord { file: String, line: usize, message: String },
+
+    #[display(fmt = "not a Torrust Index backup (format `{format}`)")]
+    UnknownFormat { format: String },
+
+    #[display(fmt = "unsupported backup version {version}. Supported version: {VERSION}")]
+    UnsupportedVersion { version: u32 },
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    /// Export date in RFC 3339 format.
+    pub created_at: String,
+    pub users: usize,
+    pub categories: usize,
+    pub tags: usize,
+    pub torrents: usize,
+}
+
+impl Manifest {
+    /// It checks the archive was generated by a compatible exporter.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the format is unknown or the version is not
+    /// supported.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.format != FORMAT {
+            return Err(Error::UnknownFormat {
+                format: self.format.clone(),
+            });
+        }
+
+        if self.version != VERSION {
+            return Err(Error::UnsupportedVersion { version: self.version });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub administrator: bool,
+    pub password_hash: String,
+    pub date_registered: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRecord {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagRecord {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TorrentRecord {
+    /// The canonical info-hash.
+    pub info_hash: String,
+    /// The original info-hashes in the canonical info-hash group.
+    pub original_info_hashes: Vec<String>,
+    /// Path of the `.torrent` file relative to the archive directory. It's
+    /// `None` for torrents listed from a magnet link without meta-info.
+    pub file: Option<String>,
+    /// The magnet link of torrents without meta-info.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub magnet_link: Option<String>,
+    pub uploader: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub date_uploaded: String,
+    pub seeders: i64,
+    pub leechers: i64,
+}
+
+/// It writes the records to a NDJSON file, one record per line.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be written.
+///
+/// # Panics
+///
+/// Will panic if a record can't be serialized into JSON.
+pub fn write_ndjson<T: Serialize>(dir: &Path, file_name: &str, records: &[T]) -> Result<(), Error> {
+    let io_error = |source| Error::Io {
+        file: file_name.to_string(),
+        source,
+    };
+
+    let mut writer = BufWriter::new(File::create(dir.join(file_name)).map_err(io_error)?);
+
+    for record in records {
+        let line = serde_json::to_string(record).expect("archive records should be serializable");
+        writeln!(writer, "{line}").map_err(io_error)?;
+    }
+
+    writer.flush().map_err(io_error)
+}
+
+/// It reads all the records in a NDJSON file. Empty lines are ignored.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be read or a line is not a valid
+/// record.
+pub fn read_ndjson<T: DeserializeOwned>(dir: &Path, file_name: &str) -> Result<Vec<T>, Error> {
+    let file = File::open(dir.join(file_name)).map_err(|source| Error::Io {
+        file: file_name.to_string(),
+        source,
+    })?;
+
+    parse_ndjson(BufReader::new(file), file_name)
+}
+
+fn parse_ndjson<T: DeserializeOwned>(reader: impl BufRead, file_name: &str) -> Result<Vec<T>, Error> {
+    let mut records = vec![];
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|source| Error::Io {
+            file: file_name.to_string(),
+            source,
+        })?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str(&line).map_err(|err| Error::InvalidRecord {
+            file: file_name.to_string(),
+            line: index + 1,
+            message: err.to_string(),
+        })?;
+
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// # Errors
+///
+/// Will return an error if the manifest file can't be written.
+///
+/// # Panics
+///
+/// Will panic if the manifest can't be serialized into JSON.
+pub fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(manifest).expect("the manifest should be serializable");
+
+    fs::write(dir.join(MANIFEST_FILE), json).map_err(|source| Error::Io {
+        file: MANIFEST_FILE.to_string(),
+        source,
+    })
+}
+
+/// It reads the manifest and checks the archive version is supported.
+///
+/// # Errors
+///
+/// Will return an error if the manifest can't be read or the archive is not
+/// supported.
+pub fn read_manifest(dir: &Path) -> Result<Manifest, Error> {
+    let json = fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|source| Error::Io {
+        file: MANIFEST_FILE.to_string(),
+        source,
+    })?;
+
+    let manifest: Manifest = serde_json::from_str(&json).map_err(|err| Error::InvalidRecord {
+        file: MANIFEST_FILE.to_string(),
+        line: err.line(),
+        message: err.to_string(),
+    })?;
+
+    manifest.check()?;
+
+    Ok(manifest)
+}
+
+/// Path of the `.torrent` file, relative to the archive directory, for a
+/// canonical info-hash.
+#[must_use]
+pub fn torrent_file_path(info_hash: &str) -> String {
+    format!("{TORRENTS_DIR}/{info_hash}.torrent")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ndjson, Manifest, TorrentRecord, FORMAT, VERSION};
+
+    fn sample_manifest() -> Manifest {
+        Manifest {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            created_at: "2024-04-01T10:00:00+00:00".to_string(),
+            users: 1,
+            categories: 2,
+            tags: 3,
+            torrents: 4,
+        }
+    }
+
+    #[test]
+    fn it_should_accept_a_manifest_with_the_current_version() {
+        assert!(sample_manifest().check().is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_a_manifest_with_an_unsupported_version() {
+        let manifest = Manifest {
+            version: VERSION + 1,
+            ..sample_manifest()
+        };
+
+        assert!(manifest.check().is_err());
+    }
+
+    #[test]
+    fn it_should_reject_a_manifest_with_an_unknown_format() {
+        let manifest = Manifest {
+            format: "other".to_string(),
+            ..sample_manifest()
+        };
+
+        assert!(manifest.check().is_err());
+    }
+
+    #[test]
+    fn it_should_parse_ndjson_records_ignoring_empty_lines() {
+        let record = TorrentRecord {
+            info_hash: "6c690018c5786dbbb00161f62b0712d69296df97".to_string(),
+            original_info_hashes: vec!["6c690018c5786dbbb00161f62b0712d69296df97".to_string()],
+            file: Some("torrents/6c690018c5786dbbb00161f62b0712d69296df97.torrent".to_string()),
+            magnet_link: None,
+            uploader: "admin".to_string(),
+            title: "title".to_string(),
+            description: None,
+            category: Some("software".to_string()),
+            tags: vec!["linux".to_string()],
+            date_uploaded: "2024-04-01 10:00:00".to_string(),
+            seeders: 1,
+            leechers: 2,
+        };
+
+        let contents = format!("{}\n\n", serde_json::to_string(&record).unwrap());
+
+        let records: Vec<TorrentRecord> = parse_ndjson(contents.as_bytes(), "torrents.ndjson").unwrap();
+
+        assert_eq!(records, vec![record]);
+    }
+
+    #[test]
+    fn it_should_report_the_line_of_an_invalid_ndjson_record() {
+        let result = parse_ndjson::<TorrentRecord>("\n{}\n".as_bytes(), "torrents.ndjson");
+
+        assert!(matches!(result, Err(super::Error::InvalidRecord { line: 2, .. })));
+    }
+}
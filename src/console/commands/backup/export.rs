@@ -0,0 +1,280 @@
+//! Console app to export the whole Index into a portable backup archive.
+//!
+//! Run with:
+//!
+//! ```text
+//! cargo run --bin export_index -- --dir <DIR> [--database-url <DATABASE_URL>]
+//! ```
+//!
+//! For example:
+//!
+//! ```text
+//! cargo run --bin export_index -- --dir ./storage/backup
+//! ```
+//!
+//! The database defaults to the one in the Index configuration. See the
+//! [`archive`](crate::console::commands::backup::archive) module for the
+//! archive layout.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+use chrono::Utc;
+use clap::Parser;
+use text_colorizer::Colorize;
+
+use super::archive::{self, CategoryRecord, Manifest, TagRecord, TorrentRecord, UserRecord};
+use super::database_connect_url;
+use crate::bootstrap::config::initialize_configuration;
+use crate::bootstrap::logging;
+use crate::databases::database::{self, Database};
+use crate::models::info_hash::{InfoHash, InfoHashV2};
+use crate::models::magnet_link::MagnetLink;
+use crate::models::torrent::TorrentListing;
+use crate::utils::parse_torrent::encode_torrent;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Directory where the backup archive is written. It must not contain
+    /// another backup.
+    #[arg(short, long)]
+    dir: PathBuf,
+
+    /// Database to export. Defaults to the database in the configuration.
+    #[arg(long)]
+    database_url: Option<String>,
+}
+
+/// # Errors
+///
+/// Will return an error if the database can't be read or the archive can't be
+/// written.
+pub async fn run() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let configuration = initialize_configuration();
+
+    let threshold = configuration.settings.read().await.logging.threshold.clone();
+
+    logging::setup(&threshold);
+
+    if args.dir.join(archive::MANIFEST_FILE).exists() {
+        return Err(anyhow!("the directory {} already contains a backup", args.dir.display()));
+    }
+
+    fs::create_dir_all(args.dir.join(archive::TORRENTS_DIR)).context("failed to create the backup directory")?;
+
+    let database_connect_url = database_connect_url(&configuration, args.database_url).await;
+
+    let database = database::connect(&database_connect_url)
+        .await
+        .map_err(|err| anyhow!("failed to connect to the database: {err:?}"))?;
+
+    println!("Exporting the Index into {} ...", args.dir.display().to_string().yellow());
+
+    let manifest = export(database.as_ref(), &args.dir).await?;
+
+    println!(
+        "Exported {} users, {} categories, {} tags and {} torrents",
+        manifest.users.to_string().green(),
+        manifest.categories.to_string().green(),
+        manifest.tags.to_string().green(),
+        manifest.torrents.to_string().green()
+    );
+
+    Ok(())
+}
+
+/// It exports the whole Index into a backup archive in `dir`.
+///
+/// # Errors
+///
+/// Will return an error if the database can't be read or the archive can't be
+/// written.
+pub async fn export(database: &dyn Database, dir: &Path) -> anyhow::Result<Manifest> {
+    let users = export_users(database).await?;
+    archive::write_ndjson(dir, archive::USERS_FILE, &users)?;
+
+    let categories: Vec<CategoryRecord> = database
+        .get_categories()
+        .await
+        .map_err(|err| anyhow!("failed to load categories: {err:?}"))?
+        .into_iter()
+        .map(|category| CategoryRecord { name: category.name })
+        .collect();
+    archive::write_ndjson(dir, archive::CATEGORIES_FILE, &categories)?;
+
+    let tags: Vec<TagRecord> = database
+        .get_tags()
+        .await
+        .map_err(|err| anyhow!("failed to load tags: {err:?}"))?
+        .into_iter()
+        .map(|tag| TagRecord { name: tag.name })
+        .collect();
+    archive::write_ndjson(dir, archive::TAGS_FILE, &tags)?;
+
+    let torrents = export_torrents(database, dir).await?;
+    archive::write_ndjson(dir, archive::TORRENTS_FILE, &torrents)?;
+
+    // The manifest is written last, so an interrupted export is not mistaken
+    // for a complete backup.
+    let manifest = Manifest {
+        format: archive::FORMAT.to_string(),
+        version: archive::VERSION,
+        created_at: Utc::now().to_rfc3339(),
+        users: users.len(),
+        categories: categories.len(),
+        tags: tags.len(),
+        torrents: torrents.len(),
+    };
+
+    archive::write_manifest(dir, &manifest)?;
+
+    Ok(manifest)
+}
+
+async fn export_users(database: &dyn Database) -> anyhow::Result<Vec<UserRecord>> {
+    let users = database
+        .get_users()
+        .await
+        .map_err(|err| anyhow!("failed to load users: {err:?}"))?;
+
+    let mut records = vec![];
+
+    for user in users {
+        let load_error = |err| anyhow!("failed to load user #{}: {err:?}", user.user_id);
+
+        let compact = database.get_user_compact_from_id(user.user_id).await.map_err(load_error)?;
+
+        let profile = database
+            .get_user_profile_from_username(&compact.username)
+            .await
+            .map_err(load_error)?;
+
+        let authentication = database
+            .get_user_authentication_from_id(user.user_id)
+            .await
+            .map_err(load_error)?;
+
+        records.push(UserRecord {
+            username: profile.username,
+            email: profile.email,
+            email_verified: profile.email_verified,
+            administrator: user.administrator,
+            password_hash: authentication.password_hash,
+            date_registered: user.date_registered,
+        });
+    }
+
+    Ok(records)
+}
+
+async fn export_torrents(database: &dyn Database, dir: &Path) -> anyhow::Result<Vec<TorrentRecord>> {
+    let torrents = database
+        .get_all_torrents_compact()
+        .await
+        .map_err(|err| anyhow!("failed to load torrents: {err:?}"))?;
+
+    let mut records = vec![];
+
+    for compact in torrents {
+        let load_error = |err| anyhow!("failed to load torrent {}: {err:?}", compact.info_hash);
+
+        let listing = database
+            .get_torrent_listing_from_id(compact.torrent_id)
+            .await
+            .map_err(load_error)?;
+
+        let category = match listing.category_id {
+            Some(category_id) => Some(database.get_category_from_id(category_id).await.map_err(load_error)?.name),
+            None => None,
+        };
+
+        let tags = database
+            .get_tags_for_torrent_id(compact.torrent_id)
+            .await
+            .map_err(load_error)?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+
+        // Torrents listed from a magnet link don't have meta-info to export.
+        let (canonical_info_hash, file, magnet_link) = if listing.magnet_only {
+            let magnet_link = export_magnet_link(database, &listing).await?;
+
+            (magnet_link.info_hash, None, Some(magnet_link.to_string()))
+        } else {
+            let torrent = database.get_torrent_from_id(compact.torrent_id).await.map_err(load_error)?;
+
+            let canonical_info_hash = torrent.canonical_info_hash();
+
+            let bytes = encode_torrent(&torrent).with_context(|| format!("failed to encode torrent {}", compact.info_hash))?;
+
+            let file = archive::torrent_file_path(&canonical_info_hash.to_hex_string());
+
+            fs::write(dir.join(&file), bytes).with_context(|| format!("failed to write {file}"))?;
+
+            (canonical_info_hash, Some(file), None)
+        };
+
+        let original_info_hashes = database
+            .get_torrent_canonical_info_hash_group(&canonical_info_hash)
+            .await
+            .map_err(load_error)?
+            .original_info_hashes
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        records.push(TorrentRecord {
+            info_hash: canonical_info_hash.to_hex_string(),
+            original_info_hashes,
+            file,
+            magnet_link,
+            uploader: listing.uploader,
+            title: listing.title,
+            description: listing.description,
+            category,
+            tags,
+            date_uploaded: listing.date_uploaded,
+            seeders: listing.seeders,
+            leechers: listing.leechers,
+        });
+    }
+
+    Ok(records)
+}
+
+/// It rebuilds the magnet link of a torrent listed without meta-info.
+async fn export_magnet_link(database: &dyn Database, listing: &TorrentListing) -> anyhow::Result<MagnetLink> {
+    let load_error = |err| anyhow!("failed to load torrent {}: {err:?}", listing.info_hash);
+
+    let info_hash =
+        InfoHash::from_str(&listing.info_hash).map_err(|_| anyhow!("invalid info-hash for torrent {}", listing.info_hash))?;
+
+    let trackers = database
+        .get_torrent_announce_urls_from_id(listing.torrent_id)
+        .await
+        .map_err(load_error)?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let web_seeds = database
+        .get_torrent_web_seed_urls_from_id(listing.torrent_id)
+        .await
+        .map_err(load_error)?;
+
+    Ok(MagnetLink {
+        info_hash,
+        info_hash_v2: listing
+            .info_hash_v2
+            .as_ref()
+            .and_then(|info_hash_v2| InfoHashV2::from_str(info_hash_v2).ok()),
+        display_name: Some(listing.name.clone()),
+        trackers,
+        web_seeds,
+    })
+}
@@ -0,0 +1,21 @@
+//! Commands to back up and restore the whole Index.
+//!
+//! - `export_index` writes the Index data into a portable archive.
+//! - `import_index` restores an archive into an empty database.
+//!
+//! Both commands work with any database driver, so they can also be used to
+//! migrate an Index from `SQLite` to `MySQL` and vice versa.
+pub mod archive;
+pub mod export;
+pub mod import;
+
+use crate::config::Configuration;
+
+/// It returns the database URL passed as argument or, if missing, the one in
+/// the Index configuration.
+async fn database_connect_url(configuration: &Configuration, database_url: Option<String>) -> String {
+    match database_url {
+        Some(database_url) => database_url,
+        None => configuration.settings.read().await.database.connect_url.to_string(),
+    }
+}
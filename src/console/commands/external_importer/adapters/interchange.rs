@@ -0,0 +1,345 @@
+//! Generic interchange format.
+//!
+//! Communities can convert their data into this format with their own scripts
+//! when there is no adapter for their application. It's a directory with one
+//! file per entity, all optional:
+//!
+//! ```text
+//! users.json (or users.csv)
+//! categories.json (or categories.csv)
+//! tags.json (or tags.csv)
+//! torrents.json (or torrents.csv)
+//! torrents/ubuntu-22.04.iso.torrent
+//! ```
+//!
+//! JSON files contain an array of objects. For example, `torrents.json`:
+//!
+//! ```json
+//! [
+//!   {
+//!     "file": "torrents/ubuntu-22.04.iso.torrent",
+//!     "uploader": "alice",
+//!     "title": "Ubuntu 22.04",
+//!     "description": "Ubuntu desktop image",
+//!     "category": "software",
+//!     "tags": ["linux", "ubuntu"],
+//!     "date_uploaded": "2023-04-01 10:35:15"
+//!   }
+//! ]
+//! ```
+//!
+//! CSV files have a header with the same field names. Values with commas must
+//! be quoted with `"`, and they can't contain line breaks. Tags are separated
+//! by `;` and booleans are `true` or `false`. For example, `users.csv`:
+//!
+//! ```text
+//! username,email,email_verified,administrator,password_hash,date_registered
+//! alice,alice@example.com,true,false,"$argon2id$v=19$m=4096,t=3,p=1$...",2023-04-01 10:35:15
+//! ```
+//!
+//! Fields:
+//!
+//! - `users`: `username`, `email` and `password_hash` are mandatory.
+//!   `email_verified` and `administrator` default to `false`.
+//! - `categories` and `tags`: `name`.
+//! - `torrents`: `file` (relative to the directory), `uploader` (a username)
+//!   and `title` are mandatory.
+//!
+//! Password hashes are imported as they are. Users with hashes that the Index
+//! can't verify are skipped and reported.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::SourceAdapter;
+use crate::console::commands::external_importer::records::{
+    normalize_date, CategoryRecord, Error, TagRecord, TorrentRecord, UserRecord,
+};
+use crate::console::commands::torrent_importer::metadata::split_csv_line;
+
+const CSV_TAGS_SEPARATOR: char = ';';
+
+const BOOLEAN_FIELDS: [&str; 2] = ["email_verified", "administrator"];
+
+#[derive(Debug, Deserialize)]
+struct User {
+    username: String,
+    email: String,
+    #[serde(default)]
+    email_verified: bool,
+    #[serde(default)]
+    administrator: bool,
+    password_hash: String,
+    date_registered: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Name {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Torrent {
+    file: String,
+    uploader: String,
+    title: String,
+    description: Option<String>,
+    category: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    date_uploaded: Option<String>,
+}
+
+pub struct Adapter {
+    dir: PathBuf,
+}
+
+impl Adapter {
+    #[must_use]
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf() }
+    }
+
+    /// It reads the JSON file for the entity or, if missing, the CSV file.
+    fn read<T: DeserializeOwned>(&self, entity: &str) -> Result<Vec<T>, Error> {
+        for extension in ["json", "csv"] {
+            let file = format!("{entity}.{extension}");
+            let path = self.dir.join(&file);
+
+            if !path.exists() {
+                continue;
+            }
+
+            let contents = fs::read_to_string(&path).map_err(|err| Error::CannotReadFile {
+                file: file.clone(),
+                message: err.to_string(),
+            })?;
+
+            return match extension {
+                "json" => parse_json(&contents, &file),
+                _ => parse_csv(&contents, &file),
+            };
+        }
+
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl SourceAdapter for Adapter {
+    fn name(&self) -> &'static str {
+        "interchange format"
+    }
+
+    async fn users(&self) -> Result<Vec<UserRecord>, Error> {
+        Ok(self
+            .read::<User>("users")?
+            .into_iter()
+            .map(|user| UserRecord {
+                username: user.username,
+                email: user.email,
+                email_verified: user.email_verified,
+                administrator: user.administrator,
+                password_hash: user.password_hash,
+                date_registered: user.date_registered.as_deref().and_then(normalize_date),
+            })
+            .collect())
+    }
+
+    async fn categories(&self) -> Result<Vec<CategoryRecord>, Error> {
+        Ok(self
+            .read::<Name>("categories")?
+            .into_iter()
+            .map(|category| CategoryRecord { name: category.name })
+            .collect())
+    }
+
+    async fn tags(&self) -> Result<Vec<TagRecord>, Error> {
+        Ok(self
+            .read::<Name>("tags")?
+            .into_iter()
+            .map(|tag| TagRecord { name: tag.name })
+            .collect())
+    }
+
+    async fn torrents(&self) -> Result<Vec<TorrentRecord>, Error> {
+        Ok(self
+            .read::<Torrent>("torrents")?
+            .into_iter()
+            .map(|torrent| TorrentRecord {
+                file: self.dir.join(torrent.file),
+                uploader: torrent.uploader,
+                title: torrent.title,
+                description: torrent.description,
+                category: torrent.category,
+                tags: torrent.tags,
+                date_uploaded: torrent.date_uploaded.as_deref().and_then(normalize_date),
+            })
+            .collect())
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(contents: &str, file: &str) -> Result<Vec<T>, Error> {
+    serde_json::from_str(contents).map_err(|err| Error::InvalidRecord {
+        file: file.to_string(),
+        line: err.line(),
+        message: err.to_string(),
+    })
+}
+
+/// Each CSV line is converted into the same JSON object used in the JSON
+/// files, so both formats have the same fields and defaults.
+fn parse_csv<T: DeserializeOwned>(contents: &str, file: &str) -> Result<Vec<T>, Error> {
+    let invalid_record = |line: usize, message: String| Error::InvalidRecord {
+        file: file.to_string(),
+        line,
+        message,
+    };
+
+    let mut lines = contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Ok(vec![]);
+    };
+
+    let columns: Vec<String> = split_csv_line(header)
+        .map_err(|message| invalid_record(1, message))?
+        .into_iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+
+    let mut records = vec![];
+
+    for (index, line) in lines {
+        let line_number = index + 1;
+
+        let values = split_csv_line(line).map_err(|message| invalid_record(line_number, message))?;
+
+        if values.len() != columns.len() {
+            return Err(invalid_record(
+                line_number,
+                format!("expected {} values, got {}", columns.len(), values.len()),
+            ));
+        }
+
+        let mut object = Map::new();
+
+        for (column, value) in columns.iter().zip(values) {
+            let value = value.trim();
+
+            // Empty values are missing values.
+            if value.is_empty() {
+                continue;
+            }
+
+            let value = if column == "tags" {
+                Value::Array(
+                    value
+                        .split(CSV_TAGS_SEPARATOR)
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(|tag| Value::String(tag.to_string()))
+                        .collect(),
+                )
+            } else if BOOLEAN_FIELDS.contains(&column.as_str()) {
+                match value.to_lowercase().as_str() {
+                    "true" | "1" => Value::Bool(true),
+                    "false" | "0" => Value::Bool(false),
+                    _ => {
+                        return Err(invalid_record(
+                            line_number,
+                            format!("invalid boolean for `{column}`: {value}"),
+                        ))
+                    }
+                }
+            } else {
+                Value::String(value.to_string())
+            };
+
+            object.insert(column.clone(), value);
+        }
+
+        let record = serde_json::from_value(Value::Object(object)).map_err(|err| invalid_record(line_number, err.to_string()))?;
+
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Adapter, SourceAdapter};
+
+    #[tokio::test]
+    async fn it_should_read_the_users_from_a_csv_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(
+            dir.path().join("users.csv"),
+            "username,email,email_verified,administrator,password_hash,date_registered\n\
+             alice,alice@example.com,true,,\"$argon2id$v=19$m=4096,t=3,p=1$c2FsdA$aGFzaA\",2023-04-01T10:35:15Z\n",
+        )
+        .unwrap();
+
+        let users = Adapter::new(dir.path()).users().await.unwrap();
+
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].username, "alice");
+        assert!(users[0].email_verified);
+        assert!(!users[0].administrator);
+        assert_eq!(users[0].password_hash, "$argon2id$v=19$m=4096,t=3,p=1$c2FsdA$aGFzaA");
+        assert_eq!(users[0].date_registered, Some("2023-04-01 10:35:15".to_string()));
+    }
+
+    #[tokio::test]
+    async fn it_should_read_the_torrents_from_a_json_file_with_paths_relative_to_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(
+            dir.path().join("torrents.json"),
+            r#"[{"file": "torrents/ubuntu.torrent", "uploader": "alice", "title": "Ubuntu", "tags": ["linux"]}]"#,
+        )
+        .unwrap();
+
+        let torrents = Adapter::new(dir.path()).torrents().await.unwrap();
+
+        assert_eq!(torrents.len(), 1);
+        assert_eq!(torrents[0].file, dir.path().join("torrents/ubuntu.torrent"));
+        assert_eq!(torrents[0].tags, vec!["linux".to_string()]);
+        assert_eq!(torrents[0].category, None);
+    }
+
+    #[tokio::test]
+    async fn it_should_split_the_tags_in_csv_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(
+            dir.path().join("torrents.csv"),
+            "file,uploader,title,tags\nubuntu.torrent,alice,Ubuntu,linux; ubuntu\n",
+        )
+        .unwrap();
+
+        let torrents = Adapter::new(dir.path()).torrents().await.unwrap();
+
+        assert_eq!(torrents[0].tags, vec!["linux".to_string(), "ubuntu".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn missing_files_should_have_no_records_and_invalid_records_should_be_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(dir.path().join("categories.csv"), "title\nsoftware\n").unwrap();
+
+        let adapter = Adapter::new(dir.path());
+
+        assert!(adapter.tags().await.unwrap().is_empty());
+        assert!(adapter.categories().await.is_err());
+    }
+}
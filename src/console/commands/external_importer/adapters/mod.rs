@@ -0,0 +1,37 @@
+//! Readers for the data exported by other applications.
+//!
+//! Each adapter reads the data from one application and maps it to the
+//! [`records`](super::records) the transferrers write into the Index. New
+//! applications can be supported by implementing the [`SourceAdapter`] trait.
+pub mod interchange;
+pub mod unit3d;
+
+use async_trait::async_trait;
+
+use super::records::{CategoryRecord, Error, TagRecord, TorrentRecord, UserRecord};
+
+#[async_trait]
+pub trait SourceAdapter: Sync + Send {
+    /// The application the data comes from.
+    fn name(&self) -> &'static str;
+
+    /// # Errors
+    ///
+    /// Will return an error if the users can't be read.
+    async fn users(&self) -> Result<Vec<UserRecord>, Error>;
+
+    /// # Errors
+    ///
+    /// Will return an error if the categories can't be read.
+    async fn categories(&self) -> Result<Vec<CategoryRecord>, Error>;
+
+    /// # Errors
+    ///
+    /// Will return an error if the tags can't be read.
+    async fn tags(&self) -> Result<Vec<TagRecord>, Error>;
+
+    /// # Errors
+    ///
+    /// Will return an error if the torrents can't be read.
+    async fn torrents(&self) -> Result<Vec<TorrentRecord>, Error>;
+}
@@ -0,0 +1,188 @@
+//! Adapter for [UNIT3D](https://github.com/HDInnovations/UNIT3D-Community-Edition)
+//! sites.
+//!
+//! It reads a UNIT3D database. Load the SQL dump of the site into a local
+//! `MySQL` server, or convert it into a `SQLite` file, and copy the torrent
+//! files from the `storage/app/torrents` directory of the site.
+//!
+//! Only these tables and columns are used, so they are the only ones needed
+//! in the database:
+//!
+//! - `users`: `id`, `username`, `email`, `password`, `group_id`, `active` and
+//!   `created_at`.
+//! - `groups`: `id`, `is_admin` and `is_owner`. Users in these groups are
+//!   imported as administrators.
+//! - `categories`: `id` and `name`.
+//! - `torrents`: `id`, `name`, `description`, `file_name`, `user_id`,
+//!   `category_id` and `created_at`.
+//! - `keywords`: `torrent_id` and `name`. Keywords are imported as tags.
+//!
+//! UNIT3D hashes passwords with bcrypt. The hashes are imported as they are
+//! and replaced with `Argon2id` hashes when the users log in.
+//! Descriptions are imported as they are, so they can contain `BBCode`.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use super::SourceAdapter;
+use crate::console::commands::external_importer::records::{
+    normalize_date, CategoryRecord, Error, TagRecord, TorrentRecord, UserRecord,
+};
+use crate::upgrades::database::Connection;
+
+// All the values are cast, so the same queries return the same types in
+// `SQLite` and `MySQL`, where ids are unsigned and dates are not strings.
+
+const USERS_QUERY: &str = "
+    SELECT
+        CAST(u.username AS CHAR) AS username,
+        CAST(u.email AS CHAR) AS email,
+        CAST(u.password AS CHAR) AS password,
+        CAST(COALESCE(u.active, 0) AS SIGNED) AS active,
+        CAST(COALESCE(g.is_admin, 0) + COALESCE(g.is_owner, 0) AS SIGNED) AS administrator,
+        CAST(u.created_at AS CHAR) AS created_at
+    FROM users u
+    LEFT JOIN `groups` g ON g.id = u.group_id
+    ORDER BY u.id";
+
+const CATEGORIES_QUERY: &str = "SELECT CAST(name AS CHAR) AS name FROM categories ORDER BY id";
+
+const TAGS_QUERY: &str = "SELECT DISTINCT CAST(name AS CHAR) AS name FROM keywords ORDER BY name";
+
+const TORRENTS_QUERY: &str = "
+    SELECT
+        CAST(t.id AS SIGNED) AS id,
+        CAST(t.name AS CHAR) AS name,
+        CAST(t.description AS CHAR) AS description,
+        CAST(t.file_name AS CHAR) AS file_name,
+        CAST(u.username AS CHAR) AS uploader,
+        CAST(c.name AS CHAR) AS category,
+        CAST(t.created_at AS CHAR) AS created_at
+    FROM torrents t
+    LEFT JOIN users u ON u.id = t.user_id
+    LEFT JOIN categories c ON c.id = t.category_id
+    ORDER BY t.id";
+
+const KEYWORDS_QUERY: &str = "SELECT CAST(torrent_id AS SIGNED) AS torrent_id, CAST(name AS CHAR) AS name FROM keywords";
+
+#[derive(sqlx::FromRow)]
+struct UserRow {
+    username: String,
+    email: String,
+    password: String,
+    active: i64,
+    administrator: i64,
+    created_at: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct NameRow {
+    name: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct TorrentRow {
+    id: i64,
+    name: String,
+    description: Option<String>,
+    file_name: String,
+    uploader: Option<String>,
+    category: Option<String>,
+    created_at: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct KeywordRow {
+    torrent_id: i64,
+    name: String,
+}
+
+pub struct Adapter {
+    database: Connection,
+    torrents_dir: PathBuf,
+}
+
+impl Adapter {
+    /// # Errors
+    ///
+    /// Will return an error if it can't connect to the UNIT3D database.
+    pub async fn new(database_url: &str, torrents_dir: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            database: Connection::connect(database_url).await.map_err(source_database_error)?,
+            torrents_dir: torrents_dir.to_path_buf(),
+        })
+    }
+}
+
+#[async_trait]
+impl SourceAdapter for Adapter {
+    fn name(&self) -> &'static str {
+        "UNIT3D"
+    }
+
+    async fn users(&self) -> Result<Vec<UserRecord>, Error> {
+        let rows: Vec<UserRow> = self.database.fetch_all(USERS_QUERY).await.map_err(source_database_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| UserRecord {
+                username: row.username,
+                email: row.email,
+                email_verified: row.active > 0,
+                administrator: row.administrator > 0,
+                password_hash: row.password,
+                date_registered: row.created_at.as_deref().and_then(normalize_date),
+            })
+            .collect())
+    }
+
+    async fn categories(&self) -> Result<Vec<CategoryRecord>, Error> {
+        let rows: Vec<NameRow> = self
+            .database
+            .fetch_all(CATEGORIES_QUERY)
+            .await
+            .map_err(source_database_error)?;
+
+        Ok(rows.into_iter().map(|row| CategoryRecord { name: row.name }).collect())
+    }
+
+    async fn tags(&self) -> Result<Vec<TagRecord>, Error> {
+        let rows: Vec<NameRow> = self.database.fetch_all(TAGS_QUERY).await.map_err(source_database_error)?;
+
+        Ok(rows.into_iter().map(|row| TagRecord { name: row.name }).collect())
+    }
+
+    async fn torrents(&self) -> Result<Vec<TorrentRecord>, Error> {
+        let keywords: Vec<KeywordRow> = self.database.fetch_all(KEYWORDS_QUERY).await.map_err(source_database_error)?;
+
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+
+        for keyword in keywords {
+            tags.entry(keyword.torrent_id).or_default().push(keyword.name);
+        }
+
+        let rows: Vec<TorrentRow> = self.database.fetch_all(TORRENTS_QUERY).await.map_err(source_database_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TorrentRecord {
+                file: self.torrents_dir.join(row.file_name),
+                // Torrents from deleted users are skipped by the importer.
+                uploader: row.uploader.unwrap_or_default(),
+                title: row.name,
+                description: row.description,
+                category: row.category,
+                tags: tags.remove(&row.id).unwrap_or_default(),
+                date_uploaded: row.created_at.as_deref().and_then(normalize_date),
+            })
+            .collect())
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn source_database_error(err: crate::upgrades::Error) -> Error {
+    Error::SourceDatabase {
+        message: err.to_string(),
+    }
+}
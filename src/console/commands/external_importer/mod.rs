@@ -0,0 +1,13 @@
+//! Command to import the data of other torrent index applications.
+//!
+//! Each supported application has an [adapter](adapters) that reads its data
+//! and maps it to the Index concepts. Then, the [transferrers] write it into
+//! the Index database, like the transferrers of the
+//! [`v1.0.0` to `v2.0.0` upgrade](crate::upgrades::from_v1_0_0_to_v2_0_0::transferrers).
+//!
+//! Applications without an adapter can be imported by converting their data
+//! into the generic [interchange format](adapters::interchange).
+pub mod adapters;
+pub mod app;
+pub mod records;
+pub mod transferrers;
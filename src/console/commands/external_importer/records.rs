@@ -0,0 +1,80 @@
+//! Data read from the external applications, already mapped to the Index
+//! concepts.
+use std::path::PathBuf;
+
+use derive_more::{Display, Error};
+
+#[derive(Debug, Display, Error)]
+pub enum Error {
+    #[display(fmt = "cannot read `{file}`: {message}")]
+    CannotReadFile { file: String, message: String },
+
+    #[display(fmt = "invalid record in `{file}` at line {line}: {message}")]
+    InvalidRecord { file: String, line: usize, message: String },
+
+    #[display(fmt = "cannot read the source database: {message}")]
+    SourceDatabase { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserRecord {
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub administrator: bool,
+    /// The hash is imported as it is. Users whose hash can't be verified by
+    /// the Index are skipped.
+    pub password_hash: String,
+    pub date_registered: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryRecord {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRecord {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentRecord {
+    /// Path of the `.torrent` file.
+    pub file: PathBuf,
+    /// The username of the uploader.
+    pub uploader: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub date_uploaded: Option<String>,
+}
+
+/// Dates are imported in the Index format, `2022-11-10 10:35:15`, without
+/// fractional seconds or time zone.
+#[must_use]
+pub fn normalize_date(date: &str) -> Option<String> {
+    let date = date.trim().replace('T', " ");
+
+    if date.len() < 19 || !date.is_char_boundary(19) {
+        return None;
+    }
+
+    Some(date[..19].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_date;
+
+    #[test]
+    fn dates_should_be_imported_without_fractional_seconds_or_time_zone() {
+        assert_eq!(
+            normalize_date("2023-04-01T10:35:15.000000Z"),
+            Some("2023-04-01 10:35:15".to_string())
+        );
+        assert_eq!(normalize_date("2023-04-01 10:35:15"), Some("2023-04-01 10:35:15".to_string()));
+        assert_eq!(normalize_date("2023-04-01"), None);
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+
+use super::Report;
+use crate::console::commands::external_importer::adapters::SourceAdapter;
+use crate::databases::database::Database;
+use crate::models::category::CategoryId;
+
+/// It imports the categories. Existing categories with the same name are
+/// reused.
+///
+/// # Errors
+///
+/// Will return an error if the categories can't be read or inserted.
+pub async fn transfer_categories(
+    adapter: &dyn SourceAdapter,
+    database: &dyn Database,
+    report: &mut Report,
+) -> anyhow::Result<HashMap<String, CategoryId>> {
+    println!("Transferring categories ...");
+
+    let mut category_ids = HashMap::new();
+
+    for category in adapter.categories().await? {
+        let category_id = if let Ok(existing) = database.get_category_from_name(&category.name).await {
+            existing.category_id
+        } else {
+            let category_id = database
+                .insert_category_and_get_id(&category.name)
+                .await
+                .map_err(|err| anyhow!("failed to import category {}: {err:?}", category.name))?;
+
+            report.categories += 1;
+
+            category_id
+        };
+
+        category_ids.insert(category.name, category_id);
+    }
+
+    Ok(category_ids)
+}
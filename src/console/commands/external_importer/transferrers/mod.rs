@@ -0,0 +1,23 @@
+//! They write the records read by the adapters into the Index database.
+//!
+//! Records that conflict with the data already in the Index, like existing
+//! usernames or torrents, are skipped and reported. Other errors abort the
+//! import.
+pub mod category_transferrer;
+pub mod tag_transferrer;
+pub mod torrent_transferrer;
+pub mod user_transferrer;
+
+/// The number of records imported and skipped.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub users: usize,
+    pub skipped_users: usize,
+    /// Users skipped because their password hash can't be verified by the
+    /// Index.
+    pub users_with_unsupported_password_hash: usize,
+    pub categories: usize,
+    pub tags: usize,
+    pub torrents: usize,
+    pub skipped_torrents: usize,
+}
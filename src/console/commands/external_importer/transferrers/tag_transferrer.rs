@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+
+use super::Report;
+use crate::console::commands::external_importer::adapters::SourceAdapter;
+use crate::databases::database::Database;
+use crate::models::torrent_tag::TagId;
+
+/// It imports the tags. Existing tags with the same name are reused.
+///
+/// # Errors
+///
+/// Will return an error if the tags can't be read or inserted.
+pub async fn transfer_tags(
+    adapter: &dyn SourceAdapter,
+    database: &dyn Database,
+    report: &mut Report,
+) -> anyhow::Result<HashMap<String, TagId>> {
+    println!("Transferring tags ...");
+
+    let mut tag_ids = HashMap::new();
+
+    for tag in adapter.tags().await? {
+        let tag_id = if let Ok(existing) = database.get_tag_from_name(&tag.name).await {
+            existing.tag_id
+        } else {
+            let tag_id = database
+                .insert_tag_and_get_id(&tag.name)
+                .await
+                .map_err(|err| anyhow!("failed to import tag {}: {err:?}", tag.name))?;
+
+            report.tags += 1;
+
+            tag_id
+        };
+
+        tag_ids.insert(tag.name, tag_id);
+    }
+
+    Ok(tag_ids)
+}
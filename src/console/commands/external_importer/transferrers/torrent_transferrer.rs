@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+
+use anyhow::anyhow;
+use tracing::warn;
+
+use super::Report;
+use crate::console::commands::external_importer::adapters::SourceAdapter;
+use crate::console::commands::external_importer::records::TorrentRecord;
+use crate::databases::database::{self, Database};
+use crate::models::category::CategoryId;
+use crate::models::torrent::Metadata;
+use crate::models::torrent_tag::TagId;
+use crate::models::user::UserId;
+use crate::services::content_type;
+use crate::utils::parse_torrent::decode_torrent;
+
+/// It imports the torrents of the imported users. Torrents without a
+/// category, with a missing or invalid torrent file, or already in the Index
+/// are skipped.
+///
+/// # Errors
+///
+/// Will return an error if the torrents can't be read or inserted.
+#[allow(clippy::implicit_hasher)]
+pub async fn transfer_torrents(
+    adapter: &dyn SourceAdapter,
+    database: &dyn Database,
+    user_ids: &HashMap<String, UserId>,
+    category_ids: &HashMap<String, CategoryId>,
+    tag_ids: &HashMap<String, TagId>,
+    report: &mut Report,
+) -> anyhow::Result<()> {
+    println!("Transferring torrents ...");
+
+    for record in adapter.torrents().await? {
+        let Some(uploader_id) = user_ids.get(&record.uploader) else {
+            warn!("Skipping torrent {}: unknown uploader {}", record.title, record.uploader);
+            report.skipped_torrents += 1;
+            continue;
+        };
+
+        let Some(category_id) = record.category.as_ref().and_then(|category| category_ids.get(category)) else {
+            warn!("Skipping torrent {}: it has no category", record.title);
+            report.skipped_torrents += 1;
+            continue;
+        };
+
+        let tags = record
+            .tags
+            .iter()
+            .filter_map(|tag| tag_ids.get(tag).copied())
+            .collect::<Vec<TagId>>();
+
+        if transfer_torrent(database, &record, *uploader_id, *category_id, tags).await? {
+            report.torrents += 1;
+        } else {
+            report.skipped_torrents += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// It returns `false` when the torrent is skipped.
+async fn transfer_torrent(
+    database: &dyn Database,
+    record: &TorrentRecord,
+    uploader_id: UserId,
+    category_id: CategoryId,
+    tags: Vec<TagId>,
+) -> anyhow::Result<bool> {
+    let insert_error = |err| anyhow!("failed to import torrent {}: {err:?}", record.title);
+
+    let torrent = match fs::read(&record.file) {
+        Ok(bytes) => match decode_torrent(&bytes) {
+            Ok(torrent) => torrent,
+            Err(err) => {
+                warn!(
+                    "Skipping torrent {}: invalid torrent file {}: {err}",
+                    record.title,
+                    record.file.display()
+                );
+                return Ok(false);
+            }
+        },
+        Err(err) => {
+            warn!(
+                "Skipping torrent {}: cannot read {}: {err}",
+                record.title,
+                record.file.display()
+            );
+            return Ok(false);
+        }
+    };
+
+    let metadata = Metadata {
+        title: record.title.clone(),
+        description: record.description.clone().unwrap_or_default(),
+        category_id,
+        tags,
+    };
+
+    let torrent_id = match database
+        .insert_torrent_and_get_id(&torrent.canonical_info_hash(), &torrent, uploader_id, &metadata)
+        .await
+    {
+        Ok(torrent_id) => torrent_id,
+        Err(database::Error::TorrentAlreadyExists | database::Error::TorrentTitleAlreadyExists) => {
+            warn!("Skipping torrent {}: the torrent or the title already exist", record.title);
+            return Ok(false);
+        }
+        Err(err) => return Err(insert_error(err)),
+    };
+
+    database
+        .update_torrent_file_types(torrent_id, &content_type::classify_torrent(&torrent))
+        .await
+        .map_err(insert_error)?;
+
+    if let Some(date_uploaded) = &record.date_uploaded {
+        database
+            .update_torrent_date_uploaded(torrent_id, date_uploaded)
+            .await
+            .map_err(insert_error)?;
+    }
+
+    Ok(true)
+}
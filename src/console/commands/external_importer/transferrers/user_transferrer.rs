@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use tracing::warn;
+
+use super::Report;
+use crate::console::commands::external_importer::adapters::SourceAdapter;
+use crate::databases::database::{self, Database};
+use crate::models::user::UserId;
+use crate::services::authentication::is_supported_password_hash;
+
+/// It imports the users with their original password hashes. Users whose
+/// username or email already exist in the Index, or whose password hash can't
+/// be verified by the Index, are skipped.
+///
+/// # Errors
+///
+/// Will return an error if the users can't be read or inserted.
+pub async fn transfer_users(
+    adapter: &dyn SourceAdapter,
+    database: &dyn Database,
+    report: &mut Report,
+) -> anyhow::Result<HashMap<String, UserId>> {
+    println!("Transferring users ...");
+
+    let mut user_ids = HashMap::new();
+
+    for user in adapter.users().await? {
+        // They would not be able to log in.
+        if !is_supported_password_hash(&user.password_hash) {
+            warn!("Skipping user {}: the Index can't verify their password hash", user.username);
+            report.users_with_unsupported_password_hash += 1;
+            continue;
+        }
+
+        let insert_error = |err| anyhow!("failed to import user {}: {err:?}", user.username);
+
+        let user_id = match database
+            .insert_user_and_get_id(&user.username, &user.email, &user.password_hash)
+            .await
+        {
+            Ok(user_id) => user_id,
+            Err(database::Error::UsernameTaken | database::Error::EmailTaken) => {
+                warn!("Skipping user {}: the username or the email already exist", user.username);
+                report.skipped_users += 1;
+                continue;
+            }
+            Err(err) => return Err(insert_error(err)),
+        };
+
+        if user.administrator {
+            database.grant_admin_role(user_id).await.map_err(insert_error)?;
+        }
+
+        if user.email_verified {
+            database.verify_email(user_id).await.map_err(insert_error)?;
+        }
+
+        if let Some(date_registered) = &user.date_registered {
+            database
+                .update_user_date_registered(user_id, date_registered)
+                .await
+                .map_err(insert_error)?;
+        }
+
+        report.users += 1;
+
+        user_ids.insert(user.username, user_id);
+    }
+
+    Ok(user_ids)
+}
@@ -1,3 +1,7 @@
 //! Console commands that can be run manually.
+pub mod backup;
+pub mod external_importer;
 pub mod seeder;
+pub mod torrent_classifier;
+pub mod torrent_importer;
 pub mod tracker_statistics_importer;
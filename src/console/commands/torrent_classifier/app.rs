@@ -0,0 +1,108 @@
+//! Console app to classify the files of the torrents already in the Index.
+//!
+//! New torrents are classified when they are uploaded. This command backfills
+//! the file types of the torrents uploaded before the classifier existed, or
+//! the ones that could not be classified on upload.
+//!
+//! Run with:
+//!
+//! ```text
+//! cargo run --bin classify_torrents -- [--all]
+//! ```
+//!
+//! By default, only torrents without file types are classified. With `--all`
+//! every torrent is classified again, for example, after adding new extensions
+//! to the classifier.
+//!
+//! See the [`content_type`](crate::services::content_type) service for the
+//! classification rules.
+use anyhow::anyhow;
+use clap::Parser;
+use text_colorizer::Colorize;
+use tracing::warn;
+
+use crate::bootstrap::config::initialize_configuration;
+use crate::bootstrap::logging;
+use crate::databases::database;
+use crate::services::content_type;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Classify all the torrents, including the ones already classified.
+    #[arg(long)]
+    all: bool,
+}
+
+/// # Errors
+///
+/// Will return an error if it can't connect to the database or load the
+/// torrents.
+pub async fn run() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let configuration = initialize_configuration();
+
+    let settings = configuration.settings.read().await;
+    let threshold = settings.logging.threshold.clone();
+    let database_connect_url = settings.database.connect_url.clone().to_string();
+    drop(settings);
+
+    logging::setup(&threshold);
+
+    let database = database::connect(&database_connect_url)
+        .await
+        .map_err(|err| anyhow!("failed to connect to the database: {err:?}"))?;
+
+    let torrents = database
+        .get_all_torrents_compact()
+        .await
+        .map_err(|err| anyhow!("failed to load torrents: {err:?}"))?;
+
+    println!(
+        "Classifying the files of {} torrents ...",
+        torrents.len().to_string().yellow()
+    );
+
+    let mut classified = 0;
+    let mut skipped = 0;
+    let mut failed = 0;
+
+    for torrent in torrents {
+        if !args.all {
+            match database.get_torrent_file_types_from_id(torrent.torrent_id).await {
+                Ok(file_types) if !file_types.is_empty() => {
+                    skipped += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        let result = match database.get_torrent_from_id(torrent.torrent_id).await {
+            Ok(meta_info) => {
+                database
+                    .update_torrent_file_types(torrent.torrent_id, &content_type::classify_torrent(&meta_info))
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(()) => classified += 1,
+            Err(err) => {
+                warn!("Unable to classify torrent {}: {err:?}", torrent.info_hash);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "Classified: {}, already classified: {}, failed: {}",
+        classified.to_string().green(),
+        skipped.to_string().yellow(),
+        failed.to_string().red()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,631 @@
+//! Console app to import a collection of torrent files into the Index.
+//!
+//! It walks a directory recursively and adds every `.torrent` file to the
+//! Index with the same rules used by the upload endpoint: torrent file
+//! validation, canonical info-hash checks, tag linking and whitelisting in the
+//! tracker.
+//!
+//! Run with:
+//!
+//! ```text
+//! cargo run --bin import_torrents -- \
+//!   --dir <DIR> \
+//!   --uploader <USERNAME> \
+//!   [--metadata <METADATA_FILE>] \
+//!   [--category <CATEGORY>] \
+//!   [--jobs <JOBS>] \
+//!   [--state-file <STATE_FILE>] \
+//!   [--dry-run]
+//! ```
+//!
+//! For example:
+//!
+//! ```text
+//! cargo run --bin import_torrents -- \
+//!   --dir ./storage/import \
+//!   --uploader admin \
+//!   --metadata ./storage/import/metadata.csv \
+//!   --category software \
+//!   --jobs 8
+//! ```
+//!
+//! The title, description, category and tags of each torrent are taken from
+//! the optional sidecar metadata file. See the [`metadata`](crate::console::commands::torrent_importer::metadata)
+//! module for the supported formats. When a torrent has no metadata, the title
+//! is the torrent file name without the `.torrent` extension and the category
+//! is the one passed with `--category`. Tags must already exist in the Index.
+//!
+//! The command uses the same configuration as the Index, so it connects to the
+//! same database and tracker.
+//!
+//! **Resuming imports**
+//!
+//! Every torrent file with a final result (imported, duplicate or invalid) is
+//! recorded in the state file (`<DIR>/.import_torrents.state` by default).
+//! Files in the state file are skipped, so an interrupted import can be
+//! resumed by running the same command again. Files that failed for other
+//! reasons, for example because the tracker was offline, are retried.
+//!
+//! **Dry run**
+//!
+//! With `--dry-run` nothing is written. The command only validates the torrent
+//! files and their metadata and reports the files that would be imported, the
+//! duplicates (already in the Index or repeated in the directory) and the
+//! invalid files.
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use clap::Parser;
+use futures::stream::{self, StreamExt};
+use text_colorizer::Colorize;
+use tracing::info;
+
+use super::metadata::{self, TorrentMetadata};
+use crate::bootstrap::config::initialize_configuration;
+use crate::bootstrap::logging;
+use crate::config::{Configuration, UploadPolicy};
+use crate::databases::database::{self, Database};
+use crate::errors::ServiceError;
+use crate::models::info_hash::InfoHash;
+use crate::models::torrent::Metadata;
+use crate::models::torrent_tag::TagId;
+use crate::models::user::UserId;
+use crate::services::authorization::{self, CasbinConfiguration, CasbinEnforcer};
+use crate::services::category::DbCategoryRepository;
+use crate::services::event_stream;
+use crate::services::notification::{self, DbNotificationRepository};
+use crate::services::tag::DbTagRepository;
+use crate::services::torrent::{
+    self, AddTorrentRequest, DbCanonicalInfoHashGroupRepository, DbTorrentAnnounceUrlRepository, DbTorrentFileRepository,
+    DbTorrentInfoRepository, DbTorrentListingGenerator, DbTorrentOriginalFileRepository, DbTorrentRepository,
+    DbTorrentTagRepository, DbTorrentWebSeedRepository,
+};
+use crate::services::upload_policy;
+use crate::services::user::{DbUserProfileRepository, DbUserRepository, Repository};
+use crate::services::webhook::{self, DbWebhookRepository};
+use crate::tracker::statistics_importer::StatisticsImporter;
+use crate::utils::parse_torrent::decode_and_validate_torrent_file;
+use crate::{mailer, tracker};
+
+const TORRENT_FILE_EXTENSION: &str = "torrent";
+const DEFAULT_STATE_FILE_NAME: &str = ".import_torrents.state";
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Directory with the torrent files. Subdirectories are also imported.
+    #[arg(short, long)]
+    dir: PathBuf,
+
+    /// Username of the user the torrents are uploaded as.
+    #[arg(short, long)]
+    uploader: String,
+
+    /// Optional sidecar JSON or CSV file with the torrents metadata.
+    #[arg(short, long)]
+    metadata: Option<PathBuf>,
+
+    /// Category for the torrents without a category in the metadata file.
+    #[arg(short, long)]
+    category: Option<String>,
+
+    /// Number of torrents imported concurrently.
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
+
+    /// File where processed torrent files are recorded to resume imports.
+    #[arg(short, long)]
+    state_file: Option<PathBuf>,
+
+    /// Validate the torrent files and report duplicates without importing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Result of importing one torrent file.
+#[derive(Debug)]
+enum Outcome {
+    Imported { info_hash: String },
+    WouldBeImported { info_hash: String },
+    Duplicate { reason: String },
+    Invalid { reason: String },
+    Failed { reason: String },
+}
+
+impl Outcome {
+    /// Only files with a final outcome are recorded in the state file. Failed
+    /// files are retried when the import is resumed.
+    fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Outcome::Imported { .. } | Outcome::Duplicate { .. } | Outcome::Invalid { .. }
+        )
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    imported: usize,
+    would_be_imported: usize,
+    skipped: usize,
+    duplicates: Vec<(String, String)>,
+    invalid: Vec<(String, String)>,
+    failed: Vec<(String, String)>,
+}
+
+impl Report {
+    fn add(&mut self, file: String, outcome: Outcome) {
+        match outcome {
+            Outcome::Imported { info_hash } => {
+                info!(target: "importer", "Imported {file} with info-hash {info_hash}");
+                self.imported += 1;
+            }
+            Outcome::WouldBeImported { info_hash } => {
+                info!(target: "importer", "{file} with info-hash {info_hash} would be imported");
+                self.would_be_imported += 1;
+            }
+            Outcome::Duplicate { reason } => self.duplicates.push((file, reason)),
+            Outcome::Invalid { reason } => self.invalid.push((file, reason)),
+            Outcome::Failed { reason } => self.failed.push((file, reason)),
+        }
+    }
+
+    fn print(&self, dry_run: bool) {
+        println!();
+
+        if dry_run {
+            println!("{}", "Dry run report".green().bold());
+            println!("Would be imported: {}", self.would_be_imported.to_string().yellow());
+        } else {
+            println!("{}", "Import report".green().bold());
+            println!("Imported: {}", self.imported.to_string().yellow());
+        }
+
+        println!("Skipped (already processed): {}", self.skipped.to_string().yellow());
+
+        print_files("Duplicates", &self.duplicates);
+        print_files("Invalid", &self.invalid);
+        print_files("Failed", &self.failed);
+    }
+}
+
+fn print_files(title: &str, files: &[(String, String)]) {
+    println!("{title}: {}", files.len().to_string().yellow());
+
+    for (file, reason) in files {
+        println!("  {file}: {reason}");
+    }
+}
+
+/// Shared context for all the import tasks.
+struct Importer {
+    dir: PathBuf,
+    dry_run: bool,
+    uploader_id: UserId,
+    uploader_is_admin: bool,
+    upload_policy: UploadPolicy,
+    /// The `source` the torrents are stamped with when the upload policy
+    /// rewrites it.
+    rewritten_source: Option<String>,
+    metadata: HashMap<String, TorrentMetadata>,
+    default_category: Option<String>,
+    tags: HashMap<String, TagId>,
+    torrent_index: Arc<torrent::Index>,
+    category_repository: Arc<DbCategoryRepository>,
+    canonical_info_hash_group_repository: Arc<DbCanonicalInfoHashGroupRepository>,
+    /// Canonical info-hashes found so far. It's only used in dry runs to
+    /// detect duplicates inside the imported directory.
+    seen_canonical_info_hashes: Mutex<HashSet<InfoHash>>,
+}
+
+impl Importer {
+    async fn import(&self, file: &str) -> Outcome {
+        let request = match self.build_request(file).await {
+            Ok(request) => request,
+            Err(outcome) => return outcome,
+        };
+
+        if self.dry_run {
+            return self.validate(request).await;
+        }
+
+        match self.torrent_index.add_torrent(request, Some(self.uploader_id)).await {
+            Ok(response) => Outcome::Imported {
+                info_hash: response.canonical_info_hash,
+            },
+            Err(err) => classify_error(&err),
+        }
+    }
+
+    async fn build_request(&self, file: &str) -> Result<AddTorrentRequest, Outcome> {
+        let metadata = self.metadata.get(file).cloned().unwrap_or_default();
+
+        let title = metadata.title.unwrap_or_else(|| default_title(file));
+
+        let Some(category_name) = metadata.category.or_else(|| self.default_category.clone()) else {
+            return Err(Outcome::Invalid {
+                reason: "missing category".to_string(),
+            });
+        };
+
+        let mut tags = vec![];
+
+        for tag_name in &metadata.tags {
+            match self.tags.get(tag_name) {
+                Some(tag_id) => tags.push(*tag_id),
+                None => {
+                    return Err(Outcome::Invalid {
+                        reason: format!("unknown tag `{tag_name}`"),
+                    })
+                }
+            }
+        }
+
+        let torrent_buffer = tokio::fs::read(self.dir.join(file)).await.map_err(|err| Outcome::Failed {
+            reason: format!("cannot read torrent file: {err}"),
+        })?;
+
+        Ok(AddTorrentRequest {
+            title,
+            description: metadata.description.unwrap_or_default(),
+            category_name,
+            tags,
+            torrent_buffer,
+        })
+    }
+
+    /// It applies the same checks as the `add_torrent` service without writing
+    /// anything.
+    async fn validate(&self, request: AddTorrentRequest) -> Outcome {
+        if let Err(err) = upload_policy::check_torrent_file_size(&self.upload_policy, request.torrent_buffer.len()) {
+            return classify_error(&err);
+        }
+
+        let Ok(category) = self.category_repository.get_by_name(&request.category_name).await else {
+            return classify_error(&ServiceError::InvalidCategory);
+        };
+
+        if let Err(err) = Metadata::new(&request.title, &request.description, category.category_id, &request.tags) {
+            return classify_error(&err.into());
+        }
+
+        if let Err(err) = upload_policy::check_category(&self.upload_policy, &request.category_name, self.uploader_is_admin) {
+            return classify_error(&err);
+        }
+
+        let (mut torrent, original_info_hash) = match decode_and_validate_torrent_file(&request.torrent_buffer) {
+            Ok(decoded) => decoded,
+            Err(err) => return classify_error(&err.into()),
+        };
+
+        if let Some(source) = &self.rewritten_source {
+            torrent.set_source(source);
+        }
+
+        if let Err(err) = upload_policy::check_torrent(&self.upload_policy, &torrent) {
+            return classify_error(&err);
+        }
+
+        let canonical_info_hash = torrent.canonical_info_hash();
+
+        match self
+            .canonical_info_hash_group_repository
+            .find_canonical_info_hash_for(&original_info_hash)
+            .await
+        {
+            Ok(Some(_)) => return classify_error(&ServiceError::OriginalInfoHashAlreadyExists),
+            Ok(None) => {}
+            Err(err) => return classify_error(&err.into()),
+        }
+
+        match self
+            .canonical_info_hash_group_repository
+            .get_canonical_info_hash_group(&canonical_info_hash)
+            .await
+        {
+            Ok(group) if !group.is_empty() => return classify_error(&ServiceError::CanonicalInfoHashAlreadyExists),
+            Ok(_) => {}
+            Err(err) => return classify_error(&err.into()),
+        }
+
+        let mut seen_canonical_info_hashes = self
+            .seen_canonical_info_hashes
+            .lock()
+            .expect("it should get a lock on the seen info-hashes");
+
+        if !seen_canonical_info_hashes.insert(canonical_info_hash) {
+            return Outcome::Duplicate {
+                reason: "another file in the import has the same canonical info-hash".to_string(),
+            };
+        }
+
+        Outcome::WouldBeImported {
+            info_hash: canonical_info_hash.to_hex_string(),
+        }
+    }
+}
+
+fn classify_error(err: &ServiceError) -> Outcome {
+    let reason = match err {
+        ServiceError::InvalidTorrentMetaInfo(violations) => {
+            violations.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ")
+        }
+        _ => err.to_string(),
+    };
+
+    match err {
+        ServiceError::InfoHashAlreadyExists
+        | ServiceError::CanonicalInfoHashAlreadyExists
+        | ServiceError::OriginalInfoHashAlreadyExists => Outcome::Duplicate { reason },
+        ServiceError::InvalidTorrentFile
+        | ServiceError::InvalidTorrentMetaInfo(_)
+        | ServiceError::InvalidTorrentPiecesLength
+        | ServiceError::InvalidTorrentTitleLength
+        | ServiceError::MissingMandatoryMetadataFields
+        | ServiceError::InvalidCategory
+        | ServiceError::InvalidTag
+        | ServiceError::TorrentTitleAlreadyExists
+        | ServiceError::TorrentFileTooLarge
+        | ServiceError::TorrentContentTooLarge
+        | ServiceError::TooManyFilesInTorrent
+        | ServiceError::PrivateTorrentRequired
+        | ServiceError::PrivateTorrentForbidden
+        | ServiceError::MissingRequiredTorrentSource
+        | ServiceError::FileExtensionNotAllowed
+        | ServiceError::CategoryNotAllowedForUpload => Outcome::Invalid { reason },
+        _ => Outcome::Failed { reason },
+    }
+}
+
+/// The default title is the torrent file name without the extension.
+fn default_title(file: &str) -> String {
+    Path::new(file)
+        .file_stem()
+        .map_or_else(|| file.to_string(), |stem| stem.to_string_lossy().to_string())
+}
+
+/// Import Torrents Command
+///
+/// # Errors
+///
+/// Will return an error if the arguments are not valid, the uploader does not
+/// exist or the state file can't be written.
+///
+/// # Panics
+///
+/// Panics if it can't connect to the database.
+pub async fn run() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let configuration = initialize_configuration();
+
+    let threshold = configuration.settings.read().await.logging.threshold.clone();
+
+    logging::setup(&threshold);
+
+    let metadata = match &args.metadata {
+        Some(path) => metadata::load(path).context("failed to load the metadata file")?,
+        None => HashMap::new(),
+    };
+
+    let files = torrent_files_in(&args.dir).context("failed to read the torrent files directory")?;
+
+    let state_file = args
+        .state_file
+        .clone()
+        .unwrap_or_else(|| args.dir.join(DEFAULT_STATE_FILE_NAME));
+
+    let processed = processed_files(&state_file).context("failed to read the state file")?;
+
+    let importer = Arc::new(build_importer(configuration, &args, metadata).await?);
+
+    if args.dry_run {
+        println!("Validating {} torrent files (dry run) ...", files.len().to_string().yellow());
+    } else {
+        println!("Importing {} torrent files ...", files.len().to_string().yellow());
+    }
+
+    let mut report = Report::default();
+
+    let (skipped, pending): (Vec<String>, Vec<String>) = files.into_iter().partition(|file| processed.contains(file));
+
+    report.skipped = skipped.len();
+
+    let mut state = if args.dry_run {
+        None
+    } else {
+        Some(
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&state_file)
+                .context("failed to open the state file")?,
+        )
+    };
+
+    let mut results = stream::iter(pending)
+        .map(|file| {
+            let importer = importer.clone();
+            async move {
+                let outcome = importer.import(&file).await;
+                (file, outcome)
+            }
+        })
+        .buffer_unordered(args.jobs.max(1));
+
+    while let Some((file, outcome)) = results.next().await {
+        if let Some(state) = state.as_mut() {
+            if outcome.is_final() {
+                writeln!(state, "{file}").context("failed to write the state file")?;
+            }
+        }
+
+        report.add(file, outcome);
+    }
+
+    report.print(args.dry_run);
+
+    Ok(())
+}
+
+async fn build_importer(
+    configuration: Configuration,
+    args: &Args,
+    metadata: HashMap<String, TorrentMetadata>,
+) -> anyhow::Result<Importer> {
+    let configuration = Arc::new(configuration);
+
+    let settings = configuration.settings.read().await;
+    let database_connect_url = settings.database.connect_url.clone().to_string();
+    let unstable = settings.unstable.clone();
+    let upload_policy = settings.upload_policy.clone();
+    let rewritten_source = upload_policy.rewrite_source.then(|| settings.website.name.clone());
+    drop(settings);
+
+    let database: Arc<Box<dyn Database>> = Arc::new(
+        database::connect(&database_connect_url)
+            .await
+            .expect("unable to connect to db"),
+    );
+
+    let uploader = DbUserProfileRepository::new(database.clone())
+        .get_user_profile_from_username(&args.uploader)
+        .await
+        .map_err(|_| anyhow::anyhow!("uploader `{}` not found", args.uploader))?;
+
+    let tags = DbTagRepository::new(database.clone())
+        .get_all()
+        .await
+        .map_err(|_| anyhow::anyhow!("failed to load the tags"))?
+        .into_iter()
+        .map(|tag| (tag.name, tag.tag_id))
+        .collect();
+
+    let user_repository: Arc<Box<dyn Repository>> = Arc::new(Box::new(DbUserRepository::new(database.clone())));
+
+    let uploader_is_admin = user_repository
+        .get_compact(&uploader.user_id)
+        .await
+        .map_err(|_| anyhow::anyhow!("uploader `{}` not found", args.uploader))?
+        .administrator;
+    let category_repository = Arc::new(DbCategoryRepository::new(database.clone()));
+    let canonical_info_hash_group_repository = Arc::new(DbCanonicalInfoHashGroupRepository::new(database.clone()));
+    let casbin_enforcer = Arc::new(
+        if let Some(casbin) = unstable
+            .as_ref()
+            .and_then(|u| u.auth.as_ref())
+            .and_then(|auth| auth.casbin.as_ref())
+        {
+            CasbinEnforcer::with_configuration(CasbinConfiguration::new(&casbin.model, &casbin.policy)).await
+        } else {
+            CasbinEnforcer::with_default_configuration().await
+        },
+    );
+    let authorization_service = Arc::new(authorization::Service::new(user_repository.clone(), casbin_enforcer));
+    let tracker_service = Arc::new(tracker::service::Service::new(configuration.clone(), database.clone()).await);
+    // Nobody is subscribed to the event stream of this process.
+    let event_broadcaster = Arc::new(event_stream::Broadcaster::new());
+    let tracker_statistics_importer = Arc::new(
+        StatisticsImporter::new(
+            configuration.clone(),
+            tracker_service.clone(),
+            database.clone(),
+            event_broadcaster.clone(),
+        )
+        .await,
+    );
+    // Notification emails are queued in the outbox and delivered by the
+    // running Index.
+    let mailer_service = Arc::new(mailer::Service::new(configuration.clone(), database.clone()).await);
+    let notification_dispatcher = Arc::new(notification::Dispatcher::new(
+        configuration.clone(),
+        mailer_service,
+        Arc::new(DbNotificationRepository::new(database.clone())),
+    ));
+    // Webhook deliveries are also sent by the running Index.
+    let webhook_publisher = Arc::new(webhook::Publisher::new(Arc::new(DbWebhookRepository::new(database.clone()))));
+
+    let torrent_index = Arc::new(torrent::Index::new(
+        configuration.clone(),
+        tracker_statistics_importer,
+        tracker_service,
+        user_repository,
+        category_repository.clone(),
+        Arc::new(DbTorrentRepository::new(database.clone())),
+        canonical_info_hash_group_repository.clone(),
+        Arc::new(DbTorrentInfoRepository::new(database.clone())),
+        Arc::new(DbTorrentFileRepository::new(database.clone())),
+        Arc::new(DbTorrentAnnounceUrlRepository::new(database.clone())),
+        Arc::new(DbTorrentWebSeedRepository::new(database.clone())),
+        Arc::new(DbTorrentOriginalFileRepository::new(configuration.clone(), database.clone())),
+        Arc::new(DbTorrentTagRepository::new(database.clone())),
+        Arc::new(DbTorrentListingGenerator::new(database.clone())),
+        authorization_service,
+        notification_dispatcher,
+        webhook_publisher,
+        event_broadcaster,
+    ));
+
+    Ok(Importer {
+        dir: args.dir.clone(),
+        dry_run: args.dry_run,
+        uploader_id: uploader.user_id,
+        uploader_is_admin,
+        upload_policy,
+        rewritten_source,
+        metadata,
+        default_category: args.category.clone(),
+        tags,
+        torrent_index,
+        category_repository,
+        canonical_info_hash_group_repository,
+        seen_canonical_info_hashes: Mutex::new(HashSet::new()),
+    })
+}
+
+/// It returns the paths of all the torrent files in the directory and its
+/// subdirectories, relative to the directory and sorted.
+fn torrent_files_in(dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut files = vec![];
+    let mut pending_dirs = vec![dir.to_path_buf()];
+
+    while let Some(current_dir) = pending_dirs.pop() {
+        for entry in fs::read_dir(&current_dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                pending_dirs.push(path);
+            } else if path.extension().is_some_and(|extension| extension == TORRENT_FILE_EXTENSION) {
+                let relative_path = path.strip_prefix(dir).unwrap_or(&path);
+
+                let components: Vec<String> = relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().to_string())
+                    .collect();
+
+                files.push(components.join("/"));
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// It returns the files already processed in a previous run.
+fn processed_files(state_file: &Path) -> std::io::Result<HashSet<String>> {
+    if !state_file.exists() {
+        return Ok(HashSet::new());
+    }
+
+    Ok(fs::read_to_string(state_file)?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect())
+}
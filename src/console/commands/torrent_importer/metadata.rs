@@ -0,0 +1,259 @@
+//! Sidecar file with the Index metadata for the imported torrents.
+//!
+//! The sidecar file is optional. It contains the title, description, category
+//! and tags for each torrent file. Torrent files are identified by their path
+//! relative to the imported directory.
+//!
+//! It can be a JSON file with an array of objects:
+//!
+//! ```json
+//! [
+//!   {
+//!     "file": "linux/ubuntu-22.04.iso.torrent",
+//!     "title": "Ubuntu 22.04",
+//!     "description": "Ubuntu desktop image",
+//!     "category": "software",
+//!     "tags": ["linux", "ubuntu"]
+//!   }
+//! ]
+//! ```
+//!
+//! Or a CSV file with a header. Tags are separated by `;`:
+//!
+//! ```text
+//! file,title,description,category,tags
+//! linux/ubuntu-22.04.iso.torrent,Ubuntu 22.04,"Ubuntu desktop image",software,linux;ubuntu
+//! ```
+//!
+//! Only the `file` column is mandatory. Missing values fall back to the
+//! defaults provided by the command arguments.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use derive_more::{Display, Error};
+use serde::Deserialize;
+
+const CSV_TAGS_SEPARATOR: char = ';';
+
+#[derive(Debug, Display, PartialEq, Eq, Error)]
+pub enum Error {
+    #[display(fmt = "cannot read the metadata file: {message}")]
+    CannotReadFile { message: String },
+
+    #[display(fmt = "unsupported metadata file extension. Use `.json` or `.csv`.")]
+    UnsupportedFileExtension,
+
+    #[display(fmt = "invalid JSON metadata file: {message}")]
+    InvalidJson { message: String },
+
+    #[display(fmt = "invalid CSV metadata file at line {line}: {message}")]
+    InvalidCsv { line: usize, message: String },
+}
+
+/// Index metadata for one torrent file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct TorrentMetadata {
+    pub file: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// It loads the sidecar metadata file. The result is indexed by the torrent
+/// file path relative to the imported directory.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be read or it has an invalid format.
+pub fn load(path: &Path) -> Result<HashMap<String, TorrentMetadata>, Error> {
+    let contents = fs::read_to_string(path).map_err(|err| Error::CannotReadFile {
+        message: err.to_string(),
+    })?;
+
+    let records = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => parse_json(&contents)?,
+        Some("csv") => parse_csv(&contents)?,
+        _ => return Err(Error::UnsupportedFileExtension),
+    };
+
+    Ok(records.into_iter().map(|record| (record.file.clone(), record)).collect())
+}
+
+fn parse_json(contents: &str) -> Result<Vec<TorrentMetadata>, Error> {
+    serde_json::from_str(contents).map_err(|err| Error::InvalidJson {
+        message: err.to_string(),
+    })
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<TorrentMetadata>, Error> {
+    let mut lines = contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Ok(vec![]);
+    };
+
+    let columns: Vec<String> = split_csv_line(header)
+        .map_err(|message| Error::InvalidCsv { line: 1, message })?
+        .into_iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+
+    if !columns.iter().any(|column| column == "file") {
+        return Err(Error::InvalidCsv {
+            line: 1,
+            message: "missing `file` column".to_string(),
+        });
+    }
+
+    let mut records = vec![];
+
+    for (index, line) in lines {
+        let line_number = index + 1;
+
+        let values = split_csv_line(line).map_err(|message| Error::InvalidCsv {
+            line: line_number,
+            message,
+        })?;
+
+        if values.len() != columns.len() {
+            return Err(Error::InvalidCsv {
+                line: line_number,
+                message: format!("expected {} values, got {}", columns.len(), values.len()),
+            });
+        }
+
+        let mut record = TorrentMetadata::default();
+
+        for (column, value) in columns.iter().zip(values) {
+            let value = value.trim().to_string();
+
+            match column.as_str() {
+                "file" => record.file = value,
+                "title" => record.title = non_empty(value),
+                "description" => record.description = non_empty(value),
+                "category" => record.category = non_empty(value),
+                "tags" => {
+                    record.tags = value
+                        .split(CSV_TAGS_SEPARATOR)
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(ToString::to_string)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// It splits a CSV line into values. Values can be quoted with `"`, and quotes
+/// inside quoted values are escaped with another quote (`""`).
+pub(crate) fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut values = vec![];
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) => {
+                if chars.peek() == Some(&'"') {
+                    value.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            ('"', false) if value.trim().is_empty() => {
+                value.clear();
+                in_quotes = true;
+            }
+            (',', false) => values.push(std::mem::take(&mut value)),
+            _ => value.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("unterminated quoted value".to_string());
+    }
+
+    values.push(value);
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_csv, parse_json, split_csv_line, TorrentMetadata};
+
+    #[test]
+    fn it_should_split_a_csv_line_with_quoted_values() {
+        assert_eq!(
+            split_csv_line(r#"a.torrent,"Title, with comma","He said ""hi""",,"#).unwrap(),
+            vec!["a.torrent", "Title, with comma", r#"He said "hi""#, "", ""]
+        );
+    }
+
+    #[test]
+    fn it_should_fail_splitting_a_csv_line_with_an_unterminated_quoted_value() {
+        assert!(split_csv_line(r#"a.torrent,"Title"#).is_err());
+    }
+
+    #[test]
+    fn it_should_parse_a_csv_metadata_file() {
+        let contents = "file,title,category,tags\na.torrent,Title A,software,linux; ubuntu\nb.torrent,,,\n";
+
+        assert_eq!(
+            parse_csv(contents).unwrap(),
+            vec![
+                TorrentMetadata {
+                    file: "a.torrent".to_string(),
+                    title: Some("Title A".to_string()),
+                    description: None,
+                    category: Some("software".to_string()),
+                    tags: vec!["linux".to_string(), "ubuntu".to_string()],
+                },
+                TorrentMetadata {
+                    file: "b.torrent".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_require_the_file_column_in_csv_metadata_files() {
+        assert!(parse_csv("title,category\nTitle A,software\n").is_err());
+    }
+
+    #[test]
+    fn it_should_parse_a_json_metadata_file() {
+        let contents = r#"[{"file": "a.torrent", "title": "Title A", "tags": ["linux"]}]"#;
+
+        assert_eq!(
+            parse_json(contents).unwrap(),
+            vec![TorrentMetadata {
+                file: "a.torrent".to_string(),
+                title: Some("Title A".to_string()),
+                description: None,
+                category: None,
+                tags: vec!["linux".to_string()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,3 @@
+//! Command to import a collection of torrent files into the Index.
+pub mod app;
+pub mod metadata;
@@ -30,6 +30,7 @@ use text_colorizer::Colorize;
 use crate::bootstrap::config::initialize_configuration;
 use crate::bootstrap::logging;
 use crate::databases::database;
+use crate::services::event_stream::Broadcaster;
 use crate::tracker::service::Service;
 use crate::tracker::statistics_importer::StatisticsImporter;
 
@@ -109,8 +110,10 @@ pub async fn import() {
     );
 
     let tracker_service = Arc::new(Service::new(cfg.clone(), database.clone()).await);
+    // Nobody is subscribed to the event stream of this process.
+    let event_broadcaster = Arc::new(Broadcaster::new());
     let tracker_statistics_importer =
-        Arc::new(StatisticsImporter::new(cfg.clone(), tracker_service.clone(), database.clone()).await);
+        Arc::new(StatisticsImporter::new(cfg.clone(), tracker_service.clone(), database.clone(), event_broadcaster).await);
 
     tracker_statistics_importer
         .import_all_torrents_statistics()
@@ -0,0 +1,51 @@
+//! Cronjob to deliver the emails in the outbox.
+//!
+//! Emails are added to the outbox by the [`mailer`](crate::mailer) service.
+//! This cronjob is woken up as soon as a new email is added and it also runs
+//! periodically to retry the failed deliveries when they are due. It also
+//! delivers the failed emails admins retry with the
+//! [`mail`](crate::web::api::server::v1::contexts::mail) API context.
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::mailer::Service;
+
+/// Interval between executions. Retries are never scheduled earlier than 30
+/// seconds after a failed delivery.
+const EXECUTION_INTERVAL_IN_SECS: u64 = 10;
+
+#[must_use]
+pub fn start(mailer_service: &Arc<Service>) -> JoinHandle<()> {
+    let weak_mailer_service = Arc::downgrade(mailer_service);
+    let new_mail = mailer_service.new_mail_notifier();
+
+    tokio::spawn(async move {
+        info!("Mail sender cronjob starting ...");
+
+        let execution_interval_duration = std::time::Duration::from_secs(EXECUTION_INTERVAL_IN_SECS);
+        let mut execution_interval = tokio::time::interval(execution_interval_duration);
+
+        info!("Running mail sender every {EXECUTION_INTERVAL_IN_SECS} seconds and when new emails are queued ...");
+
+        loop {
+            tokio::select! {
+                _ = execution_interval.tick() => {}
+                () = new_mail.notified() => {}
+            }
+
+            if let Some(mailer_service) = weak_mailer_service.upgrade() {
+                match mailer_service.deliver_due_mails().await {
+                    Ok(0) => {}
+                    Ok(delivered) => debug!("Delivered {delivered} emails"),
+                    Err(e) => error!("Failed to deliver the emails in the outbox: {:?}", e),
+                }
+
+                drop(mailer_service);
+            } else {
+                break;
+            }
+        }
+    })
+}
@@ -1,2 +1,6 @@
 //! Cronjobs that are executed automatically.
+pub mod mail_sender;
+pub mod tracker_key_cleaner;
 pub mod tracker_statistics_importer;
+pub mod webhook_sender;
+pub mod weekly_digest;
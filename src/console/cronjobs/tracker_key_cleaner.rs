@@ -0,0 +1,47 @@
+//! Cronjob to delete expired tracker keys from the database.
+//!
+//! A new tracker key is issued every time a user downloads a torrent and
+//! their current key is about to expire. Without this cronjob the
+//! `torrust_tracker_keys` table would grow forever. Expired keys are
+//! already rejected by the tracker, so only the Index database is cleaned.
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::tracker::service::Service;
+
+/// Interval between executions. Keys are issued with a lifetime of hours or
+/// days, so there is no need to run it more often.
+const EXECUTION_INTERVAL_IN_SECS: u64 = 3600;
+
+#[must_use]
+pub fn start(tracker_service: &Arc<Service>) -> JoinHandle<()> {
+    let weak_tracker_service = Arc::downgrade(tracker_service);
+
+    tokio::spawn(async move {
+        info!("Tracker key cleaner cronjob starting ...");
+
+        let execution_interval_duration = std::time::Duration::from_secs(EXECUTION_INTERVAL_IN_SECS);
+        let mut execution_interval = tokio::time::interval(execution_interval_duration);
+
+        info!("Running tracker key cleaner every {EXECUTION_INTERVAL_IN_SECS} seconds ...");
+
+        loop {
+            // The first tick is immediate, so expired keys are also deleted
+            // when the application starts.
+            execution_interval.tick().await;
+
+            if let Some(tracker_service) = weak_tracker_service.upgrade() {
+                match tracker_service.delete_expired_tracker_keys().await {
+                    Ok(deleted) => debug!("Deleted {deleted} expired tracker keys"),
+                    Err(e) => error!("Failed to delete expired tracker keys: {:?}", e),
+                }
+
+                drop(tracker_service);
+            } else {
+                break;
+            }
+        }
+    })
+}
@@ -0,0 +1,49 @@
+//! Cronjob to send the webhook deliveries.
+//!
+//! Deliveries are added by the [`webhook`](crate::services::webhook)
+//! publisher. This cronjob is woken up as soon as a new delivery is added and
+//! it also runs periodically to retry the failed deliveries when they are due.
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::services::webhook::Publisher;
+
+/// Interval between executions. Retries are never scheduled earlier than 30
+/// seconds after a failed delivery.
+const EXECUTION_INTERVAL_IN_SECS: u64 = 10;
+
+#[must_use]
+pub fn start(webhook_publisher: &Arc<Publisher>) -> JoinHandle<()> {
+    let weak_webhook_publisher = Arc::downgrade(webhook_publisher);
+    let new_delivery = webhook_publisher.new_delivery_notifier();
+
+    tokio::spawn(async move {
+        info!("Webhook sender cronjob starting ...");
+
+        let execution_interval_duration = std::time::Duration::from_secs(EXECUTION_INTERVAL_IN_SECS);
+        let mut execution_interval = tokio::time::interval(execution_interval_duration);
+
+        info!("Running webhook sender every {EXECUTION_INTERVAL_IN_SECS} seconds and when new deliveries are added ...");
+
+        loop {
+            tokio::select! {
+                _ = execution_interval.tick() => {}
+                () = new_delivery.notified() => {}
+            }
+
+            if let Some(webhook_publisher) = weak_webhook_publisher.upgrade() {
+                match webhook_publisher.deliver_due_deliveries().await {
+                    Ok(0) => {}
+                    Ok(delivered) => debug!("Sent {delivered} webhook deliveries"),
+                    Err(e) => error!("Failed to send the webhook deliveries: {:?}", e),
+                }
+
+                drop(webhook_publisher);
+            } else {
+                break;
+            }
+        }
+    })
+}
@@ -0,0 +1,45 @@
+//! Cronjob to send the weekly digest to the users who opted into it.
+//!
+//! The digest contains the most seeded torrents uploaded during the last
+//! week. Every user has their own schedule: the digest is sent when a week has
+//! passed since the last one they received.
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::services::notification::Dispatcher;
+
+/// Interval between executions. Users receive a digest at most one hour after
+/// it is due.
+const EXECUTION_INTERVAL_IN_SECS: u64 = 3600;
+
+#[must_use]
+pub fn start(notification_dispatcher: &Arc<Dispatcher>) -> JoinHandle<()> {
+    let weak_notification_dispatcher = Arc::downgrade(notification_dispatcher);
+
+    tokio::spawn(async move {
+        info!("Weekly digest cronjob starting ...");
+
+        let execution_interval_duration = std::time::Duration::from_secs(EXECUTION_INTERVAL_IN_SECS);
+        let mut execution_interval = tokio::time::interval(execution_interval_duration);
+
+        info!("Running weekly digest every {EXECUTION_INTERVAL_IN_SECS} seconds ...");
+
+        loop {
+            execution_interval.tick().await;
+
+            if let Some(notification_dispatcher) = weak_notification_dispatcher.upgrade() {
+                match notification_dispatcher.send_weekly_digests().await {
+                    Ok(0) => {}
+                    Ok(sent) => debug!("Queued {sent} weekly digests"),
+                    Err(e) => error!("Failed to send the weekly digests: {:?}", e),
+                }
+
+                drop(notification_dispatcher);
+            } else {
+                break;
+            }
+        }
+    })
+}
@@ -5,30 +5,45 @@ use url::Url;
 
 use crate::databases::mysql::Mysql;
 use crate::databases::sqlite::Sqlite;
-use crate::models::category::CategoryId;
+use crate::models::category::{CategoryId, TorrentFileFormat};
 use crate::models::info_hash::InfoHash;
+use crate::models::magnet_link::MagnetLink;
+use crate::models::mail::{FailedMail, Mail, MailId, OutboxMail};
+use crate::models::notification::NotificationPreferences;
 use crate::models::response::TorrentsResponse;
-use crate::models::torrent::{Metadata, TorrentListing};
-use crate::models::torrent_file::{DbTorrent, Torrent, TorrentFile};
+use crate::models::torrent::{Metadata, TorrentFileType, TorrentListing, TorrentVersion};
+use crate::models::torrent_file::{DbTorrent, DbTorrentOriginalFile, Torrent, TorrentFile};
 use crate::models::torrent_tag::{TagId, TorrentTag};
 use crate::models::tracker_key::TrackerKey;
 use crate::models::user::{User, UserAuthentication, UserCompact, UserId, UserProfile};
-use crate::services::torrent::CanonicalInfoHashGroup;
+use crate::models::webhook::{DeliveryId, DueWebhookDelivery, EventFilter, Webhook, WebhookDelivery, WebhookId};
+use crate::services::content_type::{extension_of, MediaKind};
+use crate::services::torrent::{CanonicalInfoHashGroup, OriginalTorrentFile};
 
 /// Database tables to be truncated when upgrading from v1.0.0 to v2.0.0.
 /// They must be in the correct order to avoid foreign key errors.
 pub const TABLES_TO_TRUNCATE: &[&str] = &[
+    "torrust_mail_outbox",
+    "torrust_webhook_deliveries",
+    "torrust_webhooks",
     "torrust_torrent_announce_urls",
+    "torrust_torrent_file_types",
     "torrust_torrent_files",
     "torrust_torrent_info",
+    "torrust_torrent_original_files",
     "torrust_torrent_tag_links",
     "torrust_torrent_tracker_stats",
+    "torrust_torrent_versions",
     "torrust_torrents",
     "torrust_tracker_keys",
     "torrust_user_authentication",
     "torrust_user_bans",
+    "torrust_user_followed_categories",
+    "torrust_user_followed_tags",
     "torrust_user_invitation_uses",
     "torrust_user_invitations",
+    "torrust_user_notification_preferences",
+    "torrust_user_pending_emails",
     "torrust_user_profiles",
     "torrust_user_public_keys",
     "torrust_users",
@@ -56,6 +71,9 @@ pub struct Category {
     pub category_id: i64,
     pub name: String,
     pub num_torrents: i64,
+    /// The [`TorrentFileFormat`](crate::models::category::TorrentFileFormat)
+    /// served for the torrents in the category.
+    pub torrent_file_format: String,
 }
 
 /// Sorting options for torrents.
@@ -89,6 +107,10 @@ pub enum Error {
     TorrentAlreadyExists, // when uploading an already uploaded info_hash
     TorrentTitleAlreadyExists,
     TorrentInfoHashNotFound,
+    TorrentMetaInfoNotAvailable, // when the torrent was listed from a magnet link
+    WebhookNotFound,
+    WebhookDeliveryNotFound,
+    FailedMailNotFound,
 }
 
 /// Get the Driver of the Database from the Connection String
@@ -118,6 +140,47 @@ pub async fn connect(db_path: &str) -> Result<Box<dyn Database>, Error> {
     })
 }
 
+/// It builds the SQL conditions to filter torrents by the types of files they
+/// contain. The result can be appended to the `WHERE` clause of a query on
+/// the `torrust_torrents tt` table.
+///
+/// Invalid content types and extensions are ignored, so user input is never
+/// included in the query.
+pub(crate) fn file_type_filter_query(content_types: Option<&[String]>, extensions: Option<&[String]>) -> String {
+    let mut conditions = vec![];
+
+    let content_types: Vec<String> = content_types
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|content_type| content_type.parse::<MediaKind>().ok())
+        .map(|kind| format!("'{kind}'"))
+        .collect();
+
+    if !content_types.is_empty() {
+        conditions.push(format!(
+            "AND EXISTS (SELECT 1 FROM torrust_torrent_file_types ft WHERE ft.torrent_id = tt.torrent_id AND ft.kind IN ({}))",
+            content_types.join(", ")
+        ));
+    }
+
+    let extensions: Vec<String> = extensions
+        .unwrap_or_default()
+        .iter()
+        .map(|extension| extension_of(&format!("file.{}", extension.trim_start_matches('.'))))
+        .filter(|extension| !extension.is_empty())
+        .map(|extension| format!("'{extension}'"))
+        .collect();
+
+    if !extensions.is_empty() {
+        conditions.push(format!(
+            "AND EXISTS (SELECT 1 FROM torrust_torrent_file_types ft WHERE ft.torrent_id = tt.torrent_id AND ft.extension IN ({}))",
+            extensions.join(", ")
+        ));
+    }
+
+    conditions.join(" ")
+}
+
 /// Trait for database implementations.
 #[async_trait]
 pub trait Database: Sync + Send {
@@ -143,12 +206,39 @@ pub trait Database: Sync + Send {
     /// Get `UserProfile` from `username`.
     async fn get_user_profile_from_username(&self, username: &str) -> Result<UserProfile, Error>;
 
+    /// Get `UserProfile` from the lowercase `username`, ignoring the case of
+    /// the stored username.
+    async fn get_user_profile_from_case_mapped_username(&self, case_mapped_username: &str) -> Result<UserProfile, Error>;
+
+    /// Change the `username` of the user with `user_id`.
+    async fn change_username(&self, user_id: i64, new_username: &str) -> Result<(), Error>;
+
+    /// Get `UserProfile` from `email`.
+    async fn get_user_profile_from_email(&self, email: &str) -> Result<UserProfile, Error>;
+
+    /// Set the email the user with `user_id` asked to change to, replacing
+    /// any previous pending change.
+    async fn set_user_pending_email(&self, user_id: i64, email: &str, date_requested: i64) -> Result<(), Error>;
+
+    /// Get the email the user with `user_id` asked to change to, if any.
+    async fn get_user_pending_email(&self, user_id: i64) -> Result<Option<String>, Error>;
+
+    /// Replace the email of the user with `user_id` with the confirmed
+    /// `email`, mark it as verified and remove the pending change.
+    async fn change_user_email(&self, user_id: i64, email: &str) -> Result<(), Error>;
+
     /// Get `UserCompact` from `user_id`.
     async fn get_user_compact_from_id(&self, user_id: i64) -> Result<UserCompact, Error>;
 
     /// Get a user's `TrackerKey`.
     async fn get_user_tracker_key(&self, user_id: i64) -> Option<TrackerKey>;
 
+    /// Get all the not expired `TrackerKey`s of a user, newest first.
+    async fn get_user_tracker_keys(&self, user_id: i64) -> Result<Vec<TrackerKey>, Error>;
+
+    /// Get all users ordered by `user_id`.
+    async fn get_users(&self) -> Result<Vec<User>, Error>;
+
     /// Get total user count.
     async fn count_users(&self) -> Result<i64, Error>;
 
@@ -161,9 +251,141 @@ pub trait Database: Sync + Send {
     /// Verify a user's email with `user_id`.
     async fn verify_email(&self, user_id: i64) -> Result<(), Error>;
 
+    /// Set the registration date of the user with `user_id`. The date format
+    /// is `%Y-%m-%d %H:%M:%S` (UTC). It's used to restore backups.
+    async fn update_user_date_registered(&self, user_id: i64, date_registered: &str) -> Result<(), Error>;
+
     /// Link a `TrackerKey` to a certain user with `user_id`.
     async fn add_tracker_key(&self, user_id: i64, tracker_key: &TrackerKey) -> Result<(), Error>;
 
+    /// Delete a `TrackerKey`.
+    async fn delete_tracker_key(&self, tracker_key: &str) -> Result<(), Error>;
+
+    /// Delete all the expired `TrackerKey`s and return how many were deleted.
+    async fn delete_expired_tracker_keys(&self) -> Result<u64, Error>;
+
+    /// Get the notification preferences of a user. Users who never changed
+    /// them get the default ones.
+    async fn get_user_notification_preferences(&self, user_id: UserId) -> Result<NotificationPreferences, Error>;
+
+    /// Get the IDs of the categories followed by a user.
+    async fn get_user_followed_categories(&self, user_id: UserId) -> Result<Vec<CategoryId>, Error>;
+
+    /// Get the IDs of the tags followed by a user.
+    async fn get_user_followed_tags(&self, user_id: UserId) -> Result<Vec<TagId>, Error>;
+
+    /// Replace the notification preferences and the categories and tags
+    /// followed by a user, all at once.
+    async fn update_user_notification_settings(
+        &self,
+        user_id: UserId,
+        preferences: &NotificationPreferences,
+        category_ids: &[CategoryId],
+        tag_ids: &[TagId],
+    ) -> Result<(), Error>;
+
+    /// Get the profiles of the users who want to be notified about new
+    /// torrents in the category or with any of the tags.
+    async fn get_new_torrent_subscribers(
+        &self,
+        category_id: Option<CategoryId>,
+        tag_ids: &[TagId],
+    ) -> Result<Vec<UserProfile>, Error>;
+
+    /// Get the profiles of the users who follow the tag and want to be
+    /// notified about new torrents.
+    async fn get_tag_followers(&self, tag_id: TagId) -> Result<Vec<UserProfile>, Error>;
+
+    /// Get the profiles of the users who want the weekly digest and didn't
+    /// get one since `date_last_digest` (unix timestamp).
+    async fn get_weekly_digest_subscribers(&self, date_last_digest: i64) -> Result<Vec<UserProfile>, Error>;
+
+    /// Record when a user got the last weekly digest (unix timestamp).
+    async fn update_user_date_last_digest(&self, user_id: UserId, date_last_digest: i64) -> Result<(), Error>;
+
+    /// Get the torrents uploaded since `date_uploaded` (`%Y-%m-%d %H:%M:%S`,
+    /// UTC) with the most seeders.
+    async fn get_top_torrents_uploaded_since(&self, date_uploaded: &str, limit: u8) -> Result<Vec<TorrentListing>, Error>;
+
+    /// Add an email to the outbox to be delivered as soon as possible and
+    /// return its `mail_id`.
+    async fn add_mail_to_outbox(&self, mail: &Mail) -> Result<MailId, Error>;
+
+    /// Get up to `limit` pending emails in the outbox whose next delivery
+    /// attempt is due, oldest first.
+    async fn get_due_outbox_mails(&self, limit: u32) -> Result<Vec<OutboxMail>, Error>;
+
+    /// Delete a delivered email from the outbox.
+    async fn delete_outbox_mail(&self, mail_id: MailId) -> Result<(), Error>;
+
+    /// Record a failed delivery attempt and schedule the next one at
+    /// `next_attempt_at` (unix timestamp).
+    async fn reschedule_outbox_mail(&self, mail_id: MailId, next_attempt_at: i64, error: &str) -> Result<(), Error>;
+
+    /// Record the last failed delivery attempt and stop retrying the email.
+    async fn fail_outbox_mail(&self, mail_id: MailId, error: &str) -> Result<(), Error>;
+
+    /// Get the emails in the outbox with the `failed` status, oldest first.
+    async fn get_failed_outbox_mails(&self) -> Result<Vec<FailedMail>, Error>;
+
+    /// Reset a failed email in the outbox to be delivered as soon as
+    /// possible, with all the delivery attempts again.
+    async fn retry_failed_outbox_mail(&self, mail_id: MailId) -> Result<(), Error>;
+
+    /// Add a new webhook and return its `webhook_id`.
+    async fn insert_webhook(&self, url: &str, secret: &str, events: &EventFilter) -> Result<WebhookId, Error>;
+
+    /// Get all the webhooks.
+    async fn get_webhooks(&self) -> Result<Vec<Webhook>, Error>;
+
+    /// Get a webhook from `webhook_id`.
+    async fn get_webhook(&self, webhook_id: WebhookId) -> Result<Webhook, Error>;
+
+    /// Update the URL, secret, events and state of a webhook.
+    async fn update_webhook(
+        &self,
+        webhook_id: WebhookId,
+        url: &str,
+        secret: &str,
+        events: &EventFilter,
+        active: bool,
+    ) -> Result<(), Error>;
+
+    /// Delete a webhook and its deliveries.
+    async fn delete_webhook(&self, webhook_id: WebhookId) -> Result<(), Error>;
+
+    /// Add a delivery for a webhook, due now, and return its `delivery_id`.
+    async fn add_webhook_delivery(&self, webhook_id: WebhookId, event: &str, payload: &str) -> Result<DeliveryId, Error>;
+
+    /// Get the latest deliveries of a webhook, newest first.
+    async fn get_webhook_deliveries(&self, webhook_id: WebhookId, limit: u32) -> Result<Vec<WebhookDelivery>, Error>;
+
+    /// Get the pending deliveries of active webhooks whose next attempt is due.
+    async fn get_due_webhook_deliveries(&self, limit: u32) -> Result<Vec<DueWebhookDelivery>, Error>;
+
+    /// Mark a delivery as delivered.
+    async fn complete_webhook_delivery(&self, delivery_id: DeliveryId, response_status: i64) -> Result<(), Error>;
+
+    /// Record a failed delivery attempt and schedule the next one.
+    async fn reschedule_webhook_delivery(
+        &self,
+        delivery_id: DeliveryId,
+        next_attempt_at: i64,
+        response_status: Option<i64>,
+        error: &str,
+    ) -> Result<(), Error>;
+
+    /// Record the last failed delivery attempt and stop retrying the delivery.
+    async fn fail_webhook_delivery(
+        &self,
+        delivery_id: DeliveryId,
+        response_status: Option<i64>,
+        error: &str,
+    ) -> Result<(), Error>;
+
+    /// Make a delivery pending again, due now, with no failed attempts.
+    async fn replay_webhook_delivery(&self, delivery_id: DeliveryId) -> Result<(), Error>;
+
     /// Delete user and all related user data with `user_id`.
     async fn delete_user(&self, user_id: i64) -> Result<(), Error>;
 
@@ -182,12 +404,23 @@ pub trait Database: Sync + Send {
     /// Delete category with `category_name`.
     async fn delete_category(&self, category_name: &str) -> Result<(), Error>;
 
-    /// Get results of a torrent search in a paginated and sorted form as `TorrentsResponse` from `search`, `categories`, `sort`, `offset` and `page_size`.
+    /// Update the torrent file format served for the torrents in the category with `category_name`.
+    async fn update_category_torrent_file_format(
+        &self,
+        category_name: &str,
+        torrent_file_format: TorrentFileFormat,
+    ) -> Result<(), Error>;
+
+    /// Get results of a torrent search in a paginated and sorted form as `TorrentsResponse` from `search`, `categories`, `tags`,
+    /// `content_types`, `extensions`, `sort`, `offset` and `page_size`.
+    #[allow(clippy::too_many_arguments)]
     async fn get_torrents_search_sorted_paginated(
         &self,
         search: &Option<String>,
         categories: &Option<Vec<String>>,
         tags: &Option<Vec<String>>,
+        content_types: &Option<Vec<String>>,
+        extensions: &Option<Vec<String>>,
         sort: &Sorting,
         offset: u64,
         page_size: u8,
@@ -202,16 +435,61 @@ pub trait Database: Sync + Send {
         metadata: &Metadata,
     ) -> Result<i64, Error>;
 
+    /// Add a torrent listed from a magnet link, without meta-info, and return
+    /// the newly inserted `torrent_id`.
+    async fn insert_magnet_torrent_and_get_id(
+        &self,
+        magnet_link: &MagnetLink,
+        uploader_id: UserId,
+        metadata: &Metadata,
+    ) -> Result<i64, Error>;
+
+    /// Replace the meta-info of the torrent with `torrent_id` keeping its
+    /// listing data (title, description, category and tags). The replaced
+    /// meta-info and its torrent file, if any, are added to the torrent
+    /// version history and the canonical info-hash group is reset to the new
+    /// info-hashes.
+    ///
+    /// The replaced torrent file is read in the same transaction. It's the
+    /// original torrent file when there is one, otherwise the torrent file
+    /// rebuilt from the database. Original torrent files that are not stored in
+    /// the database are taken from `stored_original_file`.
+    ///
+    /// It returns the new version number.
+    async fn replace_torrent_and_get_version(
+        &self,
+        torrent_id: i64,
+        original_info_hash: &InfoHash,
+        torrent: &Torrent,
+        stored_original_file: Option<&OriginalTorrentFile>,
+        user_id: UserId,
+    ) -> Result<i64, Error>;
+
+    /// Get the previous versions of a torrent meta-info, newest first.
+    async fn get_torrent_versions_from_id(&self, torrent_id: i64) -> Result<Vec<TorrentVersion>, Error>;
+
+    /// Replace the file types of the torrent with `torrent_id`.
+    async fn update_torrent_file_types(&self, torrent_id: i64, file_types: &[TorrentFileType]) -> Result<(), Error>;
+
+    /// Get the file types of the torrent with `torrent_id`, the largest first.
+    async fn get_torrent_file_types_from_id(&self, torrent_id: i64) -> Result<Vec<TorrentFileType>, Error>;
+
     /// Get `Torrent` from `InfoHash`.
     async fn get_torrent_from_info_hash(&self, info_hash: &InfoHash) -> Result<Torrent, Error> {
         let db_torrent = self.get_torrent_info_from_info_hash(info_hash).await?;
 
+        if db_torrent.magnet_only {
+            return Err(Error::TorrentMetaInfoNotAvailable);
+        }
+
         let torrent_files = self.get_torrent_files_from_id(db_torrent.torrent_id).await?;
 
         let torrent_announce_urls = self.get_torrent_announce_urls_from_id(db_torrent.torrent_id).await?;
 
         let torrent_http_seed_urls = self.get_torrent_http_seed_urls_from_id(db_torrent.torrent_id).await?;
 
+        let torrent_web_seed_urls = self.get_torrent_web_seed_urls_from_id(db_torrent.torrent_id).await?;
+
         let torrent_nodes = self.get_torrent_nodes_from_id(db_torrent.torrent_id).await?;
 
         Ok(Torrent::from_database(
@@ -219,6 +497,7 @@ pub trait Database: Sync + Send {
             &torrent_files,
             torrent_announce_urls,
             torrent_http_seed_urls,
+            torrent_web_seed_urls,
             torrent_nodes,
         ))
     }
@@ -227,12 +506,18 @@ pub trait Database: Sync + Send {
     async fn get_torrent_from_id(&self, torrent_id: i64) -> Result<Torrent, Error> {
         let db_torrent = self.get_torrent_info_from_id(torrent_id).await?;
 
+        if db_torrent.magnet_only {
+            return Err(Error::TorrentMetaInfoNotAvailable);
+        }
+
         let torrent_files = self.get_torrent_files_from_id(torrent_id).await?;
 
         let torrent_announce_urls = self.get_torrent_announce_urls_from_id(torrent_id).await?;
 
         let torrent_http_seed_urls = self.get_torrent_http_seed_urls_from_id(db_torrent.torrent_id).await?;
 
+        let torrent_web_seed_urls = self.get_torrent_web_seed_urls_from_id(db_torrent.torrent_id).await?;
+
         let torrent_nodes = self.get_torrent_nodes_from_id(db_torrent.torrent_id).await?;
 
         Ok(Torrent::from_database(
@@ -240,6 +525,7 @@ pub trait Database: Sync + Send {
             &torrent_files,
             torrent_announce_urls,
             torrent_http_seed_urls,
+            torrent_web_seed_urls,
             torrent_nodes,
         ))
     }
@@ -284,6 +570,29 @@ pub trait Database: Sync + Send {
     /// Get all torrent's HTTP seed urls as `Vec<Vec<String>>` from `torrent_id`.
     async fn get_torrent_http_seed_urls_from_id(&self, torrent_id: i64) -> Result<Vec<String>, Error>;
 
+    /// Get all torrent's web seed urls (BEP 19 `url-list`) as `Vec<String>` from `torrent_id`.
+    async fn get_torrent_web_seed_urls_from_id(&self, torrent_id: i64) -> Result<Vec<String>, Error>;
+
+    /// Replace the web seed urls (BEP 19 `url-list`) of the torrent with `torrent_id`.
+    async fn update_torrent_web_seed_urls(&self, torrent_id: i64, web_seed_urls: &[String]) -> Result<(), Error>;
+
+    /// Add or replace the original uploaded torrent file of the torrent with
+    /// `torrent_id`. `bytes` is `None` when the file is stored outside the
+    /// database.
+    async fn insert_torrent_original_file(
+        &self,
+        torrent_id: i64,
+        info_hash: &InfoHash,
+        backend: &str,
+        bytes: Option<&[u8]>,
+    ) -> Result<(), Error>;
+
+    /// Get the original uploaded torrent file of the torrent with `torrent_id`, if it was stored.
+    async fn get_torrent_original_file_from_id(&self, torrent_id: i64) -> Result<Option<DbTorrentOriginalFile>, Error>;
+
+    /// Delete the original uploaded torrent file of the torrent with `torrent_id`.
+    async fn delete_torrent_original_file(&self, torrent_id: i64) -> Result<(), Error>;
+
     /// Get all torrent's nodes as `Vec<(String, i64)>` from `torrent_id`.
     async fn get_torrent_nodes_from_id(&self, torrent_id: i64) -> Result<Vec<(String, i64)>, Error>;
 
@@ -312,6 +621,10 @@ pub trait Database: Sync + Send {
     /// Update a torrent's category with `torrent_id` and `category_id`.
     async fn update_torrent_category(&self, torrent_id: i64, category_id: CategoryId) -> Result<(), Error>;
 
+    /// Set the upload date of the torrent with `torrent_id`. The date format
+    /// is `%Y-%m-%d %H:%M:%S` (UTC). It's used to restore backups.
+    async fn update_torrent_date_uploaded(&self, torrent_id: i64, date_uploaded: &str) -> Result<(), Error>;
+
     /// Add a new tag.
     async fn insert_tag_and_get_id(&self, name: &str) -> Result<i64, Error>;
 
@@ -348,3 +661,28 @@ pub trait Database: Sync + Send {
     /// DELETES ALL DATABASE ROWS, ONLY CALL THIS IF YOU KNOW WHAT YOU'RE DOING!
     async fn delete_all_database_rows(&self) -> Result<(), Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::file_type_filter_query;
+
+    #[test]
+    fn it_should_not_filter_by_file_type_when_there_are_no_valid_filters() {
+        assert_eq!(file_type_filter_query(None, None), "");
+        assert_eq!(
+            file_type_filter_query(Some(&["unknown".to_string()]), Some(&["' OR 1=1 --".to_string()])),
+            ""
+        );
+    }
+
+    #[test]
+    fn it_should_filter_by_content_types_and_extensions() {
+        let filter_query = file_type_filter_query(
+            Some(&["video".to_string(), "Audio".to_string()]),
+            Some(&[".FLAC".to_string()]),
+        );
+
+        assert!(filter_query.contains("ft.kind IN ('video', 'audio')"));
+        assert!(filter_query.contains("ft.extension IN ('flac')"));
+    }
+}
@@ -4,25 +4,31 @@ use std::time::Duration;
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
-use sqlx::{query, query_as, Acquire, ConnectOptions, MySqlPool};
+use sqlx::{query, query_as, Acquire, ConnectOptions, Executor, MySqlConnection, MySqlPool};
 use url::Url;
 
 use super::database::TABLES_TO_TRUNCATE;
 use crate::databases::database;
-use crate::databases::database::{Category, Database, Driver, Sorting, TorrentCompact};
-use crate::models::category::CategoryId;
+use crate::databases::database::{file_type_filter_query, Category, Database, Driver, Sorting, TorrentCompact};
+use crate::models::category::{CategoryId, TorrentFileFormat};
 use crate::models::info_hash::InfoHash;
+use crate::models::magnet_link::MagnetLink;
+use crate::models::mail::{FailedMail, Mail, MailId, OutboxMail};
+use crate::models::notification::NotificationPreferences;
 use crate::models::response::TorrentsResponse;
-use crate::models::torrent::{Metadata, TorrentListing};
+use crate::models::torrent::{Metadata, TorrentFileType, TorrentListing, TorrentVersion};
 use crate::models::torrent_file::{
-    DbTorrent, DbTorrentAnnounceUrl, DbTorrentFile, DbTorrentHttpSeedUrl, DbTorrentNode, Torrent, TorrentFile,
+    DbTorrent, DbTorrentAnnounceUrl, DbTorrentFile, DbTorrentHttpSeedUrl, DbTorrentNode, DbTorrentOriginalFile,
+    DbTorrentWebSeedUrl, Torrent, TorrentFile,
 };
 use crate::models::torrent_tag::{TagId, TorrentTag};
 use crate::models::tracker_key::TrackerKey;
 use crate::models::user::{User, UserAuthentication, UserCompact, UserId, UserProfile};
-use crate::services::torrent::{CanonicalInfoHashGroup, DbTorrentInfoHash};
+use crate::models::webhook::{DeliveryId, DueWebhookDelivery, EventFilter, Webhook, WebhookDelivery, WebhookId};
+use crate::services::torrent::{CanonicalInfoHashGroup, DbTorrentInfoHash, OriginalTorrentFile};
 use crate::utils::clock::{self, datetime_now, DATETIME_FORMAT};
 use crate::utils::hex::from_bytes;
+use crate::utils::parse_torrent::encode_torrent;
 
 pub struct Mysql {
     pub pool: MySqlPool,
@@ -90,6 +96,8 @@ impl Database for Mysql {
             .await
             .map_err(|e| match e {
                 sqlx::Error::Database(err) => {
+                    // It's either the `username` column or the case-insensitive
+                    // `torrust_user_profiles_lower_username` index.
                     if err.message().contains("username") {
                         database::Error::UsernameTaken
                     } else if err.message().contains("email") {
@@ -155,6 +163,101 @@ impl Database for Mysql {
             .map_err(|_| database::Error::UserNotFound)
     }
 
+    async fn get_user_profile_from_case_mapped_username(
+        &self,
+        case_mapped_username: &str,
+    ) -> Result<UserProfile, database::Error> {
+        query_as::<_, UserProfile>(r#"SELECT user_id, username, COALESCE(email, "") as email, email_verified, COALESCE(bio, "") as bio, COALESCE(avatar, "") as avatar FROM torrust_user_profiles WHERE LOWER(username) = ?"#)
+            .bind(case_mapped_username)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| database::Error::UserNotFound)
+    }
+
+    async fn change_username(&self, user_id: i64, new_username: &str) -> Result<(), database::Error> {
+        query("UPDATE torrust_user_profiles SET username = ? WHERE user_id = ?")
+            .bind(new_username)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                // It's either the `username` column or the case-insensitive
+                // `torrust_user_profiles_lower_username` index.
+                sqlx::Error::Database(err) if err.message().contains("username") => database::Error::UsernameTaken,
+                _ => database::Error::Error,
+            })
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::UserNotFound)
+                }
+            })
+    }
+
+    async fn get_user_profile_from_email(&self, email: &str) -> Result<UserProfile, database::Error> {
+        query_as::<_, UserProfile>(r#"SELECT user_id, username, COALESCE(email, "") as email, email_verified, COALESCE(bio, "") as bio, COALESCE(avatar, "") as avatar FROM torrust_user_profiles WHERE email = ?"#)
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| database::Error::UserNotFound)
+    }
+
+    async fn set_user_pending_email(&self, user_id: i64, email: &str, date_requested: i64) -> Result<(), database::Error> {
+        query(
+            "INSERT INTO torrust_user_pending_emails (user_id, email, date_requested)
+            VALUES (?, ?, ?)
+            ON DUPLICATE KEY UPDATE
+            email = VALUES(email),
+            date_requested = VALUES(date_requested)",
+        )
+        .bind(user_id)
+        .bind(email)
+        .bind(date_requested)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_user_pending_email(&self, user_id: i64) -> Result<Option<String>, database::Error> {
+        query_as::<_, (String,)>("SELECT email FROM torrust_user_pending_emails WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map(|v| v.map(|(email,)| email))
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn change_user_email(&self, user_id: i64, email: &str) -> Result<(), database::Error> {
+        let mut tx = self.pool.begin().await.map_err(|_| database::Error::Error)?;
+
+        query("UPDATE torrust_user_profiles SET email = ?, email_verified = TRUE WHERE user_id = ?")
+            .bind(email)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(err) if err.message().contains("email") => database::Error::EmailTaken,
+                _ => database::Error::Error,
+            })
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::UserNotFound)
+                }
+            })?;
+
+        query("DELETE FROM torrust_user_pending_emails WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| database::Error::Error)?;
+
+        tx.commit().await.map_err(|_| database::Error::Error)
+    }
+
     async fn get_user_compact_from_id(&self, user_id: i64) -> Result<UserCompact, database::Error> {
         query_as::<_, UserCompact>("SELECT tu.user_id, tp.username, tu.administrator FROM torrust_users tu INNER JOIN torrust_user_profiles tp ON tu.user_id = tp.user_id WHERE tu.user_id = ?")
             .bind(user_id)
@@ -183,6 +286,35 @@ impl Database for Mysql {
             .ok()
     }
 
+    async fn get_user_tracker_keys(&self, user_id: i64) -> Result<Vec<TrackerKey>, database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query_as::<_, TrackerKey>(
+            "SELECT tracker_key AS 'key', date_expiry AS valid_until FROM torrust_tracker_keys WHERE user_id = ? AND date_expiry > ? ORDER BY date_expiry DESC",
+        )
+        .bind(user_id)
+        .bind(current_time)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_users(&self) -> Result<Vec<User>, database::Error> {
+        query_as::<_, User>(
+            "SELECT
+                user_id,
+                DATE_FORMAT(date_registered, '%Y-%m-%d %H:%i:%s') AS date_registered,
+                DATE_FORMAT(date_imported, '%Y-%m-%d %H:%i:%s') AS date_imported,
+                administrator
+            FROM torrust_users
+            ORDER BY user_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
     async fn count_users(&self) -> Result<i64, database::Error> {
         query_as("SELECT COUNT(*) FROM torrust_users")
             .fetch_one(&self.pool)
@@ -235,6 +367,22 @@ impl Database for Mysql {
             })
     }
 
+    async fn update_user_date_registered(&self, user_id: i64, date_registered: &str) -> Result<(), database::Error> {
+        query("UPDATE torrust_users SET date_registered = ? WHERE user_id = ?")
+            .bind(date_registered)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| database::Error::Error)
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::UserNotFound)
+                }
+            })
+    }
+
     async fn add_tracker_key(&self, user_id: i64, tracker_key: &TrackerKey) -> Result<(), database::Error> {
         let key = tracker_key.key.clone();
 
@@ -248,6 +396,547 @@ impl Database for Mysql {
             .map_err(|_| database::Error::Error)
     }
 
+    async fn delete_tracker_key(&self, tracker_key: &str) -> Result<(), database::Error> {
+        query("DELETE FROM torrust_tracker_keys WHERE tracker_key = ?")
+            .bind(tracker_key)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn delete_expired_tracker_keys(&self) -> Result<u64, database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query("DELETE FROM torrust_tracker_keys WHERE date_expiry <= ?")
+            .bind(current_time)
+            .execute(&self.pool)
+            .await
+            .map(|v| v.rows_affected())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_user_notification_preferences(&self, user_id: UserId) -> Result<NotificationPreferences, database::Error> {
+        query_as::<_, NotificationPreferences>(
+            "SELECT new_torrents, torrent_moderation, weekly_digest FROM torrust_user_notification_preferences WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map(Option::unwrap_or_default)
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_user_followed_categories(&self, user_id: UserId) -> Result<Vec<CategoryId>, database::Error> {
+        query_as::<_, (CategoryId,)>(
+            "SELECT category_id FROM torrust_user_followed_categories WHERE user_id = ? ORDER BY category_id ASC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map(|v| v.into_iter().map(|(category_id,)| category_id).collect())
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_user_followed_tags(&self, user_id: UserId) -> Result<Vec<TagId>, database::Error> {
+        query_as::<_, (TagId,)>("SELECT tag_id FROM torrust_user_followed_tags WHERE user_id = ? ORDER BY tag_id ASC")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map(|v| v.into_iter().map(|(tag_id,)| tag_id).collect())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn update_user_notification_settings(
+        &self,
+        user_id: UserId,
+        preferences: &NotificationPreferences,
+        category_ids: &[CategoryId],
+        tag_ids: &[TagId],
+    ) -> Result<(), database::Error> {
+        // start db transaction. It's rolled back when dropped without commit.
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        query(
+            "INSERT INTO torrust_user_notification_preferences (user_id, new_torrents, torrent_moderation, weekly_digest)
+            VALUES (?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE
+            new_torrents = VALUES(new_torrents),
+            torrent_moderation = VALUES(torrent_moderation),
+            weekly_digest = VALUES(weekly_digest)",
+        )
+        .bind(user_id)
+        .bind(preferences.new_torrents)
+        .bind(preferences.torrent_moderation)
+        .bind(preferences.weekly_digest)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        query("DELETE FROM torrust_user_followed_categories WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        for category_id in category_ids {
+            query("INSERT INTO torrust_user_followed_categories (user_id, category_id) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(category_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::CategoryNotFound)?;
+        }
+
+        query("DELETE FROM torrust_user_followed_tags WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        for tag_id in tag_ids {
+            query("INSERT INTO torrust_user_followed_tags (user_id, tag_id) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::TagNotFound)?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))
+    }
+
+    async fn get_new_torrent_subscribers(
+        &self,
+        category_id: Option<CategoryId>,
+        tag_ids: &[TagId],
+    ) -> Result<Vec<UserProfile>, database::Error> {
+        // Tag IDs are integers, so they can't be used to inject SQL.
+        let tag_filter_query = if tag_ids.is_empty() {
+            String::new()
+        } else {
+            let tag_ids = tag_ids.iter().map(ToString::to_string).collect::<Vec<String>>().join(", ");
+
+            format!(
+                "OR EXISTS (SELECT 1 FROM torrust_user_followed_tags ft WHERE ft.user_id = tp.user_id AND ft.tag_id IN ({tag_ids}))"
+            )
+        };
+
+        let query_string = format!(
+            "SELECT tp.* FROM torrust_user_profiles tp
+            INNER JOIN torrust_user_notification_preferences np ON tp.user_id = np.user_id
+            WHERE np.new_torrents = TRUE AND (
+                EXISTS (SELECT 1 FROM torrust_user_followed_categories fc WHERE fc.user_id = tp.user_id AND fc.category_id = ?)
+                {tag_filter_query}
+            )"
+        );
+
+        query_as::<_, UserProfile>(&query_string)
+            .bind(category_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_tag_followers(&self, tag_id: TagId) -> Result<Vec<UserProfile>, database::Error> {
+        query_as::<_, UserProfile>(
+            "SELECT tp.* FROM torrust_user_profiles tp
+            INNER JOIN torrust_user_notification_preferences np ON tp.user_id = np.user_id
+            INNER JOIN torrust_user_followed_tags ft ON tp.user_id = ft.user_id
+            WHERE np.new_torrents = TRUE AND ft.tag_id = ?",
+        )
+        .bind(tag_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_weekly_digest_subscribers(&self, date_last_digest: i64) -> Result<Vec<UserProfile>, database::Error> {
+        query_as::<_, UserProfile>(
+            "SELECT tp.* FROM torrust_user_profiles tp
+            INNER JOIN torrust_user_notification_preferences np ON tp.user_id = np.user_id
+            WHERE np.weekly_digest = TRUE AND (np.date_last_digest IS NULL OR np.date_last_digest <= ?)",
+        )
+        .bind(date_last_digest)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn update_user_date_last_digest(&self, user_id: UserId, date_last_digest: i64) -> Result<(), database::Error> {
+        query("UPDATE torrust_user_notification_preferences SET date_last_digest = ? WHERE user_id = ?")
+            .bind(date_last_digest)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_top_torrents_uploaded_since(
+        &self,
+        date_uploaded: &str,
+        limit: u8,
+    ) -> Result<Vec<TorrentListing>, database::Error> {
+        query_as::<_, TorrentListing>(
+            "SELECT
+            tt.torrent_id,
+            tp.username AS uploader,
+            tt.info_hash,
+            tt.info_hash_v2,
+            ti.title,
+            ti.description,
+            tt.category_id,
+            DATE_FORMAT(tt.date_uploaded, '%Y-%m-%d %H:%i:%s') AS date_uploaded,
+            tt.size AS file_size,
+            tt.name,
+            tt.comment,
+            tt.creation_date,
+            tt.created_by,
+            tt.`encoding`,
+            tt.magnet_only,
+            CAST(COALESCE(sum(ts.seeders),0) as signed) as seeders,
+            CAST(COALESCE(sum(ts.leechers),0) as signed) as leechers
+            FROM torrust_torrents tt
+            INNER JOIN torrust_user_profiles tp ON tt.uploader_id = tp.user_id
+            INNER JOIN torrust_torrent_info ti ON tt.torrent_id = ti.torrent_id
+            LEFT JOIN torrust_torrent_tracker_stats ts ON tt.torrent_id = ts.torrent_id
+            WHERE tt.date_uploaded >= ?
+            GROUP BY tt.torrent_id
+            ORDER BY seeders DESC, tt.date_uploaded DESC
+            LIMIT ?",
+        )
+        .bind(date_uploaded)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn add_mail_to_outbox(&self, mail: &Mail) -> Result<MailId, database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query(
+            "INSERT INTO torrust_mail_outbox (recipient, subject, body_plain, body_html, next_attempt_at, date_created)
+            VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(&mail.recipient)
+        .bind(&mail.subject)
+        .bind(&mail.body_plain)
+        .bind(&mail.body_html)
+        .bind(current_time)
+        .execute(&self.pool)
+        .await
+        .map(|v| i64::try_from(v.last_insert_id()).expect("last ID is larger than i64"))
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_due_outbox_mails(&self, limit: u32) -> Result<Vec<OutboxMail>, database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query_as::<_, OutboxMail>(
+            "SELECT mail_id, recipient, subject, body_plain, body_html, attempts FROM torrust_mail_outbox
+            WHERE status = 'pending' AND next_attempt_at <= ?
+            ORDER BY next_attempt_at ASC, mail_id ASC
+            LIMIT ?",
+        )
+        .bind(current_time)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn delete_outbox_mail(&self, mail_id: MailId) -> Result<(), database::Error> {
+        query("DELETE FROM torrust_mail_outbox WHERE mail_id = ?")
+            .bind(mail_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn reschedule_outbox_mail(&self, mail_id: MailId, next_attempt_at: i64, error: &str) -> Result<(), database::Error> {
+        query("UPDATE torrust_mail_outbox SET attempts = attempts + 1, next_attempt_at = ?, last_error = ? WHERE mail_id = ?")
+            .bind(next_attempt_at)
+            .bind(error)
+            .bind(mail_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn fail_outbox_mail(&self, mail_id: MailId, error: &str) -> Result<(), database::Error> {
+        query("UPDATE torrust_mail_outbox SET attempts = attempts + 1, status = 'failed', last_error = ? WHERE mail_id = ?")
+            .bind(error)
+            .bind(mail_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_failed_outbox_mails(&self) -> Result<Vec<FailedMail>, database::Error> {
+        query_as::<_, FailedMail>(
+            "SELECT mail_id, recipient, subject, attempts, last_error, DATE_FORMAT(date_created, '%Y-%m-%d %H:%i:%s') AS date_created
+            FROM torrust_mail_outbox
+            WHERE status = 'failed'
+            ORDER BY mail_id ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn retry_failed_outbox_mail(&self, mail_id: MailId) -> Result<(), database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query(
+            "UPDATE torrust_mail_outbox
+            SET status = 'pending', attempts = 0, next_attempt_at = ?, last_error = NULL
+            WHERE mail_id = ? AND status = 'failed'",
+        )
+        .bind(current_time)
+        .bind(mail_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+        .and_then(|v| {
+            if v.rows_affected() > 0 {
+                Ok(())
+            } else {
+                Err(database::Error::FailedMailNotFound)
+            }
+        })
+    }
+
+    async fn insert_webhook(&self, url: &str, secret: &str, events: &EventFilter) -> Result<WebhookId, database::Error> {
+        query(
+            "INSERT INTO torrust_webhooks (url, secret, events, date_created)
+            VALUES (?, ?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(url)
+        .bind(secret)
+        .bind(events.to_string())
+        .execute(&self.pool)
+        .await
+        .map(|v| i64::try_from(v.last_insert_id()).expect("last ID is larger than i64"))
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_webhooks(&self) -> Result<Vec<Webhook>, database::Error> {
+        query_as::<_, Webhook>(
+            "SELECT webhook_id, url, secret, events, active, DATE_FORMAT(date_created, '%Y-%m-%d %H:%i:%s') AS date_created
+            FROM torrust_webhooks
+            ORDER BY webhook_id ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_webhook(&self, webhook_id: WebhookId) -> Result<Webhook, database::Error> {
+        query_as::<_, Webhook>(
+            "SELECT webhook_id, url, secret, events, active, DATE_FORMAT(date_created, '%Y-%m-%d %H:%i:%s') AS date_created
+            FROM torrust_webhooks
+            WHERE webhook_id = ?",
+        )
+        .bind(webhook_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)?
+        .ok_or(database::Error::WebhookNotFound)
+    }
+
+    async fn update_webhook(
+        &self,
+        webhook_id: WebhookId,
+        url: &str,
+        secret: &str,
+        events: &EventFilter,
+        active: bool,
+    ) -> Result<(), database::Error> {
+        query("UPDATE torrust_webhooks SET url = ?, secret = ?, events = ?, active = ? WHERE webhook_id = ?")
+            .bind(url)
+            .bind(secret)
+            .bind(events.to_string())
+            .bind(active)
+            .bind(webhook_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| database::Error::Error)
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::WebhookNotFound)
+                }
+            })
+    }
+
+    async fn delete_webhook(&self, webhook_id: WebhookId) -> Result<(), database::Error> {
+        query("DELETE FROM torrust_webhooks WHERE webhook_id = ?")
+            .bind(webhook_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| database::Error::Error)
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::WebhookNotFound)
+                }
+            })
+    }
+
+    async fn add_webhook_delivery(
+        &self,
+        webhook_id: WebhookId,
+        event: &str,
+        payload: &str,
+    ) -> Result<DeliveryId, database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query(
+            "INSERT INTO torrust_webhook_deliveries (webhook_id, event, payload, next_attempt_at, date_created)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(webhook_id)
+        .bind(event)
+        .bind(payload)
+        .bind(current_time)
+        .execute(&self.pool)
+        .await
+        .map(|v| i64::try_from(v.last_insert_id()).expect("last ID is larger than i64"))
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_webhook_deliveries(&self, webhook_id: WebhookId, limit: u32) -> Result<Vec<WebhookDelivery>, database::Error> {
+        query_as::<_, WebhookDelivery>(
+            "SELECT delivery_id, webhook_id, event, payload, status, attempts, response_status, last_error,
+                DATE_FORMAT(date_created, '%Y-%m-%d %H:%i:%s') AS date_created, DATE_FORMAT(date_delivered, '%Y-%m-%d %H:%i:%s') AS date_delivered
+            FROM torrust_webhook_deliveries
+            WHERE webhook_id = ?
+            ORDER BY delivery_id DESC
+            LIMIT ?",
+        )
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn get_due_webhook_deliveries(&self, limit: u32) -> Result<Vec<DueWebhookDelivery>, database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query_as::<_, DueWebhookDelivery>(
+            "SELECT d.delivery_id, d.webhook_id, w.url, w.secret, d.event, d.payload, d.attempts
+            FROM torrust_webhook_deliveries d
+            JOIN torrust_webhooks w ON w.webhook_id = d.webhook_id
+            WHERE d.status = 'pending' AND d.next_attempt_at <= ? AND w.active = TRUE
+            ORDER BY d.next_attempt_at ASC, d.delivery_id ASC
+            LIMIT ?",
+        )
+        .bind(current_time)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn complete_webhook_delivery(&self, delivery_id: DeliveryId, response_status: i64) -> Result<(), database::Error> {
+        query(
+            "UPDATE torrust_webhook_deliveries
+            SET status = 'delivered', attempts = attempts + 1, response_status = ?, last_error = NULL, date_delivered = UTC_TIMESTAMP()
+            WHERE delivery_id = ?",
+        )
+        .bind(response_status)
+        .bind(delivery_id)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn reschedule_webhook_delivery(
+        &self,
+        delivery_id: DeliveryId,
+        next_attempt_at: i64,
+        response_status: Option<i64>,
+        error: &str,
+    ) -> Result<(), database::Error> {
+        query(
+            "UPDATE torrust_webhook_deliveries
+            SET attempts = attempts + 1, next_attempt_at = ?, response_status = ?, last_error = ?
+            WHERE delivery_id = ?",
+        )
+        .bind(next_attempt_at)
+        .bind(response_status)
+        .bind(error)
+        .bind(delivery_id)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn fail_webhook_delivery(
+        &self,
+        delivery_id: DeliveryId,
+        response_status: Option<i64>,
+        error: &str,
+    ) -> Result<(), database::Error> {
+        query(
+            "UPDATE torrust_webhook_deliveries
+            SET status = 'failed', attempts = attempts + 1, response_status = ?, last_error = ?
+            WHERE delivery_id = ?",
+        )
+        .bind(response_status)
+        .bind(error)
+        .bind(delivery_id)
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn replay_webhook_delivery(&self, delivery_id: DeliveryId) -> Result<(), database::Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let current_time = i64::try_from(clock::now()).unwrap();
+
+        query(
+            "UPDATE torrust_webhook_deliveries
+            SET status = 'pending', attempts = 0, next_attempt_at = ?, response_status = NULL, last_error = NULL, date_delivered = NULL
+            WHERE delivery_id = ?",
+        )
+        .bind(current_time)
+        .bind(delivery_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+        .and_then(|v| {
+            if v.rows_affected() > 0 {
+                Ok(())
+            } else {
+                Err(database::Error::WebhookDeliveryNotFound)
+            }
+        })
+    }
+
     async fn delete_user(&self, user_id: i64) -> Result<(), database::Error> {
         query("DELETE FROM torrust_users WHERE user_id = ?")
             .bind(user_id)
@@ -273,7 +962,7 @@ impl Database for Mysql {
     }
 
     async fn get_category_from_id(&self, category_id: i64) -> Result<Category, database::Error> {
-        query_as::<_, Category>("SELECT category_id, name, (SELECT COUNT(*) FROM torrust_torrents WHERE torrust_torrents.category_id = torrust_categories.category_id) AS num_torrents FROM torrust_categories WHERE category_id = ?")
+        query_as::<_, Category>("SELECT category_id, name, (SELECT COUNT(*) FROM torrust_torrents WHERE torrust_torrents.category_id = torrust_categories.category_id) AS num_torrents, torrent_file_format FROM torrust_categories WHERE category_id = ?")
             .bind(category_id)
             .fetch_one(&self.pool)
             .await
@@ -281,7 +970,7 @@ impl Database for Mysql {
     }
 
     async fn get_category_from_name(&self, category_name: &str) -> Result<Category, database::Error> {
-        query_as::<_, Category>("SELECT category_id, name, (SELECT COUNT(*) FROM torrust_torrents WHERE torrust_torrents.category_id = torrust_categories.category_id) AS num_torrents FROM torrust_categories WHERE name = ?")
+        query_as::<_, Category>("SELECT category_id, name, (SELECT COUNT(*) FROM torrust_torrents WHERE torrust_torrents.category_id = torrust_categories.category_id) AS num_torrents, torrent_file_format FROM torrust_categories WHERE name = ?")
             .bind(category_name)
             .fetch_one(&self.pool)
             .await
@@ -289,7 +978,7 @@ impl Database for Mysql {
     }
 
     async fn get_categories(&self) -> Result<Vec<Category>, database::Error> {
-        query_as::<_, Category>("SELECT tc.category_id, tc.name, COUNT(tt.category_id) as num_torrents FROM torrust_categories tc LEFT JOIN torrust_torrents tt on tc.category_id = tt.category_id GROUP BY tc.name")
+        query_as::<_, Category>("SELECT tc.category_id, tc.name, COUNT(tt.category_id) as num_torrents, tc.torrent_file_format FROM torrust_categories tc LEFT JOIN torrust_torrents tt on tc.category_id = tt.category_id GROUP BY tc.name")
             .fetch_all(&self.pool)
             .await
             .map_err(|_| database::Error::Error)
@@ -310,13 +999,36 @@ impl Database for Mysql {
             })
     }
 
+    async fn update_category_torrent_file_format(
+        &self,
+        category_name: &str,
+        torrent_file_format: TorrentFileFormat,
+    ) -> Result<(), database::Error> {
+        query("UPDATE torrust_categories SET torrent_file_format = ? WHERE name = ?")
+            .bind(torrent_file_format.to_string())
+            .bind(category_name)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| database::Error::Error)
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::CategoryNotFound)
+                }
+            })
+    }
+
     // todo: refactor this
     #[allow(clippy::too_many_lines)]
+    #[allow(clippy::too_many_arguments)]
     async fn get_torrents_search_sorted_paginated(
         &self,
         search: &Option<String>,
         categories: &Option<Vec<String>>,
         tags: &Option<Vec<String>>,
+        content_types: &Option<Vec<String>>,
+        extensions: &Option<Vec<String>>,
         sort: &Sorting,
         offset: u64,
         limit: u8,
@@ -385,11 +1097,14 @@ impl Database for Mysql {
             String::new()
         };
 
+        let file_type_filter_query = file_type_filter_query(content_types.as_deref(), extensions.as_deref());
+
         let mut query_string = format!(
             "SELECT
             tt.torrent_id,
             tp.username AS uploader,
             tt.info_hash,
+            tt.info_hash_v2,
             ti.title,
             ti.description,
             tt.category_id,
@@ -400,6 +1115,7 @@ impl Database for Mysql {
             tt.creation_date,
             tt.created_by,
             tt.`encoding`,
+            tt.magnet_only,
             CAST(COALESCE(sum(ts.seeders),0) as signed) as seeders,
             CAST(COALESCE(sum(ts.leechers),0) as signed) as leechers
             FROM torrust_torrents tt
@@ -408,13 +1124,20 @@ impl Database for Mysql {
             INNER JOIN torrust_user_profiles tp ON tt.uploader_id = tp.user_id
             INNER JOIN torrust_torrent_info ti ON tt.torrent_id = ti.torrent_id
             LEFT JOIN torrust_torrent_tracker_stats ts ON tt.torrent_id = ts.torrent_id
-            WHERE title LIKE ?
+            WHERE (
+                title LIKE ?
+                OR tt.name LIKE ?
+                OR EXISTS (SELECT 1 FROM torrust_torrent_files tf WHERE tf.torrent_id = tt.torrent_id AND tf.path LIKE ?)
+            )
+            {file_type_filter_query}
             GROUP BY tt.torrent_id"
         );
 
         let count_query = format!("SELECT COUNT(*) as count FROM ({query_string}) AS count_table");
 
         let count_result: Result<i64, database::Error> = query_as(&count_query)
+            .bind(title.clone())
+            .bind(title.clone())
             .bind(title.clone())
             .fetch_one(&self.pool)
             .await
@@ -426,6 +1149,8 @@ impl Database for Mysql {
         query_string = format!("{query_string} ORDER BY {sort_query} LIMIT ?, ?");
 
         let res: Vec<TorrentListing> = sqlx::query_as::<_, TorrentListing>(&query_string)
+            .bind(title.clone())
+            .bind(title.clone())
             .bind(title)
             .bind(i64::saturating_add_unsigned(0, offset))
             .bind(limit)
@@ -458,7 +1183,7 @@ impl Database for Mysql {
 
         // BEP 30: <http://www.bittorrent.org/beps/bep_0030.html>.
         // Torrent file can only hold a `pieces` key or a `root hash` key
-        let is_bep_30 = !matches!(&torrent.info.pieces, Some(_pieces));
+        let is_bep_30 = torrent.info.is_bep_30();
 
         let pieces = torrent.info.pieces.as_ref().map(|pieces| from_bytes(pieces.as_ref()));
 
@@ -481,13 +1206,17 @@ impl Database for Mysql {
             piece_length,
             private,
             is_bep_30,
+            meta_version,
+            info_hash_v2,
+            file_tree,
+            piece_layers,
             `source`,
             comment,
             date_uploaded,
             creation_date,
             created_by,
             `encoding`
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP(), ?, ?, ?)",
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP(), ?, ?, ?)",
         )
         .bind(uploader_id)
         .bind(metadata.category_id)
@@ -499,6 +1228,10 @@ impl Database for Mysql {
         .bind(torrent.info.piece_length)
         .bind(torrent.info.private)
         .bind(is_bep_30)
+        .bind(torrent.info.meta_version)
+        .bind(torrent.info_hash_v2().map(|info_hash| info_hash.to_hex_string()))
+        .bind(torrent.info.get_file_tree_as_string())
+        .bind(torrent.get_piece_layers_as_string())
         .bind(torrent.info.source.clone())
         .bind(torrent.comment.clone())
         .bind(torrent.creation_date)
@@ -550,19 +1283,23 @@ impl Database for Mysql {
                 .map(|_| ())
                 .map_err(|_| database::Error::Error)
         } else {
-            let files = torrent.info.files.as_ref().unwrap();
+            // v2-only torrents don't have the v1 `files` key. The files are
+            // taken from the v2 file tree.
+            let files = torrent.info.files.clone().unwrap_or_else(|| torrent.info.v2_files());
 
             for file in files {
                 let path = file.path.join("/");
 
-                let _ = query("INSERT INTO torrust_torrent_files (md5sum, torrent_id, length, path) VALUES (?, ?, ?, ?)")
-                    .bind(file.md5sum.clone())
-                    .bind(torrent_id)
-                    .bind(file.length)
-                    .bind(path)
-                    .execute(&mut *tx)
-                    .await
-                    .map_err(|_| database::Error::Error)?;
+                let _ =
+                    query("INSERT INTO torrust_torrent_files (md5sum, torrent_id, length, path, attr) VALUES (?, ?, ?, ?, ?)")
+                        .bind(file.md5sum.clone())
+                        .bind(torrent_id)
+                        .bind(file.length)
+                        .bind(path)
+                        .bind(file.attr.clone())
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|_| database::Error::Error)?;
             }
 
             Ok(())
@@ -626,84 +1363,509 @@ impl Database for Mysql {
             Ok(())
         };
 
-        // rollback transaction on error
-        if let Err(e) = insert_torrent_http_seeds_result {
-            drop(tx.rollback().await);
-            return Err(e);
-        }
+        // rollback transaction on error
+        if let Err(e) = insert_torrent_http_seeds_result {
+            drop(tx.rollback().await);
+            return Err(e);
+        }
+
+        // add web seeds
+
+        let insert_torrent_web_seeds_result: Result<(), database::Error> = if let Some(web_seeds) = &torrent.url_list {
+            for seed_url in web_seeds {
+                let () = query("INSERT INTO torrust_torrent_web_seeds (torrent_id, seed_url) VALUES (?, ?)")
+                    .bind(torrent_id)
+                    .bind(seed_url)
+                    .execute(&mut *tx)
+                    .await
+                    .map(|_| ())
+                    .map_err(|_| database::Error::Error)?;
+            }
+
+            Ok(())
+        } else {
+            Ok(())
+        };
+
+        // rollback transaction on error
+        if let Err(e) = insert_torrent_web_seeds_result {
+            drop(tx.rollback().await);
+            return Err(e);
+        }
+
+        // add nodes
+
+        let insert_torrent_nodes_result: Result<(), database::Error> = if let Some(nodes) = &torrent.nodes {
+            for node in nodes {
+                let () = query("INSERT INTO torrust_torrent_nodes (torrent_id, node_ip, node_port) VALUES (?, ?, ?)")
+                    .bind(torrent_id)
+                    .bind(node.0.clone())
+                    .bind(node.1)
+                    .execute(&mut *tx)
+                    .await
+                    .map(|_| ())
+                    .map_err(|_| database::Error::Error)?;
+            }
+
+            Ok(())
+        } else {
+            Ok(())
+        };
+
+        // rollback transaction on error
+        if let Err(e) = insert_torrent_nodes_result {
+            drop(tx.rollback().await);
+            return Err(e);
+        }
+
+        // add tags
+
+        for tag_id in &metadata.tags {
+            let insert_torrent_tag_result = query("INSERT INTO torrust_torrent_tag_links (torrent_id, tag_id) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|err| database::Error::ErrorWithText(err.to_string()));
+
+            // rollback transaction on error
+            if let Err(e) = insert_torrent_tag_result {
+                drop(tx.rollback().await);
+                return Err(e);
+            }
+        }
+
+        let insert_torrent_info_result =
+            query(r#"INSERT INTO torrust_torrent_info (torrent_id, title, description) VALUES (?, ?, NULLIF(?, ""))"#)
+                .bind(torrent_id)
+                .bind(metadata.title.clone())
+                .bind(metadata.description.clone())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::Database(err) => {
+                        tracing::error!("DB error: {:?}", err);
+                        if err.message().contains("Duplicate entry") && err.message().contains("title") {
+                            database::Error::TorrentTitleAlreadyExists
+                        } else {
+                            database::Error::Error
+                        }
+                    }
+                    _ => database::Error::Error,
+                });
+
+        // commit or rollback transaction and return user_id on success
+        match insert_torrent_info_result {
+            Ok(_) => {
+                drop(tx.commit().await);
+                Ok(torrent_id)
+            }
+            Err(e) => {
+                drop(tx.rollback().await);
+                Err(e)
+            }
+        }
+    }
+
+    async fn insert_magnet_torrent_and_get_id(
+        &self,
+        magnet_link: &MagnetLink,
+        uploader_id: UserId,
+        metadata: &Metadata,
+    ) -> Result<i64, database::Error> {
+        let info_hash = magnet_link.info_hash.to_hex_string();
+
+        let mut tx = self.pool.begin().await.map_err(|_| database::Error::Error)?;
+
+        // The name and size are unknown until the torrent file is uploaded.
+        let torrent_id = query(
+            "INSERT INTO torrust_torrents (
+            uploader_id,
+            category_id,
+            info_hash,
+            size,
+            name,
+            piece_length,
+            info_hash_v2,
+            magnet_only,
+            date_uploaded
+        ) VALUES (?, ?, ?, 0, ?, 0, ?, TRUE, UTC_TIMESTAMP())",
+        )
+        .bind(uploader_id)
+        .bind(metadata.category_id)
+        .bind(&info_hash)
+        .bind(magnet_link.display_name.clone().unwrap_or_else(|| info_hash.clone()))
+        .bind(magnet_link.info_hash_v2.map(|info_hash| info_hash.to_hex_string()))
+        .execute(&mut *tx)
+        .await
+        .map(|v| i64::try_from(v.last_insert_id()).expect("last ID is larger than i64"))
+        .map_err(|e| match e {
+            sqlx::Error::Database(err) => {
+                tracing::error!("DB error: {:?}", err);
+                if err.message().contains("Duplicate entry") && err.message().contains("info_hash") {
+                    database::Error::TorrentAlreadyExists
+                } else {
+                    database::Error::Error
+                }
+            }
+            _ => database::Error::Error,
+        })?;
+
+        query("INSERT INTO torrust_torrent_info_hashes (info_hash, canonical_info_hash, original_is_known) VALUES (?, ?, ?)")
+            .bind(&info_hash)
+            .bind(&info_hash)
+            .bind(true)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| database::Error::Error)?;
+
+        for tracker_url in &magnet_link.trackers {
+            query("INSERT INTO torrust_torrent_announce_urls (torrent_id, tracker_url) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(tracker_url)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::Error)?;
+        }
+
+        for seed_url in &magnet_link.web_seeds {
+            query("INSERT INTO torrust_torrent_web_seeds (torrent_id, seed_url) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(seed_url)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::Error)?;
+        }
+
+        for tag_id in &metadata.tags {
+            query("INSERT INTO torrust_torrent_tag_links (torrent_id, tag_id) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+        }
+
+        query(r#"INSERT INTO torrust_torrent_info (torrent_id, title, description) VALUES (?, ?, NULLIF(?, ""))"#)
+            .bind(torrent_id)
+            .bind(metadata.title.clone())
+            .bind(metadata.description.clone())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(err) => {
+                    tracing::error!("DB error: {:?}", err);
+                    if err.message().contains("Duplicate entry") && err.message().contains("title") {
+                        database::Error::TorrentTitleAlreadyExists
+                    } else {
+                        database::Error::Error
+                    }
+                }
+                _ => database::Error::Error,
+            })?;
+
+        tx.commit().await.map_err(|_| database::Error::Error)?;
+
+        Ok(torrent_id)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn replace_torrent_and_get_version(
+        &self,
+        torrent_id: i64,
+        original_info_hash: &InfoHash,
+        torrent: &Torrent,
+        stored_original_file: Option<&OriginalTorrentFile>,
+        user_id: UserId,
+    ) -> Result<i64, database::Error> {
+        let info_hash = torrent.canonical_info_hash_hex();
+        let canonical_info_hash = torrent.canonical_info_hash();
+
+        // open pool connection
+        let mut conn = self.pool.acquire().await.map_err(|_| database::Error::Error)?;
+
+        // start db transaction. It's rolled back when dropped without commit.
+        let mut tx = conn.begin().await.map_err(|_| database::Error::Error)?;
+
+        // save the current meta-info as a new entry in the version history
+
+        let (previous_info_hash, previous_name, previous_size) =
+            query_as::<_, (String, String, i64)>("SELECT info_hash, name, size FROM torrust_torrents WHERE torrent_id = ?")
+                .bind(torrent_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|_| database::Error::TorrentNotFound)?;
+
+        let (previous_versions,) = query_as::<_, (i64,)>("SELECT COUNT(*) FROM torrust_torrent_versions WHERE torrent_id = ?")
+            .bind(torrent_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|_| database::Error::Error)?;
+
+        // The first upload is version `1`
+        let replaced_version = previous_versions + 1;
+
+        let previous_torrent_file = fetch_replaced_torrent_file(&mut tx, torrent_id, stored_original_file).await?;
+
+        query(
+            "INSERT INTO torrust_torrent_versions (torrent_id, version, info_hash, name, size, torrent_file, replaced_by, date_replaced)
+            VALUES (?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(torrent_id)
+        .bind(replaced_version)
+        .bind(&previous_info_hash)
+        .bind(previous_name)
+        .bind(previous_size)
+        .bind(previous_torrent_file)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        // remove the previous canonical info-hash group. It references the
+        // current info-hash, so it must be deleted before changing it.
+
+        query("DELETE FROM torrust_torrent_info_hashes WHERE canonical_info_hash = ?")
+            .bind(&previous_info_hash)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| database::Error::Error)?;
+
+        // update torrent
+
+        let is_bep_30 = torrent.info.is_bep_30();
+
+        let pieces = torrent.info.pieces.as_ref().map(|pieces| from_bytes(pieces.as_ref()));
+
+        let root_hash = torrent
+            .info
+            .root_hash
+            .as_ref()
+            .map(|root_hash| from_bytes(root_hash.as_ref()));
+
+        query(
+            "UPDATE torrust_torrents SET
+            info_hash = ?,
+            size = ?,
+            name = ?,
+            pieces = ?,
+            root_hash = ?,
+            piece_length = ?,
+            private = ?,
+            is_bep_30 = ?,
+            meta_version = ?,
+            info_hash_v2 = ?,
+            file_tree = ?,
+            piece_layers = ?,
+            `source` = ?,
+            comment = ?,
+            creation_date = ?,
+            created_by = ?,
+            `encoding` = ?,
+            magnet_only = FALSE
+            WHERE torrent_id = ?",
+        )
+        .bind(info_hash.to_lowercase())
+        .bind(torrent.file_size())
+        .bind(torrent.info.name.clone())
+        .bind(pieces)
+        .bind(root_hash)
+        .bind(torrent.info.piece_length)
+        .bind(torrent.info.private)
+        .bind(is_bep_30)
+        .bind(torrent.info.meta_version)
+        .bind(torrent.info_hash_v2().map(|info_hash| info_hash.to_hex_string()))
+        .bind(torrent.info.get_file_tree_as_string())
+        .bind(torrent.get_piece_layers_as_string())
+        .bind(torrent.info.source.clone())
+        .bind(torrent.comment.clone())
+        .bind(torrent.creation_date)
+        .bind(torrent.created_by.clone())
+        .bind(torrent.encoding.clone())
+        .bind(torrent_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(err) => {
+                tracing::error!("DB error: {:?}", err);
+                if err.message().contains("Duplicate entry") && err.message().contains("info_hash") {
+                    database::Error::TorrentAlreadyExists
+                } else {
+                    database::Error::Error
+                }
+            }
+            _ => database::Error::Error,
+        })?;
+
+        // add the new canonical info-hash group
 
-        // add nodes
+        query("INSERT INTO torrust_torrent_info_hashes (info_hash, canonical_info_hash, original_is_known) VALUES (?, ?, ?)")
+            .bind(original_info_hash.to_hex_string())
+            .bind(canonical_info_hash.to_hex_string())
+            .bind(true)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(err) => {
+                    tracing::error!("DB error: {:?}", err);
+                    if err.message().contains("Duplicate entry") && err.message().contains("info_hash") {
+                        database::Error::TorrentAlreadyExists
+                    } else {
+                        database::Error::Error
+                    }
+                }
+                _ => database::Error::Error,
+            })?;
+
+        // replace torrent files, announce URLs, HTTP seeds, web seeds and nodes
+
+        for table in [
+            "torrust_torrent_files",
+            "torrust_torrent_announce_urls",
+            "torrust_torrent_http_seeds",
+            "torrust_torrent_web_seeds",
+            "torrust_torrent_nodes",
+        ] {
+            query(&format!("DELETE FROM {table} WHERE torrent_id = ?"))
+                .bind(torrent_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::Error)?;
+        }
 
-        let insert_torrent_nodes_result: Result<(), database::Error> = if let Some(nodes) = &torrent.nodes {
-            for node in nodes {
-                let () = query("INSERT INTO torrust_torrent_nodes (torrent_id, node_ip, node_port) VALUES (?, ?, ?)")
+        if let Some(length) = torrent.info.length {
+            query("INSERT INTO torrust_torrent_files (md5sum, torrent_id, length) VALUES (?, ?, ?)")
+                .bind(torrent.info.md5sum.clone())
+                .bind(torrent_id)
+                .bind(length)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::Error)?;
+        } else {
+            for file in torrent.info.files.clone().unwrap_or_else(|| torrent.info.v2_files()) {
+                query("INSERT INTO torrust_torrent_files (md5sum, torrent_id, length, path, attr) VALUES (?, ?, ?, ?, ?)")
+                    .bind(file.md5sum.clone())
                     .bind(torrent_id)
-                    .bind(node.0.clone())
-                    .bind(node.1)
+                    .bind(file.length)
+                    .bind(file.path.join("/"))
+                    .bind(file.attr.clone())
                     .execute(&mut *tx)
                     .await
-                    .map(|_| ())
                     .map_err(|_| database::Error::Error)?;
             }
+        }
 
-            Ok(())
-        } else {
-            Ok(())
+        let announce_urls = match &torrent.announce_list {
+            Some(announce_list) => announce_list.iter().flatten().collect::<Vec<&String>>(),
+            None => torrent.announce.iter().collect::<Vec<&String>>(),
         };
 
-        // rollback transaction on error
-        if let Err(e) = insert_torrent_nodes_result {
-            drop(tx.rollback().await);
-            return Err(e);
+        for tracker_url in announce_urls {
+            query("INSERT INTO torrust_torrent_announce_urls (torrent_id, tracker_url) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(tracker_url)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::Error)?;
         }
 
-        // add tags
-
-        for tag_id in &metadata.tags {
-            let insert_torrent_tag_result = query("INSERT INTO torrust_torrent_tag_links (torrent_id, tag_id) VALUES (?, ?)")
+        for seed_url in torrent.httpseeds.iter().flatten() {
+            query("INSERT INTO torrust_torrent_http_seeds (torrent_id, seed_url) VALUES (?, ?)")
                 .bind(torrent_id)
-                .bind(tag_id)
+                .bind(seed_url)
                 .execute(&mut *tx)
                 .await
-                .map_err(|err| database::Error::ErrorWithText(err.to_string()));
+                .map_err(|_| database::Error::Error)?;
+        }
 
-            // rollback transaction on error
-            if let Err(e) = insert_torrent_tag_result {
-                drop(tx.rollback().await);
-                return Err(e);
-            }
+        for seed_url in torrent.url_list.iter().flatten() {
+            query("INSERT INTO torrust_torrent_web_seeds (torrent_id, seed_url) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(seed_url)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| database::Error::Error)?;
         }
 
-        let insert_torrent_info_result =
-            query(r#"INSERT INTO torrust_torrent_info (torrent_id, title, description) VALUES (?, ?, NULLIF(?, ""))"#)
+        for node in torrent.nodes.iter().flatten() {
+            query("INSERT INTO torrust_torrent_nodes (torrent_id, node_ip, node_port) VALUES (?, ?, ?)")
                 .bind(torrent_id)
-                .bind(metadata.title.clone())
-                .bind(metadata.description.clone())
+                .bind(node.0.clone())
+                .bind(node.1)
                 .execute(&mut *tx)
                 .await
-                .map_err(|e| match e {
-                    sqlx::Error::Database(err) => {
-                        tracing::error!("DB error: {:?}", err);
-                        if err.message().contains("Duplicate entry") && err.message().contains("title") {
-                            database::Error::TorrentTitleAlreadyExists
-                        } else {
-                            database::Error::Error
-                        }
-                    }
-                    _ => database::Error::Error,
-                });
+                .map_err(|_| database::Error::Error)?;
+        }
 
-        // commit or rollback transaction and return user_id on success
-        match insert_torrent_info_result {
-            Ok(_) => {
-                drop(tx.commit().await);
-                Ok(torrent_id)
-            }
-            Err(e) => {
-                drop(tx.rollback().await);
-                Err(e)
-            }
+        // stats belong to the previous swarm
+
+        query("DELETE FROM torrust_torrent_tracker_stats WHERE torrent_id = ?")
+            .bind(torrent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| database::Error::Error)?;
+
+        tx.commit().await.map_err(|_| database::Error::Error)?;
+
+        Ok(replaced_version + 1)
+    }
+
+    async fn get_torrent_versions_from_id(&self, torrent_id: i64) -> Result<Vec<TorrentVersion>, database::Error> {
+        query_as::<_, TorrentVersion>(
+            "SELECT version, info_hash, name, size, replaced_by, DATE_FORMAT(date_replaced, '%Y-%m-%d %H:%i:%s') AS date_replaced
+            FROM torrust_torrent_versions
+            WHERE torrent_id = ?
+            ORDER BY version DESC",
+        )
+        .bind(torrent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
+    }
+
+    async fn update_torrent_file_types(&self, torrent_id: i64, file_types: &[TorrentFileType]) -> Result<(), database::Error> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        query("DELETE FROM torrust_torrent_file_types WHERE torrent_id = ?")
+            .bind(torrent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        for file_type in file_types {
+            query(
+                "INSERT INTO torrust_torrent_file_types (torrent_id, extension, kind, file_count, size) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(torrent_id)
+            .bind(&file_type.extension)
+            .bind(&file_type.kind)
+            .bind(file_type.file_count)
+            .bind(file_type.size)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
         }
+
+        tx.commit()
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))
+    }
+
+    async fn get_torrent_file_types_from_id(&self, torrent_id: i64) -> Result<Vec<TorrentFileType>, database::Error> {
+        query_as::<_, TorrentFileType>(
+            "SELECT kind, extension, file_count, size
+            FROM torrust_torrent_file_types
+            WHERE torrent_id = ?
+            ORDER BY size DESC, extension ASC",
+        )
+        .bind(torrent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| database::Error::Error)
     }
 
     async fn get_torrent_canonical_info_hash_group(
@@ -766,11 +1928,7 @@ impl Database for Mysql {
     }
 
     async fn get_torrent_info_from_id(&self, torrent_id: i64) -> Result<DbTorrent, database::Error> {
-        query_as::<_, DbTorrent>("SELECT * FROM torrust_torrents WHERE torrent_id = ?")
-            .bind(torrent_id)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|_| database::Error::TorrentNotFound)
+        fetch_torrent_info_from_id(&self.pool, torrent_id).await
     }
 
     async fn get_torrent_info_from_info_hash(&self, info_hash: &InfoHash) -> Result<DbTorrent, database::Error> {
@@ -782,55 +1940,81 @@ impl Database for Mysql {
     }
 
     async fn get_torrent_files_from_id(&self, torrent_id: i64) -> Result<Vec<TorrentFile>, database::Error> {
-        let db_torrent_files =
-            query_as::<_, DbTorrentFile>("SELECT md5sum, length, path FROM torrust_torrent_files WHERE torrent_id = ?")
-                .bind(torrent_id)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|_| database::Error::TorrentNotFound)?;
+        fetch_torrent_files_from_id(&self.pool, torrent_id).await
+    }
 
-        let torrent_files: Vec<TorrentFile> = db_torrent_files
-            .into_iter()
-            .map(|tf| TorrentFile {
-                path: tf
-                    .path
-                    .unwrap_or_default()
-                    .split('/')
-                    .map(std::string::ToString::to_string)
-                    .collect(),
-                length: tf.length,
-                md5sum: tf.md5sum,
-            })
-            .collect();
+    async fn get_torrent_announce_urls_from_id(&self, torrent_id: i64) -> Result<Vec<Vec<String>>, database::Error> {
+        fetch_torrent_announce_urls_from_id(&self.pool, torrent_id).await
+    }
 
-        Ok(torrent_files)
+    async fn get_torrent_http_seed_urls_from_id(&self, torrent_id: i64) -> Result<Vec<String>, database::Error> {
+        fetch_torrent_http_seed_urls_from_id(&self.pool, torrent_id).await
     }
 
-    async fn get_torrent_announce_urls_from_id(&self, torrent_id: i64) -> Result<Vec<Vec<String>>, database::Error> {
-        query_as::<_, DbTorrentAnnounceUrl>("SELECT tracker_url FROM torrust_torrent_announce_urls WHERE torrent_id = ?")
+    async fn get_torrent_web_seed_urls_from_id(&self, torrent_id: i64) -> Result<Vec<String>, database::Error> {
+        fetch_torrent_web_seed_urls_from_id(&self.pool, torrent_id).await
+    }
+
+    async fn update_torrent_web_seed_urls(&self, torrent_id: i64, web_seed_urls: &[String]) -> Result<(), database::Error> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        query("DELETE FROM torrust_torrent_web_seeds WHERE torrent_id = ?")
             .bind(torrent_id)
-            .fetch_all(&self.pool)
+            .execute(&mut *tx)
             .await
-            .map(|v| v.iter().map(|a| vec![a.tracker_url.to_string()]).collect())
-            .map_err(|_| database::Error::TorrentNotFound)
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+
+        for seed_url in web_seed_urls {
+            query("INSERT INTO torrust_torrent_web_seeds (torrent_id, seed_url) VALUES (?, ?)")
+                .bind(torrent_id)
+                .bind(seed_url)
+                .execute(&mut *tx)
+                .await
+                .map_err(|err| database::Error::ErrorWithText(err.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))
     }
 
-    async fn get_torrent_http_seed_urls_from_id(&self, torrent_id: i64) -> Result<Vec<String>, database::Error> {
-        query_as::<_, DbTorrentHttpSeedUrl>("SELECT seed_url FROM torrust_torrent_http_seeds WHERE torrent_id = ?")
+    async fn insert_torrent_original_file(
+        &self,
+        torrent_id: i64,
+        info_hash: &InfoHash,
+        backend: &str,
+        bytes: Option<&[u8]>,
+    ) -> Result<(), database::Error> {
+        query("REPLACE INTO torrust_torrent_original_files (torrent_id, info_hash, backend, bytes) VALUES (?, ?, ?, ?)")
             .bind(torrent_id)
-            .fetch_all(&self.pool)
+            .bind(info_hash.to_hex_string())
+            .bind(backend)
+            .bind(bytes)
+            .execute(&self.pool)
             .await
-            .map(|v| v.iter().map(|a| a.seed_url.to_string()).collect())
-            .map_err(|_| database::Error::TorrentNotFound)
+            .map(|_| ())
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))
     }
 
-    async fn get_torrent_nodes_from_id(&self, torrent_id: i64) -> Result<Vec<(String, i64)>, database::Error> {
-        query_as::<_, DbTorrentNode>("SELECT node_ip, node_port FROM torrust_torrent_nodes WHERE torrent_id = ?")
+    async fn get_torrent_original_file_from_id(&self, torrent_id: i64) -> Result<Option<DbTorrentOriginalFile>, database::Error> {
+        fetch_torrent_original_file_from_id(&self.pool, torrent_id).await
+    }
+
+    async fn delete_torrent_original_file(&self, torrent_id: i64) -> Result<(), database::Error> {
+        query("DELETE FROM torrust_torrent_original_files WHERE torrent_id = ?")
             .bind(torrent_id)
-            .fetch_all(&self.pool)
+            .execute(&self.pool)
             .await
-            .map(|v| v.iter().map(|a| (a.node_ip.to_string(), a.node_port)).collect())
-            .map_err(|_| database::Error::TorrentNotFound)
+            .map(|_| ())
+            .map_err(|err| database::Error::ErrorWithText(err.to_string()))
+    }
+
+    async fn get_torrent_nodes_from_id(&self, torrent_id: i64) -> Result<Vec<(String, i64)>, database::Error> {
+        fetch_torrent_nodes_from_id(&self.pool, torrent_id).await
     }
 
     async fn get_torrent_listing_from_id(&self, torrent_id: i64) -> Result<TorrentListing, database::Error> {
@@ -839,6 +2023,7 @@ impl Database for Mysql {
             tt.torrent_id,
             tp.username AS uploader,
             tt.info_hash,
+            tt.info_hash_v2,
             ti.title,
             ti.description,
             tt.category_id,
@@ -849,6 +2034,7 @@ impl Database for Mysql {
             tt.creation_date,
             tt.created_by,
             tt.`encoding`,
+            tt.magnet_only,
             CAST(COALESCE(sum(ts.seeders),0) as signed) as seeders,
             CAST(COALESCE(sum(ts.leechers),0) as signed) as leechers
             FROM torrust_torrents tt
@@ -870,6 +2056,7 @@ impl Database for Mysql {
             tt.torrent_id,
             tp.username AS uploader,
             tt.info_hash,
+            tt.info_hash_v2,
             ti.title,
             ti.description,
             tt.category_id,
@@ -880,6 +2067,7 @@ impl Database for Mysql {
             tt.creation_date,
             tt.created_by,
             tt.`encoding`,
+            tt.magnet_only,
             CAST(COALESCE(sum(ts.seeders),0) as signed) as seeders,
             CAST(COALESCE(sum(ts.leechers),0) as signed) as leechers
             FROM torrust_torrents tt
@@ -981,6 +2169,22 @@ impl Database for Mysql {
             })
     }
 
+    async fn update_torrent_date_uploaded(&self, torrent_id: i64, date_uploaded: &str) -> Result<(), database::Error> {
+        query("UPDATE torrust_torrents SET date_uploaded = ? WHERE torrent_id = ?")
+            .bind(date_uploaded)
+            .bind(torrent_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| database::Error::Error)
+            .and_then(|v| {
+                if v.rows_affected() > 0 {
+                    Ok(())
+                } else {
+                    Err(database::Error::TorrentNotFound)
+                }
+            })
+    }
+
     async fn insert_tag_and_get_id(&self, name: &str) -> Result<i64, database::Error> {
         query("INSERT INTO torrust_torrent_tags (name) VALUES (?)")
             .bind(name)
@@ -1132,3 +2336,148 @@ impl Database for Mysql {
         Ok(())
     }
 }
+
+// Queries used both on their own and inside the transaction that replaces the
+// torrent meta-info.
+
+async fn fetch_torrent_info_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<DbTorrent, database::Error> {
+    query_as::<_, DbTorrent>("SELECT * FROM torrust_torrents WHERE torrent_id = ?")
+        .bind(torrent_id)
+        .fetch_one(executor)
+        .await
+        .map_err(|_| database::Error::TorrentNotFound)
+}
+
+async fn fetch_torrent_files_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<Vec<TorrentFile>, database::Error> {
+    let db_torrent_files =
+        query_as::<_, DbTorrentFile>("SELECT md5sum, length, path, attr FROM torrust_torrent_files WHERE torrent_id = ?")
+            .bind(torrent_id)
+            .fetch_all(executor)
+            .await
+            .map_err(|_| database::Error::TorrentNotFound)?;
+
+    let torrent_files: Vec<TorrentFile> = db_torrent_files
+        .into_iter()
+        .map(|tf| TorrentFile {
+            path: tf
+                .path
+                .unwrap_or_default()
+                .split('/')
+                .map(std::string::ToString::to_string)
+                .collect(),
+            length: tf.length,
+            md5sum: tf.md5sum,
+            attr: tf.attr,
+        })
+        .collect();
+
+    Ok(torrent_files)
+}
+
+async fn fetch_torrent_announce_urls_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<Vec<Vec<String>>, database::Error> {
+    query_as::<_, DbTorrentAnnounceUrl>("SELECT tracker_url FROM torrust_torrent_announce_urls WHERE torrent_id = ?")
+        .bind(torrent_id)
+        .fetch_all(executor)
+        .await
+        .map(|v| v.iter().map(|a| vec![a.tracker_url.to_string()]).collect())
+        .map_err(|_| database::Error::TorrentNotFound)
+}
+
+async fn fetch_torrent_http_seed_urls_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<Vec<String>, database::Error> {
+    query_as::<_, DbTorrentHttpSeedUrl>("SELECT seed_url FROM torrust_torrent_http_seeds WHERE torrent_id = ?")
+        .bind(torrent_id)
+        .fetch_all(executor)
+        .await
+        .map(|v| v.iter().map(|a| a.seed_url.to_string()).collect())
+        .map_err(|_| database::Error::TorrentNotFound)
+}
+
+async fn fetch_torrent_web_seed_urls_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<Vec<String>, database::Error> {
+    query_as::<_, DbTorrentWebSeedUrl>("SELECT seed_url FROM torrust_torrent_web_seeds WHERE torrent_id = ?")
+        .bind(torrent_id)
+        .fetch_all(executor)
+        .await
+        .map(|v| v.into_iter().map(|a| a.seed_url).collect())
+        .map_err(|_| database::Error::TorrentNotFound)
+}
+
+async fn fetch_torrent_original_file_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<Option<DbTorrentOriginalFile>, database::Error> {
+    query_as::<_, DbTorrentOriginalFile>(
+        "SELECT torrent_id, info_hash, backend, bytes FROM torrust_torrent_original_files WHERE torrent_id = ?",
+    )
+    .bind(torrent_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| database::Error::ErrorWithText(err.to_string()))
+}
+
+async fn fetch_torrent_nodes_from_id<'e>(
+    executor: impl Executor<'e, Database = sqlx::MySql>,
+    torrent_id: i64,
+) -> Result<Vec<(String, i64)>, database::Error> {
+    query_as::<_, DbTorrentNode>("SELECT node_ip, node_port FROM torrust_torrent_nodes WHERE torrent_id = ?")
+        .bind(torrent_id)
+        .fetch_all(executor)
+        .await
+        .map(|v| v.iter().map(|a| (a.node_ip.to_string(), a.node_port)).collect())
+        .map_err(|_| database::Error::TorrentNotFound)
+}
+
+/// It gets the torrent file replaced by a new version of the meta-info: the
+/// original torrent file when the Index stores it, or the torrent file rebuilt
+/// from the database otherwise. Torrents without meta-info don't have one.
+///
+/// Original torrent files stored in the filesystem can't be read from the
+/// database, so they are only used when they belong to the stored record.
+async fn fetch_replaced_torrent_file(
+    conn: &mut MySqlConnection,
+    torrent_id: i64,
+    stored_original_file: Option<&OriginalTorrentFile>,
+) -> Result<Option<Vec<u8>>, database::Error> {
+    if let Some(original) = fetch_torrent_original_file_from_id(&mut *conn, torrent_id).await? {
+        if let Some(bytes) = original.bytes {
+            return Ok(Some(bytes));
+        }
+
+        if let Some(stored) = stored_original_file.filter(|stored| stored.info_hash.to_hex_string() == original.info_hash) {
+            return Ok(Some(stored.bytes.clone()));
+        }
+    }
+
+    let db_torrent = fetch_torrent_info_from_id(&mut *conn, torrent_id).await?;
+
+    if db_torrent.magnet_only {
+        return Ok(None);
+    }
+
+    let torrent = Torrent::from_database(
+        &db_torrent,
+        &fetch_torrent_files_from_id(&mut *conn, torrent_id).await?,
+        fetch_torrent_announce_urls_from_id(&mut *conn, torrent_id).await?,
+        fetch_torrent_http_seed_urls_from_id(&mut *conn, torrent_id).await?,
+        fetch_torrent_web_seed_urls_from_id(&mut *conn, torrent_id).await?,
+        fetch_torrent_nodes_from_id(&mut *conn, torrent_id).await?,
+    );
+
+    encode_torrent(&torrent)
+        .map(Some)
+        .map_err(|err| database::Error::ErrorWithText(err.to_string()))
+}
@@ -5,7 +5,9 @@ use derive_more::{Display, Error};
 use hyper::StatusCode;
 
 use crate::databases::database;
+use crate::models::magnet_link::ParseMagnetLinkError;
 use crate::models::torrent::MetadataError;
+use crate::services::torrent_file::CreateTorrentError;
 use crate::tracker::service::TrackerAPIError;
 use crate::utils::parse_torrent::DecodeTorrentFileError;
 
@@ -59,6 +61,23 @@ pub enum ServiceError {
     PasswordTooLong,
     #[display(fmt = "Passwords don't match")]
     PasswordsDontMatch,
+    #[display(fmt = "Password must contain a lowercase letter")]
+    PasswordMissingLowercase,
+    #[display(fmt = "Password must contain an uppercase letter")]
+    PasswordMissingUppercase,
+    #[display(fmt = "Password must contain a digit")]
+    PasswordMissingDigit,
+    #[display(fmt = "Password must contain a special character")]
+    PasswordMissingSpecialCharacter,
+    #[display(fmt = "Password is too easy to guess")]
+    PasswordTooWeak,
+    #[display(fmt = "Password can't contain the username")]
+    PasswordContainsUsername,
+    #[display(fmt = "Password can't contain the email")]
+    PasswordContainsEmail,
+    /// when the password is in the configured list of breached passwords
+    #[display(fmt = "Password has appeared in a data breach, please choose another one")]
+    PasswordBreached,
 
     /// when the a username is already taken
     #[display(fmt = "Username not available")]
@@ -74,6 +93,10 @@ pub enum ServiceError {
     #[display(fmt = "Please verify your email before logging in")]
     EmailNotVerified,
 
+    /// when the email change link is not for the last email change requested
+    #[display(fmt = "This email change is not pending anymore. Please request it again")]
+    EmailChangeNotPending,
+
     /// when the a token name is already taken
     /// token not found
     #[display(fmt = "Token not found. Please sign in.")]
@@ -90,6 +113,10 @@ pub enum ServiceError {
     #[display(fmt = "Uploaded torrent is not valid.")]
     InvalidTorrentFile,
 
+    /// It contains all the rules the uploaded torrent file violates.
+    #[display(fmt = "Uploaded torrent is not valid.")]
+    InvalidTorrentMetaInfo(#[error(not(source))] Vec<DecodeTorrentFileError>),
+
     #[display(fmt = "Uploaded torrent has an invalid pieces key.")]
     InvalidTorrentPiecesLength,
 
@@ -108,6 +135,48 @@ pub enum ServiceError {
     #[display(fmt = "Selected tag does not exist.")]
     InvalidTag,
 
+    #[display(fmt = "Web seeds must be valid HTTP or HTTPS URLs.")]
+    InvalidWebSeedUrl,
+
+    #[display(fmt = "Invalid magnet link.")]
+    InvalidMagnetLink,
+
+    #[display(fmt = "Piece length must be a power of two of at least 16 KiB.")]
+    InvalidTorrentPieceLength,
+
+    #[display(fmt = "The content of the torrent to create is not valid.")]
+    InvalidTorrentContent,
+
+    #[display(fmt = "The number of piece hashes does not match the content length.")]
+    TorrentContentPiecesMismatch,
+
+    #[display(fmt = "Uploaded torrent file exceeds the maximum allowed size.")]
+    TorrentFileTooLarge,
+
+    #[display(fmt = "Torrent content exceeds the maximum allowed size.")]
+    TorrentContentTooLarge,
+
+    #[display(fmt = "Torrent has more files than allowed.")]
+    TooManyFilesInTorrent,
+
+    #[display(fmt = "Too many torrents requested in a batch download.")]
+    TooManyTorrentsToDownload,
+
+    #[display(fmt = "Only private torrents (`private=1`) can be uploaded.")]
+    PrivateTorrentRequired,
+
+    #[display(fmt = "Private torrents (`private=1`) can't be uploaded.")]
+    PrivateTorrentForbidden,
+
+    #[display(fmt = "Torrent does not have the required `source` key in the `info` dictionary.")]
+    MissingRequiredTorrentSource,
+
+    #[display(fmt = "Torrent contains files with an extension that is not allowed.")]
+    FileExtensionNotAllowed,
+
+    #[display(fmt = "You are not allowed to upload torrents to the selected category.")]
+    CategoryNotAllowedForUpload,
+
     #[display(fmt = "Unauthorized action.")]
     UnauthorizedAction,
 
@@ -146,15 +215,39 @@ pub enum ServiceError {
     #[display(fmt = "Tag name cannot be empty.")]
     TagNameEmpty,
 
+    #[display(fmt = "Webhook URL must be an absolute HTTP or HTTPS URL.")]
+    InvalidWebhookUrl,
+
+    #[display(fmt = "Webhook secret cannot be empty.")]
+    WebhookSecretEmpty,
+
+    #[display(fmt = "Webhook must be subscribed to at least one event.")]
+    WebhookEventsEmpty,
+
     #[display(fmt = "Torrent not found.")]
     TorrentNotFound,
 
+    #[display(fmt = "Directory not found in torrent.")]
+    TorrentDirectoryNotFound,
+
+    #[display(fmt = "The torrent was listed from a magnet link. Its torrent file is not available yet.")]
+    TorrentMetaInfoNotAvailable,
+
     #[display(fmt = "Category not found.")]
     CategoryNotFound,
 
     #[display(fmt = "Tag not found.")]
     TagNotFound,
 
+    #[display(fmt = "Webhook not found.")]
+    WebhookNotFound,
+
+    #[display(fmt = "Webhook delivery not found.")]
+    WebhookDeliveryNotFound,
+
+    #[display(fmt = "Failed email not found.")]
+    FailedMailNotFound,
+
     #[display(fmt = "Database error.")]
     DatabaseError,
 
@@ -244,6 +337,28 @@ impl From<MetadataError> for ServiceError {
     }
 }
 
+impl From<CreateTorrentError> for ServiceError {
+    fn from(e: CreateTorrentError) -> Self {
+        eprintln!("{e}");
+        match e {
+            CreateTorrentError::InvalidPieceLength => ServiceError::InvalidTorrentPieceLength,
+            CreateTorrentError::PiecesMismatch => ServiceError::TorrentContentPiecesMismatch,
+            CreateTorrentError::InvalidName
+            | CreateTorrentError::InvalidFileLayout
+            | CreateTorrentError::InvalidFileLength
+            | CreateTorrentError::EmptyContent
+            | CreateTorrentError::InvalidPieceHash => ServiceError::InvalidTorrentContent,
+        }
+    }
+}
+
+impl From<ParseMagnetLinkError> for ServiceError {
+    fn from(e: ParseMagnetLinkError) -> Self {
+        eprintln!("{e}");
+        ServiceError::InvalidMagnetLink
+    }
+}
+
 impl From<DecodeTorrentFileError> for ServiceError {
     fn from(e: DecodeTorrentFileError) -> Self {
         eprintln!("{e}");
@@ -251,8 +366,27 @@ impl From<DecodeTorrentFileError> for ServiceError {
             DecodeTorrentFileError::InvalidTorrentPiecesLength => ServiceError::InvalidTorrentTitleLength,
             DecodeTorrentFileError::CannotBencodeInfoDict
             | DecodeTorrentFileError::InvalidInfoDictionary
-            | DecodeTorrentFileError::InvalidBencodeData => ServiceError::InvalidTorrentFile,
+            | DecodeTorrentFileError::InvalidBencodeData
+            | DecodeTorrentFileError::InvalidPieceLength
+            | DecodeTorrentFileError::PieceCountMismatch
+            | DecodeTorrentFileError::InvalidFileLayout
+            | DecodeTorrentFileError::NegativeFileLength
+            | DecodeTorrentFileError::UnsafeName
+            | DecodeTorrentFileError::UnsafeFilePath
+            | DecodeTorrentFileError::NameIsNotValidUtf8
+            | DecodeTorrentFileError::PathIsNotValidUtf8
+            | DecodeTorrentFileError::TooManyFiles
+            | DecodeTorrentFileError::PathTooDeep => ServiceError::InvalidTorrentFile,
+        }
+    }
+}
+
+impl From<Vec<DecodeTorrentFileError>> for ServiceError {
+    fn from(violations: Vec<DecodeTorrentFileError>) -> Self {
+        for violation in &violations {
+            eprintln!("{violation}");
         }
+        ServiceError::InvalidTorrentMetaInfo(violations)
     }
 }
 
@@ -266,8 +400,14 @@ impl From<TrackerAPIError> for ServiceError {
             TrackerAPIError::UnexpectedResponseStatus
             | TrackerAPIError::MissingResponseBody
             | TrackerAPIError::FailedToParseTrackerResponse { body: _ } => ServiceError::TrackerUnknownResponse,
-            TrackerAPIError::CannotSaveUserKey => ServiceError::DatabaseError,
+            TrackerAPIError::CannotSaveUserKey | TrackerAPIError::CannotLoadUserKeys | TrackerAPIError::CannotDeleteUserKey => {
+                ServiceError::DatabaseError
+            }
             TrackerAPIError::InvalidToken => ServiceError::InvalidTrackerToken,
+            TrackerAPIError::CannotRevokeUserKeys { errors } => errors
+                .into_iter()
+                .next()
+                .map_or(ServiceError::TrackerResponseError, ServiceError::from),
         }
     }
 }
@@ -290,21 +430,47 @@ pub fn http_status_code_for_service_error(error: &ServiceError) -> StatusCode {
         ServiceError::PasswordTooShort => StatusCode::BAD_REQUEST,
         ServiceError::PasswordTooLong => StatusCode::BAD_REQUEST,
         ServiceError::PasswordsDontMatch => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordMissingLowercase => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordMissingUppercase => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordMissingDigit => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordMissingSpecialCharacter => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordTooWeak => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordContainsUsername => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordContainsEmail => StatusCode::BAD_REQUEST,
+        ServiceError::PasswordBreached => StatusCode::BAD_REQUEST,
         ServiceError::UsernameTaken => StatusCode::BAD_REQUEST,
         ServiceError::UsernameInvalid => StatusCode::BAD_REQUEST,
         ServiceError::EmailTaken => StatusCode::BAD_REQUEST,
+        ServiceError::EmailChangeNotPending => StatusCode::BAD_REQUEST,
         ServiceError::EmailNotVerified => StatusCode::FORBIDDEN,
         ServiceError::TokenNotFound => StatusCode::UNAUTHORIZED,
         ServiceError::TokenExpired => StatusCode::UNAUTHORIZED,
         ServiceError::TokenInvalid => StatusCode::UNAUTHORIZED,
         ServiceError::TorrentNotFound => StatusCode::NOT_FOUND,
+        ServiceError::TorrentDirectoryNotFound => StatusCode::NOT_FOUND,
+        ServiceError::TorrentMetaInfoNotAvailable => StatusCode::NOT_FOUND,
         ServiceError::InvalidTorrentFile => StatusCode::BAD_REQUEST,
+        ServiceError::InvalidTorrentMetaInfo(_) => StatusCode::BAD_REQUEST,
         ServiceError::InvalidTorrentPiecesLength => StatusCode::BAD_REQUEST,
         ServiceError::InvalidFileType => StatusCode::BAD_REQUEST,
         ServiceError::InvalidTorrentTitleLength => StatusCode::BAD_REQUEST,
         ServiceError::MissingMandatoryMetadataFields => StatusCode::BAD_REQUEST,
         ServiceError::InvalidCategory => StatusCode::BAD_REQUEST,
         ServiceError::InvalidTag => StatusCode::BAD_REQUEST,
+        ServiceError::InvalidWebSeedUrl => StatusCode::BAD_REQUEST,
+        ServiceError::InvalidMagnetLink => StatusCode::BAD_REQUEST,
+        ServiceError::InvalidTorrentPieceLength => StatusCode::BAD_REQUEST,
+        ServiceError::InvalidTorrentContent => StatusCode::BAD_REQUEST,
+        ServiceError::TorrentContentPiecesMismatch => StatusCode::BAD_REQUEST,
+        ServiceError::TorrentFileTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        ServiceError::TorrentContentTooLarge => StatusCode::BAD_REQUEST,
+        ServiceError::TooManyFilesInTorrent => StatusCode::BAD_REQUEST,
+        ServiceError::TooManyTorrentsToDownload => StatusCode::BAD_REQUEST,
+        ServiceError::PrivateTorrentRequired => StatusCode::BAD_REQUEST,
+        ServiceError::PrivateTorrentForbidden => StatusCode::BAD_REQUEST,
+        ServiceError::MissingRequiredTorrentSource => StatusCode::BAD_REQUEST,
+        ServiceError::FileExtensionNotAllowed => StatusCode::BAD_REQUEST,
+        ServiceError::CategoryNotAllowedForUpload => StatusCode::FORBIDDEN,
         ServiceError::UnauthorizedAction => StatusCode::FORBIDDEN,
         ServiceError::UnauthorizedActionForGuests => StatusCode::UNAUTHORIZED,
         ServiceError::InfoHashAlreadyExists => StatusCode::BAD_REQUEST,
@@ -316,6 +482,9 @@ pub fn http_status_code_for_service_error(error: &ServiceError) -> StatusCode {
         ServiceError::CategoryAlreadyExists => StatusCode::BAD_REQUEST,
         ServiceError::TagNameEmpty => StatusCode::BAD_REQUEST,
         ServiceError::TagAlreadyExists => StatusCode::BAD_REQUEST,
+        ServiceError::InvalidWebhookUrl => StatusCode::BAD_REQUEST,
+        ServiceError::WebhookSecretEmpty => StatusCode::BAD_REQUEST,
+        ServiceError::WebhookEventsEmpty => StatusCode::BAD_REQUEST,
         ServiceError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
         ServiceError::EmailMissing => StatusCode::NOT_FOUND,
         ServiceError::FailedToSendVerificationEmail => StatusCode::INTERNAL_SERVER_ERROR,
@@ -323,6 +492,9 @@ pub fn http_status_code_for_service_error(error: &ServiceError) -> StatusCode {
         ServiceError::DatabaseError => StatusCode::INTERNAL_SERVER_ERROR,
         ServiceError::CategoryNotFound => StatusCode::NOT_FOUND,
         ServiceError::TagNotFound => StatusCode::NOT_FOUND,
+        ServiceError::WebhookNotFound => StatusCode::NOT_FOUND,
+        ServiceError::WebhookDeliveryNotFound => StatusCode::NOT_FOUND,
+        ServiceError::FailedMailNotFound => StatusCode::NOT_FOUND,
         ServiceError::TrackerResponseError => StatusCode::INTERNAL_SERVER_ERROR,
         ServiceError::TrackerUnknownResponse => StatusCode::INTERNAL_SERVER_ERROR,
         ServiceError::TorrentNotFoundInTracker => StatusCode::NOT_FOUND,
@@ -348,5 +520,9 @@ pub fn map_database_error_to_service_error(error: &database::Error) -> ServiceEr
         database::Error::TorrentTitleAlreadyExists => ServiceError::TorrentTitleAlreadyExists,
         database::Error::UnrecognizedDatabaseDriver => ServiceError::InternalServerError,
         database::Error::TorrentInfoHashNotFound => ServiceError::TorrentNotFound,
+        database::Error::TorrentMetaInfoNotAvailable => ServiceError::TorrentMetaInfoNotAvailable,
+        database::Error::WebhookNotFound => ServiceError::WebhookNotFound,
+        database::Error::WebhookDeliveryNotFound => ServiceError::WebhookDeliveryNotFound,
+        database::Error::FailedMailNotFound => ServiceError::FailedMailNotFound,
     }
 }
@@ -201,6 +201,13 @@
 //! [mail]
 //! from = "example@email.com"
 //! reply_to = "noreply@email.com"
+//! transport = "smtp"
+//!
+//! [mail.file]
+//! path = "./storage/index/lib/mail"
+//!
+//! [mail.sendmail]
+//! command = "sendmail"
 //!
 //! [mail.smtp]
 //! port = 25
@@ -220,10 +227,24 @@
 //! [api]
 //! default_torrent_page_size = 10
 //! max_torrent_page_size = 30
+//! max_torrents_per_batch_download = 50
 //!
 //! [tracker_statistics_importer]
 //! torrent_info_update_interval = 3600
 //! port = 3002
+//!
+//! [torrent_storage]
+//! backend = "database"
+//! path = "./storage/index/lib/torrents"
+//!
+//! [upload_policy]
+//! max_torrent_file_size = 10485760
+//! private_flag = "any"
+//! rewrite_source = false
+//! allowed_extensions = []
+//! forbidden_extensions = []
+//!
+//! [upload_policy.allowed_categories]
 //! ```
 //!
 //! For more information about configuration you can visit the documentation for the [`config`]) module.
@@ -259,10 +280,12 @@
 //! ## Upgrader
 //!
 //! This console command allows you to manually upgrade the application from one
-//! version to another.
+//! version to another. It detects the version of the database, shows what it
+//! would change with `--dry-run`, verifies the upgraded data and can resume a
+//! failed upgrade.
 //!
 //! For more information about this command you can visit the documentation for
-//! the [`Upgrade app from version 1.0.0 to 2.0.0`](crate::upgrades::from_v1_0_0_to_v2_0_0::upgrader) module.
+//! the [`Upgrader`](crate::upgrades::upgrader) module.
 //!
 //! # Contributing
 //!
@@ -1,36 +1,142 @@
+//! Outbound emails.
+//!
+//! Emails are not sent while handling the request that triggers them. The
+//! [`Service`] renders them with [Tera](https://keats.github.io/tera/)
+//! templates and adds them to the outbox table in the database. The
+//! [`mail_sender`](crate::console::cronjobs::mail_sender) cronjob delivers
+//! them in the background with the configured [`Transport`].
+//!
+//! When a delivery fails, it's retried later with an exponential backoff.
+//! After [`MAX_DELIVERY_ATTEMPTS`] failed attempts the email is kept in the
+//! outbox with the `failed` status (dead letter) and the error is logged.
+//! Admins can list and retry those emails with the
+//! [`mail`](crate::web::api::server::v1::contexts::mail) API context.
+//!
+//! Every message type has an HTML (`<name>.html`) and a plain text
+//! (`<name>.txt`) template. The notification and email change HTML templates
+//! extend `layout.html`. The built-in templates are in the `templates`
+//! directory of the repository and they are embedded in the binary. They can
+//! be overridden with the `mail.templates_path` option.
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use jsonwebtoken::{encode, EncodingKey, Header};
-use lazy_static::lazy_static;
-use lettre::message::{MessageBuilder, MultiPart, SinglePart};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::{Credentials, Mechanism};
-use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use lettre::{AsyncFileTransport, AsyncSendmailTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 use serde::{Deserialize, Serialize};
 use serde_json::value::{to_value, Value};
 use tera::{try_get_value, Context, Tera};
+use tokio::sync::Notify;
+use tracing::{debug, error, warn};
 
-use crate::config::Configuration;
+use crate::config::{Configuration, MailTransport};
+use crate::databases::database::{self, Database};
 use crate::errors::ServiceError;
-use crate::utils::clock;
+use crate::models::mail::{Mail, OutboxMail};
+use crate::utils::{backoff, clock};
 use crate::web::api::server::v1::routes::API_VERSION_URL_PREFIX;
 
-lazy_static! {
-    pub static ref TEMPLATES: Tera = {
-        let mut tera = Tera::default();
+/// Failed deliveries before giving up on an email.
+pub const MAX_DELIVERY_ATTEMPTS: i64 = 10;
+
+/// Delay before retrying the first failed delivery. It's doubled for every
+/// following failure.
+const FIRST_RETRY_DELAY_IN_SECS: u64 = 30;
+
+const MAX_RETRY_DELAY_IN_SECS: u64 = 3600;
+
+/// Emails loaded from the outbox at once.
+const DELIVERY_BATCH_SIZE: u32 = 50;
+
+/// How long the link to confirm a new email address is valid.
+pub const EMAIL_CHANGE_TOKEN_VALID_SECS: u64 = 24 * 3600;
+
+/// Built-in templates embedded in the binary.
+const BUILT_IN_TEMPLATES: &[(&str, &str)] = &[
+    ("email_change.html", include_str!("../templates/email_change.html")),
+    ("email_change.txt", include_str!("../templates/email_change.txt")),
+    (
+        "email_change_notice.html",
+        include_str!("../templates/email_change_notice.html"),
+    ),
+    (
+        "email_change_notice.txt",
+        include_str!("../templates/email_change_notice.txt"),
+    ),
+    (
+        "followed_tag_deleted.html",
+        include_str!("../templates/followed_tag_deleted.html"),
+    ),
+    (
+        "followed_tag_deleted.txt",
+        include_str!("../templates/followed_tag_deleted.txt"),
+    ),
+    ("layout.html", include_str!("../templates/layout.html")),
+    ("new_torrent.html", include_str!("../templates/new_torrent.html")),
+    ("new_torrent.txt", include_str!("../templates/new_torrent.txt")),
+    ("torrent_deleted.html", include_str!("../templates/torrent_deleted.html")),
+    ("torrent_deleted.txt", include_str!("../templates/torrent_deleted.txt")),
+    ("torrent_edited.html", include_str!("../templates/torrent_edited.html")),
+    ("torrent_edited.txt", include_str!("../templates/torrent_edited.txt")),
+    ("verify_email.html", include_str!("../templates/verify_email.html")),
+    ("verify_email.txt", include_str!("../templates/verify_email.txt")),
+    ("weekly_digest.html", include_str!("../templates/weekly_digest.html")),
+    ("weekly_digest.txt", include_str!("../templates/weekly_digest.txt")),
+];
+
+/// The message types sent by the Index.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Template {
+    /// Sent after the registration to verify the user's email address.
+    VerifyEmail,
+    /// Sent to the new address when a user changes their email, to confirm
+    /// it.
+    EmailChange,
+    /// Sent to the current address when a user changes their email.
+    EmailChangeNotice,
+    /// A new torrent in a category or with a tag the user follows.
+    NewTorrent,
+    /// A tag the user follows was deleted.
+    FollowedTagDeleted,
+    /// A moderator edited one of the user's torrents.
+    TorrentEdited,
+    /// A moderator deleted one of the user's torrents.
+    TorrentDeleted,
+    /// The top torrents uploaded in the last week.
+    WeeklyDigest,
+}
 
-        match tera.add_template_file("templates/verify.html", Some("html_verify_email")) {
-            Ok(()) => {}
-            Err(e) => {
-                println!("Parsing error(s): {e}");
-                ::std::process::exit(1);
-            }
-        };
+impl Template {
+    /// The name of the template files without the extension.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Template::VerifyEmail => "verify_email",
+            Template::EmailChange => "email_change",
+            Template::EmailChangeNotice => "email_change_notice",
+            Template::NewTorrent => "new_torrent",
+            Template::FollowedTagDeleted => "followed_tag_deleted",
+            Template::TorrentEdited => "torrent_edited",
+            Template::TorrentDeleted => "torrent_deleted",
+            Template::WeeklyDigest => "weekly_digest",
+        }
+    }
 
-        tera.autoescape_on(vec![".html", ".sql"]);
-        tera.register_filter("do_nothing", do_nothing_filter);
-        tera
-    };
+    #[must_use]
+    pub fn subject(&self) -> &'static str {
+        match self {
+            Template::VerifyEmail => "Torrust - Email verification",
+            Template::EmailChange => "Torrust - Confirm your new email",
+            Template::EmailChangeNotice => "Torrust - Your email is being changed",
+            Template::NewTorrent => "Torrust - New torrent",
+            Template::FollowedTagDeleted => "Torrust - A tag you follow was deleted",
+            Template::TorrentEdited => "Torrust - Your torrent was edited",
+            Template::TorrentDeleted => "Torrust - Your torrent was deleted",
+            Template::WeeklyDigest => "Torrust - Weekly digest",
+        }
+    }
 }
 
 /// This function is a dummy filter for tera.
@@ -48,9 +154,33 @@ pub fn do_nothing_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Res
     Ok(to_value(s).unwrap())
 }
 
+/// The transports emails can be delivered with.
+pub enum Transport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    File(AsyncFileTransport<Tokio1Executor>),
+    Sendmail(AsyncSendmailTransport<Tokio1Executor>),
+}
+
+impl Transport {
+    /// # Errors
+    ///
+    /// Will return the transport error message if the email can't be
+    /// delivered.
+    pub async fn send(&self, message: Message) -> Result<(), String> {
+        match self {
+            Transport::Smtp(transport) => transport.send(message).await.map(|_| ()).map_err(|e| e.to_string()),
+            Transport::File(transport) => transport.send(message).await.map(|_| ()).map_err(|e| e.to_string()),
+            Transport::Sendmail(transport) => transport.send(message).await.map_err(|e| e.to_string()),
+        }
+    }
+}
+
 pub struct Service {
     cfg: Arc<Configuration>,
-    mailer: Arc<Mailer>,
+    database: Arc<Box<dyn Database>>,
+    templates: Tera,
+    transport: Transport,
+    new_mail: Arc<Notify>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -58,47 +188,97 @@ pub struct VerifyClaims {
     pub iss: String,
     pub sub: i64,
     pub exp: u64,
+    /// The new email address. Only email change tokens have it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
 }
 
 impl Service {
-    pub async fn new(cfg: Arc<Configuration>) -> Service {
-        let mailer = Arc::new(Self::get_mailer(&cfg).await);
+    /// # Panics
+    ///
+    /// Will panic if the templates in the configured templates directory
+    /// can't be loaded.
+    pub async fn new(cfg: Arc<Configuration>, database: Arc<Box<dyn Database>>) -> Service {
+        let settings = cfg.settings.read().await;
+
+        let templates = match load_templates(settings.mail.templates_path.as_deref()) {
+            Ok(templates) => templates,
+            Err(e) => panic!("Failed to load the mail templates: {e:?}"),
+        };
 
-        Self { cfg, mailer }
+        drop(settings);
+
+        let transport = Self::get_transport(&cfg).await;
+
+        Self {
+            cfg,
+            database,
+            templates,
+            transport,
+            new_mail: Arc::new(Notify::new()),
+        }
     }
 
-    async fn get_mailer(cfg: &Configuration) -> Mailer {
+    async fn get_transport(cfg: &Configuration) -> Transport {
         let settings = cfg.settings.read().await;
 
-        if !settings.mail.smtp.credentials.username.is_empty() && !settings.mail.smtp.credentials.password.is_empty() {
-            // SMTP authentication
-            let creds = Credentials::new(
-                settings.mail.smtp.credentials.username.clone(),
-                settings.mail.smtp.credentials.password.clone(),
-            );
-
-            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.mail.smtp.server)
-                .port(settings.mail.smtp.port)
-                .credentials(creds)
-                .authentication(vec![Mechanism::Login, Mechanism::Xoauth2, Mechanism::Plain])
-                .build()
-        } else {
-            // SMTP without authentication
-            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.mail.smtp.server)
-                .port(settings.mail.smtp.port)
-                .build()
+        match settings.mail.transport {
+            MailTransport::Smtp => {
+                if !settings.mail.smtp.credentials.username.is_empty() && !settings.mail.smtp.credentials.password.is_empty() {
+                    // SMTP authentication
+                    let creds = Credentials::new(
+                        settings.mail.smtp.credentials.username.clone(),
+                        settings.mail.smtp.credentials.password.clone(),
+                    );
+
+                    Transport::Smtp(
+                        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.mail.smtp.server)
+                            .port(settings.mail.smtp.port)
+                            .credentials(creds)
+                            .authentication(vec![Mechanism::Login, Mechanism::Xoauth2, Mechanism::Plain])
+                            .build(),
+                    )
+                } else {
+                    // SMTP without authentication
+                    Transport::Smtp(
+                        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.mail.smtp.server)
+                            .port(settings.mail.smtp.port)
+                            .build(),
+                    )
+                }
+            }
+            MailTransport::File => {
+                // Deliveries will fail and be retried until the directory exists.
+                if let Err(e) = tokio::fs::create_dir_all(&settings.mail.file.path).await {
+                    warn!(
+                        "Failed to create the mail directory {}: {e}",
+                        settings.mail.file.path.display()
+                    );
+                }
+
+                Transport::File(AsyncFileTransport::<Tokio1Executor>::new(&settings.mail.file.path))
+            }
+            MailTransport::Sendmail => Transport::Sendmail(AsyncSendmailTransport::<Tokio1Executor>::new_with_command(
+                &settings.mail.sendmail.command,
+            )),
         }
     }
 
+    /// It returns the notifier used to wake up the mail sender when a new
+    /// email is added to the outbox.
+    #[must_use]
+    pub fn new_mail_notifier(&self) -> Arc<Notify> {
+        self.new_mail.clone()
+    }
+
     /// Send Verification Email.
     ///
-    /// # Errors
-    ///
-    /// This function will return an error if unable to send an email.
+    /// The email is added to the outbox and delivered in the background.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the multipart builder had an error.
+    /// This function will return an error if unable to render the email or
+    /// to add it to the outbox.
     pub async fn send_verification_mail(
         &self,
         to: &str,
@@ -106,117 +286,343 @@ impl Service {
         user_id: i64,
         base_url: &str,
     ) -> Result<(), ServiceError> {
-        let builder = self.get_builder(to).await;
         let verification_url = self.get_verification_url(user_id, base_url).await;
 
-        let mail = build_letter(verification_url.as_str(), username, builder)?;
+        let mut context = Context::new();
+        context.insert("verification", &verification_url);
+        context.insert("username", &username);
+
+        self.enqueue(to, Template::VerifyEmail, &context).await
+    }
+
+    /// It sends the link to confirm the new email address of a user to that
+    /// address. The link expires after [`EMAIL_CHANGE_TOKEN_VALID_SECS`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if unable to render the email or
+    /// to add it to the outbox.
+    pub async fn send_email_change_mail(
+        &self,
+        new_email: &str,
+        username: &str,
+        user_id: i64,
+        base_url: &str,
+    ) -> Result<(), ServiceError> {
+        let claims = VerifyClaims {
+            iss: String::from("email-change"),
+            sub: user_id,
+            exp: clock::now() + EMAIL_CHANGE_TOKEN_VALID_SECS,
+            email: Some(new_email.to_string()),
+        };
+
+        let verification_url = self.get_token_url(&claims, "user/email/change", base_url).await;
+
+        let mut context = Context::new();
+        context.insert("verification", &verification_url);
+        context.insert("username", &username);
+        context.insert("expiration_hours", &(EMAIL_CHANGE_TOKEN_VALID_SECS / 3600));
+
+        self.enqueue(new_email, Template::EmailChange, &context).await
+    }
+
+    /// It warns a user on their current email address that the email of
+    /// their account is being changed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if unable to render the email or
+    /// to add it to the outbox.
+    pub async fn send_email_change_notice(&self, to: &str, username: &str, new_email: &str) -> Result<(), ServiceError> {
+        let mut context = Context::new();
+        context.insert("username", &username);
+        context.insert("new_email", &new_email);
+
+        self.enqueue(to, Template::EmailChangeNotice, &context).await
+    }
+
+    /// It renders an email and adds it to the outbox.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the recipient is not a valid
+    /// email address, the templates can't be rendered or the email can't be
+    /// added to the outbox.
+    pub async fn enqueue(&self, to: &str, template: Template, context: &Context) -> Result<(), ServiceError> {
+        if to.parse::<Mailbox>().is_err() {
+            return Err(ServiceError::EmailInvalid);
+        }
+
+        let mail = render(&self.templates, to, template, context).map_err(|e| {
+            error!("Failed to render the {} email: {e:?}", template.name());
+            ServiceError::InternalServerError
+        })?;
+
+        self.database
+            .add_mail_to_outbox(&mail)
+            .await
+            .map_err(|_| ServiceError::DatabaseError)?;
+
+        self.new_mail.notify_one();
+
+        Ok(())
+    }
+
+    /// It tries to deliver all the emails in the outbox whose delivery is
+    /// due. Failed deliveries are rescheduled or, after too many attempts,
+    /// marked as failed.
+    ///
+    /// It returns the number of delivered emails.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the outbox can't be read or updated.
+    pub async fn deliver_due_mails(&self) -> Result<usize, database::Error> {
+        let mut delivered = 0;
+
+        loop {
+            let mails = self.database.get_due_outbox_mails(DELIVERY_BATCH_SIZE).await?;
+
+            for mail in &mails {
+                match self.deliver(mail).await {
+                    Ok(()) => {
+                        self.database.delete_outbox_mail(mail.mail_id).await?;
+                        delivered += 1;
+                    }
+                    Err(e) => self.handle_failed_delivery(mail, &e).await?,
+                }
+            }
+
+            // Failed deliveries are rescheduled, so they are not loaded again.
+            if mails.len() < DELIVERY_BATCH_SIZE as usize {
+                return Ok(delivered);
+            }
+        }
+    }
+
+    async fn deliver(&self, mail: &OutboxMail) -> Result<(), DeliveryError> {
+        let message = self.build_message(mail).await.map_err(DeliveryError::Permanent)?;
+
+        self.transport.send(message).await.map_err(DeliveryError::Transient)
+    }
+
+    async fn handle_failed_delivery(&self, mail: &OutboxMail, error: &DeliveryError) -> Result<(), database::Error> {
+        let attempts = mail.attempts + 1;
+
+        match error {
+            DeliveryError::Transient(e) if attempts < MAX_DELIVERY_ATTEMPTS => {
+                let delay = backoff::retry_delay_in_secs(attempts, FIRST_RETRY_DELAY_IN_SECS, MAX_RETRY_DELAY_IN_SECS);
 
-        match self.mailer.send(mail).await {
-            Ok(_res) => Ok(()),
-            Err(e) => {
-                eprintln!("Failed to send email: {e}");
-                Err(ServiceError::FailedToSendVerificationEmail)
+                debug!(
+                    "Failed to deliver email {} (attempt {attempts}), retrying in {delay} seconds: {e}",
+                    mail.mail_id
+                );
+
+                // casting current_time() to i64 will overflow in the year 2262
+                let next_attempt_at = i64::try_from(clock::now() + delay).unwrap();
+
+                self.database.reschedule_outbox_mail(mail.mail_id, next_attempt_at, e).await
+            }
+            DeliveryError::Transient(e) | DeliveryError::Permanent(e) => {
+                error!(
+                    "Giving up delivering email {} \"{}\" to {} after {attempts} attempts: {e}",
+                    mail.mail_id, mail.subject, mail.recipient
+                );
+
+                self.database.fail_outbox_mail(mail.mail_id, e).await
             }
         }
     }
 
-    async fn get_builder(&self, to: &str) -> MessageBuilder {
+    async fn build_message(&self, mail: &OutboxMail) -> Result<Message, String> {
         let settings = self.cfg.settings.read().await;
 
+        let to: Mailbox = mail.recipient.parse().map_err(|e| format!("invalid recipient: {e}"))?;
+
         Message::builder()
             .from(settings.mail.from.clone())
             .reply_to(settings.mail.reply_to.clone())
-            .to(to.parse().unwrap())
+            .to(to)
+            .subject(mail.subject.clone())
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(
+                        SinglePart::builder()
+                            .header(lettre::message::header::ContentType::TEXT_PLAIN)
+                            .body(mail.body_plain.clone()),
+                    )
+                    .singlepart(
+                        SinglePart::builder()
+                            .header(lettre::message::header::ContentType::TEXT_HTML)
+                            .body(mail.body_html.clone()),
+                    ),
+            )
+            .map_err(|e| e.to_string())
     }
 
     async fn get_verification_url(&self, user_id: i64, base_url: &str) -> String {
-        let settings = self.cfg.settings.read().await;
-
-        // create verification JWT
-        let key = settings.auth.user_claim_token_pepper.as_bytes();
-
         // Create non expiring token that is only valid for email-verification
         let claims = VerifyClaims {
             iss: String::from("email-verification"),
             sub: user_id,
             exp: clock::now() + 315_569_260, // 10 years from now
+            email: None,
         };
 
-        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(key)).unwrap();
+        self.get_token_url(&claims, "user/email/verify", base_url).await
+    }
+
+    /// It returns the URL of the API `path` with the signed `claims` as the
+    /// last segment.
+    async fn get_token_url(&self, claims: &VerifyClaims, path: &str, base_url: &str) -> String {
+        let settings = self.cfg.settings.read().await;
+
+        // create verification JWT
+        let key = settings.auth.user_claim_token_pepper.as_bytes();
+
+        let token = encode(&Header::default(), claims, &EncodingKey::from_secret(key)).unwrap();
 
         let base_url = match &settings.net.base_url {
             Some(url) => url.to_string(),
             None => base_url.to_string(),
         };
 
-        format!("{base_url}/{API_VERSION_URL_PREFIX}/user/email/verify/{token}")
+        format!("{base_url}/{API_VERSION_URL_PREFIX}/{path}/{token}")
     }
 }
 
-fn build_letter(verification_url: &str, username: &str, builder: MessageBuilder) -> Result<Message, ServiceError> {
-    let (plain_body, html_body) = build_content(verification_url, username).map_err(|e| {
-        tracing::error!("{e}");
-        ServiceError::InternalServerError
-    })?;
-
-    Ok(builder
-        .subject("Torrust - Email verification")
-        .multipart(
-            MultiPart::alternative()
-                .singlepart(
-                    SinglePart::builder()
-                        .header(lettre::message::header::ContentType::TEXT_PLAIN)
-                        .body(plain_body),
-                )
-                .singlepart(
-                    SinglePart::builder()
-                        .header(lettre::message::header::ContentType::TEXT_HTML)
-                        .body(html_body),
-                ),
-        )
-        .expect("the `multipart` builder had an error"))
+enum DeliveryError {
+    /// The delivery can be retried, for example, when the SMTP server is down.
+    Transient(String),
+    /// The email can't be built, so retrying is pointless.
+    Permanent(String),
 }
 
-fn build_content(verification_url: &str, username: &str) -> Result<(String, String), tera::Error> {
-    let plain_body = format!(
-        r#"
-                Welcome to Torrust, {username}!
-
-                Please click the confirmation link below to verify your account.
-                {verification_url}
-
-                If this account wasn't made by you, you can ignore this email.
-            "#
-    );
-    let mut context = Context::new();
-    context.insert("verification", &verification_url);
-    context.insert("username", &username);
-    let html_body = TEMPLATES.render("html_verify_email", &context)?;
-    Ok((plain_body, html_body))
+/// It loads the built-in templates, overridden by the ones in
+/// `templates_path`.
+fn load_templates(templates_path: Option<&Path>) -> Result<Tera, tera::Error> {
+    let mut tera = Tera::default();
+
+    tera.add_raw_templates(BUILT_IN_TEMPLATES.to_vec())?;
+
+    if let Some(templates_path) = templates_path {
+        for (name, _) in BUILT_IN_TEMPLATES {
+            let path = templates_path.join(name);
+
+            if path.is_file() {
+                tera.add_template_file(path, Some(name))?;
+            }
+        }
+    }
+
+    tera.autoescape_on(vec![".html", ".sql"]);
+    tera.register_filter("do_nothing", do_nothing_filter);
+
+    Ok(tera)
 }
 
-pub type Mailer = AsyncSmtpTransport<Tokio1Executor>;
+fn render(templates: &Tera, to: &str, template: Template, context: &Context) -> Result<Mail, tera::Error> {
+    Ok(Mail {
+        recipient: to.to_string(),
+        subject: template.subject().to_string(),
+        body_plain: templates.render(&format!("{}.txt", template.name()), context)?,
+        body_html: templates.render(&format!("{}.html", template.name()), context)?,
+    })
+}
 
 #[cfg(test)]
 mod tests {
-    use lettre::Message;
+    use tera::Context;
 
-    use super::{build_content, build_letter};
+    use super::{load_templates, render, Template};
+
+    fn verify_email_context() -> Context {
+        let mut context = Context::new();
+        context.insert("verification", "https://a.b.c/verify?token=1&x=2");
+        context.insert("username", "<user>");
+        context
+    }
 
     #[test]
-    fn it_should_build_a_letter() {
-        let builder = Message::builder()
-            .from("from@a.b.c".parse().unwrap())
-            .reply_to("reply@a.b.c".parse().unwrap())
-            .to("to@a.b.c".parse().unwrap());
+    fn it_should_render_the_built_in_templates() {
+        let templates = load_templates(None).unwrap();
 
-        let _letter = build_letter("https://a.b.c/", "user", builder).unwrap();
+        let mail = render(&templates, "to@a.b.c", Template::VerifyEmail, &verify_email_context()).unwrap();
+
+        assert_eq!(mail.recipient, "to@a.b.c");
+        assert_eq!(mail.subject, "Torrust - Email verification");
+        assert!(mail.body_plain.contains("Welcome to Torrust, <user>!"));
+        assert!(mail.body_plain.contains("https://a.b.c/verify?token=1&x=2"));
+        assert!(mail.body_html.contains("Welcome to Torrust, &lt;user&gt;."));
+        assert!(mail.body_html.contains("href=\"https://a.b.c/verify?token=1&x=2\""));
+    }
+
+    #[test]
+    fn the_notification_templates_should_extend_the_layout() {
+        let templates = load_templates(None).unwrap();
+
+        let mut context = Context::new();
+        context.insert("username", "user");
+        context.insert(
+            "torrents",
+            &vec![serde_json::json!({
+                "title": "Ubuntu 24.04",
+                "info_hash": "443c7602b4fde83d1154d6d9da48808418b181b6",
+                "seeders": 7,
+                "leechers": 2,
+            })],
+        );
+
+        let mail = render(&templates, "to@a.b.c", Template::WeeklyDigest, &context).unwrap();
+
+        assert_eq!(mail.subject, "Torrust - Weekly digest");
+        assert!(mail.body_plain.contains("- Ubuntu 24.04 (7 seeders, 2 leechers)"));
+        assert!(mail.body_html.contains("<html"));
+        assert!(mail.body_html.contains("443c7602b4fde83d1154d6d9da48808418b181b6"));
     }
 
     #[test]
-    fn it_should_build_content() {
-        let (plain_body, html_body) = build_content("https://a.b.c/", "user").unwrap();
-        assert_ne!(plain_body, "");
-        assert_ne!(html_body, "");
+    fn the_followed_tag_deleted_notification_should_include_the_tag_name() {
+        let templates = load_templates(None).unwrap();
+
+        let mut context = Context::new();
+        context.insert("username", "user");
+        context.insert("tag_name", "linux");
+
+        let mail = render(&templates, "to@a.b.c", Template::FollowedTagDeleted, &context).unwrap();
+
+        assert_eq!(mail.subject, "Torrust - A tag you follow was deleted");
+        assert!(mail.body_plain.contains("The tag \"linux\" you followed was deleted"));
+        assert!(mail.body_html.contains("<strong>linux</strong>"));
+    }
+
+    #[test]
+    fn the_email_change_notice_should_not_have_the_notification_footer() {
+        let templates = load_templates(None).unwrap();
+
+        let mut context = Context::new();
+        context.insert("username", "user");
+        context.insert("new_email", "new@a.b.c");
+
+        let mail = render(&templates, "old@a.b.c", Template::EmailChangeNotice, &context).unwrap();
+
+        assert_eq!(mail.subject, "Torrust - Your email is being changed");
+        assert!(mail.body_plain.contains("new@a.b.c"));
+        assert!(mail.body_html.contains("<strong>new@a.b.c</strong>"));
+        assert!(mail.body_html.contains("security notice"));
+        assert!(!mail.body_html.contains("you enabled this notification"));
+    }
+
+    #[test]
+    fn templates_in_the_templates_directory_should_override_the_built_in_ones() {
+        let templates_path = tempfile::tempdir().unwrap();
+        std::fs::write(templates_path.path().join("verify_email.txt"), "Hi {{ username }}").unwrap();
+
+        let templates = load_templates(Some(templates_path.path())).unwrap();
+
+        let mail = render(&templates, "to@a.b.c", Template::VerifyEmail, &verify_email_context()).unwrap();
+
+        assert_eq!(mail.body_plain, "Hi <user>");
+        assert!(mail.body_html.contains("Welcome to Torrust"));
     }
 }
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
 use crate::databases::database::Category as DatabaseCategory;
@@ -24,3 +27,38 @@ impl From<DatabaseCategory> for Category {
         }
     }
 }
+
+/// The torrent file served when a torrent in the category is downloaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TorrentFileFormat {
+    /// The torrent file is rebuilt from the database. Non-standard keys in the
+    /// `info` dictionary are removed, so the info-hash could change.
+    #[default]
+    Canonical,
+    /// The torrent file is served exactly as it was uploaded. Only the
+    /// announce keys are rewritten, so the original info-hash is kept.
+    Original,
+}
+
+impl fmt::Display for TorrentFileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let display_str = match self {
+            TorrentFileFormat::Canonical => "canonical",
+            TorrentFileFormat::Original => "original",
+        };
+        write!(f, "{display_str}")
+    }
+}
+
+impl FromStr for TorrentFileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "canonical" => Ok(TorrentFileFormat::Canonical),
+            "original" => Ok(TorrentFileFormat::Original),
+            _ => Err(format!("Unknown torrent file format: {s}")),
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Directory tree built from the files in a torrent.
+//!
+//! Torrent files only contain a flat list of files with their paths. This
+//! module groups them in directories, so clients can browse large torrents one
+//! directory at a time. Every directory contains the total size and number of
+//! files inside it, including its subdirectories.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::torrent_file::TorrentFile;
+
+/// A directory in the torrent file tree.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Directory {
+    pub name: String,
+    /// Total size in bytes of the files in the directory and its subdirectories.
+    pub size: i64,
+    /// Number of files in the directory and its subdirectories.
+    pub file_count: u64,
+    directories: BTreeMap<String, Directory>,
+    files: Vec<TorrentFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Directory,
+    File,
+}
+
+/// A directory or file in the file listing.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileTreeEntry {
+    pub kind: EntryKind,
+    pub name: String,
+    /// The full path from the torrent root.
+    pub path: Vec<String>,
+    pub size: i64,
+    /// Number of files in the directory. It's always 1 for files.
+    pub file_count: u64,
+    pub md5sum: Option<String>,
+}
+
+/// Criteria to search files in the tree. Both conditions are case-insensitive.
+#[derive(Debug, Default)]
+pub struct FileFilter {
+    /// Text the file name must contain.
+    pub name: Option<String>,
+    /// File extension, with or without the leading dot.
+    pub extension: Option<String>,
+}
+
+impl FileFilter {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.extension.is_none()
+    }
+
+    fn matches(&self, file_name: &str) -> bool {
+        let file_name = file_name.to_lowercase();
+
+        let name_matches = self
+            .name
+            .as_ref()
+            .map_or(true, |name| file_name.contains(&name.to_lowercase()));
+
+        let extension_matches = self.extension.as_ref().map_or(true, |extension| {
+            let extension = extension.trim_start_matches('.').to_lowercase();
+            file_name
+                .rsplit_once('.')
+                .is_some_and(|(_, file_extension)| file_extension == extension)
+        });
+
+        name_matches && extension_matches
+    }
+}
+
+impl Directory {
+    /// It builds the tree with all the torrent files. The returned directory
+    /// is the torrent root, which has no name.
+    #[must_use]
+    pub fn from_files(files: &[TorrentFile]) -> Self {
+        let mut root = Directory::default();
+
+        for file in files {
+            root.insert(&file.path, file);
+        }
+
+        root
+    }
+
+    fn insert(&mut self, relative_path: &[String], file: &TorrentFile) {
+        self.size += file.length;
+        self.file_count += 1;
+
+        match relative_path {
+            [] | [_] => self.files.push(file.clone()),
+            [directory_name, rest @ ..] => self
+                .directories
+                .entry(directory_name.clone())
+                .or_insert_with(|| Directory {
+                    name: directory_name.clone(),
+                    ..Default::default()
+                })
+                .insert(rest, file),
+        }
+    }
+
+    /// It returns the subdirectory at `path`, relative to this directory.
+    #[must_use]
+    pub fn find(&self, path: &[String]) -> Option<&Directory> {
+        match path {
+            [] => Some(self),
+            [directory_name, rest @ ..] => self.directories.get(directory_name)?.find(rest),
+        }
+    }
+
+    /// It returns the direct children of the directory: first the
+    /// subdirectories and then the files, both sorted by name.
+    ///
+    /// `path` is the path of this directory from the torrent root.
+    #[must_use]
+    pub fn entries(&self, path: &[String]) -> Vec<FileTreeEntry> {
+        let directories = self.directories.values().map(|directory| FileTreeEntry {
+            kind: EntryKind::Directory,
+            name: directory.name.clone(),
+            path: child_path(path, &directory.name),
+            size: directory.size,
+            file_count: directory.file_count,
+            md5sum: None,
+        });
+
+        let mut files: Vec<FileTreeEntry> = self.files.iter().map(file_entry).collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        directories.chain(files).collect()
+    }
+
+    /// It returns all the files in the directory and its subdirectories
+    /// matching the filter, sorted by path.
+    #[must_use]
+    pub fn search(&self, filter: &FileFilter) -> Vec<FileTreeEntry> {
+        let mut files = vec![];
+
+        self.collect_matching_files(filter, &mut files);
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        files
+    }
+
+    fn collect_matching_files(&self, filter: &FileFilter, files: &mut Vec<FileTreeEntry>) {
+        files.extend(
+            self.files
+                .iter()
+                .filter(|file| filter.matches(file.path.last().map_or("", String::as_str)))
+                .map(file_entry),
+        );
+
+        for directory in self.directories.values() {
+            directory.collect_matching_files(filter, files);
+        }
+    }
+}
+
+fn file_entry(file: &TorrentFile) -> FileTreeEntry {
+    FileTreeEntry {
+        kind: EntryKind::File,
+        name: file.path.last().cloned().unwrap_or_default(),
+        path: file.path.clone(),
+        size: file.length,
+        file_count: 1,
+        md5sum: file.md5sum.clone(),
+    }
+}
+
+fn child_path(path: &[String], name: &str) -> Vec<String> {
+    let mut child_path = path.to_vec();
+    child_path.push(name.to_string());
+    child_path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Directory, EntryKind, FileFilter};
+    use crate::models::torrent_file::TorrentFile;
+
+    fn file(path: &str, length: i64) -> TorrentFile {
+        TorrentFile {
+            path: path.split('/').map(ToString::to_string).collect(),
+            length,
+            md5sum: None,
+            attr: None,
+        }
+    }
+
+    fn sample_tree() -> Directory {
+        Directory::from_files(&[
+            file("README.txt", 10),
+            file("docs/manual.pdf", 100),
+            file("docs/images/cover.PNG", 1000),
+            file("src/main.rs", 5),
+        ])
+    }
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('/').map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn it_should_aggregate_the_size_and_number_of_files_of_each_directory() {
+        let root = sample_tree();
+
+        assert_eq!((root.size, root.file_count), (1115, 4));
+
+        let docs = root.find(&path("docs")).unwrap();
+
+        assert_eq!((docs.size, docs.file_count), (1100, 2));
+    }
+
+    #[test]
+    fn it_should_list_subdirectories_before_files() {
+        let root = sample_tree();
+
+        let entries = root.entries(&[]);
+
+        let names: Vec<(EntryKind, &str)> = entries.iter().map(|entry| (entry.kind, entry.name.as_str())).collect();
+
+        assert_eq!(
+            names,
+            vec![
+                (EntryKind::Directory, "docs"),
+                (EntryKind::Directory, "src"),
+                (EntryKind::File, "README.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_include_the_full_path_in_the_entries_of_a_subdirectory() {
+        let root = sample_tree();
+
+        let docs_path = path("docs");
+
+        let entries = root.find(&docs_path).unwrap().entries(&docs_path);
+
+        assert_eq!(entries[0].path, path("docs/images"));
+        assert_eq!(entries[1].path, path("docs/manual.pdf"));
+    }
+
+    #[test]
+    fn it_should_return_none_for_a_non_existing_directory() {
+        assert!(sample_tree().find(&path("docs/missing")).is_none());
+    }
+
+    #[test]
+    fn it_should_search_files_by_extension_ignoring_case() {
+        let filter = FileFilter {
+            name: None,
+            extension: Some(".png".to_string()),
+        };
+
+        let files = sample_tree().search(&filter);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, path("docs/images/cover.PNG"));
+    }
+
+    #[test]
+    fn it_should_search_files_by_name() {
+        let filter = FileFilter {
+            name: Some("MAN".to_string()),
+            extension: None,
+        };
+
+        let files = sample_tree().search(&filter);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, path("docs/manual.pdf"));
+    }
+}
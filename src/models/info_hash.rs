@@ -8,7 +8,11 @@
 //!
 //! This modules provides a type that can be used to represent info-hashes.
 //!
-//! > **NOTICE**: It only supports Info Hash v1.
+//! `BitTorrent` v2 torrents ([BEP 52](https://www.bittorrent.org/beps/bep_0052.html))
+//! use the 32-byte SHA-256 hash of the info dictionary instead. It's represented
+//! by the [`InfoHashV2`] type. Hybrid torrents have both info-hashes. v2-only
+//! torrents use the v2 info-hash truncated to 20 bytes wherever a v1 info-hash
+//! is expected, for example, in the tracker protocol or in the Index database.
 //!
 //! Typically info-hashes are represented as hex strings, but internally they are
 //! a 20-byte array.
@@ -363,6 +367,79 @@ impl<'v> serde::de::Visitor<'v> for InfoHashVisitor {
     }
 }
 
+/// `BitTorrent` Info Hash v2. The SHA-256 hash of the bencoded info dictionary.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct InfoHashV2(pub [u8; 32]);
+
+const INFO_HASH_V2_BYTES_LEN: usize = 32;
+
+/// Multihash prefix for SHA-256 hashes: the hash function code (`0x12`)
+/// followed by the digest length (`0x20`). It's used in `urn:btmh:` magnet
+/// links.
+const SHA256_MULTIHASH_PREFIX: &str = "1220";
+
+impl InfoHashV2 {
+    /// Create a new `InfoHashV2` from a byte slice.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if byte slice does not contains the exact amount of bytes need for the `InfoHashV2`.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), INFO_HASH_V2_BYTES_LEN);
+        let mut ret = Self([0u8; INFO_HASH_V2_BYTES_LEN]);
+        ret.0.clone_from_slice(bytes);
+        ret
+    }
+
+    /// Returns the `InfoHashV2` as a hex string.
+    #[must_use]
+    pub fn to_hex_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the info-hash in multihash format, as used in `urn:btmh:`
+    /// magnet links.
+    #[must_use]
+    pub fn to_multihash_string(&self) -> String {
+        format!("{SHA256_MULTIHASH_PREFIX}{self}")
+    }
+
+    /// Returns the first 20 bytes of the info-hash. It's the info-hash used
+    /// for v2-only torrents where a v1 info-hash is expected.
+    #[must_use]
+    pub fn truncated(&self) -> InfoHash {
+        InfoHash::from_bytes(&self.0[..INFO_HASH_BYTES_LEN])
+    }
+}
+
+impl std::fmt::Display for InfoHashV2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut chars = [0u8; 64];
+        binascii::bin2hex(&self.0, &mut chars).expect("failed to hexlify");
+        write!(f, "{}", std::str::from_utf8(&chars).unwrap())
+    }
+}
+
+impl std::str::FromStr for InfoHashV2 {
+    type Err = binascii::ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut i = Self([0u8; 32]);
+        if s.len() != 64 {
+            return Err(binascii::ConvertError::InvalidInputLength);
+        }
+        binascii::hex2bin(s.as_bytes(), &mut i.0)?;
+        Ok(i)
+    }
+}
+
+impl std::convert::From<[u8; 32]> for InfoHashV2 {
+    fn from(val: [u8; 32]) -> Self {
+        InfoHashV2(val)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -371,7 +448,7 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use serde_json::json;
 
-    use super::InfoHash;
+    use super::{InfoHash, InfoHashV2};
 
     #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
     struct ContainingInfoHash {
@@ -484,4 +561,30 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn a_v2_info_hash_can_only_be_created_from_a_64_utf8_char_string() {
+        assert!(InfoHashV2::from_str(&"F".repeat(64)).is_ok());
+        assert!(InfoHashV2::from_str(&"F".repeat(40)).is_err());
+    }
+
+    #[test]
+    fn a_v2_info_hash_should_be_truncated_to_its_first_20_bytes() {
+        let info_hash = InfoHashV2::from_str("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e").unwrap();
+
+        assert_eq!(
+            info_hash.truncated(),
+            InfoHash::from_str("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa").unwrap()
+        );
+    }
+
+    #[test]
+    fn a_v2_info_hash_should_be_represented_as_a_sha256_multihash() {
+        let info_hash = InfoHashV2::from_str("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e").unwrap();
+
+        assert_eq!(
+            info_hash.to_multihash_string(),
+            "1220caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e"
+        );
+    }
 }
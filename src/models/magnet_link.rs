@@ -0,0 +1,237 @@
+//! Magnet links ([BEP 9](https://www.bittorrent.org/beps/bep_0009.html)).
+//!
+//! They are used to list torrents without their meta-info. Only the fields
+//! the Index stores are parsed: the info-hashes (`xt`), the display name
+//! (`dn`), the trackers (`tr`) and the web seeds (`ws`).
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::info_hash::{InfoHash, InfoHashV2};
+
+const MAGNET_LINK_PREFIX: &str = "magnet:?";
+
+/// The `xt` prefix for v1 info-hashes, hex or base32 encoded.
+const BTIH_URN_PREFIX: &str = "urn:btih:";
+
+/// The `xt` prefix for v2 info-hashes in multihash format.
+const BTMH_URN_PREFIX: &str = "urn:btmh:";
+
+/// Multihash prefix for SHA-256 hashes.
+const SHA256_MULTIHASH_PREFIX: &str = "1220";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseMagnetLinkError {
+    #[error("Magnet links must start with `magnet:?`.")]
+    MissingPrefix,
+    #[error("Magnet links must have a `urn:btih` or `urn:btmh` exact topic.")]
+    MissingInfoHash,
+    #[error("Invalid info-hash in the magnet link.")]
+    InvalidInfoHash,
+    #[error("Invalid percent-encoding in the magnet link.")]
+    InvalidEncoding,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagnetLink {
+    /// The v1 info-hash. For v2-only torrents, it's the truncated v2
+    /// info-hash.
+    pub info_hash: InfoHash,
+    pub info_hash_v2: Option<InfoHashV2>,
+    pub display_name: Option<String>,
+    pub trackers: Vec<String>,
+    pub web_seeds: Vec<String>,
+}
+
+impl FromStr for MagnetLink {
+    type Err = ParseMagnetLinkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let query = s
+            .trim()
+            .strip_prefix(MAGNET_LINK_PREFIX)
+            .ok_or(ParseMagnetLinkError::MissingPrefix)?;
+
+        let mut info_hash = None;
+        let mut info_hash_v2 = None;
+        let mut display_name = None;
+        let mut trackers: Vec<String> = vec![];
+        let mut web_seeds: Vec<String> = vec![];
+
+        for (key, value) in query.split('&').filter_map(|param| param.split_once('=')) {
+            let value = urlencoding::decode(value)
+                .map_err(|_| ParseMagnetLinkError::InvalidEncoding)?
+                .into_owned();
+
+            // Indexed parameters like `tr.1` are allowed too.
+            let key = key.split_once('.').map_or(key, |(key, _index)| key);
+
+            match key {
+                "xt" => {
+                    if let Some(urn) = value.strip_prefix(BTIH_URN_PREFIX) {
+                        info_hash = Some(parse_btih(urn)?);
+                    } else if let Some(urn) = value.strip_prefix(BTMH_URN_PREFIX) {
+                        info_hash_v2 = Some(parse_btmh(urn)?);
+                    }
+                }
+                "dn" if !value.trim().is_empty() => display_name = Some(value.trim().to_string()),
+                "tr" if !value.is_empty() && !trackers.contains(&value) => trackers.push(value),
+                "ws" if !value.is_empty() && !web_seeds.contains(&value) => web_seeds.push(value),
+                _ => {}
+            }
+        }
+
+        let info_hash = match (info_hash, info_hash_v2) {
+            (Some(info_hash), _) => info_hash,
+            (None, Some(info_hash_v2)) => info_hash_v2.truncated(),
+            (None, None) => return Err(ParseMagnetLinkError::MissingInfoHash),
+        };
+
+        Ok(Self {
+            info_hash,
+            info_hash_v2,
+            display_name,
+            trackers,
+            web_seeds,
+        })
+    }
+}
+
+impl fmt::Display for MagnetLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{MAGNET_LINK_PREFIX}xt={BTIH_URN_PREFIX}{}",
+            self.info_hash.to_hex_string()
+        )?;
+
+        if let Some(info_hash_v2) = &self.info_hash_v2 {
+            write!(f, "&xt={BTMH_URN_PREFIX}{}", info_hash_v2.to_multihash_string())?;
+        }
+
+        if let Some(display_name) = &self.display_name {
+            write!(f, "&dn={}", urlencoding::encode(display_name))?;
+        }
+
+        for tracker in &self.trackers {
+            write!(f, "&tr={}", urlencoding::encode(tracker))?;
+        }
+
+        for web_seed in &self.web_seeds {
+            write!(f, "&ws={}", urlencoding::encode(web_seed))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// v1 info-hashes are 40 hex characters or 32 base32 characters.
+fn parse_btih(urn: &str) -> Result<InfoHash, ParseMagnetLinkError> {
+    match urn.len() {
+        40 => InfoHash::from_str(&urn.to_lowercase()).map_err(|_| ParseMagnetLinkError::InvalidInfoHash),
+        32 => {
+            // The decoder needs room for a whole last block.
+            let mut bytes = [0u8; 25];
+            binascii::b32decode(urn.to_uppercase().as_bytes(), &mut bytes).map_err(|_| ParseMagnetLinkError::InvalidInfoHash)?;
+            Ok(InfoHash::from_bytes(&bytes[..20]))
+        }
+        _ => Err(ParseMagnetLinkError::InvalidInfoHash),
+    }
+}
+
+fn parse_btmh(urn: &str) -> Result<InfoHashV2, ParseMagnetLinkError> {
+    urn.strip_prefix(SHA256_MULTIHASH_PREFIX)
+        .and_then(|hex| InfoHashV2::from_str(&hex.to_lowercase()).ok())
+        .ok_or(ParseMagnetLinkError::InvalidInfoHash)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::models::info_hash::{InfoHash, InfoHashV2};
+    use crate::models::magnet_link::{MagnetLink, ParseMagnetLinkError};
+
+    #[test]
+    fn it_should_parse_a_magnet_link_with_a_hex_info_hash() {
+        let magnet_link = MagnetLink::from_str(
+            "magnet:?xt=urn:btih:6C6A0D3F4F4D3F0E6DD2B7B7D2D8C2F4E1E5B6A7&dn=Mandelbrot%20Set&tr=udp%3A%2F%2Ftracker.example.com%3A6969&ws=https%3A%2F%2Fexample.com%2Fseed",
+        )
+        .unwrap();
+
+        assert_eq!(
+            magnet_link,
+            MagnetLink {
+                info_hash: InfoHash::from_str("6c6a0d3f4f4d3f0e6dd2b7b7d2d8c2f4e1e5b6a7").unwrap(),
+                info_hash_v2: None,
+                display_name: Some("Mandelbrot Set".to_string()),
+                trackers: vec!["udp://tracker.example.com:6969".to_string()],
+                web_seeds: vec!["https://example.com/seed".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_parse_a_base32_info_hash() {
+        // cspell:disable-next-line
+        let magnet_link = MagnetLink::from_str("magnet:?xt=urn:btih:NRVA2P2PJU7Q43OSW635FWGC6TQ6LNVH").unwrap();
+
+        assert_eq!(
+            magnet_link.info_hash.to_hex_string(),
+            "6c6a0d3f4f4d3f0e6dd2b7b7d2d8c2f4e1e5b6a7"
+        );
+    }
+
+    #[test]
+    fn it_should_use_the_truncated_v2_info_hash_when_there_is_no_v1_info_hash() {
+        let info_hash_v2 = InfoHashV2::from_str("8a0e8108d017d17e039fab7d2a02ef92daab6e907a2163f66d6a7d56d99b2648").unwrap();
+
+        let magnet_link = MagnetLink::from_str(&format!("magnet:?xt=urn:btmh:{}", info_hash_v2.to_multihash_string())).unwrap();
+
+        assert_eq!(magnet_link.info_hash, info_hash_v2.truncated());
+        assert_eq!(magnet_link.info_hash_v2, Some(info_hash_v2));
+    }
+
+    #[test]
+    fn it_should_accept_indexed_trackers_and_ignore_duplicates() {
+        let magnet_link = MagnetLink::from_str(
+            "magnet:?xt=urn:btih:6c6a0d3f4f4d3f0e6dd2b7b7d2d8c2f4e1e5b6a7&tr.1=udp%3A%2F%2Fa.com&tr.2=udp%3A%2F%2Fb.com&tr=udp%3A%2F%2Fa.com",
+        )
+        .unwrap();
+
+        assert_eq!(
+            magnet_link.trackers,
+            vec!["udp://a.com".to_string(), "udp://b.com".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_should_be_parsed_back_from_its_string_representation() {
+        let magnet_link = MagnetLink {
+            info_hash: InfoHash::from_str("6c6a0d3f4f4d3f0e6dd2b7b7d2d8c2f4e1e5b6a7").unwrap(),
+            info_hash_v2: Some(InfoHashV2::from_str("8a0e8108d017d17e039fab7d2a02ef92daab6e907a2163f66d6a7d56d99b2648").unwrap()),
+            display_name: Some("Mandelbrot Set & more".to_string()),
+            trackers: vec!["udp://tracker.example.com:6969".to_string()],
+            web_seeds: vec!["https://example.com/seed?file=1".to_string()],
+        };
+
+        assert_eq!(MagnetLink::from_str(&magnet_link.to_string()), Ok(magnet_link));
+    }
+
+    #[test]
+    fn it_should_fail_without_an_info_hash() {
+        assert_eq!(
+            MagnetLink::from_str("magnet:?dn=sample"),
+            Err(ParseMagnetLinkError::MissingInfoHash)
+        );
+        assert_eq!(
+            MagnetLink::from_str("magnet:?xt=urn:btih:1234"),
+            Err(ParseMagnetLinkError::InvalidInfoHash)
+        );
+        assert_eq!(
+            MagnetLink::from_str("https://example.com"),
+            Err(ParseMagnetLinkError::MissingPrefix)
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Emails in the outbox.
+//!
+//! Emails are rendered when they are requested and stored in the outbox until
+//! the mailer worker delivers them.
+use serde::Serialize;
+
+pub type MailId = i64;
+
+/// A rendered email ready to be added to the outbox.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Mail {
+    pub recipient: String,
+    pub subject: String,
+    pub body_plain: String,
+    pub body_html: String,
+}
+
+/// An email waiting in the outbox to be delivered.
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow)]
+pub struct OutboxMail {
+    pub mail_id: MailId,
+    pub recipient: String,
+    pub subject: String,
+    pub body_plain: String,
+    pub body_html: String,
+    /// Failed delivery attempts so far.
+    pub attempts: i64,
+}
+
+/// An email the mailer worker gave up delivering. It's kept in the outbox
+/// until an admin retries it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, sqlx::FromRow)]
+pub struct FailedMail {
+    pub mail_id: MailId,
+    pub recipient: String,
+    pub subject: String,
+    /// Failed delivery attempts.
+    pub attempts: i64,
+    /// The error of the last delivery attempt.
+    pub last_error: Option<String>,
+    pub date_created: String,
+}
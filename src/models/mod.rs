@@ -1,8 +1,13 @@
 pub mod category;
+pub mod file_tree;
 pub mod info_hash;
+pub mod magnet_link;
+pub mod mail;
+pub mod notification;
 pub mod response;
 pub mod torrent;
 pub mod torrent_file;
 pub mod torrent_tag;
 pub mod tracker_key;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,16 @@
+//! Email notifications users can opt into.
+//!
+//! All of them are disabled by default. New torrent notifications are only
+//! sent for the categories and tags the user follows.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NotificationPreferences {
+    /// A new torrent is uploaded in a followed category or with a followed
+    /// tag.
+    pub new_torrents: bool,
+    /// One of the user's torrents is edited or deleted by a moderator.
+    pub torrent_moderation: bool,
+    /// A weekly digest with the top new uploads.
+    pub weekly_digest: bool,
+}
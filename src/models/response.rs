@@ -2,9 +2,10 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 use super::category::Category;
+use super::file_tree::FileTreeEntry;
 use super::torrent::TorrentId;
 use crate::databases::database::Category as DatabaseCategory;
-use crate::models::torrent::TorrentListing;
+use crate::models::torrent::{TorrentFileType, TorrentListing};
 use crate::models::torrent_file::TorrentFile;
 use crate::models::torrent_tag::TorrentTag;
 use crate::services::torrent::CanonicalInfoHashGroup;
@@ -53,6 +54,8 @@ pub struct TorrentResponse {
     pub torrent_id: i64,
     pub uploader: String,
     pub info_hash: String,
+    /// The BEP 52 info-hash for v2 and hybrid torrents.
+    pub info_hash_v2: Option<String>,
     pub title: String,
     pub description: Option<String>,
     pub category: Option<Category>,
@@ -61,7 +64,13 @@ pub struct TorrentResponse {
     pub seeders: i64,
     pub leechers: i64,
     pub files: Vec<TorrentFile>,
+    /// The [`MediaKind`](crate::services::content_type::MediaKind) with the
+    /// largest total size.
+    pub media_kind: Option<String>,
+    pub file_types: Vec<TorrentFileType>,
     pub trackers: Vec<String>,
+    /// BEP 19 web seed URLs.
+    pub web_seeds: Vec<String>,
     pub magnet_link: String,
     pub tags: Vec<TorrentTag>,
     pub name: String,
@@ -70,6 +79,9 @@ pub struct TorrentResponse {
     pub created_by: Option<String>,
     pub encoding: Option<String>,
     pub canonical_info_hash_group: Vec<String>,
+    /// The torrent was listed from a magnet link. Only the magnet link is
+    /// available until someone uploads the torrent file.
+    pub magnet_only: bool,
 }
 
 impl TorrentResponse {
@@ -83,6 +95,7 @@ impl TorrentResponse {
             torrent_id: torrent_listing.torrent_id,
             uploader: torrent_listing.uploader,
             info_hash: torrent_listing.info_hash,
+            info_hash_v2: torrent_listing.info_hash_v2,
             title: torrent_listing.title,
             description: torrent_listing.description,
             category: category.map(std::convert::Into::into),
@@ -91,7 +104,10 @@ impl TorrentResponse {
             seeders: torrent_listing.seeders,
             leechers: torrent_listing.leechers,
             files: vec![],
+            media_kind: None,
+            file_types: vec![],
             trackers: vec![],
+            web_seeds: vec![],
             magnet_link: String::new(),
             tags: vec![],
             name: torrent_listing.name,
@@ -104,6 +120,7 @@ impl TorrentResponse {
                 .iter()
                 .map(super::info_hash::InfoHash::to_hex_string)
                 .collect(),
+            magnet_only: torrent_listing.magnet_only,
         }
     }
 
@@ -123,3 +140,18 @@ pub struct TorrentsResponse {
     pub total: u32,
     pub results: Vec<TorrentListing>,
 }
+
+/// A page of the file listing of a torrent.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TorrentFilesResponse {
+    /// The listed directory, from the torrent root.
+    pub path: Vec<String>,
+    /// Total size in bytes of the listed directory.
+    pub size: i64,
+    /// Number of files in the listed directory and its subdirectories.
+    pub file_count: u64,
+    /// Number of entries in all the pages.
+    pub total: u32,
+    pub results: Vec<FileTreeEntry>,
+}
@@ -16,6 +16,8 @@ pub struct TorrentListing {
     pub torrent_id: TorrentId,
     pub uploader: String,
     pub info_hash: String,
+    /// The BEP 52 info-hash for v2 and hybrid torrents.
+    pub info_hash_v2: Option<String>,
     pub title: String,
     pub description: Option<String>,
     pub category_id: Option<i64>,
@@ -28,6 +30,36 @@ pub struct TorrentListing {
     pub creation_date: Option<i64>,
     pub created_by: Option<String>,
     pub encoding: Option<String>,
+    /// The torrent was listed from a magnet link and it doesn't have the
+    /// meta-info yet.
+    pub magnet_only: bool,
+}
+
+/// A previous version of the torrent meta-info. It's recorded every time the
+/// uploader replaces the torrent file of an existing listing.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TorrentVersion {
+    pub version: i64,
+    pub info_hash: String,
+    pub name: String,
+    pub size: i64,
+    /// The user who uploaded the torrent file that replaced this version.
+    pub replaced_by: Option<i64>,
+    pub date_replaced: String,
+}
+
+/// Number and total size of the files with the same extension in a torrent.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TorrentFileType {
+    /// The [`MediaKind`](crate::services::content_type::MediaKind) of the extension.
+    pub kind: String,
+    /// Lowercase extension without the leading dot. It's empty for files
+    /// without extension.
+    pub extension: String,
+    pub file_count: i64,
+    pub size: i64,
 }
 
 #[derive(Debug, Display, PartialEq, Eq, Error)]
@@ -1,13 +1,20 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_bencode::ser;
+use serde_bencode::value::Value;
 use serde_bytes::ByteBuf;
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use tracing::error;
 use url::Url;
 
-use super::info_hash::InfoHash;
+use super::info_hash::{InfoHash, InfoHashV2};
 use crate::utils::hex::{from_bytes, into_bytes};
 
+/// Value of the `meta version` field for `BitTorrent` v2 torrents.
+const META_VERSION_2: i64 = 2;
+
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Torrent {
     pub info: TorrentInfoDictionary, //
@@ -19,6 +26,12 @@ pub struct Torrent {
     pub encoding: Option<String>,
     #[serde(default)]
     pub httpseeds: Option<Vec<String>>,
+    /// BEP 19: web seed URLs. The key can contain a single URL or a list of
+    /// URLs. It's always serialized as a list.
+    #[serde(default)]
+    #[serde(rename = "url-list")]
+    #[serde(deserialize_with = "deserialize_url_list")]
+    pub url_list: Option<Vec<String>>,
     #[serde(default)]
     #[serde(rename = "announce-list")]
     pub announce_list: Option<Vec<Vec<String>>>,
@@ -30,6 +43,11 @@ pub struct Torrent {
     #[serde(default)]
     #[serde(rename = "created by")]
     pub created_by: Option<String>,
+    /// BEP 52: the hashes of the piece layer of every file larger than one
+    /// piece, indexed by the file's `pieces root`.
+    #[serde(default)]
+    #[serde(rename = "piece layers")]
+    pub piece_layers: Option<BTreeMap<ByteBuf, ByteBuf>>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +72,16 @@ pub struct TorrentInfoDictionary {
     pub root_hash: Option<String>,
     #[serde(default)]
     pub source: Option<String>,
+    /// BEP 52: it's `2` for v2 and hybrid torrents.
+    #[serde(default)]
+    #[serde(rename = "meta version")]
+    pub meta_version: Option<i64>,
+    /// BEP 52: the v2 file tree. It's a nested dictionary of directories
+    /// where each file is a dictionary with an empty key containing its
+    /// `length` and `pieces root`.
+    #[serde(default)]
+    #[serde(rename = "file tree")]
+    pub file_tree: Option<Value>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +90,18 @@ pub struct TorrentFile {
     pub length: i64,
     #[serde(default)]
     pub md5sum: Option<String>,
+    /// BEP 47 file attributes. Hybrid torrents use padding files (`p`) to
+    /// align the v1 files to piece boundaries.
+    #[serde(default)]
+    pub attr: Option<String>,
+}
+
+impl TorrentFile {
+    /// It returns true if the file is a BEP 47 padding file.
+    #[must_use]
+    pub fn is_padding(&self) -> bool {
+        self.attr.as_ref().is_some_and(|attr| attr.contains('p'))
+    }
 }
 
 impl Torrent {
@@ -77,33 +117,77 @@ impl Torrent {
         torrent_files: &[TorrentFile],
         torrent_announce_urls: Vec<Vec<String>>,
         torrent_http_seed_urls: Vec<String>,
+        torrent_web_seed_urls: Vec<String>,
         torrent_nodes: Vec<(String, i64)>,
     ) -> Self {
-        let pieces_or_root_hash = if db_torrent.is_bep_30 == 0 {
-            if let Some(pieces) = &db_torrent.pieces {
-                pieces.clone()
-            } else {
-                error!("Invalid torrent #{}. Null `pieces` in database", db_torrent.torrent_id);
-                String::new()
+        let info_dict = if db_torrent.meta_version == Some(META_VERSION_2) && db_torrent.pieces.is_none() {
+            // A v2-only torrent. It doesn't have the v1 `pieces`, `length`
+            // and `files` keys.
+            TorrentInfoDictionary {
+                name: db_torrent.name.clone(),
+                pieces: None,
+                piece_length: db_torrent.piece_length,
+                md5sum: None,
+                length: None,
+                files: None,
+                private: db_torrent.private,
+                path: None,
+                root_hash: None,
+                source: None,
+                meta_version: None,
+                file_tree: None,
             }
         } else {
-            // A BEP-30 torrent
-            if let Some(root_hash) = &db_torrent.root_hash {
-                root_hash.clone()
+            let pieces_or_root_hash = if db_torrent.is_bep_30 == 0 {
+                if let Some(pieces) = &db_torrent.pieces {
+                    pieces.clone()
+                } else {
+                    error!("Invalid torrent #{}. Null `pieces` in database", db_torrent.torrent_id);
+                    String::new()
+                }
             } else {
-                error!("Invalid torrent #{}. Null `root_hash` in database", db_torrent.torrent_id);
-                String::new()
-            }
+                // A BEP-30 torrent
+                if let Some(root_hash) = &db_torrent.root_hash {
+                    root_hash.clone()
+                } else {
+                    error!("Invalid torrent #{}. Null `root_hash` in database", db_torrent.torrent_id);
+                    String::new()
+                }
+            };
+
+            TorrentInfoDictionary::with(
+                &db_torrent.name,
+                db_torrent.piece_length,
+                db_torrent.private,
+                db_torrent.is_bep_30,
+                &pieces_or_root_hash,
+                torrent_files,
+            )
+        };
+
+        let info_dict = TorrentInfoDictionary {
+            source: db_torrent.source.clone(),
+            meta_version: db_torrent.meta_version,
+            file_tree: db_torrent.file_tree.as_ref().and_then(|file_tree| {
+                let file_tree = decode_hex_bencoded(file_tree);
+                if file_tree.is_none() {
+                    error!("Invalid torrent #{}. Invalid `file_tree` in database", db_torrent.torrent_id);
+                }
+                file_tree
+            }),
+            ..info_dict
         };
 
-        let info_dict = TorrentInfoDictionary::with(
-            &db_torrent.name,
-            db_torrent.piece_length,
-            db_torrent.private,
-            db_torrent.is_bep_30,
-            &pieces_or_root_hash,
-            torrent_files,
-        );
+        let piece_layers = db_torrent.piece_layers.as_ref().and_then(|piece_layers| {
+            let piece_layers = decode_hex_bencoded(piece_layers);
+            if piece_layers.is_none() {
+                error!(
+                    "Invalid torrent #{}. Invalid `piece_layers` in database",
+                    db_torrent.torrent_id
+                );
+            }
+            piece_layers
+        });
 
         Self {
             info: info_dict,
@@ -115,10 +199,16 @@ impl Torrent {
             } else {
                 Some(torrent_http_seed_urls)
             },
+            url_list: if torrent_web_seed_urls.is_empty() {
+                None
+            } else {
+                Some(torrent_web_seed_urls)
+            },
             announce_list: Some(torrent_announce_urls),
             creation_date: db_torrent.creation_date,
             comment: db_torrent.comment.clone(),
             created_by: db_torrent.created_by.clone(),
+            piece_layers,
         }
     }
 
@@ -131,6 +221,13 @@ impl Torrent {
         self.add_url_to_front_of_announce_list(tracker_url);
     }
 
+    /// Sets the `source` key of the `info` dictionary. Private trackers use it
+    /// to give cross-seeded torrents a unique info-hash, so it changes the
+    /// info-hash.
+    pub fn set_source(&mut self, source: &str) {
+        self.info.source = Some(source.to_owned());
+    }
+
     /// Sets the announce url to the tracker url.
     pub fn set_announce_to(&mut self, tracker_url: &Url) {
         self.announce = Some(tracker_url.to_owned().to_string());
@@ -179,11 +276,17 @@ impl Torrent {
 
     /// It calculates the info hash of the torrent file.
     ///
+    /// For v2-only torrents it's the v2 info-hash truncated to 20 bytes.
+    ///
     /// # Panics
     ///
     /// This function will panic if the `info` part of the torrent file cannot be serialized.
     #[must_use]
     pub fn calculate_info_hash_as_bytes(&self) -> [u8; 20] {
+        if self.info.is_v2_only() {
+            return self.calculate_info_hash_v2().truncated().bytes();
+        }
+
         let info_bencoded = ser::to_bytes(&self.info).expect("variable `info` was not able to be serialized.");
         let mut hasher = Sha1::new();
         hasher.update(info_bencoded);
@@ -193,6 +296,30 @@ impl Torrent {
         sum_bytes
     }
 
+    /// It calculates the v2 info hash of the torrent file, even if it's not a
+    /// v2 torrent.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the `info` part of the torrent file cannot be serialized.
+    #[must_use]
+    pub fn calculate_info_hash_v2(&self) -> InfoHashV2 {
+        let info_bencoded = ser::to_bytes(&self.info).expect("variable `info` was not able to be serialized.");
+        let mut hasher = Sha256::new();
+        hasher.update(info_bencoded);
+        InfoHashV2::from_bytes(hasher.finalize().as_slice())
+    }
+
+    /// The v2 info-hash for v2 and hybrid torrents.
+    #[must_use]
+    pub fn info_hash_v2(&self) -> Option<InfoHashV2> {
+        if self.info.is_v2() {
+            Some(self.calculate_info_hash_v2())
+        } else {
+            None
+        }
+    }
+
     #[must_use]
     pub fn canonical_info_hash(&self) -> InfoHash {
         self.calculate_info_hash_as_bytes().into()
@@ -203,15 +330,16 @@ impl Torrent {
         self.canonical_info_hash().to_hex_string()
     }
 
+    /// The total size of the files in the torrent, excluding padding files.
     #[must_use]
     pub fn file_size(&self) -> i64 {
         match self.info.length {
             Some(length) => length,
             None => match &self.info.files {
-                None => 0,
+                None => self.info.v2_files().iter().map(|file| file.length).sum(),
                 Some(files) => {
                     let mut file_size = 0;
-                    for file in files {
+                    for file in files.iter().filter(|file| !file.is_padding()) {
                         file_size += file.length;
                     }
                     file_size
@@ -220,6 +348,18 @@ impl Torrent {
         }
     }
 
+    /// The BEP 52 piece layers as a hex string of the bencoded dictionary.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the piece layers cannot be serialized.
+    #[must_use]
+    pub fn get_piece_layers_as_string(&self) -> Option<String> {
+        self.piece_layers.as_ref().map(|piece_layers| {
+            from_bytes(&ser::to_bytes(piece_layers).expect("variable `piece_layers` was not able to be serialized."))
+        })
+    }
+
     /// It returns the announce urls of the torrent file.
     ///
     /// # Panics
@@ -273,6 +413,8 @@ impl TorrentInfoDictionary {
             path: None,
             root_hash: None,
             source: None,
+            meta_version: None,
+            file_tree: None,
         };
 
         // BEP 30: <http://www.bittorrent.org/beps/bep_0030.html>.
@@ -334,6 +476,45 @@ impl TorrentInfoDictionary {
         }
     }
 
+    /// The BEP 52 file tree as a hex string of the bencoded dictionary.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the file tree cannot be serialized.
+    #[must_use]
+    pub fn get_file_tree_as_string(&self) -> Option<String> {
+        self.file_tree
+            .as_ref()
+            .map(|file_tree| from_bytes(&ser::to_bytes(file_tree).expect("variable `file_tree` was not able to be serialized.")))
+    }
+
+    /// It returns the files in the BEP 52 file tree, sorted by path. It's
+    /// empty if the torrent is not a v2 torrent.
+    #[must_use]
+    pub fn v2_files(&self) -> Vec<TorrentFile> {
+        let mut files = vec![];
+
+        if let Some(file_tree) = &self.file_tree {
+            collect_file_tree_files(file_tree, &mut vec![], &mut files);
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        files
+    }
+
+    /// It returns true if the torrent is a v2 or hybrid torrent (BEP 52).
+    #[must_use]
+    pub fn is_v2(&self) -> bool {
+        self.meta_version == Some(META_VERSION_2)
+    }
+
+    /// It returns true if the torrent is a v2 torrent without the v1 fields.
+    #[must_use]
+    pub fn is_v2_only(&self) -> bool {
+        self.is_v2() && self.pieces.is_none()
+    }
+
     /// It returns true if the torrent is a BEP-30 torrent.
     #[must_use]
     pub fn is_bep_30(&self) -> bool {
@@ -351,6 +532,66 @@ impl TorrentInfoDictionary {
     }
 }
 
+/// It adds the files in a BEP 52 file tree node to `files`. `path` is the path
+/// of the node from the torrent root.
+fn collect_file_tree_files(node: &Value, path: &mut Vec<String>, files: &mut Vec<TorrentFile>) {
+    let Value::Dict(entries) = node else {
+        return;
+    };
+
+    for (name, child) in entries {
+        if name.is_empty() {
+            // The file entry. Its parent key is the file name.
+            if let Value::Dict(file) = child {
+                let length = match file.get(b"length".as_slice()) {
+                    Some(Value::Int(length)) => *length,
+                    _ => 0,
+                };
+
+                files.push(TorrentFile {
+                    path: path.clone(),
+                    length,
+                    md5sum: None,
+                    attr: None,
+                });
+            }
+        } else {
+            path.push(String::from_utf8_lossy(name).to_string());
+            collect_file_tree_files(child, path, files);
+            path.pop();
+        }
+    }
+}
+
+fn decode_hex_bencoded<T: serde::de::DeserializeOwned>(hex: &str) -> Option<T> {
+    let bytes = into_bytes(hex).ok()?;
+    serde_bencode::from_bytes(&bytes).ok()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UrlList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// It deserializes the BEP 19 `url-list` key, which can be a single URL or a
+/// list of URLs. Empty URLs are ignored.
+fn deserialize_url_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let urls = match Option::<UrlList>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(UrlList::One(url)) => vec![url],
+        Some(UrlList::Many(urls)) => urls,
+    };
+
+    let urls: Vec<String> = urls.into_iter().filter(|url| !url.is_empty()).collect();
+
+    Ok(if urls.is_empty() { None } else { Some(urls) })
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct DbTorrent {
     pub torrent_id: i64,
@@ -366,6 +607,18 @@ pub struct DbTorrent {
     pub creation_date: Option<i64>,
     pub created_by: Option<String>,
     pub encoding: Option<String>,
+    pub meta_version: Option<i64>,
+    pub info_hash_v2: Option<String>,
+    /// Hex string of the bencoded BEP 52 file tree.
+    pub file_tree: Option<String>,
+    /// Hex string of the bencoded BEP 52 piece layers.
+    pub piece_layers: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// The torrent was listed from a magnet link and it doesn't have the
+    /// meta-info yet.
+    #[serde(default)]
+    pub magnet_only: bool,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -375,6 +628,8 @@ pub struct DbTorrentFile {
     pub length: i64,
     #[serde(default)]
     pub md5sum: Option<String>,
+    #[serde(default)]
+    pub attr: Option<String>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -387,6 +642,24 @@ pub struct DbTorrentHttpSeedUrl {
     pub seed_url: String,
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DbTorrentWebSeedUrl {
+    pub seed_url: String,
+}
+
+/// The original torrent file uploaded by the user.
+#[derive(PartialEq, Eq, Debug, Clone, sqlx::FromRow)]
+pub struct DbTorrentOriginalFile {
+    pub torrent_id: i64,
+    /// The original info-hash.
+    pub info_hash: String,
+    /// The [`Backend`](crate::config::TorrentStorageBackend) where the bytes
+    /// are stored.
+    pub backend: String,
+    /// The file contents when the backend is `database`.
+    pub bytes: Option<Vec<u8>>,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct DbTorrentNode {
     pub node_ip: String,
@@ -451,6 +724,8 @@ mod tests {
                 path: None,
                 root_hash: None,
                 source: None,
+                meta_version: None,
+                file_tree: None,
             };
 
             let torrent = Torrent {
@@ -463,6 +738,8 @@ mod tests {
                 nodes: None,
                 encoding: None,
                 httpseeds: None,
+                url_list: None,
+                piece_layers: None,
             };
 
             assert_eq!(torrent.canonical_info_hash_hex(), "79fa9e4a2927804fe4feab488a76c8c2d3d1cdca");
@@ -492,6 +769,8 @@ mod tests {
                     path: None,
                     root_hash: None,
                     source: None,
+                    meta_version: None,
+                    file_tree: None,
                 };
 
                 let torrent = Torrent {
@@ -504,6 +783,8 @@ mod tests {
                     nodes: None,
                     encoding: None,
                     httpseeds: None,
+                    url_list: None,
+                    piece_layers: None,
                 };
 
                 assert_eq!(torrent.canonical_info_hash_hex(), "79fa9e4a2927804fe4feab488a76c8c2d3d1cdca");
@@ -526,11 +807,14 @@ mod tests {
                         path: vec!["sample.txt".to_string()],
                         length: sample_data_in_txt_file.len().try_into().unwrap(),
                         md5sum: None,
+                        attr: None,
                     }]),
                     private: None,
                     path: None,
                     root_hash: None,
                     source: None,
+                    meta_version: None,
+                    file_tree: None,
                 };
 
                 let torrent = Torrent {
@@ -543,6 +827,8 @@ mod tests {
                     nodes: None,
                     encoding: None,
                     httpseeds: None,
+                    url_list: None,
+                    piece_layers: None,
                 };
 
                 assert_eq!(torrent.canonical_info_hash_hex(), "aa2aca91ab650c4d249c475ca3fa604f2ccb0d2a");
@@ -566,6 +852,8 @@ mod tests {
                     path: None,
                     root_hash: None,
                     source: Some("ABC".to_string()), // The tracker three-letter code
+                    meta_version: None,
+                    file_tree: None,
                 };
 
                 let torrent = Torrent {
@@ -578,6 +866,8 @@ mod tests {
                     nodes: None,
                     encoding: None,
                     httpseeds: None,
+                    url_list: None,
+                    piece_layers: None,
                 };
 
                 assert_eq!(torrent.canonical_info_hash_hex(), "ccc1cf4feb59f3fa85c96c9be1ebbafcfe8a9cc8");
@@ -601,6 +891,8 @@ mod tests {
                     path: None,
                     root_hash: None,
                     source: None,
+                    meta_version: None,
+                    file_tree: None,
                 };
 
                 let torrent = Torrent {
@@ -613,10 +905,261 @@ mod tests {
                     nodes: None,
                     encoding: None,
                     httpseeds: None,
+                    url_list: None,
+                    piece_layers: None,
                 };
 
                 assert_eq!(torrent.canonical_info_hash_hex(), "d3a558d0a19aaa23ba6f9f430f40924d10fefa86");
             }
         }
     }
+
+    mod bittorrent_v2 {
+
+        use crate::models::info_hash::InfoHashV2;
+        use crate::models::torrent_file::{DbTorrent, Torrent, TorrentFile};
+        use crate::utils::parse_torrent::{decode_torrent, encode_torrent};
+
+        /// A hybrid single-file torrent with two pieces. It contains the
+        /// `piece layers` for the file.
+        fn hybrid_torrent_bytes() -> &'static [u8] {
+            include_bytes!("../../tests/fixtures/torrents/b514d284ac40445d7456ca85a5b8ea651a5bf229_hybrid.torrent")
+        }
+
+        /// A v2-only torrent with two files: `a.txt` and `docs/b.txt`.
+        fn v2_only_torrent_bytes() -> &'static [u8] {
+            include_bytes!("../../tests/fixtures/torrents/8a0e8108d017d17e039fab7d2a02ef92daab6e90_v2_only.torrent")
+        }
+
+        /// The torrent as it's stored in the database.
+        fn db_torrent(torrent: &Torrent) -> DbTorrent {
+            DbTorrent {
+                torrent_id: 1,
+                info_hash: torrent.canonical_info_hash_hex(),
+                name: torrent.info.name.clone(),
+                pieces: torrent.info.pieces.as_ref().map(|_| torrent.info.get_pieces_as_string()),
+                root_hash: None,
+                piece_length: torrent.info.piece_length,
+                private: torrent.info.private,
+                is_bep_30: 0,
+                comment: torrent.comment.clone(),
+                creation_date: torrent.creation_date,
+                created_by: torrent.created_by.clone(),
+                encoding: torrent.encoding.clone(),
+                meta_version: torrent.info.meta_version,
+                info_hash_v2: torrent.info_hash_v2().map(|info_hash| info_hash.to_hex_string()),
+                file_tree: torrent.info.get_file_tree_as_string(),
+                piece_layers: torrent.get_piece_layers_as_string(),
+                source: torrent.info.source.clone(),
+                magnet_only: false,
+            }
+        }
+
+        fn from_database(torrent: &Torrent, files: &[TorrentFile]) -> Torrent {
+            Torrent::from_database(
+                &db_torrent(torrent),
+                files,
+                vec![vec![torrent.announce.clone().unwrap()]],
+                vec![],
+                vec![],
+                vec![],
+            )
+        }
+
+        #[test]
+        fn it_should_keep_the_v2_fields_when_a_hybrid_torrent_is_encoded_again() {
+            let torrent = decode_torrent(hybrid_torrent_bytes()).unwrap();
+
+            assert!(torrent.info.is_v2());
+            assert!(!torrent.info.is_v2_only());
+            assert_eq!(torrent.piece_layers.as_ref().map(std::collections::BTreeMap::len), Some(1));
+            assert_eq!(encode_torrent(&torrent).unwrap(), hybrid_torrent_bytes());
+        }
+
+        #[test]
+        fn it_should_calculate_both_info_hashes_for_a_hybrid_torrent() {
+            let torrent = decode_torrent(hybrid_torrent_bytes()).unwrap();
+
+            assert_eq!(torrent.canonical_info_hash_hex(), "b514d284ac40445d7456ca85a5b8ea651a5bf229");
+            assert_eq!(
+                torrent.info_hash_v2().map(|info_hash| info_hash.to_hex_string()),
+                Some("446524d56a01abc6ae4464e7a00d7d620a11246311b7245899b143c0e2c574a5".to_string())
+            );
+        }
+
+        #[test]
+        fn it_should_use_the_truncated_v2_info_hash_as_the_info_hash_for_v2_only_torrents() {
+            let torrent = decode_torrent(v2_only_torrent_bytes()).unwrap();
+
+            let info_hash_v2: InfoHashV2 = torrent.info_hash_v2().unwrap();
+
+            assert_eq!(
+                info_hash_v2.to_hex_string(),
+                "8a0e8108d017d17e039fab7d2a02ef92daab6e907a2163f66d6a7d56d99b2648"
+            );
+            assert_eq!(torrent.canonical_info_hash(), info_hash_v2.truncated());
+        }
+
+        #[test]
+        fn it_should_list_the_files_in_the_v2_file_tree() {
+            let torrent = decode_torrent(v2_only_torrent_bytes()).unwrap();
+
+            let files: Vec<(String, i64)> = torrent
+                .info
+                .v2_files()
+                .iter()
+                .map(|file| (file.path.join("/"), file.length))
+                .collect();
+
+            assert_eq!(files, vec![("a.txt".to_string(), 9), ("docs/b.txt".to_string(), 12)]);
+            assert_eq!(torrent.file_size(), 21);
+        }
+
+        #[test]
+        fn it_should_rebuild_a_hybrid_torrent_from_the_database() {
+            let torrent = decode_torrent(hybrid_torrent_bytes()).unwrap();
+
+            let files = vec![TorrentFile {
+                path: vec![String::new()],
+                length: torrent.info.length.unwrap(),
+                md5sum: None,
+                attr: None,
+            }];
+
+            let rebuilt_torrent = from_database(&torrent, &files);
+
+            assert_eq!(rebuilt_torrent.info, torrent.info);
+            assert_eq!(rebuilt_torrent.piece_layers, torrent.piece_layers);
+        }
+
+        #[test]
+        fn it_should_rebuild_a_v2_only_torrent_from_the_database() {
+            let torrent = decode_torrent(v2_only_torrent_bytes()).unwrap();
+
+            let rebuilt_torrent = from_database(&torrent, &torrent.info.v2_files());
+
+            assert_eq!(rebuilt_torrent.info, torrent.info);
+            assert_eq!(rebuilt_torrent.canonical_info_hash(), torrent.canonical_info_hash());
+        }
+    }
+
+    mod web_seeds {
+
+        use crate::models::torrent_file::Torrent;
+        use crate::utils::parse_torrent::{decode_torrent, encode_torrent};
+
+        /// A single-file torrent with the given bencoded `url-list` value.
+        fn torrent_with_url_list(url_list: &str) -> Vec<u8> {
+            format!("d4:infod6:lengthi11e4:name10:sample.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaae8:url-list{url_list}e")
+                .into_bytes()
+        }
+
+        fn decode(bytes: &[u8]) -> Torrent {
+            decode_torrent(bytes).unwrap()
+        }
+
+        #[test]
+        fn it_should_decode_a_url_list_with_a_single_url() {
+            let torrent = decode(&torrent_with_url_list("24:https://example.com/seed"));
+
+            assert_eq!(torrent.url_list, Some(vec!["https://example.com/seed".to_string()]));
+        }
+
+        #[test]
+        fn it_should_decode_a_url_list_with_a_list_of_urls() {
+            let torrent = decode(&torrent_with_url_list("l18:https://a.com/seed18:https://b.com/seede"));
+
+            assert_eq!(
+                torrent.url_list,
+                Some(vec!["https://a.com/seed".to_string(), "https://b.com/seed".to_string()])
+            );
+        }
+
+        #[test]
+        fn it_should_ignore_an_empty_url_list() {
+            assert_eq!(decode(&torrent_with_url_list("0:")).url_list, None);
+            assert_eq!(decode(&torrent_with_url_list("le")).url_list, None);
+        }
+
+        #[test]
+        fn it_should_encode_the_url_list_as_a_list() {
+            let torrent = decode(&torrent_with_url_list("24:https://example.com/seed"));
+
+            let bytes = encode_torrent(&torrent).unwrap();
+
+            assert_eq!(decode(&bytes).url_list, torrent.url_list);
+            assert!(bytes.ends_with(b"8:url-listl24:https://example.com/seedee"));
+        }
+
+        #[test]
+        fn it_should_not_change_the_info_hash() {
+            let with_url_list = decode(&torrent_with_url_list("24:https://example.com/seed"));
+            let without_url_list = Torrent {
+                url_list: None,
+                ..with_url_list.clone()
+            };
+
+            assert_eq!(with_url_list.canonical_info_hash(), without_url_list.canonical_info_hash());
+        }
+    }
+
+    mod source {
+
+        use crate::models::torrent_file::{DbTorrent, Torrent, TorrentFile};
+        use crate::utils::parse_torrent::decode_torrent;
+
+        fn sample_torrent() -> Torrent {
+            decode_torrent(b"d4:infod6:lengthi11e4:name10:sample.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee")
+                .unwrap()
+        }
+
+        #[test]
+        fn setting_the_source_should_change_the_info_hash() {
+            let mut torrent = sample_torrent();
+            let original_info_hash = torrent.canonical_info_hash();
+
+            torrent.set_source("Torrust");
+
+            assert_eq!(torrent.info.source, Some("Torrust".to_string()));
+            assert_ne!(torrent.canonical_info_hash(), original_info_hash);
+        }
+
+        #[test]
+        fn it_should_keep_the_source_when_the_torrent_is_rebuilt_from_the_database() {
+            let mut torrent = sample_torrent();
+            torrent.set_source("Torrust");
+
+            let db_torrent = DbTorrent {
+                torrent_id: 1,
+                info_hash: torrent.canonical_info_hash_hex(),
+                name: torrent.info.name.clone(),
+                pieces: Some(torrent.info.get_pieces_as_string()),
+                root_hash: None,
+                piece_length: torrent.info.piece_length,
+                private: None,
+                is_bep_30: 0,
+                comment: None,
+                creation_date: None,
+                created_by: None,
+                encoding: None,
+                meta_version: None,
+                info_hash_v2: None,
+                file_tree: None,
+                piece_layers: None,
+                source: torrent.info.source.clone(),
+                magnet_only: false,
+            };
+
+            let files = vec![TorrentFile {
+                path: vec![String::new()],
+                length: 11,
+                md5sum: None,
+                attr: None,
+            }];
+
+            let rebuilt = Torrent::from_database(&db_torrent, &files, vec![], vec![], vec![], vec![]);
+
+            assert_eq!(rebuilt.canonical_info_hash(), torrent.canonical_info_hash());
+        }
+    }
 }
@@ -65,23 +65,54 @@ pub struct UserClaims {
 const MAX_USERNAME_LENGTH: usize = 20;
 const USERNAME_VALIDATION_ERROR_MSG: &str = "Usernames must consist of 1-20 alphanumeric characters, dashes, or underscore";
 
+/// Offset between the fullwidth forms (`U+FF01`..`U+FF5E`) and their ASCII
+/// equivalents (`U+0021`..`U+007E`).
+const FULLWIDTH_OFFSET: u32 = 0xFEE0;
+
 #[derive(Debug, Clone)]
-pub struct UsernameParseError {
-    message: String,
+pub enum UsernameParseError {
+    /// The username contains characters the
+    /// [`UsernameCaseMapped`](https://tools.ietf.org/html/rfc8265#section-3.3)
+    /// profile does not allow, like spaces, control characters or symbols.
+    DisallowedCharacters { message: String },
+    /// The username is allowed by the profile but it does not follow the
+    /// Index rules.
+    Invalid { message: String },
 }
 
 // Implement std::fmt::Display for UsernameParseError
 impl fmt::Display for UsernameParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "UsernameParseError: {}", self.message)
+        match self {
+            Self::DisallowedCharacters { message } | Self::Invalid { message } => write!(f, "UsernameParseError: {message}"),
+        }
     }
 }
 
 // Implement std::error::Error for UsernameParseError
 impl std::error::Error for UsernameParseError {}
 
+/// A valid username.
+///
+/// Parsing applies the width mapping of the RFC 8265 `UsernameCaseMapped`
+/// profile, so fullwidth characters like `Ａｌｉｃｅ` become `Alice`. The case
+/// is preserved for display, and [`Username::case_mapped`] returns the form
+/// used to compare usernames.
 pub struct Username(String);
 
+impl Username {
+    /// The username with the case mapping of the RFC 8265
+    /// `UsernameCaseMapped` profile. Two usernames with the same case mapped
+    /// form are the same username.
+    ///
+    /// Valid usernames are ASCII, so the Unicode normalization of the profile
+    /// does not change them.
+    #[must_use]
+    pub fn case_mapped(&self) -> String {
+        self.0.to_lowercase()
+    }
+}
+
 impl fmt::Display for Username {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -93,8 +124,16 @@ impl FromStr for Username {
     type Err = UsernameParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: String = s.chars().map(width_map).collect();
+
+        if s.chars().any(is_disallowed) {
+            return Err(UsernameParseError::DisallowedCharacters {
+                message: format!("'{s}' contains characters that are not allowed in usernames. {USERNAME_VALIDATION_ERROR_MSG}."),
+            });
+        }
+
         if s.len() > MAX_USERNAME_LENGTH {
-            return Err(UsernameParseError {
+            return Err(UsernameParseError::Invalid {
                 message: format!("username '{s}' is too long. {USERNAME_VALIDATION_ERROR_MSG}."),
             });
         }
@@ -102,16 +141,32 @@ impl FromStr for Username {
         let pattern = format!(r"^[A-Za-z0-9-_]{{1,{MAX_USERNAME_LENGTH}}}$");
         let re = Regex::new(&pattern).expect("username regexp should be valid");
 
-        if re.is_match(s) {
-            Ok(Username(s.to_string()))
+        if re.is_match(&s) {
+            Ok(Username(s))
         } else {
-            Err(UsernameParseError {
+            Err(UsernameParseError::Invalid {
                 message: format!("'{s}' is not a valid username. {USERNAME_VALIDATION_ERROR_MSG}."),
             })
         }
     }
 }
 
+/// It maps fullwidth characters to their ASCII equivalents and the
+/// ideographic space to a space.
+fn width_map(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - FULLWIDTH_OFFSET).unwrap_or(c),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
+
+/// Characters disallowed by the `IdentifierClass` of the profile: spaces,
+/// control characters and symbols and punctuation outside ASCII.
+fn is_disallowed(c: char) -> bool {
+    c.is_whitespace() || c.is_control() || !(c.is_alphanumeric() || c.is_ascii_graphic())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,6 +189,30 @@ mod tests {
         assert!(username_str.parse::<Username>().is_err());
     }
 
+    #[test]
+    fn username_should_not_allow_characters_disallowed_by_the_username_case_mapped_profile() {
+        for username_str in ["first last", "tab\tname", "smile\u{263A}", "first\u{3000}last"] {
+            assert!(matches!(
+                username_str.parse::<Username>(),
+                Err(UsernameParseError::DisallowedCharacters { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn username_should_map_fullwidth_characters_to_ascii() {
+        let username = "\u{FF21}lice".parse::<Username>().unwrap();
+        assert_eq!(username.to_string(), "Alice");
+    }
+
+    #[test]
+    fn usernames_differing_only_in_case_should_have_the_same_case_mapped_form() {
+        let first = "Alice".parse::<Username>().unwrap();
+        let second = "aLICE".parse::<Username>().unwrap();
+        assert_eq!(first.case_mapped(), second.case_mapped());
+        assert_eq!(first.to_string(), "Alice");
+    }
+
     #[test]
     fn username_should_be_displayed() {
         let username = Username("FirstLast-01".to_string());
@@ -0,0 +1,174 @@
+//! Webhooks configured by the admins to be notified about Index events.
+//!
+//! Every event sent to a webhook is stored as a [`WebhookDelivery`]. The
+//! deliveries are kept after they succeed or fail, so they can be inspected
+//! and replayed.
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub type WebhookId = i64;
+
+pub type DeliveryId = i64;
+
+/// The Index events webhooks can subscribe to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "torrent.uploaded")]
+    TorrentUploaded,
+    #[serde(rename = "torrent.updated")]
+    TorrentUpdated,
+    #[serde(rename = "torrent.deleted")]
+    TorrentDeleted,
+    #[serde(rename = "user.registered")]
+    UserRegistered,
+    #[serde(rename = "user.banned")]
+    UserBanned,
+}
+
+impl WebhookEvent {
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::TorrentUploaded => "torrent.uploaded",
+            WebhookEvent::TorrentUpdated => "torrent.updated",
+            WebhookEvent::TorrentDeleted => "torrent.deleted",
+            WebhookEvent::UserRegistered => "user.registered",
+            WebhookEvent::UserBanned => "user.banned",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "torrent.uploaded" => Ok(WebhookEvent::TorrentUploaded),
+            "torrent.updated" => Ok(WebhookEvent::TorrentUpdated),
+            "torrent.deleted" => Ok(WebhookEvent::TorrentDeleted),
+            "user.registered" => Ok(WebhookEvent::UserRegistered),
+            "user.banned" => Ok(WebhookEvent::UserBanned),
+            _ => Err(format!("unknown webhook event: {s}")),
+        }
+    }
+}
+
+/// The events a webhook is subscribed to.
+///
+/// It's stored in the database as a comma-separated list of event names.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventFilter(Vec<WebhookEvent>);
+
+impl EventFilter {
+    /// It removes the duplicated events.
+    #[must_use]
+    pub fn new(mut events: Vec<WebhookEvent>) -> Self {
+        events.sort_unstable();
+        events.dedup();
+        Self(events)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[must_use]
+    pub fn contains(&self, event: WebhookEvent) -> bool {
+        self.0.contains(&event)
+    }
+}
+
+impl fmt::Display for EventFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(WebhookEvent::name).collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+impl TryFrom<String> for EventFilter {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(WebhookEvent::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map(EventFilter::new)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, sqlx::FromRow)]
+pub struct Webhook {
+    pub webhook_id: WebhookId,
+    pub url: String,
+    /// Key used to sign the payloads. It's never returned by the API.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    #[sqlx(try_from = "String")]
+    pub events: EventFilter,
+    pub active: bool,
+    pub date_created: String,
+}
+
+/// An event sent, or to be sent, to a webhook.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub delivery_id: DeliveryId,
+    pub webhook_id: WebhookId,
+    pub event: String,
+    pub payload: String,
+    /// `pending`, `delivered` or `failed`.
+    pub status: String,
+    pub attempts: i64,
+    /// HTTP status code returned by the receiver in the last attempt.
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub date_created: String,
+    pub date_delivered: Option<String>,
+}
+
+/// A pending delivery with the webhook data needed to send it.
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow)]
+pub struct DueWebhookDelivery {
+    pub delivery_id: DeliveryId,
+    pub webhook_id: WebhookId,
+    pub url: String,
+    pub secret: String,
+    pub event: String,
+    pub payload: String,
+    /// Failed delivery attempts so far.
+    pub attempts: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventFilter, WebhookEvent};
+
+    #[test]
+    fn the_event_filter_should_be_stored_as_a_comma_separated_list_of_event_names() {
+        let filter = EventFilter::new(vec![
+            WebhookEvent::UserBanned,
+            WebhookEvent::TorrentUploaded,
+            WebhookEvent::UserBanned,
+        ]);
+
+        assert_eq!(filter.to_string(), "torrent.uploaded,user.banned");
+        assert_eq!(EventFilter::try_from(filter.to_string()), Ok(filter));
+    }
+
+    #[test]
+    fn the_event_filter_should_reject_unknown_events() {
+        assert!(EventFilter::try_from("torrent.uploaded,torrent.commented".to_string()).is_err());
+    }
+}
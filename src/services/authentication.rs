@@ -1,12 +1,17 @@
 //! Authentication services.
+use std::str::FromStr;
 use std::sync::Arc;
 
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use tracing::warn;
 
 use super::user::DbUserProfileRepository;
-use crate::config::Configuration;
+use crate::config::{Argon2Params, Configuration};
 use crate::databases::database::{Database, Error};
 use crate::errors::ServiceError;
 use crate::models::user::{UserAuthentication, UserClaims, UserCompact, UserId};
@@ -69,6 +74,8 @@ impl Service {
 
         let settings = self.configuration.settings.read().await;
 
+        let argon2_params = settings.auth.argon2.clone();
+
         // Fail login if email verification is required and this email is not verified
         if let Some(registration) = &settings.registration {
             if let Some(email) = &registration.email {
@@ -81,6 +88,10 @@ impl Service {
         // Drop read lock on settings
         drop(settings);
 
+        if password_hash_needs_rehash(&user_authentication.password_hash, &argon2_params) {
+            self.rehash_password(user_profile.user_id, password, &argon2_params).await;
+        }
+
         let user_compact = self.user_repository.get_compact(&user_profile.user_id).await?;
 
         // Sign JWT with compact user details as payload
@@ -113,6 +124,27 @@ impl Service {
 
         Ok((token, user_compact))
     }
+
+    /// It replaces the password hash with a new one generated with the
+    /// current algorithm and parameters. The login does not fail if the hash
+    /// can't be replaced, it will be tried again the next time.
+    async fn rehash_password(&self, user_id: UserId, password: &str, argon2_params: &Argon2Params) {
+        let password_hash = match hash_password(password, argon2_params) {
+            Ok(password_hash) => password_hash,
+            Err(err) => {
+                warn!("Failed to rehash the password of user {user_id}: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = self
+            .user_authentication_repository
+            .change_password(user_id, &password_hash)
+            .await
+        {
+            warn!("Failed to save the rehashed password of user {user_id}: {err:?}");
+        }
+    }
 }
 
 pub struct JsonWebToken {
@@ -198,38 +230,103 @@ impl DbUserAuthenticationRepository {
     }
 }
 
+/// Hash the password with `Argon2id` and the cost parameters in the
+/// configuration.
+///
+/// # Errors
+///
+/// This function will return an error if the cost parameters are not valid or
+/// the password can't be hashed.
+pub fn hash_password(password: &str, argon2_params: &Argon2Params) -> Result<String, ServiceError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    let params = Params::new(
+        argon2_params.memory_cost,
+        argon2_params.time_cost,
+        argon2_params.parallelism,
+        None,
+    )
+    .map_err(|_| ServiceError::InternalServerError)?;
+
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params);
+
+    // Hash password to PHC string ($argon2id$v=19$...)
+    let password_hash = argon2.hash_password(password.as_bytes(), &salt)?.to_string();
+
+    Ok(password_hash)
+}
+
 /// Verify if the user supplied and the database supplied passwords match
 ///
 /// # Errors
 ///
 /// This function will return an error if unable to parse password hash from the stored user authentication value.
-/// This function will return a `ServiceError::InvalidPassword` if unable to match the password with either `argon2id` or `pbkdf2-sha256`.
+/// This function will return a `ServiceError::InvalidPassword` if unable to match the password with either `argon2id`,
+/// `pbkdf2-sha256`, `scrypt` or `bcrypt`.
 pub fn verify_password(password: &[u8], user_authentication: &UserAuthentication) -> Result<(), ServiceError> {
+    // bcrypt hashes ($2y$10$...) are not PHC strings
+    if is_bcrypt_hash(&user_authentication.password_hash) {
+        return match bcrypt::verify(password, &user_authentication.password_hash) {
+            Ok(true) => Ok(()),
+            _ => Err(ServiceError::InvalidPassword),
+        };
+    }
+
     // wrap string of the hashed password into a PasswordHash struct for verification
     let parsed_hash = PasswordHash::new(&user_authentication.password_hash)?;
 
-    match parsed_hash.algorithm.as_str() {
-        "argon2id" => {
-            if Argon2::default().verify_password(password, &parsed_hash).is_err() {
-                return Err(ServiceError::InvalidPassword);
-            }
+    let verified = match parsed_hash.algorithm.as_str() {
+        "argon2id" => Argon2::default().verify_password(password, &parsed_hash).is_ok(),
+        "pbkdf2-sha256" => Pbkdf2.verify_password(password, &parsed_hash).is_ok(),
+        "scrypt" => Scrypt.verify_password(password, &parsed_hash).is_ok(),
+        _ => false,
+    };
 
-            Ok(())
-        }
-        "pbkdf2-sha256" => {
-            if Pbkdf2.verify_password(password, &parsed_hash).is_err() {
-                return Err(ServiceError::InvalidPassword);
-            }
+    if !verified {
+        return Err(ServiceError::InvalidPassword);
+    }
 
-            Ok(())
-        }
-        _ => Err(ServiceError::InvalidPassword),
+    Ok(())
+}
+
+/// It checks if [`verify_password`] can verify passwords against the hash, for
+/// example, when the hash was imported from another application.
+#[must_use]
+pub fn is_supported_password_hash(password_hash: &str) -> bool {
+    is_bcrypt_hash(password_hash)
+        || PasswordHash::new(password_hash)
+            .is_ok_and(|parsed_hash| matches!(parsed_hash.algorithm.as_str(), "argon2id" | "pbkdf2-sha256" | "scrypt"))
+}
+
+/// It checks if the hash was not generated by [`hash_password`] with the
+/// current parameters, so it should be replaced the next time the user logs
+/// in. For example, hashes generated with `pbkdf2-sha256` or with an older
+/// `Argon2` memory cost.
+#[must_use]
+pub fn password_hash_needs_rehash(password_hash: &str, argon2_params: &Argon2Params) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
+        return true;
+    };
+
+    if parsed_hash.algorithm != argon2::ARGON2ID_IDENT || parsed_hash.version != Some(Version::V0x13.into()) {
+        return true;
     }
+
+    Params::try_from(&parsed_hash).map_or(true, |params| {
+        params.m_cost() != argon2_params.memory_cost
+            || params.t_cost() != argon2_params.time_cost
+            || params.p_cost() != argon2_params.parallelism
+    })
+}
+
+fn is_bcrypt_hash(password_hash: &str) -> bool {
+    bcrypt::HashParts::from_str(password_hash).is_ok()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::verify_password;
+    use super::{hash_password, is_supported_password_hash, password_hash_needs_rehash, verify_password};
+    use crate::config::Argon2Params;
     use crate::models::user::UserAuthentication;
 
     #[test]
@@ -259,4 +356,91 @@ mod tests {
         assert!(verify_password(password, &user_authentication).is_ok());
         assert!(verify_password("incorrect password".as_bytes(), &user_authentication).is_err());
     }
+
+    #[test]
+    fn password_hashed_with_bcrypt_should_be_verified() {
+        let password = "87654321".as_bytes();
+        let password_hash = "$2y$04$1U4CN9stPtUhunkoEulh9ugA3va6Jrs9KBFDvdPlBNNjnU8fXcn62".to_string();
+        let user_authentication = UserAuthentication {
+            user_id: 1i64,
+            password_hash,
+        };
+
+        assert!(verify_password(password, &user_authentication).is_ok());
+        assert!(verify_password("incorrect password".as_bytes(), &user_authentication).is_err());
+    }
+
+    #[test]
+    fn password_hashed_with_scrypt_should_be_verified() {
+        let password = "12345678".as_bytes();
+        let password_hash = "$scrypt$ln=4,r=8,p=1$d404T93O546vy6u2VH/EPw$5qfFrGbFXSbZ/yDO2DH2Z4cUhvvWle5s/CLfE82qv2s".to_string();
+        let user_authentication = UserAuthentication {
+            user_id: 1i64,
+            password_hash,
+        };
+
+        assert!(verify_password(password, &user_authentication).is_ok());
+        assert!(verify_password("incorrect password".as_bytes(), &user_authentication).is_err());
+    }
+
+    #[test]
+    fn password_should_be_hashed_with_the_configured_argon2_parameters() {
+        let argon2_params = Argon2Params {
+            memory_cost: 4096,
+            time_cost: 1,
+            parallelism: 1,
+        };
+
+        let password_hash = hash_password("12345678", &argon2_params).unwrap();
+
+        assert!(password_hash.starts_with("$argon2id$v=19$m=4096,t=1,p=1$"));
+        assert!(verify_password(
+            "12345678".as_bytes(),
+            &UserAuthentication {
+                user_id: 1i64,
+                password_hash: password_hash.clone(),
+            }
+        )
+        .is_ok());
+        assert!(!password_hash_needs_rehash(&password_hash, &argon2_params));
+    }
+
+    #[test]
+    fn password_hashes_with_other_algorithms_or_argon2_parameters_should_be_rehashed() {
+        let argon2_params = Argon2Params::default();
+
+        assert!(password_hash_needs_rehash(
+            "$argon2id$v=19$m=4096,t=3,p=1$ycK5lJ4xmFBnaJ51M1j1eA$kU3UlNiSc3JDbl48TCj7JBDKmrT92DOUAgo4Yq0+nMw",
+            &argon2_params
+        ));
+        assert!(password_hash_needs_rehash(
+            "$pbkdf2-sha256$i=10000,l=32$pZIh8nilm+cg6fk5Ubf2zQ$AngLuZ+sGUragqm4bIae/W+ior0TWxYFFaTx8CulqtY",
+            &argon2_params
+        ));
+        assert!(password_hash_needs_rehash(
+            "$2y$04$1U4CN9stPtUhunkoEulh9ugA3va6Jrs9KBFDvdPlBNNjnU8fXcn62",
+            &argon2_params
+        ));
+        assert!(!password_hash_needs_rehash(
+            &hash_password("12345678", &argon2_params).unwrap(),
+            &argon2_params
+        ));
+    }
+
+    #[test]
+    fn only_the_password_hashes_that_can_be_verified_should_be_supported() {
+        assert!(is_supported_password_hash(
+            "$argon2id$v=19$m=4096,t=3,p=1$ycK5lJ4xmFBnaJ51M1j1eA$kU3UlNiSc3JDbl48TCj7JBDKmrT92DOUAgo4Yq0+nMw"
+        ));
+        assert!(is_supported_password_hash(
+            "$pbkdf2-sha256$i=10000,l=32$pZIh8nilm+cg6fk5Ubf2zQ$AngLuZ+sGUragqm4bIae/W+ior0TWxYFFaTx8CulqtY"
+        ));
+        assert!(is_supported_password_hash(
+            "$scrypt$ln=4,r=8,p=1$d404T93O546vy6u2VH/EPw$5qfFrGbFXSbZ/yDO2DH2Z4cUhvvWle5s/CLfE82qv2s"
+        ));
+        assert!(is_supported_password_hash(
+            "$2y$04$1U4CN9stPtUhunkoEulh9ugA3va6Jrs9KBFDvdPlBNNjnU8fXcn62"
+        ));
+        assert!(!is_supported_password_hash("5f4dcc3b5aa765d61d8327deb882cf99"));
+    }
 }
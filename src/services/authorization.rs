@@ -35,6 +35,7 @@ pub enum ACTION {
     GetLicensePage,
     AddCategory,
     DeleteCategory,
+    UpdateCategory,
     GetCategories,
     GetImageByUrl,
     GetSettings,
@@ -50,8 +51,23 @@ pub enum ACTION {
     GetTorrentInfo,
     GenerateTorrentInfoListing,
     GetCanonicalInfoHash,
+    ReplaceTorrent,
     ChangePassword,
+    ChangeEmail,
     BanUser,
+    RenameUser,
+    GetTrackerKey,
+    RotateTrackerKey,
+    RevokeUserTrackerKeys,
+    GetNotificationSettings,
+    UpdateNotificationSettings,
+    AddWebhook,
+    GetWebhooks,
+    UpdateWebhook,
+    DeleteWebhook,
+    ReplayWebhookDelivery,
+    GetFailedMails,
+    RetryFailedMail,
 }
 
 pub struct Service {
@@ -231,6 +247,7 @@ impl Default for CasbinConfiguration {
                 admin, GetLicensePage
                 admin, AddCategory
                 admin, DeleteCategory
+                admin, UpdateCategory
                 admin, GetCategories
                 admin, GetImageByUrl
                 admin, GetSettings
@@ -246,8 +263,23 @@ impl Default for CasbinConfiguration {
                 admin, GetTorrentInfo
                 admin, GenerateTorrentInfoListing
                 admin, GetCanonicalInfoHash
+                admin, ReplaceTorrent
                 admin, ChangePassword
+                admin, ChangeEmail
                 admin, BanUser
+                admin, RenameUser
+                admin, GetTrackerKey
+                admin, RotateTrackerKey
+                admin, RevokeUserTrackerKeys
+                admin, GetNotificationSettings
+                admin, UpdateNotificationSettings
+                admin, AddWebhook
+                admin, GetWebhooks
+                admin, UpdateWebhook
+                admin, DeleteWebhook
+                admin, ReplayWebhookDelivery
+                admin, GetFailedMails
+                admin, RetryFailedMail
                 registered, GetAboutPage
                 registered, GetLicensePage
                 registered, GetCategories
@@ -260,7 +292,13 @@ impl Default for CasbinConfiguration {
                 registered, GetTorrentInfo
                 registered, GenerateTorrentInfoListing
                 registered, GetCanonicalInfoHash
+                registered, ReplaceTorrent
                 registered, ChangePassword
+                registered, ChangeEmail
+                registered, GetTrackerKey
+                registered, RotateTrackerKey
+                registered, GetNotificationSettings
+                registered, UpdateNotificationSettings
                 guest, GetAboutPage
                 guest, GetLicensePage
                 guest, GetCategories
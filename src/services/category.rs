@@ -4,7 +4,7 @@ use std::sync::Arc;
 use super::authorization::{self, ACTION};
 use crate::databases::database::{Category, Database, Error as DatabaseError};
 use crate::errors::ServiceError;
-use crate::models::category::CategoryId;
+use crate::models::category::{CategoryId, TorrentFileFormat};
 use crate::models::user::UserId;
 
 pub struct Service {
@@ -79,6 +79,38 @@ impl Service {
         }
     }
 
+    /// Sets the torrent file format served for the torrents in a category.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * The category does not exist.
+    /// * There is a database error.
+    pub async fn update_torrent_file_format(
+        &self,
+        category_name: &str,
+        torrent_file_format: TorrentFileFormat,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<(), ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::UpdateCategory, maybe_user_id)
+            .await?;
+
+        match self
+            .category_repository
+            .update_torrent_file_format(category_name, torrent_file_format)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(e) => match e {
+                DatabaseError::CategoryNotFound => Err(ServiceError::CategoryNotFound),
+                _ => Err(ServiceError::DatabaseError),
+            },
+        }
+    }
+
     /// Returns all the categories from the database
     ///
     /// # Errors
@@ -136,6 +168,21 @@ impl DbCategoryRepository {
         self.database.delete_category(category_name).await
     }
 
+    /// Sets the torrent file format served for the torrents in a category.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn update_torrent_file_format(
+        &self,
+        category_name: &str,
+        torrent_file_format: TorrentFileFormat,
+    ) -> Result<(), DatabaseError> {
+        self.database
+            .update_category_torrent_file_format(category_name, torrent_file_format)
+            .await
+    }
+
     /// It finds a category by name
     ///
     /// # Errors
@@ -0,0 +1,232 @@
+//! Content-type classifier for torrents.
+//!
+//! It classifies the files in a torrent by their extension. For each
+//! extension it counts the files and adds up their sizes. The dominant
+//! [`MediaKind`] of the torrent is the kind with the largest total size, so a
+//! movie with a few subtitle files is classified as `video`.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::torrent::TorrentFileType;
+use crate::models::torrent_file::Torrent;
+
+/// Extensions longer than this are not considered extensions. For example,
+/// `file.2024-01-01_backup` has no extension.
+const MAX_EXTENSION_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Video,
+    Audio,
+    Image,
+    DiskImage,
+    Archive,
+    Document,
+    Software,
+    Subtitle,
+    Other,
+}
+
+impl MediaKind {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaKind::Video => "video",
+            MediaKind::Audio => "audio",
+            MediaKind::Image => "image",
+            MediaKind::DiskImage => "disk_image",
+            MediaKind::Archive => "archive",
+            MediaKind::Document => "document",
+            MediaKind::Software => "software",
+            MediaKind::Subtitle => "subtitle",
+            MediaKind::Other => "other",
+        }
+    }
+
+    /// It returns the kind of files with the given lowercase extension.
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Self {
+        match extension {
+            "mkv" | "mp4" | "m4v" | "avi" | "mov" | "wmv" | "flv" | "webm" | "mpg" | "mpeg" | "ts" | "m2ts" | "vob" | "ogv"
+            | "3gp" => MediaKind::Video,
+            "flac" | "mp3" | "ogg" | "oga" | "opus" | "wav" | "aac" | "m4a" | "wma" | "alac" | "ape" | "aiff" | "dsf" | "mka" => {
+                MediaKind::Audio
+            }
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "svg" | "heic" | "raw" | "cr2" | "nef" => {
+                MediaKind::Image
+            }
+            "iso" | "img" | "dmg" | "bin" | "cue" | "nrg" | "mdf" | "mds" | "vhd" | "vhdx" | "vmdk" | "qcow2" => {
+                MediaKind::DiskImage
+            }
+            "zip" | "rar" | "7z" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "lz" | "lzma" | "cab" => MediaKind::Archive,
+            "pdf" | "epub" | "mobi" | "azw3" | "djvu" | "doc" | "docx" | "odt" | "rtf" | "txt" | "md" | "nfo" | "cbz" | "cbr"
+            | "xls" | "xlsx" | "ppt" | "pptx" => MediaKind::Document,
+            "exe" | "msi" | "apk" | "deb" | "rpm" | "appimage" | "pkg" | "jar" | "sh" | "bat" => MediaKind::Software,
+            "srt" | "ass" | "ssa" | "sub" | "idx" | "vtt" => MediaKind::Subtitle,
+            _ => MediaKind::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for MediaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseMediaKindError;
+
+impl FromStr for MediaKind {
+    type Err = ParseMediaKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "video" => Ok(MediaKind::Video),
+            "audio" => Ok(MediaKind::Audio),
+            "image" => Ok(MediaKind::Image),
+            "disk_image" => Ok(MediaKind::DiskImage),
+            "archive" => Ok(MediaKind::Archive),
+            "document" => Ok(MediaKind::Document),
+            "software" => Ok(MediaKind::Software),
+            "subtitle" => Ok(MediaKind::Subtitle),
+            "other" => Ok(MediaKind::Other),
+            _ => Err(ParseMediaKindError),
+        }
+    }
+}
+
+/// It returns the lowercase extension of a file name, or an empty string if
+/// the file has no extension.
+#[must_use]
+pub fn extension_of(file_name: &str) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension))
+            if !stem.is_empty()
+                && !extension.is_empty()
+                && extension.len() <= MAX_EXTENSION_LENGTH
+                && extension.chars().all(char::is_alphanumeric) =>
+        {
+            extension.to_lowercase()
+        }
+        _ => String::new(),
+    }
+}
+
+/// It classifies the torrent files. The result is sorted by total size, the
+/// largest first. Padding files are ignored.
+#[must_use]
+pub fn classify_torrent(torrent: &Torrent) -> Vec<TorrentFileType> {
+    if let Some(length) = torrent.info.length {
+        return classify([(torrent.info.name.as_str(), length)].into_iter());
+    }
+
+    // v2-only torrents don't have the v1 `files` key.
+    let files = torrent.info.files.clone().unwrap_or_else(|| torrent.info.v2_files());
+
+    classify(
+        files
+            .iter()
+            .filter(|file| !file.is_padding())
+            .map(|file| (file.path.last().map_or("", String::as_str), file.length)),
+    )
+}
+
+/// It classifies a list of files given their names and sizes.
+fn classify<'a>(files: impl Iterator<Item = (&'a str, i64)>) -> Vec<TorrentFileType> {
+    let mut extensions: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+
+    for (file_name, length) in files {
+        let totals = extensions.entry(extension_of(file_name)).or_default();
+        totals.0 += 1;
+        totals.1 += length;
+    }
+
+    let mut file_types: Vec<TorrentFileType> = extensions
+        .into_iter()
+        .map(|(extension, (file_count, size))| TorrentFileType {
+            kind: MediaKind::from_extension(&extension).to_string(),
+            extension,
+            file_count,
+            size,
+        })
+        .collect();
+
+    file_types.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.extension.cmp(&b.extension)));
+
+    file_types
+}
+
+/// It returns the kind with the largest total size.
+#[must_use]
+pub fn dominant_media_kind(file_types: &[TorrentFileType]) -> Option<MediaKind> {
+    let mut sizes: BTreeMap<MediaKind, i64> = BTreeMap::new();
+
+    for file_type in file_types {
+        let kind = MediaKind::from_str(&file_type.kind).unwrap_or(MediaKind::Other);
+        *sizes.entry(kind).or_default() += file_type.size;
+    }
+
+    sizes
+        .into_iter()
+        .max_by(|(kind_a, size_a), (kind_b, size_b)| size_a.cmp(size_b).then_with(|| kind_b.cmp(kind_a)))
+        .map(|(kind, _)| kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{classify, dominant_media_kind, extension_of, MediaKind};
+
+    #[test]
+    fn it_should_get_the_lowercase_extension_of_a_file_name() {
+        assert_eq!(extension_of("Movie.MKV"), "mkv");
+        assert_eq!(extension_of("archive.tar.gz"), "gz");
+        assert_eq!(extension_of("README"), "");
+        assert_eq!(extension_of(".hidden"), "");
+        assert_eq!(extension_of("notes.2024-01-01"), "");
+    }
+
+    #[test]
+    fn it_should_group_the_files_by_extension() {
+        let file_types = classify([("a.flac", 30), ("b.FLAC", 20), ("cover.jpg", 5)].into_iter());
+
+        assert_eq!(file_types.len(), 2);
+        assert_eq!(
+            (
+                file_types[0].extension.as_str(),
+                file_types[0].kind.as_str(),
+                file_types[0].file_count,
+                file_types[0].size
+            ),
+            ("flac", "audio", 2, 50)
+        );
+        assert_eq!(
+            (file_types[1].extension.as_str(), file_types[1].kind.as_str()),
+            ("jpg", "image")
+        );
+    }
+
+    #[test]
+    fn it_should_use_the_kind_with_the_largest_size_as_the_dominant_media_kind() {
+        let file_types = classify([("movie.mkv", 1000), ("en.srt", 1), ("es.srt", 1), ("fr.srt", 1)].into_iter());
+
+        assert_eq!(dominant_media_kind(&file_types), Some(MediaKind::Video));
+    }
+
+    #[test]
+    fn it_should_not_have_a_dominant_media_kind_without_files() {
+        assert_eq!(dominant_media_kind(&[]), None);
+    }
+
+    #[test]
+    fn it_should_parse_a_media_kind_from_its_name() {
+        assert_eq!(MediaKind::from_str("disk_image"), Ok(MediaKind::DiskImage));
+        assert_eq!(MediaKind::from_str(&MediaKind::Video.to_string()), Ok(MediaKind::Video));
+        assert!(MediaKind::from_str("unknown").is_err());
+    }
+}
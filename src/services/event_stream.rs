@@ -0,0 +1,335 @@
+//! Real-time stream of torrent events.
+//!
+//! Frontends subscribe to the `/v1/events` endpoint to be notified when
+//! torrents are added, updated or deleted, and when the tracker statistics
+//! importer detects a change in the number of seeders or leechers. This way
+//! they don't need to poll the torrent listing.
+//!
+//! The events are broadcast in memory with the [`Broadcaster`]. They are not
+//! persisted: subscribers only receive the events that happen while they are
+//! connected to the same Index process.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use super::authorization::{self, ACTION};
+use super::category::DbCategoryRepository;
+use super::tag::DbTagRepository;
+use crate::errors::ServiceError;
+use crate::models::category::CategoryId;
+use crate::models::torrent::TorrentId;
+use crate::models::torrent_tag::TagId;
+use crate::models::user::UserId;
+
+/// Events kept in the channel for subscribers that can't keep up. The oldest
+/// ones are skipped when a subscriber lags behind.
+pub const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Event {
+    TorrentAdded {
+        torrent_id: TorrentId,
+        info_hash: String,
+        title: String,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+    },
+    TorrentUpdated {
+        torrent_id: TorrentId,
+        info_hash: String,
+        title: String,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+    },
+    TorrentDeleted {
+        torrent_id: TorrentId,
+        info_hash: String,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+    },
+    TorrentStatsUpdated {
+        torrent_id: TorrentId,
+        info_hash: String,
+        seeders: i64,
+        leechers: i64,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+    },
+}
+
+impl Event {
+    /// The event name used in the stream.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::TorrentAdded { .. } => "torrent.added",
+            Event::TorrentUpdated { .. } => "torrent.updated",
+            Event::TorrentDeleted { .. } => "torrent.deleted",
+            Event::TorrentStatsUpdated { .. } => "torrent.stats_updated",
+        }
+    }
+
+    fn category_id(&self) -> Option<CategoryId> {
+        match self {
+            Event::TorrentAdded { category_id, .. }
+            | Event::TorrentUpdated { category_id, .. }
+            | Event::TorrentDeleted { category_id, .. }
+            | Event::TorrentStatsUpdated { category_id, .. } => *category_id,
+        }
+    }
+
+    fn tag_ids(&self) -> &[TagId] {
+        match self {
+            Event::TorrentAdded { tag_ids, .. }
+            | Event::TorrentUpdated { tag_ids, .. }
+            | Event::TorrentDeleted { tag_ids, .. }
+            | Event::TorrentStatsUpdated { tag_ids, .. } => tag_ids,
+        }
+    }
+}
+
+/// User request to subscribe to the event stream.
+#[derive(Debug, Default, Deserialize)]
+pub struct SubscriptionRequest {
+    /// Expects comma separated string, eg: "?categories=movie,other,app"
+    pub categories: Option<String>,
+    /// Expects comma separated string, eg: "?tags=Linux,Ubuntu"
+    pub tags: Option<String>,
+}
+
+/// The torrents a subscriber is interested in.
+///
+/// Like in the torrent listing, an event matches when the torrent belongs to
+/// any of the categories and has any of the tags. Filters without categories
+/// or tags are ignored.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Filter {
+    categories: Option<HashSet<CategoryId>>,
+    tags: Option<HashSet<TagId>>,
+}
+
+impl Filter {
+    #[must_use]
+    pub fn new(categories: Vec<CategoryId>, tags: Vec<TagId>) -> Self {
+        Self {
+            categories: (!categories.is_empty()).then(|| categories.into_iter().collect()),
+            tags: (!tags.is_empty()).then(|| tags.into_iter().collect()),
+        }
+    }
+
+    #[must_use]
+    pub fn matches(&self, event: &Event) -> bool {
+        let category_matches = self.categories.as_ref().map_or(true, |categories| {
+            event
+                .category_id()
+                .is_some_and(|category_id| categories.contains(&category_id))
+        });
+
+        let tags_match = self
+            .tags
+            .as_ref()
+            .map_or(true, |tags| event.tag_ids().iter().any(|tag_id| tags.contains(tag_id)));
+
+        category_matches && tags_match
+    }
+}
+
+/// It sends the events to all the current subscribers.
+pub struct Broadcaster {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for Broadcaster {
+    fn default() -> Self {
+        let (sender, _receiver) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl Broadcaster {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// It sends the event to the subscribers. Events are dropped when there
+    /// are no subscribers.
+    pub fn broadcast(&self, event: Event) {
+        drop(self.sender.send(event));
+    }
+
+    /// It allows skipping the work needed to build an event when nobody is
+    /// listening.
+    #[must_use]
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+/// A subscriber to the event stream.
+pub struct Subscription {
+    receiver: broadcast::Receiver<Event>,
+    filter: Filter,
+}
+
+impl Subscription {
+    /// It waits for the next event matching the subscription filter.
+    ///
+    /// It returns `None` when the broadcaster is dropped.
+    pub async fn next(&mut self) -> Option<Event> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => {
+                    if self.filter.matches(&event) {
+                        return Some(event);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Event stream subscriber lagged behind. {skipped} events were skipped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[allow(clippy::struct_field_names)]
+pub struct Service {
+    broadcaster: Arc<Broadcaster>,
+    category_repository: Arc<DbCategoryRepository>,
+    tag_repository: Arc<DbTagRepository>,
+    authorization_service: Arc<authorization::Service>,
+}
+
+impl Service {
+    #[must_use]
+    pub fn new(
+        broadcaster: Arc<Broadcaster>,
+        category_repository: Arc<DbCategoryRepository>,
+        tag_repository: Arc<DbTagRepository>,
+        authorization_service: Arc<authorization::Service>,
+    ) -> Service {
+        Service {
+            broadcaster,
+            category_repository,
+            tag_repository,
+            authorization_service,
+        }
+    }
+
+    /// It subscribes the user to the event stream.
+    ///
+    /// The stream contains the same torrents the user can see in the torrent
+    /// listing, so it requires the same permission.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user is not allowed to list torrents.
+    /// * A category or tag in the filter does not exist.
+    /// * There is a database error.
+    pub async fn subscribe(
+        &self,
+        request: &SubscriptionRequest,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<Subscription, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GenerateTorrentInfoListing, maybe_user_id)
+            .await?;
+
+        let categories = match &request.categories {
+            Some(names) => {
+                let categories = self.category_repository.get_all().await?;
+
+                split_names(names)
+                    .iter()
+                    .map(|name| {
+                        categories
+                            .iter()
+                            .find(|category| category.name == *name)
+                            .map(|category| category.category_id)
+                            .ok_or(ServiceError::InvalidCategory)
+                    })
+                    .collect::<Result<Vec<CategoryId>, ServiceError>>()?
+            }
+            None => vec![],
+        };
+
+        let tags = match &request.tags {
+            Some(names) => {
+                let tags = self.tag_repository.get_all().await?;
+
+                split_names(names)
+                    .iter()
+                    .map(|name| {
+                        tags.iter()
+                            .find(|tag| tag.name == *name)
+                            .map(|tag| tag.tag_id)
+                            .ok_or(ServiceError::InvalidTag)
+                    })
+                    .collect::<Result<Vec<TagId>, ServiceError>>()?
+            }
+            None => vec![],
+        };
+
+        Ok(Subscription {
+            receiver: self.broadcaster.subscribe(),
+            filter: Filter::new(categories, tags),
+        })
+    }
+}
+
+fn split_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Filter};
+
+    fn torrent_added(category_id: Option<i64>, tag_ids: Vec<i64>) -> Event {
+        Event::TorrentAdded {
+            torrent_id: 1,
+            info_hash: "443c7602b4fde83d1154d6d9da48808418b181b6".to_string(),
+            title: "Ubuntu".to_string(),
+            category_id,
+            tag_ids,
+        }
+    }
+
+    #[test]
+    fn an_empty_filter_should_match_all_the_events() {
+        assert!(Filter::default().matches(&torrent_added(None, vec![])));
+    }
+
+    #[test]
+    fn the_filter_should_match_events_for_torrents_in_any_of_the_categories() {
+        let filter = Filter::new(vec![1, 2], vec![]);
+
+        assert!(filter.matches(&torrent_added(Some(2), vec![])));
+        assert!(!filter.matches(&torrent_added(Some(3), vec![])));
+        assert!(!filter.matches(&torrent_added(None, vec![])));
+    }
+
+    #[test]
+    fn the_filter_should_match_events_for_torrents_with_any_of_the_tags_and_in_any_of_the_categories() {
+        let filter = Filter::new(vec![1], vec![10, 20]);
+
+        assert!(filter.matches(&torrent_added(Some(1), vec![20, 30])));
+        assert!(!filter.matches(&torrent_added(Some(1), vec![30])));
+        assert!(!filter.matches(&torrent_added(Some(2), vec![10])));
+    }
+}
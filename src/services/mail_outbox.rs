@@ -0,0 +1,74 @@
+//! Admin actions on the emails in the outbox.
+//!
+//! The [`mailer`](crate::mailer) gives up delivering an email after
+//! [`MAX_DELIVERY_ATTEMPTS`](crate::mailer::MAX_DELIVERY_ATTEMPTS) failed
+//! attempts and keeps it in the outbox with the `failed` status. Admins can
+//! list those emails and, once the problem is fixed, retry them.
+use std::sync::Arc;
+
+use super::authorization::{self, ACTION};
+use crate::databases::database::Database;
+use crate::errors::ServiceError;
+use crate::mailer;
+use crate::models::mail::{FailedMail, MailId};
+use crate::models::user::UserId;
+
+#[allow(clippy::struct_field_names)]
+pub struct Service {
+    database: Arc<Box<dyn Database>>,
+    mailer: Arc<mailer::Service>,
+    authorization_service: Arc<authorization::Service>,
+}
+
+impl Service {
+    #[must_use]
+    pub fn new(
+        database: Arc<Box<dyn Database>>,
+        mailer: Arc<mailer::Service>,
+        authorization_service: Arc<authorization::Service>,
+    ) -> Service {
+        Service {
+            database,
+            mailer,
+            authorization_service,
+        }
+    }
+
+    /// It returns the emails the mailer gave up delivering, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * There is a database error.
+    pub async fn get_failed_mails(&self, maybe_user_id: Option<UserId>) -> Result<Vec<FailedMail>, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GetFailedMails, maybe_user_id)
+            .await?;
+
+        Ok(self.database.get_failed_outbox_mails().await?)
+    }
+
+    /// It delivers a failed email again as soon as possible. It gets all the
+    /// delivery attempts again.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * There isn't a failed email with the ID.
+    /// * There is a database error.
+    pub async fn retry_failed_mail(&self, mail_id: MailId, maybe_user_id: Option<UserId>) -> Result<(), ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::RetryFailedMail, maybe_user_id)
+            .await?;
+
+        self.database.retry_failed_outbox_mail(mail_id).await?;
+
+        self.mailer.new_mail_notifier().notify_one();
+
+        Ok(())
+    }
+}
@@ -3,10 +3,18 @@ pub mod about;
 pub mod authentication;
 pub mod authorization;
 pub mod category;
+pub mod content_type;
+pub mod event_stream;
 pub mod hasher;
+pub mod mail_outbox;
+pub mod notification;
+pub mod password_policy;
 pub mod proxy;
 pub mod settings;
 pub mod tag;
 pub mod torrent;
 pub mod torrent_file;
+pub mod upload_policy;
 pub mod user;
+pub mod username_policy;
+pub mod webhook;
@@ -0,0 +1,493 @@
+//! Email notifications.
+//!
+//! Users opt into the notifications they want with their
+//! [`NotificationPreferences`]. The services dispatch an [`Event`] when
+//! something users can be notified about happens, and the [`Dispatcher`]
+//! queues an email in the [`mailer`] outbox for every user who wants to know
+//! about it. The weekly digest is sent by the
+//! [`weekly_digest`](crate::console::cronjobs::weekly_digest) cronjob.
+//!
+//! Notifications are a side effect: a failure to dispatch them is logged, but
+//! it does not make the action that triggered them fail.
+//!
+//! There are no torrent comments in the Index yet, so there is no
+//! notification for them.
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tera::Context;
+use tracing::{debug, error};
+
+use super::authorization::{self, ACTION};
+use crate::config::Configuration;
+use crate::databases::database::{Database, Error};
+use crate::errors::ServiceError;
+use crate::mailer::{self, Template};
+use crate::models::category::CategoryId;
+use crate::models::notification::NotificationPreferences;
+use crate::models::torrent::TorrentListing;
+use crate::models::torrent_tag::TagId;
+use crate::models::user::{UserId, UserProfile};
+use crate::utils::clock;
+
+/// Torrents included in the weekly digest.
+const WEEKLY_DIGEST_TORRENTS: u8 = 10;
+
+const ONE_WEEK_IN_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Something users can be notified about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    TorrentUploaded {
+        info_hash: String,
+        title: String,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+        uploader: String,
+    },
+    /// A torrent was edited. The uploader is only notified when the editor
+    /// is someone else, that is, a moderator.
+    TorrentEdited {
+        info_hash: String,
+        title: String,
+        uploader: String,
+        editor: String,
+    },
+    /// A torrent was deleted. The uploader is only notified when it was
+    /// deleted by someone else, that is, a moderator.
+    TorrentDeleted {
+        info_hash: String,
+        title: String,
+        uploader: String,
+        editor: String,
+    },
+    /// A tag was deleted. The followers are loaded before deleting it
+    /// because they are removed with the tag.
+    FollowedTagDeleted { tag_name: String, followers: Vec<UserProfile> },
+}
+
+/// The notifications a user opted into and what they follow.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct UserNotificationSettings {
+    #[serde(flatten)]
+    pub preferences: NotificationPreferences,
+    pub followed_categories: Vec<CategoryId>,
+    pub followed_tags: Vec<TagId>,
+}
+
+pub struct Service {
+    notification_repository: Arc<DbNotificationRepository>,
+    authorization_service: Arc<authorization::Service>,
+}
+
+impl Service {
+    #[must_use]
+    pub fn new(
+        notification_repository: Arc<DbNotificationRepository>,
+        authorization_service: Arc<authorization::Service>,
+    ) -> Service {
+        Service {
+            notification_repository,
+            authorization_service,
+        }
+    }
+
+    /// It returns the notification settings of the logged-in user.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user is not logged in.
+    /// * The user does not have the required permissions.
+    /// * There is a database error.
+    pub async fn get_settings(&self, maybe_user_id: Option<UserId>) -> Result<UserNotificationSettings, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::GetNotificationSettings, maybe_user_id)
+            .await?;
+
+        Ok(self.notification_repository.get_settings(&user_id).await?)
+    }
+
+    /// It replaces the notification settings of the logged-in user.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user is not logged in.
+    /// * The user does not have the required permissions.
+    /// * A followed category or tag does not exist.
+    /// * There is a database error.
+    pub async fn update_settings(
+        &self,
+        settings: &UserNotificationSettings,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<UserNotificationSettings, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::UpdateNotificationSettings, maybe_user_id)
+            .await?;
+
+        let mut settings = settings.clone();
+
+        settings.followed_categories.sort_unstable();
+        settings.followed_categories.dedup();
+
+        settings.followed_tags.sort_unstable();
+        settings.followed_tags.dedup();
+
+        self.notification_repository.update_settings(&user_id, &settings).await?;
+
+        Ok(self.notification_repository.get_settings(&user_id).await?)
+    }
+}
+
+/// It turns events into emails for the users who want to be notified.
+pub struct Dispatcher {
+    configuration: Arc<Configuration>,
+    mailer: Arc<mailer::Service>,
+    notification_repository: Arc<DbNotificationRepository>,
+}
+
+impl Dispatcher {
+    #[must_use]
+    pub fn new(
+        configuration: Arc<Configuration>,
+        mailer: Arc<mailer::Service>,
+        notification_repository: Arc<DbNotificationRepository>,
+    ) -> Dispatcher {
+        Dispatcher {
+            configuration,
+            mailer,
+            notification_repository,
+        }
+    }
+
+    /// It queues the emails for the users who want to be notified about the
+    /// event. Errors are only logged.
+    pub async fn dispatch(&self, event: Event) {
+        if let Err(e) = self.notify(&event).await {
+            error!("Failed to dispatch the notifications for {event:?}: {e}");
+        }
+    }
+
+    async fn notify(&self, event: &Event) -> Result<(), ServiceError> {
+        match event {
+            Event::TorrentUploaded {
+                info_hash,
+                title,
+                category_id,
+                tag_ids,
+                uploader,
+            } => {
+                let subscribers = self
+                    .notification_repository
+                    .get_new_torrent_subscribers(category_id, tag_ids)
+                    .await?;
+
+                for subscriber in subscribers.iter().filter(|subscriber| subscriber.username != *uploader) {
+                    let mut context = Context::new();
+                    context.insert("username", &subscriber.username);
+                    context.insert("uploader", uploader);
+                    context.insert("title", title);
+                    context.insert("info_hash", info_hash);
+
+                    self.send(subscriber, Template::NewTorrent, &context).await?;
+                }
+
+                Ok(())
+            }
+            Event::TorrentEdited {
+                info_hash,
+                title,
+                uploader,
+                editor,
+            } => {
+                self.notify_moderation(Template::TorrentEdited, info_hash, title, uploader, editor)
+                    .await
+            }
+            Event::TorrentDeleted {
+                info_hash,
+                title,
+                uploader,
+                editor,
+            } => {
+                self.notify_moderation(Template::TorrentDeleted, info_hash, title, uploader, editor)
+                    .await
+            }
+            Event::FollowedTagDeleted { tag_name, followers } => {
+                for follower in followers {
+                    let mut context = Context::new();
+                    context.insert("username", &follower.username);
+                    context.insert("tag_name", tag_name);
+
+                    self.send(follower, Template::FollowedTagDeleted, &context).await?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    async fn notify_moderation(
+        &self,
+        template: Template,
+        info_hash: &str,
+        title: &str,
+        uploader: &str,
+        editor: &str,
+    ) -> Result<(), ServiceError> {
+        if uploader == editor {
+            return Ok(());
+        }
+
+        let uploader = self.notification_repository.get_user_profile_from_username(uploader).await?;
+
+        let preferences = self.notification_repository.get_preferences(&uploader.user_id).await?;
+
+        if !preferences.torrent_moderation {
+            return Ok(());
+        }
+
+        let mut context = Context::new();
+        context.insert("username", &uploader.username);
+        context.insert("moderator", editor);
+        context.insert("title", title);
+        context.insert("info_hash", info_hash);
+
+        self.send(&uploader, template, &context).await?;
+
+        Ok(())
+    }
+
+    /// It sends the weekly digest to the users who want it and didn't get it
+    /// in the last week. Users don't get the digest when there are no new
+    /// torrents.
+    ///
+    /// It returns the number of digests sent.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the current time does not fit in an `i64`.
+    pub async fn send_weekly_digests(&self) -> Result<usize, ServiceError> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let now = i64::try_from(clock::now()).unwrap();
+
+        let subscribers = self
+            .notification_repository
+            .get_weekly_digest_subscribers(now - ONE_WEEK_IN_SECS)
+            .await?;
+
+        if subscribers.is_empty() {
+            return Ok(0);
+        }
+
+        let one_week_ago = (Utc::now() - Duration::weeks(1)).format("%Y-%m-%d %H:%M:%S").to_string();
+
+        let torrents = self
+            .notification_repository
+            .get_top_torrents_uploaded_since(&one_week_ago, WEEKLY_DIGEST_TORRENTS)
+            .await?;
+
+        let mut sent = 0;
+
+        for subscriber in &subscribers {
+            if !torrents.is_empty() {
+                let mut context = Context::new();
+                context.insert("username", &subscriber.username);
+                context.insert("torrents", &digest_torrents(&torrents));
+
+                if self.send(subscriber, Template::WeeklyDigest, &context).await? {
+                    sent += 1;
+                }
+            }
+
+            self.notification_repository
+                .update_date_last_digest(&subscriber.user_id, now)
+                .await?;
+        }
+
+        Ok(sent)
+    }
+
+    /// It queues the email if the user can receive emails. It returns
+    /// whether the email was queued.
+    async fn send(&self, user_profile: &UserProfile, template: Template, context: &Context) -> Result<bool, ServiceError> {
+        if !self.can_receive_emails(user_profile).await {
+            debug!(
+                "Not sending the {} notification to {}: the user does not have a verified email",
+                template.name(),
+                user_profile.username
+            );
+            return Ok(false);
+        }
+
+        self.mailer.enqueue(&user_profile.email, template, context).await?;
+
+        Ok(true)
+    }
+
+    /// Users need an email address, and it has to be verified when the Index
+    /// requires verifying email addresses.
+    async fn can_receive_emails(&self, user_profile: &UserProfile) -> bool {
+        if user_profile.email.is_empty() {
+            return false;
+        }
+
+        let settings = self.configuration.settings.read().await;
+
+        let verification_required = settings
+            .registration
+            .as_ref()
+            .and_then(|registration| registration.email.as_ref())
+            .is_some_and(|email| email.verification_required);
+
+        !verification_required || user_profile.email_verified
+    }
+}
+
+#[derive(Serialize)]
+struct DigestTorrent<'a> {
+    title: &'a str,
+    info_hash: &'a str,
+    seeders: i64,
+    leechers: i64,
+}
+
+fn digest_torrents(torrents: &[TorrentListing]) -> Vec<DigestTorrent<'_>> {
+    torrents
+        .iter()
+        .map(|torrent| DigestTorrent {
+            title: &torrent.title,
+            info_hash: &torrent.info_hash,
+            seeders: torrent.seeders,
+            leechers: torrent.leechers,
+        })
+        .collect()
+}
+
+pub struct DbNotificationRepository {
+    database: Arc<Box<dyn Database>>,
+}
+
+impl DbNotificationRepository {
+    #[must_use]
+    pub fn new(database: Arc<Box<dyn Database>>) -> Self {
+        Self { database }
+    }
+
+    /// It returns the notification preferences and the followed categories
+    /// and tags of a user.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_settings(&self, user_id: &UserId) -> Result<UserNotificationSettings, Error> {
+        Ok(UserNotificationSettings {
+            preferences: self.database.get_user_notification_preferences(*user_id).await?,
+            followed_categories: self.database.get_user_followed_categories(*user_id).await?,
+            followed_tags: self.database.get_user_followed_tags(*user_id).await?,
+        })
+    }
+
+    /// It returns the notification preferences of a user.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_preferences(&self, user_id: &UserId) -> Result<NotificationPreferences, Error> {
+        self.database.get_user_notification_preferences(*user_id).await
+    }
+
+    /// It replaces the notification preferences and the followed categories
+    /// and tags of a user in a single transaction.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if a category or a tag does not exist or there is
+    /// a database error.
+    pub async fn update_settings(&self, user_id: &UserId, settings: &UserNotificationSettings) -> Result<(), Error> {
+        self.database
+            .update_user_notification_settings(
+                *user_id,
+                &settings.preferences,
+                &settings.followed_categories,
+                &settings.followed_tags,
+            )
+            .await
+    }
+
+    /// It returns the users who want to be notified about new torrents in
+    /// the category or with any of the tags.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_new_torrent_subscribers(
+        &self,
+        category_id: &Option<CategoryId>,
+        tag_ids: &[TagId],
+    ) -> Result<Vec<UserProfile>, Error> {
+        self.database.get_new_torrent_subscribers(*category_id, tag_ids).await
+    }
+
+    /// It returns the users who follow the tag and want to be notified about
+    /// new torrents.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_tag_followers(&self, tag_id: &TagId) -> Result<Vec<UserProfile>, Error> {
+        self.database.get_tag_followers(*tag_id).await
+    }
+
+    /// It returns the users who want the weekly digest and didn't get one
+    /// since `date_last_digest`.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_weekly_digest_subscribers(&self, date_last_digest: i64) -> Result<Vec<UserProfile>, Error> {
+        self.database.get_weekly_digest_subscribers(date_last_digest).await
+    }
+
+    /// It records when a user got the last weekly digest.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn update_date_last_digest(&self, user_id: &UserId, date_last_digest: i64) -> Result<(), Error> {
+        self.database.update_user_date_last_digest(*user_id, date_last_digest).await
+    }
+
+    /// It returns the torrents uploaded since the date with the most seeders.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_top_torrents_uploaded_since(&self, date_uploaded: &str, limit: u8) -> Result<Vec<TorrentListing>, Error> {
+        self.database.get_top_torrents_uploaded_since(date_uploaded, limit).await
+    }
+
+    /// It returns the user profile from the username.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_user_profile_from_username(&self, username: &str) -> Result<UserProfile, Error> {
+        self.database.get_user_profile_from_username(username).await
+    }
+}
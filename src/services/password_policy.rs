@@ -0,0 +1,316 @@
+//! Password policy enforcement.
+//!
+//! It checks new passwords against the [`PasswordConstraints`] configuration:
+//! the length, the required character classes, the
+//! [strength score](crate::utils::password_strength), the personal
+//! information they contain and the list of breached passwords.
+//!
+//! Breached passwords are looked up in a local copy of the
+//! [Pwned Passwords](https://haveibeenpwned.com/Passwords) SHA-1 hashes, for
+//! example, downloaded with the `PwnedPasswordsDownloader`. When the hashes
+//! are split into range files, only the file for the first five characters of
+//! the hash is read, like in the k-anonymity model of the Pwned Passwords API.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use tracing::error;
+
+use crate::config::PasswordConstraints;
+use crate::errors::ServiceError;
+use crate::services::hasher::sha1;
+use crate::utils::password_strength;
+
+/// Length of the hash prefix used to name the range files.
+const HASH_PREFIX_LENGTH: usize = 5;
+
+/// Usernames and emails shorter than this are not searched in the password.
+const MIN_PERSONAL_INFORMATION_LENGTH: usize = 3;
+
+/// It checks the new password against the policy.
+///
+/// # Errors
+///
+/// It returns a specific error for the first rule the password breaks, or a
+/// `ServiceError::InternalServerError` if the breached passwords can't be
+/// read.
+pub fn validate_password_constraints(
+    password: &str,
+    confirm_password: &str,
+    username: &str,
+    email: Option<&str>,
+    password_rules: &PasswordConstraints,
+) -> Result<(), ServiceError> {
+    if password != confirm_password {
+        return Err(ServiceError::PasswordsDontMatch);
+    }
+
+    let password_length = password.len();
+
+    if password_length < password_rules.min_password_length {
+        return Err(ServiceError::PasswordTooShort);
+    }
+
+    if password_length > password_rules.max_password_length {
+        return Err(ServiceError::PasswordTooLong);
+    }
+
+    check_character_classes(password, password_rules)?;
+
+    if password_rules.reject_username_or_email {
+        check_personal_information(password, username, email)?;
+    }
+
+    if password_rules.min_strength_score > 0 {
+        let mut user_inputs = vec![username];
+        user_inputs.extend(email);
+        user_inputs.extend(email.and_then(|email| email.split('@').next()));
+
+        if password_strength::score(password, &user_inputs) < password_rules.min_strength_score {
+            return Err(ServiceError::PasswordTooWeak);
+        }
+    }
+
+    if let Some(breached_passwords_path) = &password_rules.breached_passwords_path {
+        let breached = is_breached(password, breached_passwords_path).map_err(|err| {
+            error!(
+                "Failed to read the breached passwords in {}: {err}",
+                breached_passwords_path.display()
+            );
+            ServiceError::InternalServerError
+        })?;
+
+        if breached {
+            return Err(ServiceError::PasswordBreached);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_character_classes(password: &str, password_rules: &PasswordConstraints) -> Result<(), ServiceError> {
+    if password_rules.require_lowercase && !password.chars().any(char::is_lowercase) {
+        return Err(ServiceError::PasswordMissingLowercase);
+    }
+
+    if password_rules.require_uppercase && !password.chars().any(char::is_uppercase) {
+        return Err(ServiceError::PasswordMissingUppercase);
+    }
+
+    if password_rules.require_digit && !password.chars().any(char::is_numeric) {
+        return Err(ServiceError::PasswordMissingDigit);
+    }
+
+    if password_rules.require_special_character && password.chars().all(char::is_alphanumeric) {
+        return Err(ServiceError::PasswordMissingSpecialCharacter);
+    }
+
+    Ok(())
+}
+
+/// The username, the email and the part of the email before the `@` are
+/// searched without case.
+fn check_personal_information(password: &str, username: &str, email: Option<&str>) -> Result<(), ServiceError> {
+    let password = password.to_lowercase();
+
+    let contains =
+        |value: &str| value.chars().count() >= MIN_PERSONAL_INFORMATION_LENGTH && password.contains(&value.to_lowercase());
+
+    if contains(username) {
+        return Err(ServiceError::PasswordContainsUsername);
+    }
+
+    if let Some(email) = email {
+        let local_part = email.split('@').next().unwrap_or_default();
+
+        if contains(email) || contains(local_part) {
+            return Err(ServiceError::PasswordContainsEmail);
+        }
+    }
+
+    Ok(())
+}
+
+/// The path is a directory with one range file per hash prefix, like
+/// `21BD1.txt`, containing the suffixes of the hashes, or a file with the
+/// whole hashes.
+fn is_breached(password: &str, path: &Path) -> io::Result<bool> {
+    let hash = sha1(password).to_uppercase();
+
+    if path.is_dir() {
+        let (prefix, suffix) = hash.split_at(HASH_PREFIX_LENGTH);
+
+        let range_file = path.join(format!("{prefix}.txt"));
+
+        // There are no breached passwords with this prefix.
+        if !range_file.exists() {
+            return Ok(false);
+        }
+
+        return contains_hash(&range_file, suffix);
+    }
+
+    contains_hash(path, &hash)
+}
+
+/// Lines are `<HASH>:<COUNT>`. The count is optional.
+fn contains_hash(file: &Path, hash: &str) -> io::Result<bool> {
+    let reader = BufReader::new(File::open(file)?);
+
+    for line in reader.lines() {
+        let line = line?;
+
+        let line_hash = line.split(':').next().unwrap_or_default().trim();
+
+        if line_hash.eq_ignore_ascii_case(hash) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::validate_password_constraints;
+    use crate::config::PasswordConstraints;
+    use crate::errors::ServiceError;
+
+    /// SHA-1 of `password`.
+    const BREACHED_PASSWORD_HASH: &str = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    fn validate(password: &str, password_rules: &PasswordConstraints) -> Result<(), ServiceError> {
+        validate_password_constraints(password, password, "indiana", Some("jones@example.com"), password_rules)
+    }
+
+    #[test]
+    fn the_default_policy_should_only_check_the_length() {
+        let password_rules = PasswordConstraints::default();
+
+        assert!(validate("password", &password_rules).is_ok());
+        assert!(matches!(
+            validate("pass", &password_rules),
+            Err(ServiceError::PasswordTooShort)
+        ));
+        assert!(matches!(
+            validate_password_constraints("password", "passw0rd", "indiana", None, &password_rules),
+            Err(ServiceError::PasswordsDontMatch)
+        ));
+    }
+
+    #[test]
+    fn it_should_require_the_configured_character_classes() {
+        let password_rules = PasswordConstraints {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_special_character: true,
+            ..PasswordConstraints::default()
+        };
+
+        assert!(matches!(
+            validate("PASSWORD", &password_rules),
+            Err(ServiceError::PasswordMissingLowercase)
+        ));
+        assert!(matches!(
+            validate("password", &password_rules),
+            Err(ServiceError::PasswordMissingUppercase)
+        ));
+        assert!(matches!(
+            validate("Password", &password_rules),
+            Err(ServiceError::PasswordMissingDigit)
+        ));
+        assert!(matches!(
+            validate("Passw0rd", &password_rules),
+            Err(ServiceError::PasswordMissingSpecialCharacter)
+        ));
+        assert!(validate("Passw0rd!", &password_rules).is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_passwords_containing_the_username_or_the_email() {
+        let password_rules = PasswordConstraints {
+            reject_username_or_email: true,
+            ..PasswordConstraints::default()
+        };
+
+        assert!(matches!(
+            validate("my-Indiana-password", &password_rules),
+            Err(ServiceError::PasswordContainsUsername)
+        ));
+        assert!(matches!(
+            validate("jones1234", &password_rules),
+            Err(ServiceError::PasswordContainsEmail)
+        ));
+        assert!(validate("a-secret-password", &password_rules).is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_passwords_below_the_minimum_strength_score() {
+        let password_rules = PasswordConstraints {
+            min_strength_score: 3,
+            ..PasswordConstraints::default()
+        };
+
+        assert!(matches!(
+            validate("password1", &password_rules),
+            Err(ServiceError::PasswordTooWeak)
+        ));
+        assert!(matches!(
+            validate("indiana2024", &password_rules),
+            Err(ServiceError::PasswordTooWeak)
+        ));
+        assert!(validate("x7#Kq9!vRm2$", &password_rules).is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_breached_passwords_from_a_directory_of_range_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (prefix, suffix) = BREACHED_PASSWORD_HASH.split_at(5);
+        fs::write(dir.path().join(format!("{prefix}.txt")), format!("{suffix}:10437277\n")).unwrap();
+
+        let password_rules = PasswordConstraints {
+            breached_passwords_path: Some(dir.path().to_path_buf()),
+            ..PasswordConstraints::default()
+        };
+
+        assert!(matches!(
+            validate("password", &password_rules),
+            Err(ServiceError::PasswordBreached)
+        ));
+        assert!(validate("x7#Kq9!vRm2$", &password_rules).is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_breached_passwords_from_a_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("pwnedpasswords.txt");
+        fs::write(&file, format!("{}:10437277\n", BREACHED_PASSWORD_HASH.to_lowercase())).unwrap();
+
+        let password_rules = PasswordConstraints {
+            breached_passwords_path: Some(file),
+            ..PasswordConstraints::default()
+        };
+
+        assert!(matches!(
+            validate("password", &password_rules),
+            Err(ServiceError::PasswordBreached)
+        ));
+        assert!(validate("x7#Kq9!vRm2$", &password_rules).is_ok());
+    }
+
+    #[test]
+    fn it_should_fail_when_the_breached_passwords_file_does_not_exist() {
+        let password_rules = PasswordConstraints {
+            breached_passwords_path: Some("./does-not-exist.txt".into()),
+            ..PasswordConstraints::default()
+        };
+
+        assert!(matches!(
+            validate("x7#Kq9!vRm2$", &password_rules),
+            Err(ServiceError::InternalServerError)
+        ));
+    }
+}
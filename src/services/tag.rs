@@ -1,22 +1,35 @@
 //! Tag service.
 use std::sync::Arc;
 
+use tracing::error;
+
 use super::authorization::{self, ACTION};
+use super::notification::{self, DbNotificationRepository, Event};
 use crate::databases::database::{Database, Error as DatabaseError, Error};
 use crate::errors::ServiceError;
 use crate::models::torrent_tag::{TagId, TorrentTag};
 use crate::models::user::UserId;
 
+#[allow(clippy::struct_field_names)]
 pub struct Service {
     tag_repository: Arc<DbTagRepository>,
+    notification_repository: Arc<DbNotificationRepository>,
+    notification_dispatcher: Arc<notification::Dispatcher>,
     authorization_service: Arc<authorization::Service>,
 }
 
 impl Service {
     #[must_use]
-    pub fn new(tag_repository: Arc<DbTagRepository>, authorization_service: Arc<authorization::Service>) -> Service {
+    pub fn new(
+        tag_repository: Arc<DbTagRepository>,
+        notification_repository: Arc<DbNotificationRepository>,
+        notification_dispatcher: Arc<notification::Dispatcher>,
+        authorization_service: Arc<authorization::Service>,
+    ) -> Service {
         Service {
             tag_repository,
+            notification_repository,
+            notification_dispatcher,
             authorization_service,
         }
     }
@@ -47,7 +60,7 @@ impl Service {
         }
     }
 
-    /// Deletes a tag.
+    /// Deletes a tag. The users following it are notified.
     ///
     /// # Errors
     ///
@@ -58,13 +71,38 @@ impl Service {
     pub async fn delete_tag(&self, tag_id: &TagId, maybe_user_id: Option<UserId>) -> Result<(), ServiceError> {
         self.authorization_service.authorize(ACTION::DeleteTag, maybe_user_id).await?;
 
-        match self.tag_repository.delete(tag_id).await {
-            Ok(()) => Ok(()),
-            Err(e) => match e {
-                DatabaseError::TagNotFound => Err(ServiceError::TagNotFound),
-                _ => Err(ServiceError::DatabaseError),
-            },
-        }
+        let tag = self
+            .tag_repository
+            .get_all()
+            .await
+            .map_err(|_| ServiceError::DatabaseError)?
+            .into_iter()
+            .find(|tag| tag.tag_id == *tag_id)
+            .ok_or(ServiceError::TagNotFound)?;
+
+        // The followers are removed with the tag.
+        let followers = self
+            .notification_repository
+            .get_tag_followers(tag_id)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to get the followers of the tag #{tag_id}: {e:?}");
+                vec![]
+            });
+
+        self.tag_repository.delete(tag_id).await.map_err(|e| match e {
+            DatabaseError::TagNotFound => ServiceError::TagNotFound,
+            _ => ServiceError::DatabaseError,
+        })?;
+
+        self.notification_dispatcher
+            .dispatch(Event::FollowedTagDeleted {
+                tag_name: tag.name,
+                followers,
+            })
+            .await;
+
+        Ok(())
     }
 
     /// Returns all the tags from the database
@@ -1,25 +1,34 @@
 //! Torrent service.
+use std::fmt::Write;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use serde_derive::{Deserialize, Serialize};
-use tracing::debug;
+use tracing::{debug, warn};
 use url::Url;
 
 use super::authorization::{self, ACTION};
 use super::category::DbCategoryRepository;
-use crate::config::Configuration;
+use super::content_type;
+use super::notification::{self, Event};
+use super::torrent_file::{CreateTorrentRequest, TorrentContent};
+use super::upload_policy;
+use super::{event_stream, webhook};
+use crate::config::{Configuration, TorrentStorageBackend};
 use crate::databases::database::{Database, Error, Sorting};
 use crate::errors::ServiceError;
-use crate::models::category::CategoryId;
-use crate::models::info_hash::InfoHash;
-use crate::models::response::{DeletedTorrentResponse, TorrentResponse, TorrentsResponse};
-use crate::models::torrent::{Metadata, TorrentId, TorrentListing};
+use crate::models::category::{CategoryId, TorrentFileFormat};
+use crate::models::file_tree::{Directory, FileFilter};
+use crate::models::info_hash::{InfoHash, InfoHashV2};
+use crate::models::magnet_link::MagnetLink;
+use crate::models::response::{DeletedTorrentResponse, TorrentFilesResponse, TorrentResponse, TorrentsResponse};
+use crate::models::torrent::{Metadata, TorrentFileType, TorrentId, TorrentListing, TorrentVersion};
 use crate::models::torrent_file::{DbTorrent, Torrent, TorrentFile};
 use crate::models::torrent_tag::{TagId, TorrentTag};
-use crate::models::user::UserId;
+use crate::models::user::{UserCompact, UserId};
 use crate::services::user::Repository;
 use crate::tracker::statistics_importer::StatisticsImporter;
-use crate::utils::parse_torrent::decode_and_validate_torrent_file;
+use crate::utils::parse_torrent::{decode_and_validate_torrent_file, encode_torrent, rewrite_announce_keys};
 use crate::{tracker, AsCSV};
 
 pub struct Index {
@@ -33,9 +42,14 @@ pub struct Index {
     torrent_info_repository: Arc<DbTorrentInfoRepository>,
     torrent_file_repository: Arc<DbTorrentFileRepository>,
     torrent_announce_url_repository: Arc<DbTorrentAnnounceUrlRepository>,
+    torrent_web_seed_repository: Arc<DbTorrentWebSeedRepository>,
+    torrent_original_file_repository: Arc<DbTorrentOriginalFileRepository>,
     torrent_tag_repository: Arc<DbTorrentTagRepository>,
     torrent_listing_generator: Arc<DbTorrentListingGenerator>,
     authorization_service: Arc<authorization::Service>,
+    notification_dispatcher: Arc<notification::Dispatcher>,
+    webhook_publisher: Arc<webhook::Publisher>,
+    event_broadcaster: Arc<event_stream::Broadcaster>,
 }
 
 pub struct AddTorrentRequest {
@@ -46,12 +60,48 @@ pub struct AddTorrentRequest {
     pub torrent_buffer: Vec<u8>,
 }
 
+pub struct AddMagnetTorrentRequest {
+    pub title: String,
+    pub description: String,
+    pub category_name: String,
+    pub tags: Vec<TagId>,
+    pub magnet_link: String,
+}
+
 pub struct AddTorrentResponse {
     pub torrent_id: TorrentId,
     pub canonical_info_hash: String,
     pub info_hash: String,
 }
 
+pub struct ReplaceTorrentResponse {
+    pub torrent_id: TorrentId,
+    pub version: i64,
+    pub canonical_info_hash: String,
+    pub info_hash: String,
+    pub previous_info_hash: String,
+}
+
+/// A request to create a new torrent from its content and add it to the
+/// index.
+pub struct CreateTorrentFromContentRequest {
+    pub title: String,
+    pub description: String,
+    pub category_name: String,
+    pub tags: Vec<TagId>,
+    pub piece_length: i64,
+    pub content: TorrentContent,
+}
+
+/// A torrent file ready to be downloaded.
+pub struct TorrentFileDownload {
+    pub name: String,
+    /// The info-hash of the torrent file. It's the original info-hash when the
+    /// original torrent file is served.
+    pub info_hash: String,
+    pub bytes: Vec<u8>,
+}
+
 /// User request to generate a torrent listing.
 #[derive(Debug, Deserialize)]
 pub struct ListingRequest {
@@ -62,20 +112,49 @@ pub struct ListingRequest {
     pub categories: Option<String>,
     /// Expects comma separated string, eg: "?tags=Linux,Ubuntu"
     pub tags: Option<String>,
+    /// Expects comma separated string of media kinds, eg: `?content_types=video,audio`
+    pub content_types: Option<String>,
+    /// Expects comma separated string of file extensions, eg: "?extensions=flac,iso"
+    pub extensions: Option<String>,
     pub search: Option<String>,
 }
 
+/// User request to download several torrents in a ZIP archive.
+#[derive(Debug)]
+pub enum BatchDownloadRequest {
+    /// The torrents with these info-hashes.
+    InfoHashes(Vec<InfoHash>),
+    /// The torrents in a listing. The page size defaults to the maximum
+    /// number of torrents per batch download.
+    Listing(ListingRequest),
+}
+
 /// Internal specification for torrent listings.
 #[derive(Debug, Deserialize)]
 pub struct ListingSpecification {
     pub search: Option<String>,
     pub categories: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
+    pub content_types: Option<Vec<String>>,
+    pub extensions: Option<Vec<String>>,
     pub sort: Sorting,
     pub offset: u64,
     pub page_size: u8,
 }
 
+/// User request to list the files in a torrent.
+#[derive(Debug, Default, Deserialize)]
+pub struct FileListingRequest {
+    /// Directory to list, with `/` as separator. Defaults to the torrent root.
+    pub path: Option<String>,
+    /// Only list the files whose name contains this text.
+    pub name: Option<String>,
+    /// Only list the files with this extension.
+    pub extension: Option<String>,
+    pub page_size: Option<u8>,
+    pub page: Option<u32>,
+}
+
 impl Index {
     #[allow(clippy::too_many_arguments)]
     #[must_use]
@@ -90,9 +169,14 @@ impl Index {
         torrent_info_repository: Arc<DbTorrentInfoRepository>,
         torrent_file_repository: Arc<DbTorrentFileRepository>,
         torrent_announce_url_repository: Arc<DbTorrentAnnounceUrlRepository>,
+        torrent_web_seed_repository: Arc<DbTorrentWebSeedRepository>,
+        torrent_original_file_repository: Arc<DbTorrentOriginalFileRepository>,
         torrent_tag_repository: Arc<DbTorrentTagRepository>,
         torrent_listing_repository: Arc<DbTorrentListingGenerator>,
         authorization_service: Arc<authorization::Service>,
+        notification_dispatcher: Arc<notification::Dispatcher>,
+        webhook_publisher: Arc<webhook::Publisher>,
+        event_broadcaster: Arc<event_stream::Broadcaster>,
     ) -> Self {
         Self {
             configuration,
@@ -105,9 +189,14 @@ impl Index {
             torrent_info_repository,
             torrent_file_repository,
             torrent_announce_url_repository,
+            torrent_web_seed_repository,
+            torrent_original_file_repository,
             torrent_tag_repository,
             torrent_listing_generator: torrent_listing_repository,
             authorization_service,
+            notification_dispatcher,
+            webhook_publisher,
+            event_broadcaster,
         }
     }
 
@@ -142,12 +231,45 @@ impl Index {
             .authorize(ACTION::AddTorrent, maybe_user_id)
             .await?;
 
-        let metadata = self.validate_and_build_metadata(&add_torrent_req).await?;
+        let upload_policy = self.configuration.settings.read().await.upload_policy.clone();
+
+        upload_policy::check_torrent_file_size(&upload_policy, add_torrent_req.torrent_buffer.len())?;
+
+        let metadata = self
+            .validate_and_build_metadata(
+                &add_torrent_req.title,
+                &add_torrent_req.description,
+                &add_torrent_req.category_name,
+                &add_torrent_req.tags,
+            )
+            .await?;
+
+        let uploader = self.user_repository.get_compact(&user_id).await?;
+
+        upload_policy::check_category(&upload_policy, &add_torrent_req.category_name, uploader.administrator)?;
 
         let (mut torrent, original_info_hash) = decode_and_validate_torrent_file(&add_torrent_req.torrent_buffer)?;
 
+        self.rewrite_source_if_enabled(&mut torrent).await;
+
+        upload_policy::check_torrent(&upload_policy, &torrent)?;
+
         self.customize_announcement_info_for(&mut torrent).await;
 
+        // Magnet links use the info-hash of the uploaded torrent file, which
+        // changes when the source is rewritten.
+        if let Some(torrent_listing) = self.find_magnet_only_listing(&original_info_hash).await {
+            return self
+                .add_meta_info_to_magnet_only_listing(
+                    &torrent_listing,
+                    &original_info_hash,
+                    &torrent,
+                    &add_torrent_req.torrent_buffer,
+                    uploader,
+                )
+                .await;
+        }
+
         self.canonical_info_hash_group_checks(&original_info_hash, &torrent.canonical_info_hash())
             .await?;
 
@@ -156,8 +278,26 @@ impl Index {
             .add(&original_info_hash, &torrent, &metadata, user_id)
             .await?;
 
+        if let Err(e) = self
+            .store_original_torrent_file_if_needed(
+                torrent_id,
+                Some(metadata.category_id),
+                &original_info_hash,
+                &torrent.canonical_info_hash(),
+                &add_torrent_req.torrent_buffer,
+            )
+            .await
+        {
+            // If the original torrent file can't be stored, remove the torrent from database
+            drop(self.torrent_original_file_repository.delete(&torrent_id).await);
+            drop(self.torrent_repository.delete(&torrent_id).await);
+            return Err(e);
+        }
+
         // Synchronous secondary tasks
 
+        self.classify_torrent_files(torrent_id, &torrent).await;
+
         // code-review: consider moving this to a background task
         self.import_torrent_statistics_from_tracker(torrent_id, &torrent.canonical_info_hash())
             .await;
@@ -174,10 +314,30 @@ impl Index {
             .await
         {
             // If the torrent can't be whitelisted somehow, remove the torrent from database
+            drop(self.torrent_original_file_repository.delete(&torrent_id).await);
             drop(self.torrent_repository.delete(&torrent_id).await);
+
+            if original_info_hash != torrent.canonical_info_hash() {
+                drop(
+                    self.tracker_service
+                        .remove_info_hash_from_whitelist(original_info_hash.to_hex_string())
+                        .await,
+                );
+            }
+
             return Err(e.into());
         }
 
+        self.publish_torrent_added(
+            torrent_id,
+            torrent.canonical_info_hash_hex(),
+            metadata.title,
+            Some(metadata.category_id),
+            metadata.tags,
+            uploader.username,
+        )
+        .await;
+
         // Build response
 
         Ok(AddTorrentResponse {
@@ -187,23 +347,218 @@ impl Index {
         })
     }
 
-    async fn validate_and_build_metadata(&self, add_torrent_req: &AddTorrentRequest) -> Result<Metadata, ServiceError> {
-        if add_torrent_req.category_name.is_empty() {
+    /// Creates a v1 torrent from its content and adds it to the index.
+    ///
+    /// The content can be a file manifest with precomputed piece hashes or a
+    /// small file uploaded directly. The torrent is private when the tracker
+    /// runs in private mode. It returns the generated torrent file, as it
+    /// would be downloaded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The user is not allowed to add torrents.
+    /// * The piece length or the content are not valid.
+    /// * The torrent can't be added to the index (see [`Index::add_torrent`]).
+    pub async fn create_torrent(
+        &self,
+        create_torrent_req: CreateTorrentFromContentRequest,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<TorrentFileDownload, ServiceError> {
+        if maybe_user_id.is_none() {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        }
+
+        self.authorization_service
+            .authorize(ACTION::AddTorrent, maybe_user_id)
+            .await?;
+
+        let mut torrent_request =
+            CreateTorrentRequest::from_content(&create_torrent_req.content, create_torrent_req.piece_length)?;
+
+        if self.configuration.settings.read().await.tracker.private {
+            torrent_request.private = Some(1);
+        }
+
+        let torrent_buffer = encode_torrent(&torrent_request.build_torrent()).map_err(|_| ServiceError::InternalServerError)?;
+
+        let response = self
+            .add_torrent(
+                AddTorrentRequest {
+                    title: create_torrent_req.title,
+                    description: create_torrent_req.description,
+                    category_name: create_torrent_req.category_name,
+                    tags: create_torrent_req.tags,
+                    torrent_buffer,
+                },
+                maybe_user_id,
+            )
+            .await?;
+
+        let info_hash = InfoHash::from_str(&response.canonical_info_hash).map_err(|_| ServiceError::InternalServerError)?;
+
+        self.get_torrent_file(&info_hash, maybe_user_id).await
+    }
+
+    /// Lists a torrent from a magnet link, without its meta-info.
+    ///
+    /// The listing only has the info-hash, the display name, the trackers and
+    /// the web seeds in the magnet link. It gets the meta-info when someone
+    /// uploads the torrent file with the same canonical info-hash.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The user is not allowed to add torrents.
+    /// * The magnet link or the metadata are not valid.
+    /// * The info-hash was already uploaded.
+    /// * The info-hash can't be whitelisted in the tracker.
+    pub async fn add_magnet_torrent(
+        &self,
+        add_magnet_torrent_req: AddMagnetTorrentRequest,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<AddTorrentResponse, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::AddTorrent, maybe_user_id)
+            .await?;
+
+        let metadata = self
+            .validate_and_build_metadata(
+                &add_magnet_torrent_req.title,
+                &add_magnet_torrent_req.description,
+                &add_magnet_torrent_req.category_name,
+                &add_magnet_torrent_req.tags,
+            )
+            .await?;
+
+        let uploader = self.user_repository.get_compact(&user_id).await?;
+
+        let upload_policy = self.configuration.settings.read().await.upload_policy.clone();
+
+        upload_policy::check_category(&upload_policy, &add_magnet_torrent_req.category_name, uploader.administrator)?;
+
+        let mut magnet_link = MagnetLink::from_str(&add_magnet_torrent_req.magnet_link)?;
+
+        magnet_link.web_seeds = parse_web_seed_urls(&magnet_link.web_seeds)?;
+
+        self.canonical_info_hash_group_checks(&magnet_link.info_hash, &magnet_link.info_hash)
+            .await?;
+
+        let torrent_id = self.torrent_repository.add_magnet(&magnet_link, &metadata, user_id).await?;
+
+        if let Err(e) = self
+            .tracker_service
+            .whitelist_info_hash(magnet_link.info_hash.to_hex_string())
+            .await
+        {
+            drop(self.torrent_repository.delete(&torrent_id).await);
+            return Err(e.into());
+        }
+
+        self.publish_torrent_added(
+            torrent_id,
+            magnet_link.info_hash.to_hex_string(),
+            metadata.title,
+            Some(metadata.category_id),
+            metadata.tags,
+            uploader.username,
+        )
+        .await;
+
+        Ok(AddTorrentResponse {
+            torrent_id,
+            canonical_info_hash: magnet_link.info_hash.to_hex_string(),
+            info_hash: magnet_link.info_hash.to_hex_string(),
+        })
+    }
+
+    /// It returns the listing with the info-hash if it was listed from a
+    /// magnet link and it doesn't have the meta-info yet.
+    async fn find_magnet_only_listing(&self, info_hash: &InfoHash) -> Option<TorrentListing> {
+        self.torrent_listing_generator
+            .one_torrent_by_info_hash(info_hash)
+            .await
+            .ok()
+            .filter(|torrent_listing| torrent_listing.magnet_only)
+    }
+
+    /// It completes a listing created from a magnet link with the uploaded
+    /// meta-info. The listing keeps its title, description, category and
+    /// tags. The magnet-only listing is kept as the first version of the
+    /// torrent.
+    async fn add_meta_info_to_magnet_only_listing(
+        &self,
+        torrent_listing: &TorrentListing,
+        original_info_hash: &InfoHash,
+        torrent: &Torrent,
+        torrent_buffer: &[u8],
+        uploader: UserCompact,
+    ) -> Result<AddTorrentResponse, ServiceError> {
+        let canonical_info_hash = torrent.canonical_info_hash();
+
+        // The magnet link info-hash belongs to the listing, any other group
+        // means the torrent was already uploaded.
+        if self
+            .torrent_info_hash_repository
+            .find_canonical_info_hash_for(original_info_hash)
+            .await?
+            .is_some_and(|canonical| canonical.to_hex_string() != torrent_listing.info_hash)
+        {
+            return Err(ServiceError::OriginalInfoHashAlreadyExists);
+        }
+
+        self.replace_meta_info(torrent_listing, original_info_hash, torrent, torrent_buffer, uploader.user_id)
+            .await?;
+
+        self.remove_previous_info_hashes_from_whitelist(torrent_listing, None, original_info_hash, torrent)
+            .await;
+
+        self.classify_torrent_files(torrent_listing.torrent_id, torrent).await;
+
+        self.import_torrent_statistics_from_tracker(torrent_listing.torrent_id, &canonical_info_hash)
+            .await;
+
+        self.publish_torrent_added(
+            torrent_listing.torrent_id,
+            canonical_info_hash.to_hex_string(),
+            torrent_listing.title.clone(),
+            torrent_listing.category_id,
+            self.torrent_tag_ids(&torrent_listing.torrent_id).await,
+            uploader.username,
+        )
+        .await;
+
+        Ok(AddTorrentResponse {
+            torrent_id: torrent_listing.torrent_id,
+            canonical_info_hash: canonical_info_hash.to_hex_string(),
+            info_hash: original_info_hash.to_string(),
+        })
+    }
+
+    async fn validate_and_build_metadata(
+        &self,
+        title: &str,
+        description: &str,
+        category_name: &str,
+        tags: &[TagId],
+    ) -> Result<Metadata, ServiceError> {
+        if category_name.is_empty() {
             return Err(ServiceError::MissingMandatoryMetadataFields);
         }
 
         let category = self
             .category_repository
-            .get_by_name(&add_torrent_req.category_name)
+            .get_by_name(category_name)
             .await
             .map_err(|_| ServiceError::InvalidCategory)?;
 
-        let metadata = Metadata::new(
-            &add_torrent_req.title,
-            &add_torrent_req.description,
-            category.category_id,
-            &add_torrent_req.tags,
-        )?;
+        let metadata = Metadata::new(title, description, category.category_id, tags)?;
 
         Ok(metadata)
     }
@@ -252,6 +607,69 @@ impl Index {
         torrent.reset_announce_list_if_private();
     }
 
+    /// It stamps the website name into the `source` key when the upload
+    /// policy rewrites it. The torrent gets a new canonical info-hash, which
+    /// is the one whitelisted, while the uploaded info-hash is recorded in the
+    /// canonical info-hash group.
+    async fn rewrite_source_if_enabled(&self, torrent: &mut Torrent) {
+        let settings = self.configuration.settings.read().await;
+        if settings.upload_policy.rewrite_source {
+            torrent.set_source(&settings.website.name);
+        }
+    }
+
+    /// It returns the torrent file format configured for the category.
+    async fn torrent_file_format_for(&self, category_id: Option<CategoryId>) -> Result<TorrentFileFormat, ServiceError> {
+        let Some(category_id) = category_id else {
+            return Ok(TorrentFileFormat::default());
+        };
+
+        let category = self.category_repository.get_by_id(&category_id).await?;
+
+        Ok(category.torrent_file_format.parse().unwrap_or_default())
+    }
+
+    /// It stores the exact uploaded torrent file when the category serves the
+    /// original torrent files. Otherwise, it removes the previously stored
+    /// one, if any. The original info-hash is whitelisted too, so the
+    /// uploader's swarm keeps working.
+    async fn store_original_torrent_file_if_needed(
+        &self,
+        torrent_id: TorrentId,
+        category_id: Option<CategoryId>,
+        original_info_hash: &InfoHash,
+        canonical_info_hash: &InfoHash,
+        torrent_buffer: &[u8],
+    ) -> Result<(), ServiceError> {
+        if self.torrent_file_format_for(category_id).await? == TorrentFileFormat::Canonical {
+            self.torrent_original_file_repository.delete(&torrent_id).await?;
+            return Ok(());
+        }
+
+        self.torrent_original_file_repository
+            .add(&torrent_id, original_info_hash, torrent_buffer)
+            .await?;
+
+        if original_info_hash != canonical_info_hash {
+            self.tracker_service
+                .whitelist_info_hash(original_info_hash.to_hex_string())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// It stores the file types of the torrent. Torrents that can't be
+    /// classified now can be classified later with the `classify_torrents`
+    /// console command, so errors are only logged.
+    async fn classify_torrent_files(&self, torrent_id: TorrentId, torrent: &Torrent) {
+        let file_types = content_type::classify_torrent(torrent);
+
+        if let Err(err) = self.torrent_repository.update_file_types(&torrent_id, &file_types).await {
+            warn!("Unable to store the file types of torrent #{torrent_id}: {err:?}");
+        }
+    }
+
     async fn import_torrent_statistics_from_tracker(&self, torrent_id: TorrentId, canonical_info_hash: &InfoHash) {
         drop(
             self.tracker_statistics_importer
@@ -291,6 +709,47 @@ impl Index {
         Ok(torrent)
     }
 
+    /// Gets the torrent file to download.
+    ///
+    /// When the torrent category serves the original torrent files and the
+    /// original file was stored, it's the uploaded file with only the announce
+    /// keys rewritten. Otherwise, it's the canonical torrent file rebuilt from
+    /// the database.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if unable to get the torrent from the
+    /// database or from the original torrent file storage.
+    pub async fn get_torrent_file(
+        &self,
+        info_hash: &InfoHash,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<TorrentFileDownload, ServiceError> {
+        let torrent = self.get_torrent(info_hash, maybe_user_id).await?;
+
+        let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
+
+        if self.torrent_file_format_for(torrent_listing.category_id).await? == TorrentFileFormat::Original {
+            if let Some(original) = self.torrent_original_file_repository.get(&torrent_listing.torrent_id).await? {
+                let bytes = rewrite_announce_keys(&original.bytes, torrent.announce.as_ref(), torrent.announce_list.as_ref())?;
+
+                return Ok(TorrentFileDownload {
+                    name: torrent.info.name,
+                    info_hash: original.info_hash.to_hex_string(),
+                    bytes,
+                });
+            }
+        }
+
+        let bytes = encode_torrent(&torrent).map_err(|_| ServiceError::InternalServerError)?;
+
+        Ok(TorrentFileDownload {
+            info_hash: torrent.canonical_info_hash_hex(),
+            name: torrent.info.name,
+            bytes,
+        })
+    }
+
     /// Delete a Torrent from the Index
     ///
     /// # Errors
@@ -312,8 +771,26 @@ impl Index {
 
         let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
 
+        let original_info_hash = self
+            .torrent_original_file_repository
+            .find_info_hash(&torrent_listing.torrent_id)
+            .await?;
+
+        // The tag links are removed with the torrent.
+        let tag_ids = self.torrent_tag_ids(&torrent_listing.torrent_id).await;
+
+        // The original file record is removed with the torrent.
         self.torrent_repository.delete(&torrent_listing.torrent_id).await?;
 
+        if let Some(original_info_hash) = &original_info_hash {
+            if let Err(err) = self.torrent_original_file_repository.delete_file(original_info_hash).await {
+                warn!(
+                    "Unable to delete the original torrent file of torrent #{}: {err:?}",
+                    torrent_listing.torrent_id
+                );
+            }
+        }
+
         // Remove info-hash from tracker whitelist
         // todo: handle the error when the tracker is offline or not well configured.
         let _unused = self
@@ -321,6 +798,42 @@ impl Index {
             .remove_info_hash_from_whitelist(info_hash.to_string())
             .await;
 
+        if let Some(original_info_hash) = original_info_hash.filter(|original_info_hash| original_info_hash != info_hash) {
+            let _unused = self
+                .tracker_service
+                .remove_info_hash_from_whitelist(original_info_hash.to_hex_string())
+                .await;
+        }
+
+        self.event_broadcaster.broadcast(event_stream::Event::TorrentDeleted {
+            torrent_id: torrent_listing.torrent_id,
+            info_hash: torrent_listing.info_hash.clone(),
+            category_id: torrent_listing.category_id,
+            tag_ids,
+        });
+
+        if let Some(user_id) = maybe_user_id {
+            if let Ok(deleter) = self.user_repository.get_compact(&user_id).await {
+                self.webhook_publisher
+                    .publish(webhook::Event::TorrentDeleted {
+                        torrent_id: torrent_listing.torrent_id,
+                        info_hash: torrent_listing.info_hash.clone(),
+                        title: torrent_listing.title.clone(),
+                        deleter: deleter.username.clone(),
+                    })
+                    .await;
+
+                self.notification_dispatcher
+                    .dispatch(Event::TorrentDeleted {
+                        info_hash: torrent_listing.info_hash.clone(),
+                        title: torrent_listing.title.clone(),
+                        uploader: torrent_listing.uploader.clone(),
+                        editor: deleter.username,
+                    })
+                    .await;
+            }
+        }
+
         Ok(DeletedTorrentResponse {
             torrent_id: torrent_listing.torrent_id,
             info_hash: torrent_listing.info_hash,
@@ -380,6 +893,81 @@ impl Index {
         Ok(torrents_response)
     }
 
+    /// It returns the canonical info-hashes of the torrents to include in a
+    /// batch download, without duplicates.
+    ///
+    /// Magnet-only torrents are skipped when downloading a listing, because
+    /// there is no torrent file to download.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The user is not allowed to download torrents.
+    /// * There are more info-hashes than the maximum per batch download.
+    /// * One of the requested torrents does not exist or it's magnet-only.
+    /// * There is a database error.
+    pub async fn get_torrents_to_download(
+        &self,
+        request: &BatchDownloadRequest,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<Vec<InfoHash>, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GetTorrent, maybe_user_id)
+            .await?;
+
+        let max_torrents_per_batch_download = self.configuration.settings.read().await.api.max_torrents_per_batch_download;
+
+        let mut canonical_info_hashes: Vec<InfoHash> = vec![];
+
+        match request {
+            BatchDownloadRequest::InfoHashes(info_hashes) => {
+                if info_hashes.len() > usize::from(max_torrents_per_batch_download) {
+                    return Err(ServiceError::TooManyTorrentsToDownload);
+                }
+
+                for info_hash in info_hashes {
+                    let canonical_info_hash = self
+                        .torrent_info_hash_repository
+                        .find_canonical_info_hash_for(info_hash)
+                        .await?
+                        .unwrap_or(*info_hash);
+
+                    let torrent_listing = self
+                        .torrent_listing_generator
+                        .one_torrent_by_info_hash(&canonical_info_hash)
+                        .await?;
+
+                    if torrent_listing.magnet_only {
+                        return Err(ServiceError::TorrentMetaInfoNotAvailable);
+                    }
+
+                    if !canonical_info_hashes.contains(&canonical_info_hash) {
+                        canonical_info_hashes.push(canonical_info_hash);
+                    }
+                }
+            }
+            BatchDownloadRequest::Listing(listing_request) => {
+                let specification = listing_specification(
+                    listing_request,
+                    max_torrents_per_batch_download,
+                    max_torrents_per_batch_download,
+                );
+
+                let torrents_response = self.torrent_listing_generator.generate_listing(&specification).await?;
+
+                for torrent_listing in torrents_response.results.iter().filter(|listing| !listing.magnet_only) {
+                    let info_hash =
+                        InfoHash::from_str(&torrent_listing.info_hash).map_err(|_| ServiceError::InternalServerError)?;
+
+                    canonical_info_hashes.push(info_hash);
+                }
+            }
+        }
+
+        Ok(canonical_info_hashes)
+    }
+
     /// It converts the user listing request into an internal listing
     /// specification.
     async fn listing_specification_from_user_request(&self, request: &ListingRequest) -> ListingSpecification {
@@ -388,30 +976,15 @@ impl Index {
         let max_torrent_page_size = settings.api.max_torrent_page_size;
         drop(settings);
 
-        let sort = request.sort.unwrap_or(Sorting::UploadedDesc);
-        let page = request.page.unwrap_or(0);
-        let page_size = request.page_size.unwrap_or(default_torrent_page_size);
-
-        // Guard that page size does not exceed the maximum
-        let page_size = if page_size > max_torrent_page_size {
-            max_torrent_page_size
-        } else {
-            page_size
-        };
-
-        let offset = u64::from(page * u32::from(page_size));
-
-        let categories = request.categories.as_csv::<String>().unwrap_or(None);
-
-        let tags = request.tags.as_csv::<String>().unwrap_or(None);
+        listing_specification(request, default_torrent_page_size, max_torrent_page_size)
+    }
 
-        ListingSpecification {
-            search: request.search.clone(),
-            categories,
-            tags,
-            sort,
-            offset,
-            page_size,
+    /// The IDs of the torrent tags, for the event stream filters. A torrent
+    /// without tags is assumed when they can't be loaded.
+    async fn torrent_tag_ids(&self, torrent_id: &TorrentId) -> Vec<TagId> {
+        match self.torrent_tag_repository.get_tags_for_torrent(torrent_id).await {
+            Ok(tags) => tags.into_iter().map(|tag| tag.tag_id).collect(),
+            Err(_) => vec![],
         }
     }
 
@@ -425,6 +998,8 @@ impl Index {
     /// * Unable to get listing from id.
     /// * Unable to update the torrent tile or description.
     /// * User does not have the permissions to update the torrent.
+    /// * A web seed is not a valid HTTP or HTTPS URL.
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_torrent_info(
         &self,
         info_hash: &InfoHash,
@@ -432,30 +1007,475 @@ impl Index {
         description: &Option<String>,
         category_id: &Option<CategoryId>,
         tags: &Option<Vec<TagId>>,
+        web_seeds: &Option<Vec<String>>,
         user_id: &UserId,
     ) -> Result<TorrentResponse, ServiceError> {
         let updater = self.user_repository.get_compact(user_id).await?;
 
-        let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
+        let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
+
+        // Check if user is owner or administrator
+        // todo: move this to an authorization service.
+        if !(torrent_listing.uploader == updater.username || updater.administrator) {
+            return Err(ServiceError::UnauthorizedAction);
+        }
+
+        if let Some(category_id) = category_id {
+            if torrent_listing.category_id != Some(*category_id) {
+                let category = self.category_repository.get_by_id(category_id).await?;
+                let upload_policy = self.configuration.settings.read().await.upload_policy.clone();
+
+                upload_policy::check_category(&upload_policy, &category.name, updater.administrator)?;
+            }
+        }
+
+        let web_seeds = match web_seeds {
+            Some(web_seeds) => Some(parse_web_seed_urls(web_seeds)?),
+            None => None,
+        };
+
+        self.torrent_info_repository
+            .update(&torrent_listing.torrent_id, title, description, category_id, tags)
+            .await?;
+
+        if let Some(web_seeds) = web_seeds {
+            self.torrent_web_seed_repository
+                .update(&torrent_listing.torrent_id, &web_seeds)
+                .await?;
+        }
+
+        let torrent_listing = self
+            .torrent_listing_generator
+            .one_torrent_by_torrent_id(&torrent_listing.torrent_id)
+            .await?;
+
+        self.webhook_publisher
+            .publish(webhook::Event::TorrentUpdated {
+                torrent_id: torrent_listing.torrent_id,
+                info_hash: torrent_listing.info_hash.clone(),
+                title: torrent_listing.title.clone(),
+                editor: updater.username.clone(),
+            })
+            .await;
+
+        self.event_broadcaster.broadcast(event_stream::Event::TorrentUpdated {
+            torrent_id: torrent_listing.torrent_id,
+            info_hash: torrent_listing.info_hash.clone(),
+            title: torrent_listing.title.clone(),
+            category_id: torrent_listing.category_id,
+            tag_ids: self.torrent_tag_ids(&torrent_listing.torrent_id).await,
+        });
+
+        self.notification_dispatcher
+            .dispatch(Event::TorrentEdited {
+                info_hash: torrent_listing.info_hash.clone(),
+                title: torrent_listing.title.clone(),
+                uploader: torrent_listing.uploader.clone(),
+                editor: updater.username,
+            })
+            .await;
+
+        let torrent_response = self.build_short_torrent_response(torrent_listing, info_hash).await?;
+
+        Ok(torrent_response)
+    }
+
+    /// Replaces the torrent file (meta-info) of an existing torrent listing.
+    ///
+    /// The listing keeps its ID, title, description, category and tags. The
+    /// replaced meta-info is added to the version history, the new info-hash
+    /// is whitelisted in the tracker and the previous one is removed from the
+    /// whitelist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The user does not have permission to replace the torrent file.
+    /// * Unable to get the torrent listing from the info-hash.
+    /// * The new torrent file is not valid or it was already uploaded.
+    /// * Unable to add the new info-hash to the whitelist.
+    /// * Unable to replace the torrent in the database.
+    pub async fn replace_torrent_file(
+        &self,
+        info_hash: &InfoHash,
+        torrent_buffer: &[u8],
+        maybe_user_id: Option<UserId>,
+    ) -> Result<ReplaceTorrentResponse, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::ReplaceTorrent, maybe_user_id)
+            .await?;
+
+        let replacer = self.user_repository.get_compact(&user_id).await?;
+
+        let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
+
+        // Check if user is owner or administrator
+        // todo: move this to an authorization service.
+        if !(torrent_listing.uploader == replacer.username || replacer.administrator) {
+            return Err(ServiceError::UnauthorizedAction);
+        }
+
+        let upload_policy = self.configuration.settings.read().await.upload_policy.clone();
+
+        upload_policy::check_torrent_file_size(&upload_policy, torrent_buffer.len())?;
+
+        let (mut torrent, original_info_hash) = decode_and_validate_torrent_file(torrent_buffer)?;
+
+        self.rewrite_source_if_enabled(&mut torrent).await;
+
+        upload_policy::check_torrent(&upload_policy, &torrent)?;
+
+        self.customize_announcement_info_for(&mut torrent).await;
+
+        self.canonical_info_hash_group_checks(&original_info_hash, &torrent.canonical_info_hash())
+            .await?;
+
+        let previous_original_info_hash = self
+            .torrent_original_file_repository
+            .find_info_hash(&torrent_listing.torrent_id)
+            .await?;
+
+        let version = self
+            .replace_meta_info(&torrent_listing, &original_info_hash, &torrent, torrent_buffer, user_id)
+            .await?;
+
+        self.remove_previous_info_hashes_from_whitelist(
+            &torrent_listing,
+            previous_original_info_hash,
+            &original_info_hash,
+            &torrent,
+        )
+        .await;
+
+        self.classify_torrent_files(torrent_listing.torrent_id, &torrent).await;
+
+        self.import_torrent_statistics_from_tracker(torrent_listing.torrent_id, &torrent.canonical_info_hash())
+            .await;
+
+        self.publish_torrent_replaced(&torrent_listing, torrent.canonical_info_hash_hex(), replacer.username)
+            .await;
+
+        Ok(ReplaceTorrentResponse {
+            torrent_id: torrent_listing.torrent_id,
+            version,
+            canonical_info_hash: torrent.canonical_info_hash_hex(),
+            info_hash: original_info_hash.to_string(),
+            previous_info_hash: torrent_listing.info_hash,
+        })
+    }
+
+    /// It replaces the meta-info of the listing with the new torrent and
+    /// returns the new version number.
+    ///
+    /// The new info-hashes are whitelisted before changing the database, so
+    /// the listing never points to a torrent the tracker does not accept. They
+    /// are removed again if the torrent can't be stored. The original torrent
+    /// file is only stored once the meta-info has been replaced, so a failed
+    /// replacement keeps the previous one.
+    async fn replace_meta_info(
+        &self,
+        torrent_listing: &TorrentListing,
+        original_info_hash: &InfoHash,
+        torrent: &Torrent,
+        torrent_buffer: &[u8],
+        user_id: UserId,
+    ) -> Result<i64, ServiceError> {
+        let canonical_info_hash = torrent.canonical_info_hash();
+
+        let torrent_file_format = self.torrent_file_format_for(torrent_listing.category_id).await?;
+
+        let mut new_info_hashes = vec![canonical_info_hash.to_hex_string()];
+
+        if torrent_file_format == TorrentFileFormat::Original && *original_info_hash != canonical_info_hash {
+            new_info_hashes.push(original_info_hash.to_hex_string());
+        }
+
+        new_info_hashes.retain(|new_info_hash| *new_info_hash != torrent_listing.info_hash);
+
+        let mut result = Ok(0);
+
+        for new_info_hash in &new_info_hashes {
+            if let Err(e) = self.tracker_service.whitelist_info_hash(new_info_hash.clone()).await {
+                result = Err(ServiceError::from(e));
+                break;
+            }
+        }
+
+        if result.is_ok() {
+            // The replaced original torrent file goes to the version history.
+            let stored_original_file = match self.torrent_original_file_repository.get(&torrent_listing.torrent_id).await {
+                Ok(stored_original_file) => stored_original_file,
+                Err(err) => {
+                    warn!(
+                        "Unable to read the original torrent file of torrent #{}: {err:?}",
+                        torrent_listing.torrent_id
+                    );
+                    None
+                }
+            };
+
+            result = self
+                .torrent_repository
+                .replace(
+                    &torrent_listing.torrent_id,
+                    original_info_hash,
+                    torrent,
+                    stored_original_file.as_ref(),
+                    user_id,
+                )
+                .await
+                .map_err(ServiceError::from);
+        }
+
+        if result.is_err() {
+            for new_info_hash in new_info_hashes {
+                drop(self.tracker_service.remove_info_hash_from_whitelist(new_info_hash).await);
+            }
+
+            return result;
+        }
+
+        let stored = if torrent_file_format == TorrentFileFormat::Original {
+            self.torrent_original_file_repository
+                .add(&torrent_listing.torrent_id, original_info_hash, torrent_buffer)
+                .await
+        } else {
+            self.torrent_original_file_repository
+                .delete(&torrent_listing.torrent_id)
+                .await
+        };
+
+        if let Err(err) = stored {
+            // The canonical torrent file is served when there is no original one.
+            warn!(
+                "Unable to store the original torrent file of torrent #{}: {err:?}",
+                torrent_listing.torrent_id
+            );
+            drop(
+                self.torrent_original_file_repository
+                    .delete(&torrent_listing.torrent_id)
+                    .await,
+            );
+        }
+
+        result
+    }
+
+    /// It removes the info-hashes of the replaced meta-info from the tracker
+    /// whitelist, except the ones the new meta-info still uses.
+    async fn remove_previous_info_hashes_from_whitelist(
+        &self,
+        torrent_listing: &TorrentListing,
+        previous_original_info_hash: Option<InfoHash>,
+        original_info_hash: &InfoHash,
+        torrent: &Torrent,
+    ) {
+        let info_hashes_in_use = [torrent.canonical_info_hash_hex(), original_info_hash.to_hex_string()];
+
+        let mut previous_info_hashes = vec![torrent_listing.info_hash.clone()];
+
+        if let Some(previous_original_info_hash) = previous_original_info_hash
+            .map(|previous| previous.to_hex_string())
+            .filter(|previous| *previous != torrent_listing.info_hash)
+        {
+            previous_info_hashes.push(previous_original_info_hash);
+        }
+
+        for previous_info_hash in previous_info_hashes
+            .into_iter()
+            .filter(|previous| !info_hashes_in_use.contains(previous))
+        {
+            // todo: handle the error when the tracker is offline or not well configured.
+            let _unused = self.tracker_service.remove_info_hash_from_whitelist(previous_info_hash).await;
+        }
+    }
+
+    async fn publish_torrent_added(
+        &self,
+        torrent_id: TorrentId,
+        info_hash: String,
+        title: String,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+        uploader: String,
+    ) {
+        self.webhook_publisher
+            .publish(webhook::Event::TorrentUploaded {
+                torrent_id,
+                info_hash: info_hash.clone(),
+                title: title.clone(),
+                category_id,
+                tag_ids: tag_ids.clone(),
+                uploader: uploader.clone(),
+            })
+            .await;
+
+        self.event_broadcaster.broadcast(event_stream::Event::TorrentAdded {
+            torrent_id,
+            info_hash: info_hash.clone(),
+            title: title.clone(),
+            category_id,
+            tag_ids: tag_ids.clone(),
+        });
+
+        self.notification_dispatcher
+            .dispatch(Event::TorrentUploaded {
+                info_hash,
+                title,
+                category_id,
+                tag_ids,
+                uploader,
+            })
+            .await;
+    }
+
+    async fn publish_torrent_replaced(&self, torrent_listing: &TorrentListing, info_hash: String, editor: String) {
+        self.webhook_publisher
+            .publish(webhook::Event::TorrentUpdated {
+                torrent_id: torrent_listing.torrent_id,
+                info_hash: info_hash.clone(),
+                title: torrent_listing.title.clone(),
+                editor: editor.clone(),
+            })
+            .await;
+
+        self.event_broadcaster.broadcast(event_stream::Event::TorrentUpdated {
+            torrent_id: torrent_listing.torrent_id,
+            info_hash: info_hash.clone(),
+            title: torrent_listing.title.clone(),
+            category_id: torrent_listing.category_id,
+            tag_ids: self.torrent_tag_ids(&torrent_listing.torrent_id).await,
+        });
+
+        self.notification_dispatcher
+            .dispatch(Event::TorrentEdited {
+                info_hash,
+                title: torrent_listing.title.clone(),
+                uploader: torrent_listing.uploader.clone(),
+                editor,
+            })
+            .await;
+    }
+
+    /// Returns the previous versions of the torrent meta-info, newest first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The user does not have permission to get the torrent info.
+    /// * Unable to get the torrent listing from the info-hash.
+    /// * Unable to get the versions from the database.
+    pub async fn get_torrent_versions(
+        &self,
+        info_hash: &InfoHash,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<Vec<TorrentVersion>, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GetTorrentInfo, maybe_user_id)
+            .await?;
+
+        let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
+
+        Ok(self.torrent_repository.get_versions(&torrent_listing.torrent_id).await?)
+    }
+
+    /// Returns one page of the files in a torrent directory.
+    ///
+    /// Without filters, it lists the subdirectories and files directly inside
+    /// the directory. With a name or extension filter, it lists the matching
+    /// files in the directory and all its subdirectories.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    ///
+    /// * The user does not have permission to get the torrent info.
+    /// * Unable to get the torrent listing from the info-hash.
+    /// * The directory does not exist in the torrent.
+    pub async fn get_torrent_files(
+        &self,
+        info_hash: &InfoHash,
+        request: &FileListingRequest,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<TorrentFilesResponse, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GetTorrentInfo, maybe_user_id)
+            .await?;
+
+        let torrent_listing = self.torrent_listing_generator.one_torrent_by_info_hash(info_hash).await?;
+
+        let files = self
+            .get_torrent_files_with_paths(torrent_listing.torrent_id, info_hash)
+            .await?;
+
+        let root = Directory::from_files(&files);
+
+        let path: Vec<String> = request
+            .path
+            .as_deref()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(ToString::to_string)
+            .collect();
+
+        let directory = root.find(&path).ok_or(ServiceError::TorrentDirectoryNotFound)?;
+
+        let filter = FileFilter {
+            name: request.name.clone().filter(|name| !name.is_empty()),
+            extension: request.extension.clone().filter(|extension| !extension.is_empty()),
+        };
+
+        let entries = if filter.is_empty() {
+            directory.entries(&path)
+        } else {
+            directory.search(&filter)
+        };
 
-        // Check if user is owner or administrator
-        // todo: move this to an authorization service.
-        if !(torrent_listing.uploader == updater.username || updater.administrator) {
-            return Err(ServiceError::UnauthorizedAction);
-        }
+        let settings = self.configuration.settings.read().await;
+        let default_page_size = settings.api.default_torrent_page_size;
+        let max_page_size = settings.api.max_torrent_page_size;
+        drop(settings);
 
-        self.torrent_info_repository
-            .update(&torrent_listing.torrent_id, title, description, category_id, tags)
-            .await?;
+        let page = request.page.unwrap_or(0) as usize;
+        let page_size = request.page_size.unwrap_or(default_page_size).min(max_page_size) as usize;
 
-        let torrent_listing = self
-            .torrent_listing_generator
-            .one_torrent_by_torrent_id(&torrent_listing.torrent_id)
-            .await?;
+        Ok(TorrentFilesResponse {
+            path,
+            size: directory.size,
+            file_count: directory.file_count,
+            total: u32::try_from(entries.len()).unwrap_or(u32::MAX),
+            results: entries.into_iter().skip(page * page_size).take(page_size).collect(),
+        })
+    }
 
-        let torrent_response = self.build_short_torrent_response(torrent_listing, info_hash).await?;
+    /// It returns the torrent files. Single-file torrents do not store the
+    /// file path, so the torrent name is used as path. The padding files of
+    /// hybrid torrents are excluded.
+    async fn get_torrent_files_with_paths(
+        &self,
+        torrent_id: TorrentId,
+        info_hash: &InfoHash,
+    ) -> Result<Vec<TorrentFile>, ServiceError> {
+        let mut files = self.torrent_file_repository.get_by_torrent_id(&torrent_id).await?;
 
-        Ok(torrent_response)
+        files.retain(|file| !file.is_padding());
+
+        if files.len() == 1 {
+            let torrent_info = self.torrent_info_repository.get_by_info_hash(info_hash).await?;
+
+            for file in &mut files {
+                file.path = vec![torrent_info.name.clone()];
+            }
+        }
+
+        Ok(files)
     }
 
     async fn get_tracker_url(&self) -> Url {
@@ -483,11 +1503,16 @@ impl Index {
             .get_canonical_info_hash_group(info_hash)
             .await?;
 
-        Ok(TorrentResponse::from_listing(
-            torrent_listing,
-            category,
-            &canonical_info_hash_group,
-        ))
+        let web_seeds = self
+            .torrent_web_seed_repository
+            .get_by_torrent_id(&torrent_listing.torrent_id)
+            .await?;
+
+        let mut torrent_response = TorrentResponse::from_listing(torrent_listing, category, &canonical_info_hash_group);
+
+        torrent_response.web_seeds = web_seeds;
+
+        Ok(torrent_response)
     }
 
     async fn build_full_torrent_response(
@@ -502,16 +1527,14 @@ impl Index {
 
         // Add files
 
-        torrent_response.files = self.torrent_file_repository.get_by_torrent_id(&torrent_id).await?;
+        torrent_response.files = self.get_torrent_files_with_paths(torrent_id, info_hash).await?;
 
-        if torrent_response.files.len() == 1 {
-            let torrent_info = self.torrent_info_repository.get_by_info_hash(info_hash).await?;
+        // Add file types
 
-            torrent_response
-                .files
-                .iter_mut()
-                .for_each(|v| v.path = vec![torrent_info.name.to_string()]);
-        }
+        torrent_response.file_types = self.torrent_repository.get_file_types(&torrent_id).await?;
+
+        torrent_response.media_kind =
+            content_type::dominant_media_kind(&torrent_response.file_types).map(|kind| kind.to_string());
 
         // Add trackers
 
@@ -541,19 +1564,7 @@ impl Index {
 
         // Add magnet link
 
-        // todo: extract a struct or function to build the magnet links
-        let mut magnet = format!(
-            "magnet:?xt=urn:btih:{}&dn={}",
-            torrent_response.info_hash,
-            urlencoding::encode(&torrent_response.title)
-        );
-
-        // Add trackers from torrent file to magnet link
-        for tracker in &torrent_response.trackers {
-            magnet.push_str(&format!("&tr={}", urlencoding::encode(tracker)));
-        }
-
-        torrent_response.magnet_link = magnet;
+        torrent_response.magnet_link = build_magnet_link(&torrent_response);
 
         // Get realtime seeders and leechers
         if let Ok(torrent_info) = self
@@ -591,6 +1602,100 @@ impl Index {
     }
 }
 
+/// It converts the user listing request into an internal listing
+/// specification, with the page size bounded by `max_page_size`.
+fn listing_specification(request: &ListingRequest, default_page_size: u8, max_page_size: u8) -> ListingSpecification {
+    let sort = request.sort.unwrap_or(Sorting::UploadedDesc);
+    let page = request.page.unwrap_or(0);
+    let page_size = request.page_size.unwrap_or(default_page_size);
+
+    // Guard that page size does not exceed the maximum
+    let page_size = if page_size > max_page_size { max_page_size } else { page_size };
+
+    let offset = u64::from(page * u32::from(page_size));
+
+    let categories = request.categories.as_csv::<String>().unwrap_or(None);
+
+    let tags = request.tags.as_csv::<String>().unwrap_or(None);
+
+    let content_types = request.content_types.as_csv::<String>().unwrap_or(None);
+
+    let extensions = request.extensions.as_csv::<String>().unwrap_or(None);
+
+    ListingSpecification {
+        search: request.search.clone(),
+        categories,
+        tags,
+        content_types,
+        extensions,
+        sort,
+        offset,
+        page_size,
+    }
+}
+
+/// It builds the magnet link for a torrent.
+///
+/// Hybrid torrents include both the v1 (`btih`) and v2 (`btmh`) info-hashes.
+/// v2-only torrents include only the v2 info-hash, because their v1 info-hash
+/// is just the truncated v2 info-hash.
+fn build_magnet_link(torrent_response: &TorrentResponse) -> String {
+    let mut magnet = String::from("magnet:?");
+
+    let v2_info_hash = torrent_response
+        .info_hash_v2
+        .as_ref()
+        .and_then(|info_hash| InfoHashV2::from_str(info_hash).ok());
+
+    let is_v2_only =
+        v2_info_hash.is_some_and(|info_hash| info_hash.truncated().to_hex_string() == torrent_response.info_hash.to_lowercase());
+
+    if !is_v2_only {
+        let _ = write!(magnet, "xt=urn:btih:{}&", torrent_response.info_hash);
+    }
+
+    if let Some(info_hash) = v2_info_hash {
+        let _ = write!(magnet, "xt=urn:btmh:{}&", info_hash.to_multihash_string());
+    }
+
+    let _ = write!(magnet, "dn={}", urlencoding::encode(&torrent_response.title));
+
+    // Add trackers from torrent file to magnet link
+    for tracker in &torrent_response.trackers {
+        let _ = write!(magnet, "&tr={}", urlencoding::encode(tracker));
+    }
+
+    for web_seed in &torrent_response.web_seeds {
+        let _ = write!(magnet, "&ws={}", urlencoding::encode(web_seed));
+    }
+
+    magnet
+}
+
+/// It validates the web seed URLs submitted by a user. Only HTTP and HTTPS
+/// URLs are allowed. Duplicates are removed, keeping the original order.
+fn parse_web_seed_urls(web_seeds: &[String]) -> Result<Vec<String>, ServiceError> {
+    let mut urls: Vec<String> = vec![];
+
+    for web_seed in web_seeds {
+        let web_seed = web_seed.trim();
+
+        let url = Url::parse(web_seed).map_err(|_| ServiceError::InvalidWebSeedUrl)?;
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ServiceError::InvalidWebSeedUrl);
+        }
+
+        // The original URL is kept because, for multi-file torrents, the
+        // trailing slash changes how clients build the file URLs.
+        if !urls.iter().any(|url| url == web_seed) {
+            urls.push(web_seed.to_string());
+        }
+    }
+
+    Ok(urls)
+}
+
 pub struct DbTorrentRepository {
     database: Arc<Box<dyn Database>>,
 }
@@ -627,6 +1732,67 @@ impl DbTorrentRepository {
             .await
     }
 
+    /// Inserts a torrent listed from a magnet link, without meta-info.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error there is a database error.
+    pub async fn add_magnet(&self, magnet_link: &MagnetLink, metadata: &Metadata, user_id: UserId) -> Result<TorrentId, Error> {
+        self.database
+            .insert_magnet_torrent_and_get_id(magnet_link, user_id, metadata)
+            .await
+    }
+
+    /// Replaces the meta-info of an existing torrent and returns the new
+    /// version number. The replaced torrent file is kept in the version
+    /// history.
+    ///
+    /// The stored original torrent file is only needed when it's not stored
+    /// in the database.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error there is a database error.
+    pub async fn replace(
+        &self,
+        torrent_id: &TorrentId,
+        original_info_hash: &InfoHash,
+        torrent: &Torrent,
+        stored_original_file: Option<&OriginalTorrentFile>,
+        user_id: UserId,
+    ) -> Result<i64, Error> {
+        self.database
+            .replace_torrent_and_get_version(*torrent_id, original_info_hash, torrent, stored_original_file, user_id)
+            .await
+    }
+
+    /// Returns the previous versions of the torrent meta-info.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error there is a database error.
+    pub async fn get_versions(&self, torrent_id: &TorrentId) -> Result<Vec<TorrentVersion>, Error> {
+        self.database.get_torrent_versions_from_id(*torrent_id).await
+    }
+
+    /// Replaces the file types of a torrent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error there is a database error.
+    pub async fn update_file_types(&self, torrent_id: &TorrentId, file_types: &[TorrentFileType]) -> Result<(), Error> {
+        self.database.update_torrent_file_types(*torrent_id, file_types).await
+    }
+
+    /// Returns the file types of a torrent, the largest first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error there is a database error.
+    pub async fn get_file_types(&self, torrent_id: &TorrentId) -> Result<Vec<TorrentFileType>, Error> {
+        self.database.get_torrent_file_types_from_id(*torrent_id).await
+    }
+
     /// Deletes the entire torrent in the database.
     ///
     /// # Errors
@@ -835,6 +2001,172 @@ impl DbTorrentAnnounceUrlRepository {
     }
 }
 
+pub struct DbTorrentWebSeedRepository {
+    database: Arc<Box<dyn Database>>,
+}
+
+impl DbTorrentWebSeedRepository {
+    #[must_use]
+    pub fn new(database: Arc<Box<dyn Database>>) -> Self {
+        Self { database }
+    }
+
+    /// It finds the web seed URLs (BEP 19 `url-list`) by torrent id.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_by_torrent_id(&self, torrent_id: &TorrentId) -> Result<Vec<String>, Error> {
+        self.database.get_torrent_web_seed_urls_from_id(*torrent_id).await
+    }
+
+    /// It replaces the web seed URLs of a torrent.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn update(&self, torrent_id: &TorrentId, web_seed_urls: &[String]) -> Result<(), Error> {
+        self.database.update_torrent_web_seed_urls(*torrent_id, web_seed_urls).await
+    }
+}
+
+/// An original torrent file uploaded by a user.
+pub struct OriginalTorrentFile {
+    pub info_hash: InfoHash,
+    pub bytes: Vec<u8>,
+}
+
+/// Storage for the original torrent files uploaded by users. The bytes are
+/// stored in the database or in the filesystem, depending on the
+/// `torrent_storage` configuration. The database always keeps a record of the
+/// stored files.
+pub struct DbTorrentOriginalFileRepository {
+    configuration: Arc<Configuration>,
+    database: Arc<Box<dyn Database>>,
+}
+
+impl DbTorrentOriginalFileRepository {
+    #[must_use]
+    pub fn new(configuration: Arc<Configuration>, database: Arc<Box<dyn Database>>) -> Self {
+        Self { configuration, database }
+    }
+
+    /// It stores the original torrent file, replacing the previous one.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database or filesystem error.
+    pub async fn add(&self, torrent_id: &TorrentId, info_hash: &InfoHash, bytes: &[u8]) -> Result<(), Error> {
+        self.delete(torrent_id).await?;
+
+        let torrent_storage = self.configuration.settings.read().await.torrent_storage.clone();
+
+        match torrent_storage.backend {
+            TorrentStorageBackend::Database => {
+                self.database
+                    .insert_torrent_original_file(*torrent_id, info_hash, &torrent_storage.backend.to_string(), Some(bytes))
+                    .await
+            }
+            TorrentStorageBackend::Filesystem => {
+                tokio::fs::create_dir_all(&torrent_storage.path)
+                    .await
+                    .map_err(|err| Error::ErrorWithText(err.to_string()))?;
+
+                tokio::fs::write(
+                    torrent_storage.path.join(original_file_name(&info_hash.to_hex_string())),
+                    bytes,
+                )
+                .await
+                .map_err(|err| Error::ErrorWithText(err.to_string()))?;
+
+                self.database
+                    .insert_torrent_original_file(*torrent_id, info_hash, &torrent_storage.backend.to_string(), None)
+                    .await
+            }
+        }
+    }
+
+    /// It finds the original info-hash of the stored original torrent file.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn find_info_hash(&self, torrent_id: &TorrentId) -> Result<Option<InfoHash>, Error> {
+        Ok(self
+            .database
+            .get_torrent_original_file_from_id(*torrent_id)
+            .await?
+            .and_then(|original| InfoHash::from_str(&original.info_hash).ok()))
+    }
+
+    /// It gets the stored original torrent file.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database or filesystem error.
+    pub async fn get(&self, torrent_id: &TorrentId) -> Result<Option<OriginalTorrentFile>, Error> {
+        let Some(original) = self.database.get_torrent_original_file_from_id(*torrent_id).await? else {
+            return Ok(None);
+        };
+
+        let info_hash = InfoHash::from_str(&original.info_hash)
+            .map_err(|_| Error::ErrorWithText(format!("Invalid original info-hash: {}", original.info_hash)))?;
+
+        let bytes = if let Some(bytes) = original.bytes {
+            bytes
+        } else {
+            let path = self.configuration.settings.read().await.torrent_storage.path.clone();
+
+            tokio::fs::read(path.join(original_file_name(&original.info_hash)))
+                .await
+                .map_err(|err| Error::ErrorWithText(err.to_string()))?
+        };
+
+        Ok(Some(OriginalTorrentFile { info_hash, bytes }))
+    }
+
+    /// It removes the stored original torrent file, if any.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database or filesystem error.
+    pub async fn delete(&self, torrent_id: &TorrentId) -> Result<(), Error> {
+        let Some(original) = self.database.get_torrent_original_file_from_id(*torrent_id).await? else {
+            return Ok(());
+        };
+
+        if original.backend == TorrentStorageBackend::Filesystem.to_string() {
+            self.remove_file(&original.info_hash).await?;
+        }
+
+        self.database.delete_torrent_original_file(*torrent_id).await
+    }
+
+    /// It removes the original torrent file from the filesystem, if any. It's
+    /// used when the database record was already removed with the torrent.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a filesystem error.
+    pub async fn delete_file(&self, info_hash: &InfoHash) -> Result<(), Error> {
+        self.remove_file(&info_hash.to_hex_string()).await
+    }
+
+    async fn remove_file(&self, info_hash: &str) -> Result<(), Error> {
+        let path = self.configuration.settings.read().await.torrent_storage.path.clone();
+
+        match tokio::fs::remove_file(path.join(original_file_name(info_hash))).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Error::ErrorWithText(err.to_string())),
+        }
+    }
+}
+
+fn original_file_name(info_hash: &str) -> String {
+    format!("{info_hash}.torrent")
+}
+
 pub struct DbTorrentTagRepository {
     database: Arc<Box<dyn Database>>,
 }
@@ -930,6 +2262,8 @@ impl DbTorrentListingGenerator {
                 &specification.search,
                 &specification.categories,
                 &specification.tags,
+                &specification.content_types,
+                &specification.extensions,
                 &specification.sort,
                 specification.offset,
                 specification.page_size,
@@ -937,3 +2271,270 @@ impl DbTorrentListingGenerator {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sqlx::{query_as, SqlitePool};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    use super::{
+        DbCanonicalInfoHashGroupRepository, DbTorrentAnnounceUrlRepository, DbTorrentFileRepository, DbTorrentInfoRepository,
+        DbTorrentListingGenerator, DbTorrentOriginalFileRepository, DbTorrentRepository, DbTorrentTagRepository,
+        DbTorrentWebSeedRepository, Index,
+    };
+    use crate::config::Configuration;
+    use crate::databases::database::{self, Database};
+    use crate::models::category::{CategoryId, TorrentFileFormat};
+    use crate::models::torrent::{Metadata, TorrentId, TorrentListing};
+    use crate::models::torrent_file::Torrent;
+    use crate::models::user::UserId;
+    use crate::services::authorization::{self, CasbinEnforcer};
+    use crate::services::category::DbCategoryRepository;
+    use crate::services::event_stream::Broadcaster;
+    use crate::services::notification::{self, DbNotificationRepository};
+    use crate::services::torrent_file::generate_random_torrent;
+    use crate::services::user::{DbUserRepository, Repository};
+    use crate::services::webhook::{self, DbWebhookRepository};
+    use crate::tracker::statistics_importer::StatisticsImporter;
+    use crate::utils::parse_torrent::{decode_torrent, encode_torrent};
+    use crate::{mailer, tracker};
+
+    struct TestIndex {
+        index: Index,
+        database_url: String,
+        database: Arc<Box<dyn Database>>,
+        torrent_repository: Arc<DbTorrentRepository>,
+        torrent_original_file_repository: Arc<DbTorrentOriginalFileRepository>,
+        _database_dir: TempDir,
+    }
+
+    /// It builds the torrent index on top of a new `SQLite` database. The
+    /// tracker is not running, so only the code paths that don't need it can
+    /// be tested.
+    async fn new_index() -> TestIndex {
+        let database_dir = tempfile::tempdir().unwrap();
+        let database_url = format!("sqlite://{}?mode=rwc", database_dir.path().join("data.db").display());
+        let database: Arc<Box<dyn Database>> = Arc::new(database::connect(&database_url).await.unwrap());
+        let configuration = Arc::new(Configuration::default());
+
+        let user_repository: Arc<Box<dyn Repository>> = Arc::new(Box::new(DbUserRepository::new(database.clone())));
+        let torrent_repository = Arc::new(DbTorrentRepository::new(database.clone()));
+        let torrent_original_file_repository =
+            Arc::new(DbTorrentOriginalFileRepository::new(configuration.clone(), database.clone()));
+        let tracker_service = Arc::new(tracker::service::Service::new(configuration.clone(), database.clone()).await);
+        let event_broadcaster = Arc::new(Broadcaster::new());
+        let tracker_statistics_importer = Arc::new(
+            StatisticsImporter::new(
+                configuration.clone(),
+                tracker_service.clone(),
+                database.clone(),
+                event_broadcaster.clone(),
+            )
+            .await,
+        );
+        let mailer_service = Arc::new(mailer::Service::new(configuration.clone(), database.clone()).await);
+        let notification_dispatcher = Arc::new(notification::Dispatcher::new(
+            configuration.clone(),
+            mailer_service,
+            Arc::new(DbNotificationRepository::new(database.clone())),
+        ));
+        let authorization_service = Arc::new(authorization::Service::new(
+            user_repository.clone(),
+            Arc::new(CasbinEnforcer::with_default_configuration().await),
+        ));
+
+        let index = Index::new(
+            configuration.clone(),
+            tracker_statistics_importer,
+            tracker_service,
+            user_repository,
+            Arc::new(DbCategoryRepository::new(database.clone())),
+            torrent_repository.clone(),
+            Arc::new(DbCanonicalInfoHashGroupRepository::new(database.clone())),
+            Arc::new(DbTorrentInfoRepository::new(database.clone())),
+            Arc::new(DbTorrentFileRepository::new(database.clone())),
+            Arc::new(DbTorrentAnnounceUrlRepository::new(database.clone())),
+            Arc::new(DbTorrentWebSeedRepository::new(database.clone())),
+            torrent_original_file_repository.clone(),
+            Arc::new(DbTorrentTagRepository::new(database.clone())),
+            Arc::new(DbTorrentListingGenerator::new(database.clone())),
+            authorization_service,
+            notification_dispatcher,
+            Arc::new(webhook::Publisher::new(Arc::new(DbWebhookRepository::new(database.clone())))),
+            event_broadcaster,
+        );
+
+        TestIndex {
+            index,
+            database_url,
+            database,
+            torrent_repository,
+            torrent_original_file_repository,
+            _database_dir: database_dir,
+        }
+    }
+
+    async fn add_user(database: &Arc<Box<dyn Database>>) -> UserId {
+        database
+            .insert_user_and_get_id("uploader", "uploader@example.com", "password")
+            .await
+            .unwrap()
+    }
+
+    async fn add_category(database: &Arc<Box<dyn Database>>, torrent_file_format: TorrentFileFormat) -> CategoryId {
+        let category_id = database.insert_category_and_get_id("test category").await.unwrap();
+        database
+            .update_category_torrent_file_format("test category", torrent_file_format)
+            .await
+            .unwrap();
+        category_id
+    }
+
+    async fn add_torrent(
+        database: &Arc<Box<dyn Database>>,
+        torrent: &Torrent,
+        user_id: UserId,
+        category_id: CategoryId,
+    ) -> TorrentId {
+        database
+            .insert_torrent_and_get_id(
+                &torrent.canonical_info_hash(),
+                torrent,
+                user_id,
+                &Metadata::new(&torrent.info.name, "", category_id, &[]).unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    /// It gets the torrent files kept in the version history of a torrent.
+    async fn torrent_files_in_version_history(database_url: &str, torrent_id: TorrentId) -> Vec<Option<Vec<u8>>> {
+        let pool = SqlitePool::connect(database_url).await.unwrap();
+
+        query_as::<_, (Option<Vec<u8>>,)>(
+            "SELECT torrent_file FROM torrust_torrent_versions WHERE torrent_id = ? ORDER BY version",
+        )
+        .bind(torrent_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(torrent_file,)| torrent_file)
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_the_previous_original_torrent_file_when_the_meta_info_can_not_be_replaced() {
+        let test_index = new_index().await;
+        let database = &test_index.database;
+
+        let user_id = add_user(database).await;
+        let category_id = add_category(database, TorrentFileFormat::Original).await;
+
+        let torrent = generate_random_torrent(Uuid::new_v4());
+        let other_torrent = generate_random_torrent(Uuid::new_v4());
+
+        let torrent_id = add_torrent(database, &torrent, user_id, category_id).await;
+        add_torrent(database, &other_torrent, user_id, category_id).await;
+        test_index
+            .torrent_original_file_repository
+            .add(
+                &torrent_id,
+                &torrent.canonical_info_hash(),
+                &encode_torrent(&torrent).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // The listing already has the new info-hash, so the tracker is not
+        // needed, but the database rejects it because another torrent uses it.
+        let torrent_listing = TorrentListing {
+            info_hash: other_torrent.canonical_info_hash_hex(),
+            ..database.get_torrent_listing_from_id(torrent_id).await.unwrap()
+        };
+
+        let result = test_index
+            .index
+            .replace_meta_info(
+                &torrent_listing,
+                &other_torrent.canonical_info_hash(),
+                &other_torrent,
+                &encode_torrent(&other_torrent).unwrap(),
+                user_id,
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            test_index
+                .torrent_original_file_repository
+                .find_info_hash(&torrent_id)
+                .await
+                .unwrap(),
+            Some(torrent.canonical_info_hash())
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_the_replaced_original_torrent_file_in_the_version_history() {
+        let test_index = new_index().await;
+        let database = &test_index.database;
+
+        let user_id = add_user(database).await;
+        let category_id = add_category(database, TorrentFileFormat::Original).await;
+
+        let torrent = generate_random_torrent(Uuid::new_v4());
+        let torrent_id = add_torrent(database, &torrent, user_id, category_id).await;
+
+        let original_torrent_file = b"the original torrent file".to_vec();
+        test_index
+            .torrent_original_file_repository
+            .add(&torrent_id, &torrent.canonical_info_hash(), &original_torrent_file)
+            .await
+            .unwrap();
+
+        let new_torrent = generate_random_torrent(Uuid::new_v4());
+        test_index
+            .torrent_repository
+            .replace(&torrent_id, &new_torrent.canonical_info_hash(), &new_torrent, None, user_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            torrent_files_in_version_history(&test_index.database_url, torrent_id).await,
+            vec![Some(original_torrent_file)]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_the_replaced_canonical_torrent_file_in_the_version_history_when_there_is_no_original_one() {
+        let test_index = new_index().await;
+        let database = &test_index.database;
+
+        let user_id = add_user(database).await;
+        let category_id = add_category(database, TorrentFileFormat::Canonical).await;
+
+        let torrent = generate_random_torrent(Uuid::new_v4());
+        let torrent_id = add_torrent(database, &torrent, user_id, category_id).await;
+
+        let new_torrent = generate_random_torrent(Uuid::new_v4());
+        test_index
+            .torrent_repository
+            .replace(&torrent_id, &new_torrent.canonical_info_hash(), &new_torrent, None, user_id)
+            .await
+            .unwrap();
+
+        let torrent_files = torrent_files_in_version_history(&test_index.database_url, torrent_id).await;
+
+        assert_eq!(torrent_files.len(), 1);
+        assert_eq!(
+            decode_torrent(torrent_files[0].as_ref().unwrap())
+                .unwrap()
+                .canonical_info_hash(),
+            torrent.canonical_info_hash()
+        );
+    }
+}
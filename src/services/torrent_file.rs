@@ -1,13 +1,23 @@
 //! This module contains the services related to torrent file management.
+use derive_more::{Display, Error};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
 use uuid::Uuid;
 
 use crate::models::torrent_file::{Torrent, TorrentFile, TorrentInfoDictionary};
 use crate::services::hasher::sha1;
 
+/// The minimum piece length for torrents created by the Index: 16 KiB.
+pub const MIN_PIECE_LENGTH: i64 = 16 * 1024;
+
+/// The length of a SHA-1 piece hash in bytes.
+const PIECE_HASH_LENGTH: usize = 20;
+
 /// It contains the information required to create a new torrent file.
 ///
 /// It's not the full in-memory representation of a torrent file. The full
 /// in-memory representation is the `Torrent` struct.
+#[derive(Debug)]
 pub struct CreateTorrentRequest {
     // The `info` dictionary fields
     pub name: String,
@@ -41,10 +51,12 @@ impl CreateTorrentRequest {
             nodes: None,
             encoding: self.encoding.clone(),
             httpseeds: None,
+            url_list: None,
             announce_list: Some(self.announce_urls.clone()),
             creation_date: self.creation_date,
             comment: self.comment.clone(),
             created_by: self.created_by.clone(),
+            piece_layers: None,
         }
     }
 
@@ -66,6 +78,185 @@ impl CreateTorrentRequest {
     }
 }
 
+/// The content of a torrent created by the Index.
+#[derive(Debug)]
+pub enum TorrentContent {
+    /// A file manifest with the precomputed v1 piece hashes.
+    Manifest(TorrentManifest),
+    /// A small file uploaded directly. The Index calculates the piece hashes.
+    File { name: String, data: Vec<u8> },
+}
+
+/// A file manifest. It mirrors the v1 `info` dictionary: single-file torrents
+/// have a `length` and multi-file torrents have a list of `files`.
+#[derive(Debug, Deserialize)]
+pub struct TorrentManifest {
+    /// The file name for single-file torrents, or the directory name for
+    /// multi-file torrents.
+    pub name: String,
+    #[serde(default)]
+    pub length: Option<i64>,
+    #[serde(default)]
+    pub files: Option<Vec<TorrentManifestFile>>,
+    /// The hex-encoded SHA-1 hashes of the pieces, in order.
+    pub pieces: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TorrentManifestFile {
+    pub path: Vec<String>,
+    pub length: i64,
+}
+
+#[derive(Debug, Display, PartialEq, Eq, Error)]
+pub enum CreateTorrentError {
+    #[display(fmt = "Piece length must be a power of two of at least 16 KiB.")]
+    InvalidPieceLength,
+    #[display(fmt = "Invalid torrent or file name.")]
+    InvalidName,
+    #[display(fmt = "The manifest must contain either a `length` or at least two `files`.")]
+    InvalidFileLayout,
+    #[display(fmt = "File lengths cannot be negative.")]
+    InvalidFileLength,
+    #[display(fmt = "The torrent content cannot be empty.")]
+    EmptyContent,
+    #[display(fmt = "Piece hashes must be hex-encoded SHA-1 hashes.")]
+    InvalidPieceHash,
+    #[display(fmt = "The number of piece hashes does not match the content length.")]
+    PiecesMismatch,
+}
+
+impl CreateTorrentRequest {
+    /// It builds a request to create a v1 torrent from its content.
+    ///
+    /// The resulting request has no announce URLs. The Index sets them when
+    /// the torrent is added.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the piece length is not a power of two of at
+    /// least [`MIN_PIECE_LENGTH`], or if the content is not valid. For
+    /// manifests, the number of piece hashes must match the total length.
+    pub fn from_content(content: &TorrentContent, piece_length: i64) -> Result<Self, CreateTorrentError> {
+        if piece_length < MIN_PIECE_LENGTH || piece_length.count_ones() != 1 {
+            return Err(CreateTorrentError::InvalidPieceLength);
+        }
+
+        let (name, files, pieces) = match content {
+            TorrentContent::Manifest(manifest) => manifest_files_and_pieces(manifest, piece_length)?,
+            TorrentContent::File { name, data } => {
+                if data.is_empty() {
+                    return Err(CreateTorrentError::EmptyContent);
+                }
+
+                let length = i64::try_from(data.len()).map_err(|_| CreateTorrentError::InvalidFileLength)?;
+                let chunk_size = usize::try_from(piece_length).map_err(|_| CreateTorrentError::InvalidPieceLength)?;
+
+                let pieces: String = data
+                    .chunks(chunk_size)
+                    .map(|piece| hex::encode(Sha1::digest(piece)))
+                    .collect();
+
+                (name.clone(), vec![single_file(length)], pieces)
+            }
+        };
+
+        if !is_valid_path_segment(&name) {
+            return Err(CreateTorrentError::InvalidName);
+        }
+
+        Ok(Self {
+            name,
+            pieces_or_root_hash: pieces,
+            piece_length,
+            private: None,
+            is_bep_30: 0,
+            files,
+            announce_urls: vec![],
+            comment: None,
+            creation_date: None,
+            created_by: None,
+            encoding: None,
+        })
+    }
+}
+
+/// It validates the manifest and returns the torrent name, files and the
+/// concatenated piece hashes.
+fn manifest_files_and_pieces(
+    manifest: &TorrentManifest,
+    piece_length: i64,
+) -> Result<(String, Vec<TorrentFile>, String), CreateTorrentError> {
+    let files = match (manifest.length, &manifest.files) {
+        (Some(length), None) => {
+            if length < 0 {
+                return Err(CreateTorrentError::InvalidFileLength);
+            }
+            vec![single_file(length)]
+        }
+        // A multi-file torrent with only one file would be stored as a
+        // single-file torrent, changing its info-hash.
+        (None, Some(files)) if files.len() > 1 => files
+            .iter()
+            .map(|file| {
+                if file.length < 0 {
+                    return Err(CreateTorrentError::InvalidFileLength);
+                }
+                if file.path.is_empty() || !file.path.iter().all(|segment| is_valid_path_segment(segment)) {
+                    return Err(CreateTorrentError::InvalidName);
+                }
+                Ok(TorrentFile {
+                    path: file.path.clone(),
+                    length: file.length,
+                    md5sum: None,
+                    attr: None,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(CreateTorrentError::InvalidFileLayout),
+    };
+
+    let total_length = files
+        .iter()
+        .try_fold(0i64, |total, file| total.checked_add(file.length))
+        .ok_or(CreateTorrentError::InvalidFileLength)?;
+
+    if total_length == 0 {
+        return Err(CreateTorrentError::EmptyContent);
+    }
+
+    let expected_pieces = (total_length + piece_length - 1) / piece_length;
+
+    if i64::try_from(manifest.pieces.len()).ok() != Some(expected_pieces) {
+        return Err(CreateTorrentError::PiecesMismatch);
+    }
+
+    let mut pieces = String::with_capacity(manifest.pieces.len() * PIECE_HASH_LENGTH * 2);
+
+    for piece in &manifest.pieces {
+        match hex::decode(piece) {
+            Ok(hash) if hash.len() == PIECE_HASH_LENGTH => pieces.push_str(&piece.to_lowercase()),
+            _ => return Err(CreateTorrentError::InvalidPieceHash),
+        }
+    }
+
+    Ok((manifest.name.clone(), files, pieces))
+}
+
+/// A single-file torrent file. The file name is the torrent name.
+fn single_file(length: i64) -> TorrentFile {
+    TorrentFile {
+        path: vec![String::new()],
+        length,
+        md5sum: None,
+        attr: None,
+    }
+}
+
+fn is_valid_path_segment(segment: &str) -> bool {
+    !segment.is_empty() && segment != "." && segment != ".." && !segment.contains(['/', '\\', '\0'])
+}
+
 /// It generates a random single-file torrent for testing purposes.
 ///
 /// The torrent will contain a single text file with the UUID as its content.
@@ -84,6 +275,7 @@ pub fn generate_random_torrent(id: Uuid) -> Torrent {
         path: vec![String::new()],
         length: i64::try_from(file_contents.len()).expect("file contents size in bytes cannot exceed i64::MAX"),
         md5sum: None,
+        attr: None,
     }];
 
     let torrent_announce_urls: Vec<Vec<String>> = vec![];
@@ -133,6 +325,8 @@ mod tests {
                 path: None,
                 root_hash: None,
                 source: None,
+                meta_version: None,
+                file_tree: None,
             },
             announce: None,
             announce_list: Some(vec![]),
@@ -142,8 +336,197 @@ mod tests {
             nodes: None,
             encoding: None,
             httpseeds: None,
+            url_list: None,
+            piece_layers: None,
         };
 
         assert_eq!(torrent, expected_torrent);
     }
+
+    mod creating_a_torrent_from_its_content {
+        use sha1::{Digest, Sha1};
+
+        use crate::services::torrent_file::{
+            CreateTorrentError, CreateTorrentRequest, TorrentContent, TorrentManifest, TorrentManifestFile, MIN_PIECE_LENGTH,
+        };
+
+        fn sample_data() -> Vec<u8> {
+            (0..=MIN_PIECE_LENGTH).map(|i| u8::try_from(i % 256).unwrap()).collect()
+        }
+
+        fn piece_hashes(data: &[u8]) -> Vec<String> {
+            data.chunks(usize::try_from(MIN_PIECE_LENGTH).unwrap())
+                .map(|piece| hex::encode(Sha1::digest(piece)))
+                .collect()
+        }
+
+        fn manifest_file(path: &[&str], length: i64) -> TorrentManifestFile {
+            TorrentManifestFile {
+                path: path.iter().map(ToString::to_string).collect(),
+                length,
+            }
+        }
+
+        #[test]
+        fn it_should_hash_the_pieces_of_an_uploaded_file() {
+            let data = sample_data();
+
+            let torrent = CreateTorrentRequest::from_content(
+                &TorrentContent::File {
+                    name: "sample.bin".to_string(),
+                    data: data.clone(),
+                },
+                MIN_PIECE_LENGTH,
+            )
+            .unwrap()
+            .build_torrent();
+
+            assert_eq!(torrent.info.name, "sample.bin");
+            assert_eq!(torrent.info.length, Some(MIN_PIECE_LENGTH + 1));
+            assert_eq!(torrent.info.files, None);
+            assert_eq!(hex::encode(torrent.info.pieces.unwrap()), piece_hashes(&data).concat());
+        }
+
+        #[test]
+        fn it_should_build_the_same_torrent_from_a_manifest_with_the_precomputed_piece_hashes() {
+            let data = sample_data();
+
+            let from_file = CreateTorrentRequest::from_content(
+                &TorrentContent::File {
+                    name: "sample.bin".to_string(),
+                    data: data.clone(),
+                },
+                MIN_PIECE_LENGTH,
+            )
+            .unwrap()
+            .build_torrent();
+
+            let from_manifest = CreateTorrentRequest::from_content(
+                &TorrentContent::Manifest(TorrentManifest {
+                    name: "sample.bin".to_string(),
+                    length: Some(MIN_PIECE_LENGTH + 1),
+                    files: None,
+                    pieces: piece_hashes(&data),
+                }),
+                MIN_PIECE_LENGTH,
+            )
+            .unwrap()
+            .build_torrent();
+
+            assert_eq!(from_manifest.canonical_info_hash(), from_file.canonical_info_hash());
+        }
+
+        #[test]
+        fn it_should_build_a_multi_file_torrent_from_a_manifest() {
+            let data = sample_data();
+
+            let torrent = CreateTorrentRequest::from_content(
+                &TorrentContent::Manifest(TorrentManifest {
+                    name: "sample".to_string(),
+                    length: None,
+                    files: Some(vec![
+                        manifest_file(&["data", "a.bin"], MIN_PIECE_LENGTH),
+                        manifest_file(&["b.bin"], 1),
+                    ]),
+                    pieces: piece_hashes(&data),
+                }),
+                MIN_PIECE_LENGTH,
+            )
+            .unwrap()
+            .build_torrent();
+
+            let files = torrent.info.files.unwrap();
+
+            assert_eq!(torrent.info.length, None);
+            assert_eq!(files.len(), 2);
+            assert_eq!(files[0].path, vec!["data".to_string(), "a.bin".to_string()]);
+        }
+
+        #[test]
+        fn it_should_fail_when_the_piece_length_is_not_a_power_of_two_of_at_least_16_kib() {
+            let content = TorrentContent::File {
+                name: "sample.bin".to_string(),
+                data: sample_data(),
+            };
+
+            for piece_length in [0, MIN_PIECE_LENGTH / 2, MIN_PIECE_LENGTH + 1] {
+                assert_eq!(
+                    CreateTorrentRequest::from_content(&content, piece_length).unwrap_err(),
+                    CreateTorrentError::InvalidPieceLength
+                );
+            }
+        }
+
+        #[test]
+        fn it_should_fail_when_the_number_of_piece_hashes_does_not_match_the_content_length() {
+            let mut pieces = piece_hashes(&sample_data());
+            pieces.pop();
+
+            let result = CreateTorrentRequest::from_content(
+                &TorrentContent::Manifest(TorrentManifest {
+                    name: "sample.bin".to_string(),
+                    length: Some(MIN_PIECE_LENGTH + 1),
+                    files: None,
+                    pieces,
+                }),
+                MIN_PIECE_LENGTH,
+            );
+
+            assert_eq!(result.unwrap_err(), CreateTorrentError::PiecesMismatch);
+        }
+
+        #[test]
+        fn it_should_fail_when_a_piece_hash_is_not_a_hex_encoded_sha1_hash() {
+            let result = CreateTorrentRequest::from_content(
+                &TorrentContent::Manifest(TorrentManifest {
+                    name: "sample.bin".to_string(),
+                    length: Some(1),
+                    files: None,
+                    pieces: vec!["not-a-hash".to_string()],
+                }),
+                MIN_PIECE_LENGTH,
+            );
+
+            assert_eq!(result.unwrap_err(), CreateTorrentError::InvalidPieceHash);
+        }
+
+        #[test]
+        fn it_should_fail_when_a_file_path_is_not_valid() {
+            let result = CreateTorrentRequest::from_content(
+                &TorrentContent::Manifest(TorrentManifest {
+                    name: "sample".to_string(),
+                    length: None,
+                    files: Some(vec![manifest_file(&["..", "a.bin"], 1), manifest_file(&["b.bin"], 1)]),
+                    pieces: piece_hashes(&[0, 0]),
+                }),
+                MIN_PIECE_LENGTH,
+            );
+
+            assert_eq!(result.unwrap_err(), CreateTorrentError::InvalidName);
+        }
+
+        #[test]
+        fn it_should_fail_when_the_manifest_does_not_have_either_a_length_or_several_files() {
+            for (length, files) in [
+                (None, None),
+                (
+                    Some(1),
+                    Some(vec![manifest_file(&["a.bin"], 1), manifest_file(&["b.bin"], 1)]),
+                ),
+                (None, Some(vec![manifest_file(&["a.bin"], 1)])),
+            ] {
+                let result = CreateTorrentRequest::from_content(
+                    &TorrentContent::Manifest(TorrentManifest {
+                        name: "sample".to_string(),
+                        length,
+                        files,
+                        pieces: piece_hashes(&[0]),
+                    }),
+                    MIN_PIECE_LENGTH,
+                );
+
+                assert_eq!(result.unwrap_err(), CreateTorrentError::InvalidFileLayout);
+            }
+        }
+    }
 }
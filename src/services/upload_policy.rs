@@ -0,0 +1,241 @@
+//! Upload policy enforcement.
+//!
+//! It checks uploaded torrents against the [`UploadPolicy`] configuration:
+//! the torrent file size, the content size and number of files, the `private`
+//! flag, the `source` key, the file extensions and the categories each user
+//! role can upload torrents to.
+use crate::config::{PrivateFlag, UploadPolicy};
+use crate::errors::ServiceError;
+use crate::models::torrent_file::Torrent;
+use crate::services::content_type::classify_torrent;
+
+/// It checks the size of the uploaded torrent file, before decoding it.
+///
+/// # Errors
+///
+/// It returns an error if the torrent file is larger than the limit.
+pub fn check_torrent_file_size(policy: &UploadPolicy, size: usize) -> Result<(), ServiceError> {
+    if u64::try_from(size).map_or(true, |size| size > policy.max_torrent_file_size) {
+        return Err(ServiceError::TorrentFileTooLarge);
+    }
+
+    Ok(())
+}
+
+/// It checks the user can upload torrents to the category.
+///
+/// # Errors
+///
+/// It returns an error if the category is not in the list of allowed
+/// categories for the user role.
+pub fn check_category(policy: &UploadPolicy, category_name: &str, is_admin: bool) -> Result<(), ServiceError> {
+    let allowed_categories = if is_admin {
+        &policy.allowed_categories.admin
+    } else {
+        &policy.allowed_categories.registered
+    };
+
+    match allowed_categories {
+        Some(allowed_categories) if !allowed_categories.iter().any(|category| category == category_name) => {
+            Err(ServiceError::CategoryNotAllowedForUpload)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// It checks the decoded torrent against the policy.
+///
+/// # Errors
+///
+/// It returns an error for the first rule the torrent breaks.
+pub fn check_torrent(policy: &UploadPolicy, torrent: &Torrent) -> Result<(), ServiceError> {
+    if let Some(max_content_size) = policy.max_content_size {
+        if u64::try_from(torrent.file_size()).map_or(true, |size| size > max_content_size) {
+            return Err(ServiceError::TorrentContentTooLarge);
+        }
+    }
+
+    let file_types = classify_torrent(torrent);
+
+    if let Some(max_files) = policy.max_files {
+        let file_count: i64 = file_types.iter().map(|file_type| file_type.file_count).sum();
+
+        if u64::try_from(file_count).map_or(true, |file_count| file_count > max_files) {
+            return Err(ServiceError::TooManyFilesInTorrent);
+        }
+    }
+
+    let is_private = torrent.info.private == Some(1);
+
+    match policy.private_flag {
+        PrivateFlag::Required if !is_private => return Err(ServiceError::PrivateTorrentRequired),
+        PrivateFlag::Forbidden if is_private => return Err(ServiceError::PrivateTorrentForbidden),
+        _ => {}
+    }
+
+    if let Some(required_source) = &policy.required_source {
+        if torrent.info.source.as_ref() != Some(required_source) {
+            return Err(ServiceError::MissingRequiredTorrentSource);
+        }
+    }
+
+    for file_type in &file_types {
+        let is_forbidden = contains_extension(&policy.forbidden_extensions, &file_type.extension);
+        let is_allowed =
+            policy.allowed_extensions.is_empty() || contains_extension(&policy.allowed_extensions, &file_type.extension);
+
+        if is_forbidden || !is_allowed {
+            return Err(ServiceError::FileExtensionNotAllowed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Configured extensions are compared case-insensitively and can have a
+/// leading dot.
+fn contains_extension(extensions: &[String], extension: &str) -> bool {
+    !extension.is_empty()
+        && extensions
+            .iter()
+            .any(|configured| configured.trim_start_matches('.').eq_ignore_ascii_case(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{AllowedCategories, PrivateFlag, UploadPolicy};
+    use crate::errors::ServiceError;
+    use crate::models::torrent_file::Torrent;
+    use crate::services::upload_policy::{check_category, check_torrent, check_torrent_file_size};
+    use crate::utils::parse_torrent::decode_torrent;
+
+    /// A multi-file torrent with a `.mkv` and a `.srt` file.
+    fn sample_torrent() -> Torrent {
+        // cspell:disable-next-line
+        decode_torrent(b"d4:infod5:filesld6:lengthi10000e4:pathl9:movie.mkveed6:lengthi100e4:pathl8:subs.srteee4:name5:movie12:piece lengthi16384e6:pieces20:AAAAAAAAAAAAAAAAAAAAee")
+            .unwrap()
+    }
+
+    #[test]
+    fn the_default_policy_should_accept_any_valid_torrent() {
+        let policy = UploadPolicy::default();
+
+        assert_eq!(check_torrent_file_size(&policy, 1024), Ok(()));
+        assert_eq!(check_category(&policy, "movies", false), Ok(()));
+        assert_eq!(check_torrent(&policy, &sample_torrent()), Ok(()));
+    }
+
+    #[test]
+    fn it_should_reject_torrent_files_larger_than_the_limit() {
+        let policy = UploadPolicy {
+            max_torrent_file_size: 1024,
+            ..Default::default()
+        };
+
+        assert_eq!(check_torrent_file_size(&policy, 1025), Err(ServiceError::TorrentFileTooLarge));
+    }
+
+    #[test]
+    fn it_should_reject_torrents_with_too_much_content_or_too_many_files() {
+        let policy = UploadPolicy {
+            max_content_size: Some(10_099),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_torrent(&policy, &sample_torrent()),
+            Err(ServiceError::TorrentContentTooLarge)
+        );
+
+        let policy = UploadPolicy {
+            max_files: Some(1),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_torrent(&policy, &sample_torrent()),
+            Err(ServiceError::TooManyFilesInTorrent)
+        );
+    }
+
+    #[test]
+    fn it_should_enforce_the_private_flag() {
+        let mut torrent = sample_torrent();
+
+        let policy = UploadPolicy {
+            private_flag: PrivateFlag::Required,
+            ..Default::default()
+        };
+
+        assert_eq!(check_torrent(&policy, &torrent), Err(ServiceError::PrivateTorrentRequired));
+
+        torrent.info.private = Some(1);
+
+        let policy = UploadPolicy {
+            private_flag: PrivateFlag::Forbidden,
+            ..Default::default()
+        };
+
+        assert_eq!(check_torrent(&policy, &torrent), Err(ServiceError::PrivateTorrentForbidden));
+    }
+
+    #[test]
+    fn it_should_require_the_configured_source() {
+        let mut torrent = sample_torrent();
+
+        let policy = UploadPolicy {
+            required_source: Some("TORRUST".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_torrent(&policy, &torrent),
+            Err(ServiceError::MissingRequiredTorrentSource)
+        );
+
+        torrent.info.source = Some("TORRUST".to_string());
+
+        assert_eq!(check_torrent(&policy, &torrent), Ok(()));
+    }
+
+    #[test]
+    fn it_should_enforce_the_allowed_and_forbidden_extensions() {
+        let policy = UploadPolicy {
+            allowed_extensions: vec!["mkv".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_torrent(&policy, &sample_torrent()),
+            Err(ServiceError::FileExtensionNotAllowed)
+        );
+
+        let policy = UploadPolicy {
+            forbidden_extensions: vec![".SRT".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_torrent(&policy, &sample_torrent()),
+            Err(ServiceError::FileExtensionNotAllowed)
+        );
+    }
+
+    #[test]
+    fn it_should_only_allow_the_configured_categories_for_each_role() {
+        let policy = UploadPolicy {
+            allowed_categories: AllowedCategories {
+                admin: None,
+                registered: Some(vec!["movies".to_string()]),
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(check_category(&policy, "movies", false), Ok(()));
+        assert_eq!(
+            check_category(&policy, "software", false),
+            Err(ServiceError::CategoryNotAllowedForUpload)
+        );
+        assert_eq!(check_category(&policy, "software", true), Ok(()));
+    }
+}
@@ -1,26 +1,29 @@
 //! User services.
 use std::sync::Arc;
 
-use argon2::password_hash::SaltString;
-use argon2::{Argon2, PasswordHasher};
 use async_trait::async_trait;
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 #[cfg(test)]
 use mockall::automock;
-use pbkdf2::password_hash::rand_core::OsRng;
 use tracing::{debug, info};
 
 use super::authentication::DbUserAuthenticationRepository;
 use super::authorization::{self, ACTION};
-use crate::config::{Configuration, PasswordConstraints};
+use super::password_policy::validate_password_constraints;
+use super::username_policy::UsernamePolicy;
+use super::webhook;
+use crate::config::Configuration;
 use crate::databases::database::{Database, Error};
 use crate::errors::ServiceError;
-use crate::mailer;
 use crate::mailer::VerifyClaims;
+use crate::models::tracker_key::TrackerKey;
 use crate::models::user::{UserCompact, UserId, UserProfile, Username};
-use crate::services::authentication::verify_password;
+use crate::services::authentication::{hash_password, verify_password};
+use crate::utils::clock;
 use crate::utils::validation::validate_email_address;
-use crate::web::api::server::v1::contexts::user::forms::{ChangePasswordForm, RegistrationForm};
+use crate::web::api::server::v1::contexts::user::forms::{ChangeEmailForm, ChangePasswordForm, RegistrationForm};
+use crate::{mailer, tracker};
 
 /// Since user email could be optional, we need a way to represent "no email"
 /// in the database. This function returns the string that should be used for
@@ -34,6 +37,8 @@ pub struct RegistrationService {
     mailer: Arc<mailer::Service>,
     user_repository: Arc<Box<dyn Repository>>,
     user_profile_repository: Arc<DbUserProfileRepository>,
+    webhook_publisher: Arc<webhook::Publisher>,
+    username_policy: Arc<UsernamePolicy>,
 }
 
 impl RegistrationService {
@@ -43,12 +48,16 @@ impl RegistrationService {
         mailer: Arc<mailer::Service>,
         user_repository: Arc<Box<dyn Repository>>,
         user_profile_repository: Arc<DbUserProfileRepository>,
+        webhook_publisher: Arc<webhook::Publisher>,
+        username_policy: Arc<UsernamePolicy>,
     ) -> Self {
         Self {
             configuration,
             mailer,
             user_repository,
             user_profile_repository,
+            webhook_publisher,
+            username_policy,
         }
     }
 
@@ -63,7 +72,10 @@ impl RegistrationService {
     /// * `ServiceError::PasswordsDontMatch` if the supplied passwords do not match.
     /// * `ServiceError::PasswordTooShort` if the supplied password is too short.
     /// * `ServiceError::PasswordTooLong` if the supplied password is too long.
+    /// * Another `ServiceError::Password*` error if the supplied password breaks the password policy.
     /// * `ServiceError::UsernameInvalid` if the supplied username is badly formatted.
+    /// * Another `ServiceError::Username*`, `ServiceError::BlacklistError` or `ServiceError::ProfanityError` error if the supplied username breaks the username policy.
+    /// * `ServiceError::UsernameTaken` if another user has the same username, whatever the case.
     /// * `ServiceError::FailedToSendVerificationEmail` if unable to send the required verification email.
     /// * An error if unable to successfully hash the password.
     /// * An error if unable to insert user into the database.
@@ -78,9 +90,17 @@ impl RegistrationService {
 
         match &settings.registration {
             Some(registration) => {
-                let Ok(username) = registration_form.username.parse::<Username>() else {
-                    return Err(ServiceError::UsernameInvalid);
-                };
+                let username = self.username_policy.validate(&registration_form.username)?;
+
+                // Usernames differing only in case are the same username.
+                if self
+                    .user_profile_repository
+                    .get_user_profile_from_case_mapped_username(&username)
+                    .await
+                    .is_ok()
+                {
+                    return Err(ServiceError::UsernameTaken);
+                }
 
                 let opt_email = match &registration.email {
                     Some(email) => {
@@ -107,18 +127,15 @@ impl RegistrationService {
                     }
                 }
 
-                let password_constraints = PasswordConstraints {
-                    min_password_length: settings.auth.password_constraints.min_password_length,
-                    max_password_length: settings.auth.password_constraints.max_password_length,
-                };
-
                 validate_password_constraints(
                     &registration_form.password,
                     &registration_form.confirm_password,
-                    &password_constraints,
+                    &username.to_string(),
+                    opt_email.as_deref(),
+                    &settings.auth.password_constraints,
                 )?;
 
-                let password_hash = hash_password(&registration_form.password)?;
+                let password_hash = hash_password(&registration_form.password, &settings.auth.argon2)?;
 
                 let user_id = self
                     .user_repository
@@ -140,7 +157,7 @@ impl RegistrationService {
                         if let Some(email) = opt_email {
                             let mail_res = self
                                 .mailer
-                                .send_verification_mail(&email, &registration_form.username, user_id, api_base_url)
+                                .send_verification_mail(&email, &username.to_string(), user_id, api_base_url)
                                 .await;
 
                             if mail_res.is_err() {
@@ -151,6 +168,13 @@ impl RegistrationService {
                     }
                 }
 
+                self.webhook_publisher
+                    .publish(webhook::Event::UserRegistered {
+                        user_id,
+                        username: username.to_string(),
+                    })
+                    .await;
+
                 Ok(user_id)
             }
             None => Err(ServiceError::ClosedForRegistration),
@@ -196,21 +220,33 @@ impl RegistrationService {
 
 pub struct ProfileService {
     configuration: Arc<Configuration>,
+    mailer: Arc<mailer::Service>,
     user_authentication_repository: Arc<DbUserAuthenticationRepository>,
+    user_repository: Arc<Box<dyn Repository>>,
+    user_profile_repository: Arc<DbUserProfileRepository>,
     authorization_service: Arc<authorization::Service>,
+    username_policy: Arc<UsernamePolicy>,
 }
 
 impl ProfileService {
     #[must_use]
     pub fn new(
         configuration: Arc<Configuration>,
-        user_repository: Arc<DbUserAuthenticationRepository>,
+        mailer: Arc<mailer::Service>,
+        user_authentication_repository: Arc<DbUserAuthenticationRepository>,
+        user_repository: Arc<Box<dyn Repository>>,
+        user_profile_repository: Arc<DbUserProfileRepository>,
         authorization_service: Arc<authorization::Service>,
+        username_policy: Arc<UsernamePolicy>,
     ) -> Self {
         Self {
             configuration,
-            user_authentication_repository: user_repository,
+            mailer,
+            user_authentication_repository,
+            user_repository,
+            user_profile_repository,
             authorization_service,
+            username_policy,
         }
     }
 
@@ -224,6 +260,7 @@ impl ProfileService {
     /// * `ServiceError::PasswordsDontMatch` if the supplied passwords do not match.
     /// * `ServiceError::PasswordTooShort` if the supplied password is too short.
     /// * `ServiceError::PasswordTooLong` if the supplied password is too long.
+    /// * Another `ServiceError::Password*` error if the supplied password breaks the password policy.
     /// * An error if unable to successfully hash the password.
     /// * An error if unable to change the password in the database.
     /// * An error if it is not possible to authorize the action
@@ -251,18 +288,21 @@ impl ProfileService {
 
         verify_password(change_password_form.current_password.as_bytes(), &user_authentication)?;
 
-        let password_constraints = PasswordConstraints {
-            min_password_length: settings.auth.password_constraints.min_password_length,
-            max_password_length: settings.auth.password_constraints.max_password_length,
-        };
+        let username = self.user_repository.get_compact(&user_id).await?.username;
+
+        let user_profile = self.user_profile_repository.get_user_profile_from_username(&username).await?;
+
+        let email = Some(user_profile.email.as_str()).filter(|email| !email.is_empty());
 
         validate_password_constraints(
             &change_password_form.password,
             &change_password_form.confirm_password,
-            &password_constraints,
+            &username,
+            email,
+            &settings.auth.password_constraints,
         )?;
 
-        let password_hash = hash_password(&change_password_form.password)?;
+        let password_hash = hash_password(&change_password_form.password, &settings.auth.argon2)?;
 
         self.user_authentication_repository
             .change_password(user_id, &password_hash)
@@ -270,25 +310,217 @@ impl ProfileService {
 
         Ok(())
     }
+
+    /// It starts changing the user's email. The new address is kept as
+    /// pending and a link to confirm it is sent to it. The current address,
+    /// if any, gets a security notice.
+    ///
+    /// The email of the profile does not change until the new address is
+    /// [confirmed](Self::confirm_email_change).
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::UnauthorizedActionForGuests` if the user is not logged in.
+    /// * An error if it is not possible to authorize the action.
+    /// * `ServiceError::InvalidPassword` if the current password supplied is invalid.
+    /// * `ServiceError::EmailInvalid` if the new email is badly formatted.
+    /// * `ServiceError::EmailTaken` if the new email is already used by an account.
+    /// * An error if unable to save the pending email or to send the emails.
+    pub async fn request_email_change(
+        &self,
+        maybe_user_id: Option<UserId>,
+        change_email_form: &ChangeEmailForm,
+        api_base_url: &str,
+    ) -> Result<(), ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::ChangeEmail, maybe_user_id)
+            .await?;
+
+        info!("changing user email for user ID: {}", user_id);
+
+        let user_authentication = self
+            .user_authentication_repository
+            .get_user_authentication_from_id(&user_id)
+            .await?;
+
+        verify_password(change_email_form.current_password.as_bytes(), &user_authentication)?;
+
+        let new_email = change_email_form.email.trim();
+
+        if !validate_email_address(new_email) {
+            return Err(ServiceError::EmailInvalid);
+        }
+
+        if self
+            .user_profile_repository
+            .get_user_profile_from_email(new_email)
+            .await
+            .is_ok()
+        {
+            return Err(ServiceError::EmailTaken);
+        }
+
+        let username = self.user_repository.get_compact(&user_id).await?.username;
+
+        let user_profile = self.user_profile_repository.get_user_profile_from_username(&username).await?;
+
+        self.user_profile_repository.set_pending_email(&user_id, new_email).await?;
+
+        self.mailer
+            .send_email_change_mail(new_email, &username, user_id, api_base_url)
+            .await?;
+
+        if !user_profile.email.is_empty() {
+            self.mailer
+                .send_email_change_notice(&user_profile.email, &username, new_email)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// It confirms the new email of a user with the token sent to that
+    /// address. The new email replaces the current one and it's marked as
+    /// verified.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::TokenInvalid` if the token is not an email change token.
+    /// * `ServiceError::TokenExpired` if the token has expired.
+    /// * `ServiceError::EmailChangeNotPending` if the user asked to change to
+    ///   another email after this token was sent, or the change was already
+    ///   confirmed.
+    /// * `ServiceError::EmailTaken` if another account started using the new
+    ///   email meanwhile.
+    /// * An error if unable to change the email in the database.
+    pub async fn confirm_email_change(&self, token: &str) -> Result<(), ServiceError> {
+        let settings = self.configuration.settings.read().await;
+
+        let claims = match decode::<VerifyClaims>(
+            token,
+            &DecodingKey::from_secret(settings.auth.user_claim_token_pepper.as_bytes()),
+            &Validation::new(Algorithm::HS256),
+        ) {
+            Ok(token_data) => token_data.claims,
+            Err(err) if matches!(err.kind(), ErrorKind::ExpiredSignature) => return Err(ServiceError::TokenExpired),
+            Err(_) => return Err(ServiceError::TokenInvalid),
+        };
+
+        drop(settings);
+
+        if !claims.iss.eq("email-change") {
+            return Err(ServiceError::TokenInvalid);
+        }
+
+        let Some(new_email) = claims.email else {
+            return Err(ServiceError::TokenInvalid);
+        };
+
+        let user_id = claims.sub;
+
+        // Only the link for the last requested change is valid.
+        if self.user_profile_repository.get_pending_email(&user_id).await?.as_deref() != Some(new_email.as_str()) {
+            return Err(ServiceError::EmailChangeNotPending);
+        }
+
+        info!("confirming the new email for user ID: {}", user_id);
+
+        self.user_profile_repository.change_email(&user_id, &new_email).await?;
+
+        Ok(())
+    }
+
+    /// It changes the username of another user. Only administrators can
+    /// rename users, and the new username must follow the username policy.
+    /// It returns the new username.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::UnauthorizedActionForGuests` if the user is not logged in.
+    /// * An error if it is not possible to authorize the action.
+    /// * `ServiceError::UsernameInvalid` or another username policy error if the new username breaks the username policy.
+    /// * `ServiceError::UsernameTaken` if another user has the new username, whatever the case.
+    /// * An error if unable to get user profile from supplied username.
+    /// * An error if unable to change the username in the database.
+    pub async fn rename_user(
+        &self,
+        username_to_be_renamed: &str,
+        new_username: &str,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<String, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::RenameUser, maybe_user_id)
+            .await?;
+
+        info!(
+            "user with ID {} renaming username: {username_to_be_renamed} to {new_username}",
+            user_id
+        );
+
+        let new_username = self.username_policy.validate(new_username)?;
+
+        let user_profile = self
+            .user_profile_repository
+            .get_user_profile_from_username(username_to_be_renamed)
+            .await?;
+
+        // Users can be renamed to a username differing only in case from
+        // their own, but not from anybody else's.
+        if let Ok(existing_user_profile) = self
+            .user_profile_repository
+            .get_user_profile_from_case_mapped_username(&new_username)
+            .await
+        {
+            if existing_user_profile.user_id != user_profile.user_id {
+                return Err(ServiceError::UsernameTaken);
+            }
+        }
+
+        self.user_profile_repository
+            .change_username(&user_profile.user_id, &new_username)
+            .await?;
+
+        Ok(new_username.to_string())
+    }
 }
 
 pub struct BanService {
+    user_repository: Arc<Box<dyn Repository>>,
     user_profile_repository: Arc<DbUserProfileRepository>,
     banned_user_list: Arc<DbBannedUserList>,
     authorization_service: Arc<authorization::Service>,
+    webhook_publisher: Arc<webhook::Publisher>,
 }
 
 impl BanService {
     #[must_use]
     pub fn new(
+        user_repository: Arc<Box<dyn Repository>>,
         user_profile_repository: Arc<DbUserProfileRepository>,
         banned_user_list: Arc<DbBannedUserList>,
         authorization_service: Arc<authorization::Service>,
+        webhook_publisher: Arc<webhook::Publisher>,
     ) -> Self {
         Self {
+            user_repository,
             user_profile_repository,
             banned_user_list,
             authorization_service,
+            webhook_publisher,
         }
     }
 
@@ -317,10 +549,134 @@ impl BanService {
 
         self.banned_user_list.add(&user_profile.user_id).await?;
 
+        let banned_by = self.user_repository.get_compact(&user_id).await?;
+
+        self.webhook_publisher
+            .publish(webhook::Event::UserBanned {
+                user_id: user_profile.user_id,
+                username: user_profile.username,
+                banned_by: banned_by.username,
+            })
+            .await;
+
         Ok(())
     }
 }
 
+pub struct TrackerKeyService {
+    user_profile_repository: Arc<DbUserProfileRepository>,
+    tracker_service: Arc<tracker::service::Service>,
+    authorization_service: Arc<authorization::Service>,
+}
+
+impl TrackerKeyService {
+    #[must_use]
+    pub fn new(
+        user_profile_repository: Arc<DbUserProfileRepository>,
+        tracker_service: Arc<tracker::service::Service>,
+        authorization_service: Arc<authorization::Service>,
+    ) -> Self {
+        Self {
+            user_profile_repository,
+            tracker_service,
+            authorization_service,
+        }
+    }
+
+    /// It returns the tracker key embedded in the user's `.torrent`
+    /// downloads. A new one is issued if the user does not have one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::UnauthorizedActionForGuests` if the user is not logged in.
+    /// * An error if it is not possible to authorize the action.
+    /// * An error if the tracker could not issue a new key.
+    pub async fn get_tracker_key(&self, maybe_user_id: Option<UserId>) -> Result<TrackerKey, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::GetTrackerKey, maybe_user_id)
+            .await?;
+
+        Ok(self.tracker_service.get_user_tracker_key(user_id).await?)
+    }
+
+    /// It returns all the user's tracker keys that have not expired yet.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::UnauthorizedActionForGuests` if the user is not logged in.
+    /// * An error if it is not possible to authorize the action.
+    /// * An error if the keys could not be loaded from the database.
+    pub async fn get_tracker_keys(&self, maybe_user_id: Option<UserId>) -> Result<Vec<TrackerKey>, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::GetTrackerKey, maybe_user_id)
+            .await?;
+
+        Ok(self.tracker_service.get_user_tracker_keys(user_id).await?)
+    }
+
+    /// It revokes all the user's tracker keys and issues a new one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::UnauthorizedActionForGuests` if the user is not logged in.
+    /// * An error if it is not possible to authorize the action.
+    /// * An error if the old keys could not be revoked or the new one issued.
+    pub async fn rotate_tracker_key(&self, maybe_user_id: Option<UserId>) -> Result<TrackerKey, ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::RotateTrackerKey, maybe_user_id)
+            .await?;
+
+        info!("user with ID {} rotating their tracker key", user_id);
+
+        Ok(self.tracker_service.rotate_user_tracker_key(user_id).await?)
+    }
+
+    /// It revokes all the tracker keys of another user. The user will get a
+    /// new key the next time they download a torrent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a:
+    ///
+    /// * `ServiceError::UnauthorizedActionForGuests` if the user is not logged in.
+    /// * An error if it is not possible to authorize the action.
+    /// * An error if unable to get user profile from supplied username.
+    /// * An error if the keys could not be revoked.
+    pub async fn revoke_user_tracker_keys(&self, username: &str, maybe_user_id: Option<UserId>) -> Result<(), ServiceError> {
+        let Some(user_id) = maybe_user_id else {
+            return Err(ServiceError::UnauthorizedActionForGuests);
+        };
+
+        self.authorization_service
+            .authorize(ACTION::RevokeUserTrackerKeys, maybe_user_id)
+            .await?;
+
+        info!("user with ID {} revoking the tracker keys of username: {username}", user_id);
+
+        let user_profile = self.user_profile_repository.get_user_profile_from_username(username).await?;
+
+        Ok(self.tracker_service.revoke_user_tracker_keys(user_profile.user_id).await?)
+    }
+}
+
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait Repository: Sync + Send {
@@ -412,6 +768,66 @@ impl DbUserProfileRepository {
     pub async fn get_user_profile_from_username(&self, username: &str) -> Result<UserProfile, Error> {
         self.database.get_user_profile_from_username(username).await
     }
+
+    /// It gets the user profile from the case mapped username, so it finds
+    /// the user whatever the case of the stored username.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_user_profile_from_case_mapped_username(&self, username: &Username) -> Result<UserProfile, Error> {
+        self.database
+            .get_user_profile_from_case_mapped_username(&username.case_mapped())
+            .await
+    }
+
+    /// It gets the user profile from the email.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_user_profile_from_email(&self, email: &str) -> Result<UserProfile, Error> {
+        self.database.get_user_profile_from_email(email).await
+    }
+
+    /// It saves the email the user asked to change to.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn set_pending_email(&self, user_id: &UserId, email: &str) -> Result<(), Error> {
+        // casting current_time() to i64 will overflow in the year 2262
+        let date_requested = i64::try_from(clock::now()).unwrap_or(i64::MAX);
+
+        self.database.set_user_pending_email(*user_id, email, date_requested).await
+    }
+
+    /// It returns the email the user asked to change to, if any.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_pending_email(&self, user_id: &UserId) -> Result<Option<String>, Error> {
+        self.database.get_user_pending_email(*user_id).await
+    }
+
+    /// It replaces the user's email with the confirmed pending email.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn change_email(&self, user_id: &UserId, email: &str) -> Result<(), Error> {
+        self.database.change_user_email(*user_id, email).await
+    }
+
+    /// It changes the user's username.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn change_username(&self, user_id: &UserId, new_username: &Username) -> Result<(), Error> {
+        self.database.change_username(*user_id, &new_username.to_string()).await
+    }
 }
 
 pub struct DbBannedUserList {
@@ -449,37 +865,3 @@ impl DbBannedUserList {
         self.database.ban_user(*user_id, &reason, date_expiry).await
     }
 }
-
-fn validate_password_constraints(
-    password: &str,
-    confirm_password: &str,
-    password_rules: &PasswordConstraints,
-) -> Result<(), ServiceError> {
-    if password != confirm_password {
-        return Err(ServiceError::PasswordsDontMatch);
-    }
-
-    let password_length = password.len();
-
-    if password_length < password_rules.min_password_length {
-        return Err(ServiceError::PasswordTooShort);
-    }
-
-    if password_length > password_rules.max_password_length {
-        return Err(ServiceError::PasswordTooLong);
-    }
-
-    Ok(())
-}
-
-fn hash_password(password: &str) -> Result<String, ServiceError> {
-    let salt = SaltString::generate(&mut OsRng);
-
-    // Argon2 with default params (Argon2id v19)
-    let argon2 = Argon2::default();
-
-    // Hash password to PHC string ($argon2id$v=19$...)
-    let password_hash = argon2.hash_password(password.as_bytes(), &salt)?.to_string();
-
-    Ok(password_hash)
-}
@@ -0,0 +1,175 @@
+//! Username policy enforcement.
+//!
+//! It checks new usernames against the [`UsernameConstraints`]
+//! configuration. Usernames are parsed with the RFC 8265
+//! [`UsernameCaseMapped`](https://tools.ietf.org/html/rfc8265#section-3.3)
+//! profile, and then they can't be a reserved username, like the ones in the
+//! [Big Username Blacklist](https://github.com/shuttlecraft/The-Big-Username-Blacklist),
+//! nor contain a word from the profanity list.
+//!
+//! Usernames are compared in their case mapped form without dashes and
+//! underscores, so `Ad_Min` is rejected when `admin` is reserved.
+use std::path::Path;
+use std::{fs, io};
+
+use crate::config::UsernameConstraints;
+use crate::errors::ServiceError;
+use crate::models::user::{Username, UsernameParseError};
+
+/// Separators ignored when usernames are compared with the lists.
+const SEPARATORS: [char; 2] = ['-', '_'];
+
+/// The username policy, with the reserved usernames and the profanity words
+/// ready to be compared.
+///
+/// It's built once when the application starts, so the profanity words file
+/// is not read for every new username.
+#[derive(Debug)]
+pub struct UsernamePolicy {
+    reserved_usernames: Vec<String>,
+    profanity_words: Vec<String>,
+}
+
+impl UsernamePolicy {
+    /// It builds the policy from the configuration.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the profanity words file can't be read.
+    pub fn new(username_rules: &UsernameConstraints) -> io::Result<Self> {
+        let profanity_words = match &username_rules.profanity_words_path {
+            Some(profanity_words_path) => read_words(profanity_words_path)?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            reserved_usernames: username_rules
+                .reserved_usernames
+                .iter()
+                .map(|reserved_username| comparable(reserved_username))
+                .collect(),
+            profanity_words,
+        })
+    }
+
+    /// It checks the new username against the policy and returns the parsed
+    /// username.
+    ///
+    /// # Errors
+    ///
+    /// It returns a:
+    ///
+    /// * `ServiceError::UsernameCaseMappedError` if the username contains
+    ///   characters the `UsernameCaseMapped` profile does not allow.
+    /// * `ServiceError::UsernameInvalid` if the username is badly formatted.
+    /// * `ServiceError::BlacklistError` if the username is reserved.
+    /// * `ServiceError::ProfanityError` if the username contains profanity.
+    pub fn validate(&self, username: &str) -> Result<Username, ServiceError> {
+        let username = username.parse::<Username>().map_err(|err| match err {
+            UsernameParseError::DisallowedCharacters { .. } => ServiceError::UsernameCaseMappedError,
+            UsernameParseError::Invalid { .. } => ServiceError::UsernameInvalid,
+        })?;
+
+        let comparable_username = comparable(&username.case_mapped());
+
+        if self.reserved_usernames.contains(&comparable_username) {
+            return Err(ServiceError::BlacklistError);
+        }
+
+        if self
+            .profanity_words
+            .iter()
+            .any(|word| comparable_username.contains(word.as_str()))
+        {
+            return Err(ServiceError::ProfanityError);
+        }
+
+        Ok(username)
+    }
+}
+
+fn comparable(value: &str) -> String {
+    value.to_lowercase().replace(SEPARATORS, "")
+}
+
+/// One word per line. Empty lines and lines starting with `#` are skipped.
+fn read_words(path: &Path) -> io::Result<Vec<String>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(comparable)
+        .filter(|word| !word.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::UsernamePolicy;
+    use crate::config::UsernameConstraints;
+    use crate::errors::ServiceError;
+
+    fn default_policy() -> UsernamePolicy {
+        UsernamePolicy::new(&UsernameConstraints::default()).unwrap()
+    }
+
+    #[test]
+    fn the_default_policy_should_accept_valid_usernames() {
+        let username = default_policy().validate("indiana-jones").unwrap();
+
+        assert_eq!(username.to_string(), "indiana-jones");
+    }
+
+    #[test]
+    fn it_should_reject_usernames_with_characters_disallowed_by_the_username_case_mapped_profile() {
+        assert!(matches!(
+            default_policy().validate("indiana jones"),
+            Err(ServiceError::UsernameCaseMappedError)
+        ));
+        assert!(matches!(
+            default_policy().validate("indiana*jones"),
+            Err(ServiceError::UsernameInvalid)
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_reserved_usernames_ignoring_the_case_and_the_separators() {
+        for username in ["admin", "Admin", "ad_min", "\u{FF21}dmin", "Sys-Admin"] {
+            assert!(
+                matches!(default_policy().validate(username), Err(ServiceError::BlacklistError)),
+                "{username} should be reserved"
+            );
+        }
+
+        assert!(default_policy().validate("admiral").is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_usernames_containing_profanity() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("profanity.txt");
+        fs::write(&file, "# Words not allowed in usernames\n\nheck\nDARN\n").unwrap();
+
+        let policy = UsernamePolicy::new(&UsernameConstraints {
+            profanity_words_path: Some(file),
+            ..UsernameConstraints::default()
+        })
+        .unwrap();
+
+        assert!(matches!(policy.validate("what-the-heck"), Err(ServiceError::ProfanityError)));
+        assert!(matches!(policy.validate("Da_rn1"), Err(ServiceError::ProfanityError)));
+        assert!(policy.validate("indiana-jones").is_ok());
+    }
+
+    #[test]
+    fn it_should_fail_to_build_the_policy_when_the_profanity_words_file_does_not_exist() {
+        let username_rules = UsernameConstraints {
+            profanity_words_path: Some("./does-not-exist.txt".into()),
+            ..UsernameConstraints::default()
+        };
+
+        assert!(UsernamePolicy::new(&username_rules).is_err());
+    }
+}
@@ -0,0 +1,695 @@
+//! Outgoing webhooks.
+//!
+//! Admins configure webhooks to let other applications, like chat bots or
+//! mirrors, react to Index events. A webhook has a URL, a secret and the list
+//! of events it's subscribed to.
+//!
+//! The services publish an [`Event`] with the [`Publisher`] when something
+//! happens. The publisher stores a delivery for every active webhook
+//! subscribed to the event and the
+//! [`webhook_sender`](crate::console::cronjobs::webhook_sender) cronjob sends
+//! them in the background.
+//!
+//! Deliveries are `POST` requests with a JSON payload:
+//!
+//! ```json
+//! {
+//!   "event": "torrent.uploaded",
+//!   "timestamp": 1716220800,
+//!   "data": {
+//!     "torrent_id": 1,
+//!     "info_hash": "443c7602b4fde83d1154d6d9da48808418b181b6",
+//!     "title": "Ubuntu 24.04",
+//!     "category_id": 5,
+//!     "tag_ids": [],
+//!     "uploader": "indexadmin"
+//!   }
+//! }
+//! ```
+//!
+//! And these headers:
+//!
+//! - `X-Torrust-Event`: the event name.
+//! - `X-Torrust-Delivery`: the delivery ID. It does not change when the
+//!   delivery is retried or replayed.
+//! - `X-Torrust-Signature-256`: `sha256=` followed by the hex HMAC-SHA256 of
+//!   the payload using the webhook secret as the key.
+//!
+//! A delivery fails when the request can't be sent or the receiver does not
+//! respond with a `2xx` status code. Failed deliveries are retried with an
+//! exponential backoff. After [`MAX_DELIVERY_ATTEMPTS`] failed attempts the
+//! delivery is kept with the `failed` status. All the deliveries are kept in
+//! the database so admins can inspect and replay them.
+use std::sync::Arc;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::Notify;
+use tracing::{debug, error};
+use url::Url;
+
+use super::authorization::{self, ACTION};
+use crate::databases::database::{Database, Error};
+use crate::errors::ServiceError;
+use crate::models::category::CategoryId;
+use crate::models::torrent::TorrentId;
+use crate::models::torrent_tag::TagId;
+use crate::models::user::UserId;
+use crate::models::webhook::{DeliveryId, DueWebhookDelivery, EventFilter, Webhook, WebhookDelivery, WebhookEvent, WebhookId};
+use crate::utils::{backoff, clock};
+
+/// Failed attempts before giving up on a delivery.
+pub const MAX_DELIVERY_ATTEMPTS: i64 = 8;
+
+/// Delay before retrying the first failed delivery. It's doubled for every
+/// following failure.
+const FIRST_RETRY_DELAY_IN_SECS: u64 = 30;
+
+const MAX_RETRY_DELAY_IN_SECS: u64 = 3600;
+
+/// Deliveries loaded from the database at once.
+const DELIVERY_BATCH_SIZE: u32 = 50;
+
+/// Deliveries returned when an admin inspects the delivery log of a webhook.
+const DELIVERY_LOG_SIZE: u32 = 100;
+
+/// Time the receiver has to respond.
+const REQUEST_TIMEOUT_IN_SECS: u64 = 10;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Something webhooks can be subscribed to. It's serialized as the `data`
+/// field of the payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Event {
+    TorrentUploaded {
+        torrent_id: TorrentId,
+        info_hash: String,
+        title: String,
+        category_id: Option<CategoryId>,
+        tag_ids: Vec<TagId>,
+        uploader: String,
+    },
+    TorrentUpdated {
+        torrent_id: TorrentId,
+        info_hash: String,
+        title: String,
+        editor: String,
+    },
+    TorrentDeleted {
+        torrent_id: TorrentId,
+        info_hash: String,
+        title: String,
+        deleter: String,
+    },
+    UserRegistered {
+        user_id: UserId,
+        username: String,
+    },
+    UserBanned {
+        user_id: UserId,
+        username: String,
+        banned_by: String,
+    },
+}
+
+impl Event {
+    #[must_use]
+    pub fn kind(&self) -> WebhookEvent {
+        match self {
+            Event::TorrentUploaded { .. } => WebhookEvent::TorrentUploaded,
+            Event::TorrentUpdated { .. } => WebhookEvent::TorrentUpdated,
+            Event::TorrentDeleted { .. } => WebhookEvent::TorrentDeleted,
+            Event::UserRegistered { .. } => WebhookEvent::UserRegistered,
+            Event::UserBanned { .. } => WebhookEvent::UserBanned,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: WebhookEvent,
+    timestamp: u64,
+    data: &'a Event,
+}
+
+#[allow(clippy::struct_field_names)]
+pub struct Service {
+    webhook_repository: Arc<DbWebhookRepository>,
+    publisher: Arc<Publisher>,
+    authorization_service: Arc<authorization::Service>,
+}
+
+impl Service {
+    #[must_use]
+    pub fn new(
+        webhook_repository: Arc<DbWebhookRepository>,
+        publisher: Arc<Publisher>,
+        authorization_service: Arc<authorization::Service>,
+    ) -> Service {
+        Service {
+            webhook_repository,
+            publisher,
+            authorization_service,
+        }
+    }
+
+    /// It adds a new webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * The URL is not an absolute HTTP or HTTPS URL.
+    /// * The secret is empty.
+    /// * There are no events.
+    /// * There is a database error.
+    pub async fn add_webhook(
+        &self,
+        url: &str,
+        secret: &str,
+        events: Vec<WebhookEvent>,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<Webhook, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::AddWebhook, maybe_user_id)
+            .await?;
+
+        let url = parse_url(url)?;
+        let events = parse_events(events)?;
+
+        if secret.is_empty() {
+            return Err(ServiceError::WebhookSecretEmpty);
+        }
+
+        let webhook_id = self.webhook_repository.add(url.as_str(), secret, &events).await?;
+
+        Ok(self.webhook_repository.get(webhook_id).await?)
+    }
+
+    /// It returns all the webhooks.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * There is a database error.
+    pub async fn get_webhooks(&self, maybe_user_id: Option<UserId>) -> Result<Vec<Webhook>, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GetWebhooks, maybe_user_id)
+            .await?;
+
+        Ok(self.webhook_repository.get_all().await?)
+    }
+
+    /// It updates a webhook. The secret is only changed when a new one is
+    /// provided.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * The webhook does not exist.
+    /// * The URL is not an absolute HTTP or HTTPS URL.
+    /// * The new secret is empty.
+    /// * There are no events.
+    /// * There is a database error.
+    pub async fn update_webhook(
+        &self,
+        webhook_id: WebhookId,
+        url: &str,
+        secret: Option<&str>,
+        events: Vec<WebhookEvent>,
+        active: bool,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<Webhook, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::UpdateWebhook, maybe_user_id)
+            .await?;
+
+        let url = parse_url(url)?;
+        let events = parse_events(events)?;
+
+        if secret.is_some_and(str::is_empty) {
+            return Err(ServiceError::WebhookSecretEmpty);
+        }
+
+        let webhook = self.webhook_repository.get(webhook_id).await?;
+
+        self.webhook_repository
+            .update(webhook_id, url.as_str(), secret.unwrap_or(&webhook.secret), &events, active)
+            .await?;
+
+        if active && !webhook.active {
+            // Pending deliveries are not sent while the webhook is inactive.
+            self.publisher.wake_up();
+        }
+
+        Ok(self.webhook_repository.get(webhook_id).await?)
+    }
+
+    /// It deletes a webhook and its delivery log.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * The webhook does not exist.
+    /// * There is a database error.
+    pub async fn delete_webhook(&self, webhook_id: WebhookId, maybe_user_id: Option<UserId>) -> Result<(), ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::DeleteWebhook, maybe_user_id)
+            .await?;
+
+        Ok(self.webhook_repository.delete(webhook_id).await?)
+    }
+
+    /// It returns the latest deliveries of a webhook, newest first.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * The webhook does not exist.
+    /// * There is a database error.
+    pub async fn get_deliveries(
+        &self,
+        webhook_id: WebhookId,
+        maybe_user_id: Option<UserId>,
+    ) -> Result<Vec<WebhookDelivery>, ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::GetWebhooks, maybe_user_id)
+            .await?;
+
+        // It returns an error if the webhook does not exist.
+        self.webhook_repository.get(webhook_id).await?;
+
+        Ok(self.webhook_repository.get_deliveries(webhook_id, DELIVERY_LOG_SIZE).await?)
+    }
+
+    /// It sends a delivery again, with the same payload, as soon as possible.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// * The user does not have the required permissions.
+    /// * The delivery does not exist.
+    /// * There is a database error.
+    pub async fn replay_delivery(&self, delivery_id: DeliveryId, maybe_user_id: Option<UserId>) -> Result<(), ServiceError> {
+        self.authorization_service
+            .authorize(ACTION::ReplayWebhookDelivery, maybe_user_id)
+            .await?;
+
+        self.webhook_repository.replay_delivery(delivery_id).await?;
+
+        self.publisher.wake_up();
+
+        Ok(())
+    }
+}
+
+/// It stores the deliveries for the published events and sends them.
+pub struct Publisher {
+    webhook_repository: Arc<DbWebhookRepository>,
+    http_client: reqwest::Client,
+    new_delivery: Arc<Notify>,
+}
+
+impl Publisher {
+    /// # Panics
+    ///
+    /// It panics if the HTTP client can't be built.
+    #[must_use]
+    pub fn new(webhook_repository: Arc<DbWebhookRepository>) -> Publisher {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_IN_SECS))
+            .build()
+            .expect("unable to build the webhook HTTP client");
+
+        Publisher {
+            webhook_repository,
+            http_client,
+            new_delivery: Arc::new(Notify::new()),
+        }
+    }
+
+    /// It returns the notifier the webhook sender waits on to send new
+    /// deliveries without waiting for the next scheduled run.
+    #[must_use]
+    pub fn new_delivery_notifier(&self) -> Arc<Notify> {
+        self.new_delivery.clone()
+    }
+
+    /// It wakes up the webhook sender.
+    pub fn wake_up(&self) {
+        self.new_delivery.notify_one();
+    }
+
+    /// It adds a delivery for every active webhook subscribed to the event.
+    /// Errors are only logged.
+    pub async fn publish(&self, event: Event) {
+        match self.add_deliveries(&event).await {
+            Ok(0) => {}
+            Ok(_) => self.wake_up(),
+            Err(e) => error!("Failed to publish the webhook event {}: {:?}", event.kind(), e),
+        }
+    }
+
+    async fn add_deliveries(&self, event: &Event) -> Result<usize, Error> {
+        let kind = event.kind();
+
+        let webhooks: Vec<Webhook> = self
+            .webhook_repository
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|webhook| webhook.active && webhook.events.contains(kind))
+            .collect();
+
+        if webhooks.is_empty() {
+            return Ok(0);
+        }
+
+        let payload = serde_json::to_string(&Payload {
+            event: kind,
+            timestamp: clock::now(),
+            data: event,
+        })
+        .map_err(|e| Error::ErrorWithText(e.to_string()))?;
+
+        for webhook in &webhooks {
+            self.webhook_repository
+                .add_delivery(webhook.webhook_id, kind.name(), &payload)
+                .await?;
+        }
+
+        Ok(webhooks.len())
+    }
+
+    /// It sends the deliveries that are due.
+    ///
+    /// It returns the number of successful deliveries.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the deliveries can't be read or updated.
+    pub async fn deliver_due_deliveries(&self) -> Result<usize, Error> {
+        let mut delivered = 0;
+
+        loop {
+            let deliveries = self.webhook_repository.get_due_deliveries(DELIVERY_BATCH_SIZE).await?;
+
+            for delivery in &deliveries {
+                match self.deliver(delivery).await {
+                    Ok(response_status) => {
+                        self.webhook_repository
+                            .complete_delivery(delivery.delivery_id, response_status)
+                            .await?;
+                        delivered += 1;
+                    }
+                    Err((response_status, e)) => self.handle_failed_delivery(delivery, response_status, &e).await?,
+                }
+            }
+
+            // Failed deliveries are rescheduled, so they are not loaded again.
+            if deliveries.len() < DELIVERY_BATCH_SIZE as usize {
+                return Ok(delivered);
+            }
+        }
+    }
+
+    /// It returns the response status code, or the error and the status code
+    /// if there was a response.
+    async fn deliver(&self, delivery: &DueWebhookDelivery) -> Result<i64, (Option<i64>, String)> {
+        let response = self
+            .http_client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("X-Torrust-Event", &delivery.event)
+            .header("X-Torrust-Delivery", delivery.delivery_id.to_string())
+            .header("X-Torrust-Signature-256", sign(&delivery.secret, &delivery.payload))
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status = response.status();
+
+        if status.is_success() {
+            Ok(i64::from(status.as_u16()))
+        } else {
+            Err((
+                Some(i64::from(status.as_u16())),
+                format!("unexpected response status: {status}"),
+            ))
+        }
+    }
+
+    async fn handle_failed_delivery(
+        &self,
+        delivery: &DueWebhookDelivery,
+        response_status: Option<i64>,
+        error: &str,
+    ) -> Result<(), Error> {
+        let attempts = delivery.attempts + 1;
+
+        if attempts < MAX_DELIVERY_ATTEMPTS {
+            let delay = backoff::retry_delay_in_secs(attempts, FIRST_RETRY_DELAY_IN_SECS, MAX_RETRY_DELAY_IN_SECS);
+
+            debug!(
+                "Failed to deliver webhook delivery {} (attempt {attempts}), retrying in {delay} seconds: {error}",
+                delivery.delivery_id
+            );
+
+            // casting current_time() to i64 will overflow in the year 2262
+            let next_attempt_at = i64::try_from(clock::now() + delay).unwrap();
+
+            self.webhook_repository
+                .reschedule_delivery(delivery.delivery_id, next_attempt_at, response_status, error)
+                .await
+        } else {
+            error!(
+                "Giving up webhook delivery {} of event {} to {} after {attempts} attempts: {error}",
+                delivery.delivery_id, delivery.event, delivery.url
+            );
+
+            self.webhook_repository
+                .fail_delivery(delivery.delivery_id, response_status, error)
+                .await
+        }
+    }
+}
+
+/// The value of the `X-Torrust-Signature-256` header.
+///
+/// # Panics
+///
+/// It never panics because HMAC accepts keys of any size.
+#[must_use]
+pub fn sign(secret: &str, payload: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(payload.as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn parse_url(url: &str) -> Result<Url, ServiceError> {
+    let url = Url::parse(url).map_err(|_| ServiceError::InvalidWebhookUrl)?;
+
+    if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+        return Err(ServiceError::InvalidWebhookUrl);
+    }
+
+    Ok(url)
+}
+
+fn parse_events(events: Vec<WebhookEvent>) -> Result<EventFilter, ServiceError> {
+    let events = EventFilter::new(events);
+
+    if events.is_empty() {
+        return Err(ServiceError::WebhookEventsEmpty);
+    }
+
+    Ok(events)
+}
+
+pub struct DbWebhookRepository {
+    database: Arc<Box<dyn Database>>,
+}
+
+impl DbWebhookRepository {
+    #[must_use]
+    pub fn new(database: Arc<Box<dyn Database>>) -> Self {
+        Self { database }
+    }
+
+    /// It adds a new webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn add(&self, url: &str, secret: &str, events: &EventFilter) -> Result<WebhookId, Error> {
+        self.database.insert_webhook(url, secret, events).await
+    }
+
+    /// It returns all the webhooks.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_all(&self) -> Result<Vec<Webhook>, Error> {
+        self.database.get_webhooks().await
+    }
+
+    /// It returns a webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the webhook does not exist or there is a
+    /// database error.
+    pub async fn get(&self, webhook_id: WebhookId) -> Result<Webhook, Error> {
+        self.database.get_webhook(webhook_id).await
+    }
+
+    /// It updates a webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the webhook does not exist or there is a
+    /// database error.
+    pub async fn update(
+        &self,
+        webhook_id: WebhookId,
+        url: &str,
+        secret: &str,
+        events: &EventFilter,
+        active: bool,
+    ) -> Result<(), Error> {
+        self.database.update_webhook(webhook_id, url, secret, events, active).await
+    }
+
+    /// It deletes a webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the webhook does not exist or there is a
+    /// database error.
+    pub async fn delete(&self, webhook_id: WebhookId) -> Result<(), Error> {
+        self.database.delete_webhook(webhook_id).await
+    }
+
+    /// It adds a delivery for a webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn add_delivery(&self, webhook_id: WebhookId, event: &str, payload: &str) -> Result<DeliveryId, Error> {
+        self.database.add_webhook_delivery(webhook_id, event, payload).await
+    }
+
+    /// It returns the latest deliveries of a webhook.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_deliveries(&self, webhook_id: WebhookId, limit: u32) -> Result<Vec<WebhookDelivery>, Error> {
+        self.database.get_webhook_deliveries(webhook_id, limit).await
+    }
+
+    /// It returns the deliveries that are due.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn get_due_deliveries(&self, limit: u32) -> Result<Vec<DueWebhookDelivery>, Error> {
+        self.database.get_due_webhook_deliveries(limit).await
+    }
+
+    /// It marks a delivery as delivered.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn complete_delivery(&self, delivery_id: DeliveryId, response_status: i64) -> Result<(), Error> {
+        self.database.complete_webhook_delivery(delivery_id, response_status).await
+    }
+
+    /// It records a failed attempt and schedules the next one.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn reschedule_delivery(
+        &self,
+        delivery_id: DeliveryId,
+        next_attempt_at: i64,
+        response_status: Option<i64>,
+        error: &str,
+    ) -> Result<(), Error> {
+        self.database
+            .reschedule_webhook_delivery(delivery_id, next_attempt_at, response_status, error)
+            .await
+    }
+
+    /// It records the last failed attempt and stops retrying the delivery.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there is a database error.
+    pub async fn fail_delivery(&self, delivery_id: DeliveryId, response_status: Option<i64>, error: &str) -> Result<(), Error> {
+        self.database.fail_webhook_delivery(delivery_id, response_status, error).await
+    }
+
+    /// It makes a delivery pending again.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the delivery does not exist or there is a
+    /// database error.
+    pub async fn replay_delivery(&self, delivery_id: DeliveryId) -> Result<(), Error> {
+        self.database.replay_webhook_delivery(delivery_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_url, sign, Event};
+    use crate::models::webhook::WebhookEvent;
+
+    #[test]
+    fn the_signature_should_be_the_hex_hmac_sha256_of_the_payload() {
+        // Test vector from RFC 4231, test case 2.
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn only_absolute_http_urls_should_be_allowed() {
+        assert!(parse_url("https://example.com/hooks/torrust").is_ok());
+        assert!(parse_url("http://127.0.0.1:8080").is_ok());
+        assert!(parse_url("ftp://example.com").is_err());
+        assert!(parse_url("/hooks/torrust").is_err());
+    }
+
+    #[test]
+    fn the_event_data_should_not_include_the_event_name() {
+        let event = Event::UserRegistered {
+            user_id: 1,
+            username: "alice".to_string(),
+        };
+
+        assert_eq!(event.kind(), WebhookEvent::UserRegistered);
+        assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"user_id":1,"username":"alice"}"#);
+    }
+}
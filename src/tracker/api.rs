@@ -83,6 +83,18 @@ impl Client {
         self.client.post(request_url).query(&self.token_param).send().await
     }
 
+    /// Delete a tracker key. The tracker will stop accepting announces
+    /// with that key.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the HTTP request fails.
+    pub async fn delete_tracker_key(&self, key: &str) -> Result<Response, Error> {
+        let request_url = format!("{}/key/{}", self.api_base_url, key);
+
+        self.client.delete(request_url).query(&self.token_param).send().await
+    }
+
     /// Retrieve the info for one torrent.
     ///
     /// # Errors
@@ -33,6 +33,15 @@ pub enum TrackerAPIError {
     #[display(fmt = "Could not save the newly generated user key into the database.")]
     CannotSaveUserKey,
 
+    #[display(fmt = "Could not load the user keys from the database.")]
+    CannotLoadUserKeys,
+
+    #[display(fmt = "Could not delete the revoked user key from the database.")]
+    CannotDeleteUserKey,
+
+    #[display(fmt = "Could not revoke {} user keys: {}", "errors.len()", "join_errors(errors)")]
+    CannotRevokeUserKeys { errors: Vec<TrackerAPIError> },
+
     #[display(fmt = "Torrent not found.")]
     TorrentNotFound,
 
@@ -43,6 +52,10 @@ pub enum TrackerAPIError {
     FailedToParseTrackerResponse { body: String },
 }
 
+fn join_errors(errors: &[TrackerAPIError]) -> String {
+    errors.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ")
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct TorrentInfo {
     pub info_hash: String,
@@ -204,17 +217,114 @@ impl Service {
     pub async fn get_personal_announce_url(&self, user_id: UserId) -> Result<Url, TrackerAPIError> {
         debug!(target: "tracker-service", "get personal announce url for user: {user_id}");
 
-        let tracker_key = self.database.get_user_tracker_key(user_id).await;
+        match self.get_user_tracker_key(user_id).await {
+            Ok(tracker_key) => Ok(self.announce_url_with_key(&tracker_key)),
+            Err(err) => Err(TrackerAPIError::TrackerOffline { error: err.to_string() }),
+        }
+    }
+
+    /// Get the tracker key of a user used in the personal announce url.
+    ///
+    /// If the user doesn't have a tracker key valid for at least one more
+    /// hour, it will generate a new one and save it in the database.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the HTTP request to generate a new user tracker
+    /// key failed.
+    pub async fn get_user_tracker_key(&self, user_id: UserId) -> Result<TrackerKey, TrackerAPIError> {
+        match self.database.get_user_tracker_key(user_id).await {
+            Some(tracker_key) => Ok(tracker_key),
+            None => self.retrieve_new_tracker_key(user_id).await,
+        }
+    }
+
+    /// Get all the not expired tracker keys of a user, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the keys cannot be loaded from the database.
+    pub async fn get_user_tracker_keys(&self, user_id: UserId) -> Result<Vec<TrackerKey>, TrackerAPIError> {
+        self.database
+            .get_user_tracker_keys(user_id)
+            .await
+            .map_err(|_| TrackerAPIError::CannotLoadUserKeys)
+    }
+
+    /// Revoke all the user's tracker keys and issue a new one.
+    ///
+    /// It's used when a key has leaked. Old `.torrent` files downloaded by
+    /// the user stop working.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if any of the old keys cannot be revoked or the
+    /// new key cannot be generated.
+    pub async fn rotate_user_tracker_key(&self, user_id: UserId) -> Result<TrackerKey, TrackerAPIError> {
+        debug!(target: "tracker-service", "rotate key for user: {user_id}");
+
+        self.revoke_user_tracker_keys(user_id).await?;
+
+        self.retrieve_new_tracker_key(user_id).await
+    }
+
+    /// Revoke all the not expired tracker keys of a user, both in the tracker
+    /// and in the database.
+    ///
+    /// A key is only removed from the database once the tracker has revoked
+    /// it, so a failed revocation can be retried. Keys the tracker does not
+    /// know are already revoked. A key that can't be revoked does not stop
+    /// the revocation of the other ones.
+    ///
+    /// # Errors
+    ///
+    /// Will return a `TrackerAPIError::CannotRevokeUserKeys` error with all
+    /// the keys that cannot be revoked in the tracker or deleted from the
+    /// database.
+    pub async fn revoke_user_tracker_keys(&self, user_id: UserId) -> Result<(), TrackerAPIError> {
+        debug!(target: "tracker-service", "revoke keys for user: {user_id}");
+
+        let mut errors = vec![];
+
+        for tracker_key in self.get_user_tracker_keys(user_id).await? {
+            match self.delete_tracker_key(&tracker_key.key).await {
+                Ok(()) => {}
+                Err(TrackerAPIError::NotFound) => {
+                    debug!(target: "tracker-service", "key already revoked in the tracker: {}", tracker_key.key);
+                }
+                Err(err) => {
+                    error!(target: "tracker-service", "failed to revoke key {}: {err}", tracker_key.key);
+                    errors.push(err);
+                    continue;
+                }
+            }
+
+            if self.database.delete_tracker_key(&tracker_key.key).await.is_err() {
+                error!(target: "tracker-service", "failed to delete revoked key {}", tracker_key.key);
+                errors.push(TrackerAPIError::CannotDeleteUserKey);
+            }
+        }
 
-        match tracker_key {
-            Some(tracker_key) => Ok(self.announce_url_with_key(&tracker_key)),
-            None => match self.retrieve_new_tracker_key(user_id).await {
-                Ok(new_tracker_key) => Ok(self.announce_url_with_key(&new_tracker_key)),
-                Err(err) => Err(TrackerAPIError::TrackerOffline { error: err.to_string() }),
-            },
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TrackerAPIError::CannotRevokeUserKeys { errors })
         }
     }
 
+    /// Delete the expired tracker keys from the database. The tracker
+    /// already rejects them, so it's not contacted.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the keys cannot be deleted from the database.
+    pub async fn delete_expired_tracker_keys(&self) -> Result<u64, TrackerAPIError> {
+        self.database
+            .delete_expired_tracker_keys()
+            .await
+            .map_err(|_| TrackerAPIError::CannotDeleteUserKey)
+    }
+
     /// Get torrent info from tracker.
     ///
     /// # Errors
@@ -373,14 +483,68 @@ impl Service {
         }
     }
 
+    /// Revoke a tracker key in the tracker.
+    ///
+    /// It returns a `TrackerAPIError::NotFound` error if the tracker does not
+    /// know the key.
+    async fn delete_tracker_key(&self, key: &str) -> Result<(), TrackerAPIError> {
+        debug!(target: "tracker-service", "delete key: {key}");
+
+        let maybe_response = self.api_client.delete_tracker_key(key).await;
+
+        debug!(target: "tracker-service", "delete key response result: {:?}", maybe_response);
+
+        match maybe_response {
+            Ok(response) => {
+                let status: StatusCode = map_status_code(response.status());
+
+                let body = response.text().await.map_err(|_| {
+                    error!(target: "tracker-service", "response without body");
+                    TrackerAPIError::MissingResponseBody
+                })?;
+
+                match status {
+                    StatusCode::OK => Ok(()),
+                    StatusCode::NOT_FOUND => Err(TrackerAPIError::NotFound),
+                    StatusCode::INTERNAL_SERVER_ERROR => {
+                        if body == Self::invalid_token_body() {
+                            Err(TrackerAPIError::InvalidToken)
+                        } else if Self::is_unknown_key_body(&body) {
+                            Err(TrackerAPIError::NotFound)
+                        } else {
+                            error!(target: "tracker-service", "delete key 500 response: status {status}, body: {body}");
+                            Err(TrackerAPIError::InternalServerError)
+                        }
+                    }
+                    _ => {
+                        error!(target: "tracker-service", "delete key unexpected response: status {status}, body: {body}");
+                        Err(TrackerAPIError::UnexpectedResponseStatus)
+                    }
+                }
+            }
+            Err(err) => Err(TrackerAPIError::TrackerOffline { error: err.to_string() }),
+        }
+    }
+
     /// It builds the announce url appending the user tracker key.
     /// Eg: <https://tracker:7070/USER_TRACKER_KEY>
-    fn announce_url_with_key(&self, tracker_key: &TrackerKey) -> Url {
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the key cannot be appended to the tracker URL.
+    #[must_use]
+    pub fn announce_url_with_key(&self, tracker_key: &TrackerKey) -> Url {
         self.tracker_url
             .join(&tracker_key.key)
             .expect("a tracker key should be added to the tracker base URL")
     }
 
+    /// The tracker can't delete a key it does not have, so it fails with the
+    /// number of deleted rows as the error code.
+    fn is_unknown_key_body(body: &str) -> bool {
+        body.contains("failed to delete key") && body.contains("error-code: 0")
+    }
+
     fn invalid_token_body() -> String {
         "Unhandled rejection: Err { reason: \"token not valid\" }".to_string()
     }
@@ -395,3 +559,102 @@ impl Service {
 fn map_status_code(status: reqwest::StatusCode) -> hyper::StatusCode {
     StatusCode::from_u16(status.as_u16()).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::extract::Path;
+    use axum::http::StatusCode;
+    use axum::routing::delete;
+    use axum::Router;
+    use tempfile::TempDir;
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::{Service, TrackerAPIError};
+    use crate::config::Configuration;
+    use crate::databases::database::{self, Database};
+    use crate::models::tracker_key::TrackerKey;
+    use crate::utils::clock;
+
+    /// A tracker API that revokes the `revoked` key, does not know the
+    /// `unknown` key and fails to revoke the `broken` key.
+    async fn start_tracker() -> Url {
+        async fn delete_key(Path(key): Path<String>) -> (StatusCode, String) {
+            match key.as_str() {
+                "revoked" => (StatusCode::OK, "{\"status\":\"ok\"}".to_string()),
+                "unknown" => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Unhandled rejection: Err { reason: \"failed to delete key: Failed to remove record from Sqlite3 database, error-code: 0\" }".to_string(),
+                ),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "Unhandled rejection: Err { reason: \"database is locked\" }".to_string()),
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/api/v1/key/:key", delete(delete_key)))
+                .await
+                .unwrap();
+        });
+
+        url
+    }
+
+    async fn new_service(database_dir: &TempDir) -> (Service, Arc<Box<dyn Database>>) {
+        let database_url = format!("sqlite://{}?mode=rwc", database_dir.path().join("data.db").display());
+        let database: Arc<Box<dyn Database>> = Arc::new(database::connect(&database_url).await.unwrap());
+
+        let configuration = Configuration::default();
+        configuration.settings.write().await.tracker.api_url = start_tracker().await;
+
+        (Service::new(Arc::new(configuration), database.clone()).await, database)
+    }
+
+    #[tokio::test]
+    async fn it_should_revoke_all_the_user_keys_it_can_and_report_the_ones_it_can_not() {
+        let database_dir = tempfile::tempdir().unwrap();
+        let (service, database) = new_service(&database_dir).await;
+
+        let user_id = database
+            .insert_user_and_get_id("indiana", "indiana@example.com", "password")
+            .await
+            .unwrap();
+
+        let valid_until = i64::try_from(clock::now()).unwrap() + 3600;
+
+        for key in ["broken", "revoked", "unknown"] {
+            database
+                .add_tracker_key(
+                    user_id,
+                    &TrackerKey {
+                        key: key.to_string(),
+                        valid_until,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            service.revoke_user_tracker_keys(user_id).await,
+            Err(TrackerAPIError::CannotRevokeUserKeys {
+                errors: vec![TrackerAPIError::InternalServerError]
+            })
+        );
+
+        // The key the tracker does not know was already revoked.
+        let keys: Vec<String> = service
+            .get_user_tracker_keys(user_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tracker_key| tracker_key.key)
+            .collect();
+
+        assert_eq!(keys, vec!["broken".to_string()]);
+    }
+}
@@ -9,6 +9,7 @@ use url::Url;
 use super::service::{Service, TorrentInfo, TrackerAPIError};
 use crate::config::Configuration;
 use crate::databases::database::{self, Database};
+use crate::services::event_stream::{self, Broadcaster};
 
 const LOG_TARGET: &str = "Tracker Stats Importer";
 
@@ -16,10 +17,16 @@ pub struct StatisticsImporter {
     database: Arc<Box<dyn Database>>,
     tracker_service: Arc<Service>,
     tracker_url: Url,
+    event_broadcaster: Arc<Broadcaster>,
 }
 
 impl StatisticsImporter {
-    pub async fn new(cfg: Arc<Configuration>, tracker_service: Arc<Service>, database: Arc<Box<dyn Database>>) -> Self {
+    pub async fn new(
+        cfg: Arc<Configuration>,
+        tracker_service: Arc<Service>,
+        database: Arc<Box<dyn Database>>,
+        event_broadcaster: Arc<Broadcaster>,
+    ) -> Self {
         let settings = cfg.settings.read().await;
         let tracker_url = settings.tracker.url.clone();
         drop(settings);
@@ -27,6 +34,7 @@ impl StatisticsImporter {
             database,
             tracker_service,
             tracker_url,
+            event_broadcaster,
         }
     }
 
@@ -114,24 +122,12 @@ impl StatisticsImporter {
             match torrent_info_vec.iter().find(|t| t.info_hash == torrent.info_hash) {
                 None => {
                     // No stats for this torrent in the tracker
-                    drop(
-                        self.database
-                            .update_tracker_info(torrent.torrent_id, &self.tracker_url, 0, 0)
-                            .await,
-                    );
+                    self.update_tracker_info(torrent.torrent_id, 0, 0).await;
                 }
                 Some(torrent_info) => {
                     // Update torrent stats for this tracker
-                    drop(
-                        self.database
-                            .update_tracker_info(
-                                torrent.torrent_id,
-                                &self.tracker_url,
-                                torrent_info.seeders,
-                                torrent_info.leechers,
-                            )
-                            .await,
-                    );
+                    self.update_tracker_info(torrent.torrent_id, torrent_info.seeders, torrent_info.leechers)
+                        .await;
                 }
             }
         }
@@ -148,17 +144,57 @@ impl StatisticsImporter {
     pub async fn import_torrent_statistics(&self, torrent_id: i64, info_hash: &str) -> Result<TorrentInfo, TrackerAPIError> {
         match self.tracker_service.get_torrent_info(info_hash).await {
             Ok(torrent_info) => {
-                drop(
-                    self.database
-                        .update_tracker_info(torrent_id, &self.tracker_url, torrent_info.seeders, torrent_info.leechers)
-                        .await,
-                );
+                self.update_tracker_info(torrent_id, torrent_info.seeders, torrent_info.leechers)
+                    .await;
                 Ok(torrent_info)
             }
             Err(err) => {
-                drop(self.database.update_tracker_info(torrent_id, &self.tracker_url, 0, 0).await);
+                self.update_tracker_info(torrent_id, 0, 0).await;
                 Err(err)
             }
         }
     }
+
+    /// It stores the torrent stats for the tracker and broadcasts them to the
+    /// event stream subscribers when the number of seeders or leechers
+    /// changed.
+    async fn update_tracker_info(&self, torrent_id: i64, seeders: i64, leechers: i64) {
+        // The previous stats are only needed when somebody is listening.
+        let previous = if self.event_broadcaster.has_subscribers() {
+            self.database.get_torrent_listing_from_id(torrent_id).await.ok()
+        } else {
+            None
+        };
+
+        if self
+            .database
+            .update_tracker_info(torrent_id, &self.tracker_url, seeders, leechers)
+            .await
+            .is_err()
+        {
+            return;
+        }
+
+        let Some(previous) = previous else {
+            return;
+        };
+
+        if previous.seeders == seeders && previous.leechers == leechers {
+            return;
+        }
+
+        let tag_ids = match self.database.get_tags_for_torrent_id(torrent_id).await {
+            Ok(tags) => tags.into_iter().map(|tag| tag.tag_id).collect(),
+            Err(_) => vec![],
+        };
+
+        self.event_broadcaster.broadcast(event_stream::Event::TorrentStatsUpdated {
+            torrent_id,
+            info_hash: previous.info_hash,
+            seeders,
+            leechers,
+            category_id: previous.category_id,
+            tag_ids,
+        });
+    }
 }
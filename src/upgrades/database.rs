@@ -0,0 +1,392 @@
+//! Database access shared by all the upgrade steps.
+//!
+//! Upgrade steps must work with any schema version, so they can't use the
+//! [`Database`](crate::databases::database::Database) trait, which always
+//! expects the latest schema. This module only contains the generic queries
+//! needed to detect the schema version, run the migrations, verify the
+//! upgraded data and record the upgrade progress.
+use sha2::{Digest, Sha256};
+use sqlx::migrate::Migrator;
+use sqlx::mysql::{MySqlPoolOptions, MySqlRow};
+use sqlx::sqlite::{SqlitePoolOptions, SqliteRow};
+use sqlx::{query, query_as, query_scalar, FromRow, MySqlPool, SqlitePool};
+
+use super::Error;
+use crate::databases::database::{get_driver, Driver};
+
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite3");
+
+static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+
+/// Table where the upgrader records the completed phases, so an interrupted
+/// upgrade can be resumed. It's dropped when the upgrade finishes.
+pub const CHECKPOINTS_TABLE: &str = "torrust_upgrade_checkpoints";
+
+/// The version of the newest migration embedded in this build for the
+/// database driver.
+#[must_use]
+pub fn latest_migration(driver: &Driver) -> i64 {
+    migrator(driver)
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default()
+}
+
+/// A migration embedded in this build.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Number of rows and checksum of the values in a table column.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ColumnStats {
+    pub rows: i64,
+    /// Hex SHA-256 of the sorted column values. It does not depend on the
+    /// database driver or the row order.
+    pub checksum: String,
+}
+
+#[derive(Clone)]
+pub enum Connection {
+    Sqlite(SqlitePool),
+    MySql(MySqlPool),
+}
+
+impl Connection {
+    /// It connects to a `SQLite` or `MySQL` database.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the driver is not supported or the connection
+    /// fails.
+    pub async fn connect(database_url: &str) -> Result<Self, Error> {
+        let driver = get_driver(database_url).map_err(|_| Error::UnsupportedDatabaseUrl {
+            url: database_url.to_string(),
+        })?;
+
+        match driver {
+            Driver::Sqlite3 => Ok(Connection::Sqlite(SqlitePoolOptions::new().connect(database_url).await?)),
+            Driver::Mysql => Ok(Connection::MySql(MySqlPoolOptions::new().connect(database_url).await?)),
+        }
+    }
+
+    #[must_use]
+    pub fn driver(&self) -> Driver {
+        match self {
+            Connection::Sqlite(_) => Driver::Sqlite3,
+            Connection::MySql(_) => Driver::Mysql,
+        }
+    }
+
+    /// All the tables in the database, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn tables(&self) -> Result<Vec<String>, Error> {
+        let tables = match self {
+            Connection::Sqlite(pool) => {
+                query_scalar(
+                    "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            Connection::MySql(pool) => {
+                query_scalar(
+                    "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE() ORDER BY table_name",
+                )
+                .fetch_all(pool)
+                .await?
+            }
+        };
+
+        Ok(tables)
+    }
+
+    /// The columns of a table, in the order they were defined.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn columns(&self, table: &str) -> Result<Vec<String>, Error> {
+        let columns = match self {
+            Connection::Sqlite(pool) => {
+                query_scalar("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+                    .bind(table)
+                    .fetch_all(pool)
+                    .await?
+            }
+            Connection::MySql(pool) => {
+                query_scalar(
+                    "SELECT CAST(column_name AS CHAR) FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = ? ORDER BY ordinal_position",
+                )
+                .bind(table)
+                .fetch_all(pool)
+                .await?
+            }
+        };
+
+        Ok(columns)
+    }
+
+    /// It runs a query that works with all the drivers and maps the rows.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn fetch_all<T>(&self, statement: &str) -> Result<Vec<T>, Error>
+    where
+        T: for<'r> FromRow<'r, SqliteRow> + for<'r> FromRow<'r, MySqlRow> + Send + Unpin,
+    {
+        let rows = match self {
+            Connection::Sqlite(pool) => query_as(statement).fetch_all(pool).await?,
+            Connection::MySql(pool) => query_as(statement).fetch_all(pool).await?,
+        };
+
+        Ok(rows)
+    }
+
+    /// The versions of the migrations applied to the database, or `None` if
+    /// the database schema is not managed with migrations.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn applied_migrations(&self) -> Result<Option<Vec<i64>>, Error> {
+        if !self.tables().await?.iter().any(|table| table == "_sqlx_migrations") {
+            return Ok(None);
+        }
+
+        let versions = match self {
+            Connection::Sqlite(pool) => {
+                query_scalar("SELECT version FROM _sqlx_migrations WHERE success = TRUE ORDER BY version")
+                    .fetch_all(pool)
+                    .await?
+            }
+            Connection::MySql(pool) => {
+                query_scalar("SELECT version FROM _sqlx_migrations WHERE success = TRUE ORDER BY version")
+                    .fetch_all(pool)
+                    .await?
+            }
+        };
+
+        Ok(Some(versions))
+    }
+
+    /// The migrations embedded in this build that were not applied yet.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn pending_migrations(&self) -> Result<Vec<Migration>, Error> {
+        let applied = self.applied_migrations().await?.unwrap_or_default();
+
+        Ok(self
+            .migrator()
+            .iter()
+            .filter(|migration| !applied.contains(&migration.version))
+            .map(|migration| Migration {
+                version: migration.version,
+                description: migration.description.to_string(),
+            })
+            .collect())
+    }
+
+    /// It applies all the pending migrations.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if a migration fails.
+    pub async fn run_migrations(&self) -> Result<(), Error> {
+        match self {
+            Connection::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await?,
+            Connection::MySql(pool) => MYSQL_MIGRATOR.run(pool).await?,
+        }
+
+        Ok(())
+    }
+
+    /// It counts the rows of a table and calculates the checksum of the
+    /// values in one of its columns.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn column_stats(&self, table: &str, column: &str) -> Result<ColumnStats, Error> {
+        // Table and column names come from the upgrade steps, never from the
+        // user input.
+        let mut values: Vec<Option<String>> = match self {
+            Connection::Sqlite(pool) => {
+                query_scalar(&format!("SELECT CAST(`{column}` AS TEXT) FROM `{table}`"))
+                    .fetch_all(pool)
+                    .await?
+            }
+            Connection::MySql(pool) => {
+                query_scalar(&format!("SELECT CAST(`{column}` AS CHAR) FROM `{table}`"))
+                    .fetch_all(pool)
+                    .await?
+            }
+        };
+
+        // Collations are different for each driver, so the values are sorted
+        // here.
+        values.sort_unstable();
+
+        let mut hasher = Sha256::new();
+
+        for value in &values {
+            match value {
+                Some(value) => {
+                    hasher.update(b"v");
+                    hasher.update(value.as_bytes());
+                }
+                None => hasher.update(b"n"),
+            }
+            hasher.update(b"\n");
+        }
+
+        Ok(ColumnStats {
+            rows: i64::try_from(values.len()).unwrap_or(i64::MAX),
+            checksum: hex::encode(hasher.finalize()),
+        })
+    }
+
+    /// The phases of an upgrade step already completed in this database.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn completed_phases(&self, step: &str) -> Result<Vec<String>, Error> {
+        if !self.tables().await?.iter().any(|table| table == CHECKPOINTS_TABLE) {
+            return Ok(vec![]);
+        }
+
+        let statement = format!("SELECT phase FROM {CHECKPOINTS_TABLE} WHERE step = ?");
+
+        let phases = match self {
+            Connection::Sqlite(pool) => query_scalar(&statement).bind(step).fetch_all(pool).await?,
+            Connection::MySql(pool) => query_scalar(&statement).bind(step).fetch_all(pool).await?,
+        };
+
+        Ok(phases)
+    }
+
+    /// It records a completed phase of an upgrade step.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn complete_phase(&self, step: &str, phase: &str, date_completed: &str) -> Result<(), Error> {
+        let create_table = format!(
+            "CREATE TABLE IF NOT EXISTS {CHECKPOINTS_TABLE} (
+                step VARCHAR(64) NOT NULL,
+                phase VARCHAR(64) NOT NULL,
+                date_completed VARCHAR(19) NOT NULL,
+                PRIMARY KEY (step, phase)
+            )"
+        );
+        let insert = format!("INSERT INTO {CHECKPOINTS_TABLE} (step, phase, date_completed) VALUES (?, ?, ?)");
+
+        match self {
+            Connection::Sqlite(pool) => {
+                query(&create_table).execute(pool).await?;
+                query(&insert)
+                    .bind(step)
+                    .bind(phase)
+                    .bind(date_completed)
+                    .execute(pool)
+                    .await?;
+            }
+            Connection::MySql(pool) => {
+                query(&create_table).execute(pool).await?;
+                query(&insert)
+                    .bind(step)
+                    .bind(phase)
+                    .bind(date_completed)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It removes the upgrade progress.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn clear_checkpoints(&self) -> Result<(), Error> {
+        let statement = format!("DROP TABLE IF EXISTS {CHECKPOINTS_TABLE}");
+
+        match self {
+            Connection::Sqlite(pool) => {
+                query(&statement).execute(pool).await?;
+            }
+            Connection::MySql(pool) => {
+                query(&statement).execute(pool).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It deletes all the rows in the tables.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the query fails.
+    pub async fn delete_all_rows(&self, tables: &[&str]) -> Result<(), Error> {
+        for table in tables {
+            let statement = format!("DELETE FROM {table}");
+
+            match self {
+                Connection::Sqlite(pool) => {
+                    query(&statement).execute(pool).await?;
+                }
+                Connection::MySql(pool) => {
+                    query(&statement).execute(pool).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn migrator(&self) -> &'static Migrator {
+        migrator(&self.driver())
+    }
+}
+
+fn migrator(driver: &Driver) -> &'static Migrator {
+    match driver {
+        Driver::Sqlite3 => &SQLITE_MIGRATOR,
+        Driver::Mysql => &MYSQL_MIGRATOR,
+    }
+}
+
+/// It returns the number of rows of each table, for the dry-run reports.
+///
+/// # Errors
+///
+/// It returns an error if the query fails.
+pub async fn count_rows(connection: &Connection, tables: &[&str]) -> Result<Vec<(String, i64)>, Error> {
+    let mut counts = vec![];
+
+    for table in tables {
+        let statement = format!("SELECT COUNT(*) FROM {table}");
+
+        let (count,): (i64,) = match connection {
+            Connection::Sqlite(pool) => query_as(&statement).fetch_one(pool).await?,
+            Connection::MySql(pool) => query_as(&statement).fetch_one(pool).await?,
+        };
+
+        counts.push(((*table).to_string(), count));
+    }
+
+    Ok(counts)
+}
@@ -42,15 +42,9 @@ pub async fn transfer_torrents(
 
         let filepath = format!("{}/{}.torrent", upload_path, &torrent.torrent_id);
 
-        let torrent_from_file_result = read_torrent_from_file(&filepath);
-
-        assert!(
-            torrent_from_file_result.is_ok(),
-            "Error torrent file not found: {:?}",
-            &filepath
-        );
-
-        let torrent_from_file = torrent_from_file_result.unwrap();
+        // The error is not `Send`, so it can't be kept across the awaits.
+        let torrent_from_file =
+            read_torrent_from_file(&filepath).unwrap_or_else(|_| panic!("Error torrent file not found: {:?}", &filepath));
 
         let id = target_database
             .insert_torrent(&TorrentRecordV2::from_v1_data(torrent, &torrent_from_file.info, &uploader))
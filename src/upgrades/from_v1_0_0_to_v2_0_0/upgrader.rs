@@ -1,29 +1,26 @@
-//! A console command to upgrade the application from version `v1.0.0` to `v2.0.0`.
+//! Upgrade from version `v1.0.0` to `v2.0.0`.
 //!
-//! # Usage
+//! It's the first step of the [`upgrade`](crate::upgrades::upgrader) command.
+//! For example:
 //!
 //! ```bash
-//! cargo run --bin upgrade SOURCE_DB_FILE TARGET_DB_FILE TORRENT_UPLOAD_DIR
+//! cargo run --bin upgrade -- --source ./data.db --target ./data_v2.db --upload-dir ./uploads --to v2.0.0
 //! ```
 //!
 //! Where:
 //!
-//! - `SOURCE_DB_FILE` is the source database in version `v1.0.0` we want to migrate.
-//! - `TARGET_DB_FILE` is the new migrated database in version `v2.0.0`.
-//! - `TORRENT_UPLOAD_DIR` is the relative dir where torrent files are stored.
+//! - `--source` is the source database in version `v1.0.0` we want to migrate.
+//! - `--target` is the new migrated database in version `v2.0.0`.
+//! - `--upload-dir` is the relative dir where torrent files are stored.
 //!
-//! For example:
+//! Only `SQLite` databases are supported, like in version `v1.0.0`.
 //!
-//! ```bash
-//! cargo run --bin upgrade ./data.db ./data_v2.db ./uploads
-//! ```
-//!
-//! This command was created to help users to migrate from version `v1.0.0` to
+//! This step was created to help users to migrate from version `v1.0.0` to
 //! `v2.0.0`. The main changes in version `v2.0.0` were:
 //!
 //! - The database schema was changed.
 //! - The torrents are now stored entirely in the database. The torrent files
-//!   are not stored in the filesystem anymore. This command reads the torrent
+//!   are not stored in the filesystem anymore. This step reads the torrent
 //!   files from the filesystem and store them in the database.
 //!
 //! We recommend to download your production database and the torrent files dir.
@@ -46,19 +43,27 @@
 //!
 //!
 //! If you want more information about this command you can read the [issue 56](https://github.com/torrust/torrust-index/issues/56).
-use std::env;
+use std::sync::Arc;
 use std::time::SystemTime;
 
+use async_trait::async_trait;
 use chrono::prelude::{DateTime, Utc};
 use text_colorizer::Colorize;
 
-use crate::upgrades::from_v1_0_0_to_v2_0_0::databases::{current_db, migrate_target_database, new_db, truncate_target_database};
+use crate::databases::database::{get_driver, Driver};
+use crate::upgrades::database::count_rows;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::databases::sqlite_v1_0_0::SqliteDatabaseV1_0_0;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::databases::sqlite_v2_0_0::SqliteDatabaseV2_0_0;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::databases::{migrate_target_database, truncate_target_database};
 use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrers::category_transferrer::transfer_categories;
 use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrers::torrent_transferrer::transfer_torrents;
 use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrers::tracker_key_transferrer::transfer_tracker_keys;
 use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrers::user_transferrer::transfer_users;
-
-const NUMBER_OF_ARGUMENTS: usize = 3;
+use crate::upgrades::report::Check;
+use crate::upgrades::step::{Checkpoints, Context, Kind, UpgradeStep};
+use crate::upgrades::upgrader::{self, Options};
+use crate::upgrades::version::Version;
+use crate::upgrades::Error;
 
 #[derive(Debug)]
 pub struct Arguments {
@@ -70,78 +75,221 @@ pub struct Arguments {
     pub upload_path: String,
 }
 
-fn print_usage() {
-    eprintln!(
-        "{} - migrates date from version v1.0.0 to v2.0.0.
+/// It upgrades the `SQLite` database files from version `v1.0.0` to `v2.0.0`.
+///
+/// # Panics
+///
+/// It panics if the upgrade fails.
+pub async fn upgrade(args: &Arguments, date_imported: &str) {
+    upgrader::upgrade(
+        &Options {
+            source: args.source_database_file.clone(),
+            target: Some(args.target_database_file.clone()),
+            upload_dir: Some(args.upload_path.clone()),
+            to: Some(Version::V2_0_0),
+            dry_run: false,
+            resume: false,
+        },
+        date_imported,
+    )
+    .await
+    .expect("the database should be upgraded from version v1.0.0 to v2.0.0");
+}
+
+/// Current datetime in ISO8601 without time zone.
+/// For example: `2022-11-10 10:35:15`
+#[must_use]
+pub fn datetime_iso_8601() -> String {
+    let dt: DateTime<Utc> = SystemTime::now().into();
+    format!("{}", dt.format("%Y-%m-%d %H:%M:%S"))
+}
 
-        cargo run --bin upgrade SOURCE_DB_FILE TARGET_DB_FILE TORRENT_UPLOAD_DIR
+/// The phases of the upgrade, in the order they are executed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Phase {
+    /// It creates the target schema and removes all the target data.
+    Prepare,
+    Categories,
+    Users,
+    TrackerKeys,
+    Torrents,
+}
 
-        For example:
+impl Phase {
+    const ALL: [Phase; 5] = [
+        Phase::Prepare,
+        Phase::Categories,
+        Phase::Users,
+        Phase::TrackerKeys,
+        Phase::Torrents,
+    ];
 
-        cargo run --bin upgrade ./data.db ./data_v2.db ./uploads
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Prepare => "prepare",
+            Phase::Categories => "categories",
+            Phase::Users => "users",
+            Phase::TrackerKeys => "tracker_keys",
+            Phase::Torrents => "torrents",
+        }
+    }
 
-        ",
-        "Upgrader".green()
-    );
+    /// The target tables written in the phase, in the order they have to be
+    /// cleared.
+    fn tables(self) -> &'static [&'static str] {
+        match self {
+            Phase::Prepare => &[],
+            Phase::Categories => &["torrust_categories"],
+            Phase::Users => &["torrust_user_authentication", "torrust_user_profiles", "torrust_users"],
+            Phase::TrackerKeys => &["torrust_tracker_keys"],
+            Phase::Torrents => &[
+                "torrust_torrent_announce_urls",
+                "torrust_torrent_files",
+                "torrust_torrent_info",
+                "torrust_torrents",
+            ],
+        }
+    }
 }
 
-fn parse_args() -> Arguments {
-    let args: Vec<String> = env::args().skip(1).collect();
+pub struct Step;
 
-    if args.len() != NUMBER_OF_ARGUMENTS {
-        eprintln!(
-            "{} wrong number of arguments: expected {}, got {}",
-            "Error".red().bold(),
-            NUMBER_OF_ARGUMENTS,
-            args.len()
-        );
-        print_usage();
+#[async_trait]
+impl UpgradeStep for Step {
+    fn source_version(&self) -> Version {
+        Version::V1_0_0
     }
 
-    Arguments {
-        source_database_file: args[0].clone(),
-        target_database_file: args[1].clone(),
-        upload_path: args[2].clone(),
+    fn target_version(&self) -> Version {
+        Version::V2_0_0
     }
-}
 
-pub async fn run() {
-    let now = datetime_iso_8601();
-    upgrade(&parse_args(), &now).await;
-}
+    fn kind(&self) -> Kind {
+        Kind::Transfer
+    }
 
-pub async fn upgrade(args: &Arguments, date_imported: &str) {
-    // Get connection to the source database (current DB in settings)
-    let source_database = current_db(&args.source_database_file).await;
+    fn description(&self) -> &'static str {
+        "copies the data into the new schema and imports the torrent files into the database"
+    }
 
-    // Get connection to the target database (new DB we want to migrate the data)
-    let target_database = new_db(&args.target_database_file).await;
+    fn requires_upload_path(&self) -> bool {
+        true
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        vec![
+            Check::copied(("torrust_categories", "name"), ("torrust_categories", "name")),
+            Check::copied(("torrust_users", "username"), ("torrust_user_profiles", "username")),
+            Check::copied(("torrust_users", "email"), ("torrust_user_profiles", "email")),
+            Check::copied(
+                ("torrust_users", "password"),
+                ("torrust_user_authentication", "password_hash"),
+            ),
+            Check::copied(("torrust_tracker_keys", "key"), ("torrust_tracker_keys", "tracker_key")),
+            Check::copied(("torrust_torrents", "torrent_id"), ("torrust_torrents", "torrent_id")),
+            Check::copied(("torrust_torrents", "title"), ("torrust_torrent_info", "title")),
+        ]
+    }
+
+    async fn plan(&self, context: &Context) -> Result<Vec<String>, Error> {
+        self.check_drivers(context)?;
+
+        let mut plan: Vec<String> = count_rows(
+            &context.source,
+            &[
+                "torrust_categories",
+                "torrust_users",
+                "torrust_tracker_keys",
+                "torrust_torrents",
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(|(table, rows)| format!("copy {rows} rows from {table}"))
+        .collect();
+
+        if let Some(upload_path) = &context.upload_path {
+            plan.push(format!("import the torrent files in {upload_path}"));
+        }
+
+        Ok(plan)
+    }
+
+    async fn run(&self, context: &Context, checkpoints: &mut Checkpoints) -> Result<(), Error> {
+        self.check_drivers(context)?;
+
+        let Some(upload_path) = &context.upload_path else {
+            return Err(Error::UploadDirRequired { step: self.name() });
+        };
+
+        let pending_phases: Vec<Phase> = Phase::ALL
+            .into_iter()
+            .filter(|phase| !checkpoints.is_completed(phase.name()))
+            .collect();
+
+        // Data from a phase that was interrupted is removed before running it
+        // again, together with the data of the phases that depend on it. The
+        // prepare phase removes all the data.
+        if !pending_phases.contains(&Phase::Prepare) {
+            for phase in pending_phases.iter().rev() {
+                context.target.delete_all_rows(phase.tables()).await?;
+            }
+        }
+
+        let source_database = Arc::new(SqliteDatabaseV1_0_0::new(&context.source_url).await);
+        let target_database = Arc::new(SqliteDatabaseV2_0_0::new(&context.target_url).await);
 
-    println!("Upgrading data from version v1.0.0 to v2.0.0 ...");
+        println!("Upgrading data from version v1.0.0 to v2.0.0 ...");
 
-    migrate_target_database(target_database.clone()).await;
-    truncate_target_database(target_database.clone()).await;
+        for phase in Phase::ALL {
+            if !pending_phases.contains(&phase) {
+                println!("Skipping the completed phase: {}", phase.name());
+                continue;
+            }
 
-    transfer_categories(source_database.clone(), target_database.clone()).await;
-    transfer_users(source_database.clone(), target_database.clone(), date_imported).await;
-    transfer_tracker_keys(source_database.clone(), target_database.clone()).await;
-    transfer_torrents(source_database.clone(), target_database.clone(), &args.upload_path).await;
+            match phase {
+                Phase::Prepare => {
+                    migrate_target_database(target_database.clone()).await;
+                    truncate_target_database(target_database.clone()).await;
+                }
+                Phase::Categories => transfer_categories(source_database.clone(), target_database.clone()).await,
+                Phase::Users => transfer_users(source_database.clone(), target_database.clone(), &context.date_imported).await,
+                Phase::TrackerKeys => transfer_tracker_keys(source_database.clone(), target_database.clone()).await,
+                Phase::Torrents => transfer_torrents(source_database.clone(), target_database.clone(), upload_path).await,
+            }
 
-    println!("Upgrade data from version v1.0.0 to v2.0.0 finished!\n");
+            checkpoints.complete(&context.target, phase.name()).await?;
+        }
 
-    eprintln!(
-        "{}\nWe recommend you to run the command to import torrent statistics for all torrents manually. \
-         If you do not do it the statistics will be imported anyway during the normal execution of the program. \
-         You can import statistics manually with:\n {}",
-        "SUGGESTION: \n".yellow(),
-        "cargo run --bin import_tracker_statistics".yellow()
-    );
+        println!("Upgrade data from version v1.0.0 to v2.0.0 finished!\n");
+
+        eprintln!(
+            "{}\nWe recommend you to run the command to import torrent statistics for all torrents manually. \
+             If you do not do it the statistics will be imported anyway during the normal execution of the program. \
+             You can import statistics manually with:\n {}",
+            "SUGGESTION: \n".yellow(),
+            "cargo run --bin import_tracker_statistics".yellow()
+        );
+
+        Ok(())
+    }
 }
 
-/// Current datetime in ISO8601 without time zone.
-/// For example: `2022-11-10 10:35:15`
-#[must_use]
-pub fn datetime_iso_8601() -> String {
-    let dt: DateTime<Utc> = SystemTime::now().into();
-    format!("{}", dt.format("%Y-%m-%d %H:%M:%S"))
+impl Step {
+    /// The `v1.0.0` transferrers only support `SQLite` databases.
+    fn check_drivers(&self, context: &Context) -> Result<(), Error> {
+        for database_url in [&context.source_url, &context.target_url] {
+            match get_driver(database_url) {
+                Ok(Driver::Sqlite3) => {}
+                driver => {
+                    return Err(Error::UnsupportedDriver {
+                        step: self.name(),
+                        driver: driver.map_or_else(|_| (*database_url).clone(), |driver| format!("{driver:?}")),
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
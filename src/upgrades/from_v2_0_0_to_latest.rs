@@ -0,0 +1,67 @@
+//! Upgrade from version `v2.0.0` to the latest schema.
+//!
+//! Since `v2.0.0` the schema changes are made with `sqlx` migrations, which
+//! the Index also runs when it starts. This step applies the pending
+//! migrations in place, so the database can be upgraded and verified before
+//! deploying the new build. It supports `SQLite` and `MySQL` databases.
+//!
+//! Some migrations normalize the existing data, like the one converting the
+//! info-hashes to lowercase, so the verification is only informative.
+use async_trait::async_trait;
+
+use super::report::Check;
+use super::step::{Checkpoints, Context, Kind, UpgradeStep};
+use super::version::Version;
+use super::Error;
+
+const MIGRATIONS_PHASE: &str = "migrations";
+
+pub struct Step;
+
+#[async_trait]
+impl UpgradeStep for Step {
+    fn source_version(&self) -> Version {
+        Version::V2_0_0
+    }
+
+    fn target_version(&self) -> Version {
+        Version::Latest
+    }
+
+    fn kind(&self) -> Kind {
+        Kind::InPlace
+    }
+
+    fn description(&self) -> &'static str {
+        "applies the pending migrations"
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        vec![
+            Check::unchanged("torrust_categories", "name"),
+            Check::unchanged("torrust_users", "user_id"),
+            Check::unchanged("torrust_tracker_keys", "tracker_key"),
+            Check::unchanged("torrust_torrents", "info_hash"),
+        ]
+    }
+
+    async fn plan(&self, context: &Context) -> Result<Vec<String>, Error> {
+        Ok(context
+            .target
+            .pending_migrations()
+            .await?
+            .into_iter()
+            .map(|migration| format!("apply migration {} {}", migration.version, migration.description))
+            .collect())
+    }
+
+    async fn run(&self, context: &Context, checkpoints: &mut Checkpoints) -> Result<(), Error> {
+        // Migrations are recorded by `sqlx`, so an interrupted upgrade only
+        // applies the missing ones.
+        println!("Applying the pending migrations ...");
+
+        context.target.run_migrations().await?;
+
+        checkpoints.complete(&context.target, MIGRATIONS_PHASE).await
+    }
+}
@@ -1 +1,83 @@
+//! Database upgrades between Index releases.
+//!
+//! Each release that changes the database schema in a way the `sqlx`
+//! migrations can't handle provides an [`UpgradeStep`](step::UpgradeStep)
+//! from the previous release. The steps are registered in the
+//! [`Registry`](registry::Registry) and the [`upgrader`] chains them to go
+//! from the detected [`Version`](version::Version) of the source database to
+//! the requested one.
+//!
+//! There are two kinds of steps:
+//!
+//! - Transfer steps copy the data from the source database into a new target
+//!   database. For example, the [`v1.0.0` to `v2.0.0`](from_v1_0_0_to_v2_0_0)
+//!   upgrade.
+//! - In-place steps change the target database. For example, the
+//!   [`v2.0.0` to latest](from_v2_0_0_to_latest) upgrade, which only applies
+//!   the pending migrations.
+//!
+//! Adding the upgrade for a new release only requires a new module with the
+//! step and registering it in the default [`Registry`](registry::Registry).
+pub mod database;
 pub mod from_v1_0_0_to_v2_0_0;
+pub mod from_v2_0_0_to_latest;
+pub mod registry;
+pub mod report;
+pub mod step;
+pub mod upgrader;
+pub mod version;
+
+use derive_more::{Display, Error};
+
+use self::version::Version;
+
+#[derive(Debug, Display, Error)]
+pub enum Error {
+    #[display(fmt = "the database is empty")]
+    EmptyDatabase,
+
+    #[display(fmt = "the database schema does not belong to any known Index release")]
+    UnknownSchema,
+
+    #[display(fmt = "the database has migration {last_migration}, which is newer than this upgrader")]
+    NewerSchema { last_migration: i64 },
+
+    #[display(fmt = "unsupported database URL `{url}`. Expected a `sqlite://` or `mysql://` URL")]
+    UnsupportedDatabaseUrl { url: String },
+
+    #[display(fmt = "the {step} upgrade does not support {driver} databases")]
+    UnsupportedDriver { step: String, driver: String },
+
+    #[display(fmt = "there is no upgrade path from {from} to {to}")]
+    NoUpgradePath { from: Version, to: Version },
+
+    #[display(fmt = "the {step} upgrade copies the data into a new database. Use `--target` to set it")]
+    TargetRequired { step: String },
+
+    #[display(fmt = "the {step} upgrade changes the database in place. Remove `--target` or use a copy of the source database")]
+    InPlaceTargetNotSupported { step: String },
+
+    #[display(fmt = "the {step} upgrade needs the torrent files. Use `--upload-dir` to set their directory")]
+    UploadDirRequired { step: String },
+
+    #[display(fmt = "the verification of the {step} upgrade failed")]
+    VerificationFailed { step: String },
+
+    #[display(fmt = "database error: {_0}")]
+    Database(sqlx::Error),
+
+    #[display(fmt = "migration error: {_0}")]
+    Migration(sqlx::migrate::MigrateError),
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl From<sqlx::migrate::MigrateError> for Error {
+    fn from(e: sqlx::migrate::MigrateError) -> Self {
+        Error::Migration(e)
+    }
+}
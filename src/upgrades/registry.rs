@@ -0,0 +1,93 @@
+//! The available upgrade steps.
+use super::from_v1_0_0_to_v2_0_0::upgrader::Step as FromV1_0_0ToV2_0_0;
+use super::from_v2_0_0_to_latest::Step as FromV2_0_0ToLatest;
+use super::step::UpgradeStep;
+use super::version::Version;
+use super::Error;
+
+pub struct Registry {
+    steps: Vec<Box<dyn UpgradeStep>>,
+}
+
+impl Default for Registry {
+    /// All the upgrades between Index releases.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(FromV1_0_0ToV2_0_0));
+        registry.register(Box::new(FromV2_0_0ToLatest));
+        registry
+    }
+}
+
+impl Registry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { steps: vec![] }
+    }
+
+    pub fn register(&mut self, step: Box<dyn UpgradeStep>) {
+        self.steps.push(step);
+    }
+
+    /// The steps to upgrade a database from one release to another, in the
+    /// order they have to be executed. It's empty when the database is
+    /// already in the requested release.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if there isn't a chain of steps between the two
+    /// releases. Downgrades are not supported.
+    pub fn plan(&self, from: Version, to: Version) -> Result<Vec<&dyn UpgradeStep>, Error> {
+        let mut plan = vec![];
+        let mut current = from;
+
+        while current < to {
+            let Some(step) = self
+                .steps
+                .iter()
+                .filter(|step| step.source_version() == current && step.target_version() <= to)
+                .max_by_key(|step| step.target_version())
+            else {
+                return Err(Error::NoUpgradePath { from, to });
+            };
+
+            plan.push(step.as_ref());
+            current = step.target_version();
+        }
+
+        if current != to {
+            return Err(Error::NoUpgradePath { from, to });
+        }
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::upgrades::version::Version;
+
+    #[test]
+    fn it_should_chain_the_steps_between_two_releases() {
+        let registry = Registry::default();
+
+        let plan = registry.plan(Version::V1_0_0, Version::Latest).unwrap();
+
+        assert_eq!(
+            plan.iter().map(|step| step.name()).collect::<Vec<_>>(),
+            vec!["v1.0.0-to-v2.0.0", "v2.0.0-to-latest"]
+        );
+    }
+
+    #[test]
+    fn the_plan_should_be_empty_when_the_database_is_already_in_the_requested_release() {
+        assert!(Registry::default().plan(Version::Latest, Version::Latest).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_should_not_plan_downgrades_or_upgrades_without_steps() {
+        assert!(Registry::default().plan(Version::Latest, Version::V1_0_0).is_err());
+        assert!(Registry::new().plan(Version::V1_0_0, Version::V2_0_0).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Verification of the upgraded data.
+//!
+//! Each step declares the columns whose values must be the same before and
+//! after the upgrade. The upgrader compares the number of rows and the
+//! checksum of the values of each pair of columns.
+use std::fmt;
+
+use super::database::ColumnStats;
+
+/// A column to compare before and after the upgrade.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Check {
+    pub source_table: &'static str,
+    pub source_column: &'static str,
+    pub target_table: &'static str,
+    pub target_column: &'static str,
+    /// Strict checks make the upgrade fail when the values are different.
+    /// The others are only informative, for example, when the step is
+    /// expected to normalize the values.
+    pub strict: bool,
+}
+
+impl Check {
+    /// A strict check of a column that was copied into another table.
+    #[must_use]
+    pub fn copied(source: (&'static str, &'static str), target: (&'static str, &'static str)) -> Self {
+        Self {
+            source_table: source.0,
+            source_column: source.1,
+            target_table: target.0,
+            target_column: target.1,
+            strict: true,
+        }
+    }
+
+    /// An informative check of a column that the upgrade may change.
+    #[must_use]
+    pub fn unchanged(table: &'static str, column: &'static str) -> Self {
+        Self {
+            source_table: table,
+            source_column: column,
+            target_table: table,
+            target_column: column,
+            strict: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CheckReport {
+    pub check: Check,
+    pub before: ColumnStats,
+    pub after: ColumnStats,
+}
+
+impl CheckReport {
+    #[must_use]
+    pub fn matches(&self) -> bool {
+        self.before == self.after
+    }
+
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        !self.check.strict || self.matches()
+    }
+}
+
+/// The result of verifying a step.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VerificationReport {
+    pub step: String,
+    pub checks: Vec<CheckReport>,
+}
+
+impl VerificationReport {
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(CheckReport::is_ok)
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Verification of the {} upgrade:", self.step)?;
+
+        for report in &self.checks {
+            let status = match (report.matches(), report.check.strict) {
+                (true, _) => "ok",
+                (false, true) => "FAILED",
+                (false, false) => "changed",
+            };
+
+            writeln!(
+                f,
+                "  [{status}] {}.{} ({} rows, {}) -> {}.{} ({} rows, {})",
+                report.check.source_table,
+                report.check.source_column,
+                report.before.rows,
+                &report.before.checksum[..12],
+                report.check.target_table,
+                report.check.target_column,
+                report.after.rows,
+                &report.after.checksum[..12],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, CheckReport, VerificationReport};
+    use crate::upgrades::database::ColumnStats;
+
+    fn stats(rows: i64, checksum: &str) -> ColumnStats {
+        ColumnStats {
+            rows,
+            checksum: checksum.repeat(64),
+        }
+    }
+
+    fn report(check: Check, before: ColumnStats, after: ColumnStats) -> VerificationReport {
+        VerificationReport {
+            step: "v1.0.0-to-v2.0.0".to_string(),
+            checks: vec![CheckReport { check, before, after }],
+        }
+    }
+
+    #[test]
+    fn a_strict_check_should_fail_when_the_rows_or_the_values_are_different() {
+        let check = Check::copied(("torrust_users", "username"), ("torrust_user_profiles", "username"));
+
+        assert!(report(check.clone(), stats(2, "a"), stats(2, "a")).is_ok());
+        assert!(!report(check.clone(), stats(2, "a"), stats(1, "a")).is_ok());
+        assert!(!report(check, stats(2, "a"), stats(2, "b")).is_ok());
+    }
+
+    #[test]
+    fn an_informative_check_should_not_fail_when_the_values_are_different() {
+        let check = Check::unchanged("torrust_torrents", "info_hash");
+
+        assert!(report(check, stats(2, "a"), stats(1, "b")).is_ok());
+    }
+}
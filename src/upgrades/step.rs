@@ -0,0 +1,119 @@
+//! The upgrade from one Index release to the next one.
+use async_trait::async_trait;
+
+use super::database::Connection;
+use super::report::Check;
+use super::version::Version;
+use super::Error;
+
+/// How a step upgrades the data.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Kind {
+    /// The data is copied from the source database into the target database.
+    Transfer,
+    /// The target database is changed.
+    InPlace,
+}
+
+/// Everything a step needs to upgrade the data.
+pub struct Context {
+    pub source: Connection,
+    /// The `sqlx` URL of the source database.
+    pub source_url: String,
+    /// For in-place upgrades, it's the same database as the source.
+    pub target: Connection,
+    /// The `sqlx` URL of the target database.
+    pub target_url: String,
+    /// The directory with the torrent files, only for the releases that stored
+    /// them outside the database.
+    pub upload_path: Option<String>,
+    /// The date in ISO 8601 format without time zone used for the imported
+    /// records.
+    pub date_imported: String,
+}
+
+#[async_trait]
+pub trait UpgradeStep: Sync + Send {
+    /// The release the step upgrades from.
+    fn source_version(&self) -> Version;
+
+    /// The release the step upgrades to.
+    fn target_version(&self) -> Version;
+
+    fn kind(&self) -> Kind;
+
+    /// A short description of the changes made by the step.
+    fn description(&self) -> &'static str;
+
+    /// The name used in the reports and to record the step progress.
+    fn name(&self) -> String {
+        format!("{}-to-{}", self.source_version(), self.target_version())
+    }
+
+    fn requires_upload_path(&self) -> bool {
+        false
+    }
+
+    /// The columns used to verify the upgraded data.
+    ///
+    /// For transfer steps, the source columns are read from the source
+    /// database. For in-place steps, both columns are read from the target
+    /// database, before and after the upgrade.
+    fn checks(&self) -> Vec<Check>;
+
+    /// It describes the changes the step would make, without making them.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the databases can't be read.
+    async fn plan(&self, context: &Context) -> Result<Vec<String>, Error>;
+
+    /// It upgrades the data.
+    ///
+    /// Steps are made of phases. Completed phases are recorded in the
+    /// checkpoints, so a failed upgrade can be resumed from the first phase
+    /// that was not completed.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the data can't be upgraded.
+    async fn run(&self, context: &Context, checkpoints: &mut Checkpoints) -> Result<(), Error>;
+}
+
+/// The phases of a step already completed in the target database.
+pub struct Checkpoints {
+    step: String,
+    completed_phases: Vec<String>,
+    date: String,
+}
+
+impl Checkpoints {
+    /// It loads the completed phases of the step from the target database.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the checkpoints can't be read.
+    pub async fn load(step: &str, target: &Connection, date: &str) -> Result<Self, Error> {
+        Ok(Self {
+            step: step.to_string(),
+            completed_phases: target.completed_phases(step).await?,
+            date: date.to_string(),
+        })
+    }
+
+    #[must_use]
+    pub fn is_completed(&self, phase: &str) -> bool {
+        self.completed_phases.iter().any(|completed| completed == phase)
+    }
+
+    /// It records the phase as completed in the target database.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if the checkpoint can't be written.
+    pub async fn complete(&mut self, target: &Connection, phase: &str) -> Result<(), Error> {
+        target.complete_phase(&self.step, phase, &self.date).await?;
+        self.completed_phases.push(phase.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Versions of the Index database schema.
+//!
+//! Since `v2.0.0` the schema is managed with `sqlx` migrations, so the version
+//! of a database is given by the last migration applied. Version `v1.0.0` did
+//! not use migrations and it's detected by its tables.
+//!
+//! The migrations added after the last release are not part of any release
+//! yet, so a database with them is in the [`Latest`](Version::Latest)
+//! version, the schema of this build.
+use std::fmt;
+use std::str::FromStr;
+
+use super::database::{self, Connection};
+use super::Error;
+use crate::databases::database::Driver;
+
+/// The last migration included in the `v2.0.0` release.
+const V2_0_0_LAST_MIGRATION: i64 = 20_221_109_095_718;
+
+/// Index releases with a different database schema.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Version {
+    V1_0_0,
+    V2_0_0,
+    /// The schema of this build, with all the migrations embedded in it. It's
+    /// not a release.
+    Latest,
+}
+
+impl Version {
+    pub const ALL: [Version; 3] = [Version::V1_0_0, Version::V2_0_0, Version::Latest];
+
+    /// The last migration included in the version. Version `v1.0.0` did not
+    /// use migrations.
+    #[must_use]
+    pub fn last_migration(self, driver: &Driver) -> Option<i64> {
+        match self {
+            Version::V1_0_0 => None,
+            Version::V2_0_0 => Some(V2_0_0_LAST_MIGRATION),
+            Version::Latest => Some(database::latest_migration(driver)),
+        }
+    }
+
+    /// It detects the schema version of a database.
+    ///
+    /// A database with some, but not all, the migrations of a release is
+    /// detected as the previous release, so upgrading it applies the missing
+    /// migrations.
+    ///
+    /// # Errors
+    ///
+    /// It returns an error if:
+    ///
+    /// - The database is empty.
+    /// - The schema does not belong to any Index release.
+    /// - The database has migrations newer than the ones in this build.
+    /// - There is a database error.
+    pub async fn detect(connection: &Connection) -> Result<Version, Error> {
+        let tables = connection.tables().await?;
+
+        if tables.is_empty() {
+            return Err(Error::EmptyDatabase);
+        }
+
+        // `v1.0.0` also used migrations, but they were replaced in `v2.0.0`.
+        // In `v1.0.0` the password hashes were stored in the users table.
+        if tables.iter().any(|table| table == "torrust_users")
+            && connection
+                .columns("torrust_users")
+                .await?
+                .iter()
+                .any(|column| column == "password")
+        {
+            return Ok(Version::V1_0_0);
+        }
+
+        let Some(last_migration) = connection
+            .applied_migrations()
+            .await?
+            .and_then(|versions| versions.into_iter().max())
+        else {
+            return Err(Error::UnknownSchema);
+        };
+
+        let driver = connection.driver();
+
+        if last_migration > database::latest_migration(&driver) {
+            return Err(Error::NewerSchema { last_migration });
+        }
+
+        Ok(Version::ALL
+            .into_iter()
+            .rev()
+            .find(|version| version.last_migration(&driver).is_some_and(|last| last <= last_migration))
+            // Databases with only some of the `v2.0.0` migrations are
+            // upgraded by applying the missing ones.
+            .unwrap_or(Version::V2_0_0))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Version::V1_0_0 => write!(f, "v1.0.0"),
+            Version::V2_0_0 => write!(f, "v2.0.0"),
+            Version::Latest => write!(f, "latest"),
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.strip_prefix('v').unwrap_or(s);
+
+        Version::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string().trim_start_matches('v') == version)
+            .ok_or_else(|| format!("unknown version: {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::Version;
+    use crate::databases::database::Driver;
+
+    #[test]
+    fn it_should_parse_versions_with_and_without_the_v_prefix() {
+        assert_eq!(Version::from_str("v2.0.0"), Ok(Version::V2_0_0));
+        assert_eq!(Version::from_str("1.0.0"), Ok(Version::V1_0_0));
+        assert_eq!(Version::from_str("latest"), Ok(Version::Latest));
+    }
+
+    #[test]
+    fn it_should_not_parse_versions_that_are_not_released() {
+        assert!(Version::from_str("v3.0.0").is_err());
+        assert!(Version::from_str("v4.0.0").is_err());
+    }
+
+    #[test]
+    fn the_latest_version_should_include_all_the_migrations_in_the_build() {
+        for driver in [Driver::Sqlite3, Driver::Mysql] {
+            assert!(Version::Latest.last_migration(&driver) > Version::V2_0_0.last_migration(&driver));
+        }
+    }
+}
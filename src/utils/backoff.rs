@@ -0,0 +1,26 @@
+//! Exponential backoff for the deliveries retried in the background.
+
+/// Delay before the next attempt after `attempts` failed ones.
+///
+/// The first retry waits `first_delay` seconds and the delay is doubled for
+/// every following failure, up to `max_delay` seconds.
+#[must_use]
+pub fn retry_delay_in_secs(attempts: i64, first_delay: u64, max_delay: u64) -> u64 {
+    let exponent = u32::try_from(attempts.saturating_sub(1)).unwrap_or(0).min(16);
+
+    first_delay.saturating_mul(2_u64.pow(exponent)).min(max_delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::retry_delay_in_secs;
+
+    #[test]
+    fn the_retry_delay_should_grow_exponentially_up_to_the_max_delay() {
+        assert_eq!(retry_delay_in_secs(1, 30, 3600), 30);
+        assert_eq!(retry_delay_in_secs(2, 30, 3600), 60);
+        assert_eq!(retry_delay_in_secs(3, 30, 3600), 120);
+        assert_eq!(retry_delay_in_secs(8, 30, 3600), 3600);
+        assert_eq!(retry_delay_in_secs(100, 30, 3600), 3600);
+    }
+}
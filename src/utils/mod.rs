@@ -1,4 +1,8 @@
+pub mod backoff;
 pub mod clock;
 pub mod hex;
 pub mod parse_torrent;
+pub mod password_strength;
+pub mod torrent_validation;
 pub mod validation;
+pub mod zip;
@@ -5,9 +5,11 @@ use serde::{Deserialize, Serialize};
 use serde_bencode::value::Value;
 use serde_bencode::{de, Error};
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
 
-use crate::models::info_hash::InfoHash;
+use crate::models::info_hash::{InfoHash, InfoHashV2};
 use crate::models::torrent_file::Torrent;
+use crate::utils::torrent_validation::{self, MAX_FILES, MAX_PATH_DEPTH, MAX_PIECE_LENGTH, MIN_PIECE_LENGTH};
 
 #[derive(Debug, Display, PartialEq, Eq, Error)]
 pub enum DecodeTorrentFileError {
@@ -22,6 +24,38 @@ pub enum DecodeTorrentFileError {
 
     #[display(fmt = "Cannot bencode the parsed `info` dictionary again to generate the info-hash.")]
     CannotBencodeInfoDict,
+
+    #[display(
+        fmt = "Torrent piece length should be between {MIN_PIECE_LENGTH} and {MAX_PIECE_LENGTH} bytes, and a power of two for v2 torrents."
+    )]
+    InvalidPieceLength,
+
+    #[display(fmt = "Torrent number of pieces does not match the total length of the files.")]
+    PieceCountMismatch,
+
+    #[display(fmt = "Torrent info dictionary should have either a `length` key or a non-empty `files` list.")]
+    InvalidFileLayout,
+
+    #[display(fmt = "Torrent has files with a negative length.")]
+    NegativeFileLength,
+
+    #[display(fmt = "Torrent name cannot be empty, `.` or `..`, or contain path separators.")]
+    UnsafeName,
+
+    #[display(fmt = "Torrent file paths cannot be empty or have empty, `.` or `..` components, or path separators.")]
+    UnsafeFilePath,
+
+    #[display(fmt = "Torrent name is not valid UTF-8 and there is no valid `name.utf-8` alternative.")]
+    NameIsNotValidUtf8,
+
+    #[display(fmt = "Torrent file paths are not valid UTF-8 and there is no valid `path.utf-8` alternative.")]
+    PathIsNotValidUtf8,
+
+    #[display(fmt = "Torrent has more than {MAX_FILES} files.")]
+    TooManyFiles,
+
+    #[display(fmt = "Torrent file paths cannot have more than {MAX_PATH_DEPTH} components.")]
+    PathTooDeep,
 }
 
 /// It decodes and validate an array of bytes containing a torrent file.
@@ -33,20 +67,28 @@ pub enum DecodeTorrentFileError {
 ///
 /// # Errors
 ///
-/// This function will return an error if
-///
-/// - The torrent file is not a valid bencoded file.
-/// - The pieces key has a length that is not a multiple of 20.
-pub fn decode_and_validate_torrent_file(bytes: &[u8]) -> Result<(Torrent, InfoHash), DecodeTorrentFileError> {
-    let original_info_hash = calculate_info_hash(bytes)?;
+/// This function will return all the rules the torrent file violates. Refer to
+/// the [`torrent_validation`](crate::utils::torrent_validation) module for the
+/// rules.
+pub fn decode_and_validate_torrent_file(bytes: &[u8]) -> Result<(Torrent, InfoHash), Vec<DecodeTorrentFileError>> {
+    let original_info_hash = calculate_info_hash(bytes).map_err(|err| vec![err])?;
 
-    let torrent = decode_torrent(bytes).map_err(|_| DecodeTorrentFileError::InvalidBencodeData)?;
+    let mut metainfo: Value = de::from_bytes(bytes).map_err(|_| vec![DecodeTorrentFileError::InvalidBencodeData])?;
 
-    // Make sure that the pieces key has a length that is a multiple of 20
-    if let Some(pieces) = torrent.info.pieces.as_ref() {
-        if pieces.as_ref().len() % 20 != 0 {
-            return Err(DecodeTorrentFileError::InvalidTorrentPiecesLength);
-        }
+    let violations = torrent_validation::use_utf8_alternatives(&mut metainfo);
+
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+
+    let metainfo_bytes = serde_bencode::to_bytes(&metainfo).map_err(|_| vec![DecodeTorrentFileError::InvalidBencodeData])?;
+
+    let torrent = decode_torrent(&metainfo_bytes).map_err(|_| vec![DecodeTorrentFileError::InvalidBencodeData])?;
+
+    let violations = torrent_validation::validate_torrent(&torrent);
+
+    if !violations.is_empty() {
+        return Err(violations);
     }
 
     Ok((torrent, original_info_hash))
@@ -89,6 +131,9 @@ struct ParsedInfoDictFromMetainfoFile {
 
 /// Calculates the `InfoHash` from a the torrent file binary data.
 ///
+/// For v2-only torrents (BEP 52) it's the SHA-256 hash of the info dictionary
+/// truncated to 20 bytes.
+///
 /// # Errors
 ///
 /// This function will return an error if:
@@ -105,6 +150,12 @@ pub fn calculate_info_hash(bytes: &[u8]) -> Result<InfoHash, DecodeTorrentFileEr
     // Bencode the info dictionary
     let info_dict_bytes = serde_bencode::to_bytes(&metainfo.info).map_err(|_| DecodeTorrentFileError::CannotBencodeInfoDict)?;
 
+    if is_v2_only_info_dict(&metainfo.info) {
+        let mut hasher = Sha256::new();
+        hasher.update(&info_dict_bytes);
+        return Ok(InfoHashV2::from_bytes(hasher.finalize().as_slice()).truncated());
+    }
+
     // Calculate the SHA-1 hash of the bencoded info dictionary
     let mut hasher = Sha1::new();
     hasher.update(&info_dict_bytes);
@@ -113,6 +164,128 @@ pub fn calculate_info_hash(bytes: &[u8]) -> Result<InfoHash, DecodeTorrentFileEr
     Ok(InfoHash::from_bytes(&result))
 }
 
+/// It replaces the `announce` and `announce-list` keys of a bencoded torrent
+/// file, keeping the rest of the bytes untouched. The `info` dictionary is not
+/// decoded, so the original info-hash is kept even when the dictionary
+/// contains non-standard keys.
+///
+/// The new keys are inserted in sorted position. Keys set to `None` are
+/// removed.
+///
+/// # Errors
+///
+/// This function will return an error if the torrent file is not a valid
+/// bencoded dictionary.
+pub fn rewrite_announce_keys(
+    bytes: &[u8],
+    announce: Option<&String>,
+    announce_list: Option<&Vec<Vec<String>>>,
+) -> Result<Vec<u8>, DecodeTorrentFileError> {
+    let entries = dictionary_entries(bytes).ok_or(DecodeTorrentFileError::InvalidBencodeData)?;
+
+    let mut new_entries: Vec<(&[u8], Vec<u8>)> = vec![];
+
+    if let Some(announce) = announce {
+        let value = serde_bencode::to_bytes(announce).map_err(|_| DecodeTorrentFileError::InvalidBencodeData)?;
+        new_entries.push((b"announce", value));
+    }
+
+    if let Some(announce_list) = announce_list {
+        let value = serde_bencode::to_bytes(announce_list).map_err(|_| DecodeTorrentFileError::InvalidBencodeData)?;
+        new_entries.push((b"announce-list", value));
+    }
+
+    let mut new_entries = new_entries.into_iter().peekable();
+
+    let mut output = Vec::with_capacity(bytes.len());
+
+    output.push(b'd');
+
+    for (key, raw_entry) in entries {
+        if key == b"announce" || key == b"announce-list" {
+            continue;
+        }
+
+        while let Some((new_key, value)) = new_entries.next_if(|(new_key, _)| *new_key < key) {
+            write_dictionary_entry(&mut output, new_key, &value);
+        }
+
+        output.extend_from_slice(raw_entry);
+    }
+
+    for (new_key, value) in new_entries {
+        write_dictionary_entry(&mut output, new_key, &value);
+    }
+
+    output.push(b'e');
+
+    Ok(output)
+}
+
+fn write_dictionary_entry(output: &mut Vec<u8>, key: &[u8], bencoded_value: &[u8]) {
+    output.extend_from_slice(format!("{}:", key.len()).as_bytes());
+    output.extend_from_slice(key);
+    output.extend_from_slice(bencoded_value);
+}
+
+/// It returns the entries of a bencoded dictionary without decoding them.
+/// Each entry contains the key and the raw bytes of the whole entry (key and
+/// value).
+fn dictionary_entries(bytes: &[u8]) -> Option<Vec<(&[u8], &[u8])>> {
+    if bytes.first() != Some(&b'd') {
+        return None;
+    }
+
+    let mut entries = vec![];
+    let mut position = 1;
+
+    while *bytes.get(position)? != b'e' {
+        let key_end = bencoded_value_end(bytes, position)?;
+
+        // Keys must be byte strings: `<length>:<bytes>`
+        let colon = position + bytes[position..key_end].iter().position(|byte| *byte == b':')?;
+
+        let value_end = bencoded_value_end(bytes, key_end)?;
+
+        entries.push((&bytes[colon + 1..key_end], &bytes[position..value_end]));
+
+        position = value_end;
+    }
+
+    Some(entries)
+}
+
+/// It returns the position after the end of the bencoded value starting at
+/// `start`.
+fn bencoded_value_end(bytes: &[u8], start: usize) -> Option<usize> {
+    match bytes.get(start)? {
+        b'i' => Some(start + bytes[start..].iter().position(|byte| *byte == b'e')? + 1),
+        b'l' | b'd' => {
+            let mut position = start + 1;
+            while *bytes.get(position)? != b'e' {
+                position = bencoded_value_end(bytes, position)?;
+            }
+            Some(position + 1)
+        }
+        b'0'..=b'9' => {
+            let colon = start + bytes[start..].iter().position(|byte| *byte == b':')?;
+            let length: usize = std::str::from_utf8(&bytes[start..colon]).ok()?.parse().ok()?;
+            let end = colon.checked_add(1)?.checked_add(length)?;
+            (end <= bytes.len()).then_some(end)
+        }
+        _ => None,
+    }
+}
+
+/// A v2-only info dictionary has `meta version` 2 and no v1 `pieces`.
+fn is_v2_only_info_dict(info: &Value) -> bool {
+    let Value::Dict(info) = info else {
+        return false;
+    };
+
+    matches!(info.get(b"meta version".as_slice()), Some(Value::Int(2))) && !info.contains_key(b"pieces".as_slice())
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -263,4 +436,59 @@ mod tests {
             "8aa01a4c816332045ffec83247ccbc654547fedf".to_string() // DevSkim: ignore DS173237
         );
     }
+
+    #[test]
+    fn it_should_calculate_the_original_info_hash_of_a_v2_only_torrent_truncating_the_v2_info_hash() {
+        let bytes = include_bytes!("../../tests/fixtures/torrents/8a0e8108d017d17e039fab7d2a02ef92daab6e90_v2_only.torrent");
+
+        let original_info_hash = super::calculate_info_hash(bytes).unwrap();
+
+        assert_eq!(
+            original_info_hash,
+            InfoHash::from_str("8a0e8108d017d17e039fab7d2a02ef92daab6e90").unwrap() // DevSkim: ignore DS173237
+        );
+    }
+
+    #[test]
+    fn it_should_rewrite_the_announce_keys_keeping_the_original_info_hash() {
+        let announce = "udp://localhost:6969".to_string();
+        let announce_list = vec![vec![announce.clone()]];
+
+        let bytes =
+            super::rewrite_announce_keys(&torrent_with_custom_info_dict_key(), Some(&announce), Some(&announce_list)).unwrap();
+
+        let torrent = super::decode_torrent(&bytes).unwrap();
+
+        assert_eq!(torrent.announce, Some(announce));
+        assert_eq!(torrent.announce_list, Some(announce_list));
+        assert_eq!(
+            super::calculate_info_hash(&bytes).unwrap(),
+            InfoHash::from_str("6c690018c5786dbbb00161f62b0712d69296df97").unwrap() // DevSkim: ignore DS173237
+        );
+    }
+
+    #[test]
+    fn it_should_insert_the_announce_keys_in_sorted_position() {
+        let bytes = b"d7:comment4:test4:infod4:name1:aee";
+        let announce = "udp://a".to_string();
+
+        let bytes = super::rewrite_announce_keys(bytes, Some(&announce), None).unwrap();
+
+        assert_eq!(bytes, b"d8:announce7:udp://a7:comment4:test4:infod4:name1:aee".to_vec());
+    }
+
+    #[test]
+    fn it_should_remove_the_announce_keys_set_to_none() {
+        let bytes = b"d8:announce7:udp://a13:announce-listll7:udp://aee4:infod4:name1:aee";
+
+        let bytes = super::rewrite_announce_keys(bytes, None, None).unwrap();
+
+        assert_eq!(bytes, b"d4:infod4:name1:aee".to_vec());
+    }
+
+    #[test]
+    fn it_should_fail_rewriting_the_announce_keys_of_invalid_bencoded_data() {
+        assert!(super::rewrite_announce_keys(b"d4:info", None, None).is_err());
+        assert!(super::rewrite_announce_keys(b"l4:infoe", None, None).is_err());
+    }
 }
@@ -0,0 +1,484 @@
+//! Password strength estimation.
+//!
+//! A simplified version of the [zxcvbn](https://github.com/dropbox/zxcvbn)
+//! algorithm. It looks for guessable patterns in the password: common
+//! passwords and words from the user inputs, like the username, also reversed
+//! or with l33t substitutions, keyboard rows, sequences, repeated characters
+//! and recent years.
+//!
+//! The estimated number of guesses is the product of the guesses for the
+//! cheapest sequence of patterns covering the password. Characters outside the
+//! patterns are brute-forced. The score is derived from the number of guesses,
+//! like in zxcvbn:
+//!
+//! Score | Guesses        | Meaning
+//! ------|----------------|---------------------------------------
+//! 0     | < 10^3         | Too guessable, risky password.
+//! 1     | < 10^6         | Very guessable, protection from throttled online attacks.
+//! 2     | < 10^8         | Somewhat guessable, protection from unthrottled online attacks.
+//! 3     | < 10^10        | Safely unguessable, moderate protection from offline attacks.
+//! 4     | >= 10^10       | Very unguessable, strong protection from offline attacks.
+use std::collections::HashMap;
+
+use chrono::{Datelike, Utc};
+use lazy_static::lazy_static;
+
+/// Guesses needed to brute-force one character.
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+
+/// Minimum guesses for a pattern, so that a password made of several
+/// patterns is stronger than any of them.
+const MIN_GUESSES_FOR_PATTERN: f64 = 50.0;
+
+/// Shortest dictionary word, sequence or repetition matched.
+const MIN_PATTERN_LENGTH: usize = 3;
+
+/// Shortest piece of a keyboard row matched.
+const MIN_KEYBOARD_PATTERN_LENGTH: usize = 4;
+
+/// Guesses for each character of a keyboard row: the number of keys it can
+/// start on.
+const KEYBOARD_STARTING_POSITIONS: f64 = 47.0;
+
+/// Years closer than this to the current year are equally guessable.
+const MIN_YEAR_SPACE: f64 = 20.0;
+
+const SCORE_THRESHOLDS: [f64; 4] = [1e3, 1e6, 1e8, 1e10];
+
+const KEYBOARD_ROWS: [&str; 4] = ["`1234567890-=", "qwertyuiop[]\\", "asdfghjkl;'", "zxcvbnm,./"];
+
+/// The most common passwords, sorted by frequency.
+const COMMON_PASSWORDS: [&str; 112] = [
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "2000",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "klaster",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "1111",
+    "zxcvbn",
+    "555555",
+    "11111111",
+    "131313",
+    "freedom",
+    "777777",
+    "pass",
+    "maggie",
+    "159753",
+    "aaaaaa",
+    "ginger",
+    "princess",
+    "joshua",
+    "cheese",
+    "amanda",
+    "summer",
+    "love",
+    "ashley",
+    "nicole",
+    "chelsea",
+    "biteme",
+    "matthew",
+    "access",
+    "yankees",
+    "987654321",
+    "dallas",
+    "austin",
+    "thunder",
+    "taylor",
+    "matrix",
+    "admin",
+    "welcome",
+    "login",
+    "passw0rd",
+    "hello",
+    "secret",
+    "password1",
+    "qwerty123",
+    "123abc",
+    "abcdef",
+    "flower",
+    "lovely",
+    "whatever",
+    "qwe123",
+    "torrent",
+    "torrents",
+    "torrust",
+    "index",
+];
+
+lazy_static! {
+    static ref COMMON_PASSWORD_RANKS: HashMap<&'static str, usize> = COMMON_PASSWORDS
+        .iter()
+        .enumerate()
+        .map(|(index, password)| (*password, index + 1))
+        .collect();
+}
+
+/// A guessable part of the password, from `start` to `end` (exclusive) in
+/// characters.
+#[derive(Debug)]
+struct Match {
+    start: usize,
+    end: usize,
+    guesses: f64,
+}
+
+/// It returns the strength score of the password, from 0 (too guessable) to 4
+/// (very unguessable).
+///
+/// The user inputs are words the attacker could know, like the username or
+/// the email.
+#[must_use]
+pub fn score(password: &str, user_inputs: &[&str]) -> u8 {
+    let guesses = guesses(password, user_inputs);
+
+    SCORE_THRESHOLDS
+        .iter()
+        .fold(0, |score, threshold| if guesses >= *threshold { score + 1 } else { score })
+}
+
+/// It estimates the number of guesses needed to find the password.
+#[must_use]
+pub fn guesses(password: &str, user_inputs: &[&str]) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let lowercase: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+
+    let user_input_ranks: HashMap<String, usize> = user_inputs
+        .iter()
+        .map(|input| input.trim().to_lowercase())
+        .filter(|input| input.chars().count() >= MIN_PATTERN_LENGTH)
+        .enumerate()
+        .map(|(index, input)| (input, index + 1))
+        .collect();
+
+    let mut matches = dictionary_matches(&chars, &lowercase, &user_input_ranks);
+    matches.extend(keyboard_matches(&lowercase));
+    matches.extend(sequence_matches(&chars));
+    matches.extend(repeat_matches(&chars));
+    matches.extend(year_matches(&chars));
+
+    // The minimum guesses to find the first `n` characters.
+    let mut best = vec![1.0_f64; chars.len() + 1];
+
+    for end in 1..=chars.len() {
+        best[end] = best[end - 1] * BRUTEFORCE_CARDINALITY;
+
+        for pattern in matches.iter().filter(|pattern| pattern.end == end) {
+            best[end] = best[end].min(best[pattern.start] * pattern.guesses.max(MIN_GUESSES_FOR_PATTERN));
+        }
+    }
+
+    best[chars.len()]
+}
+
+fn dictionary_matches(chars: &[char], lowercase: &[char], user_input_ranks: &HashMap<String, usize>) -> Vec<Match> {
+    let unleeted: Vec<char> = lowercase.iter().map(|c| unleet(*c)).collect();
+
+    let rank = |word: &str| {
+        COMMON_PASSWORD_RANKS
+            .get(word)
+            .copied()
+            .or_else(|| user_input_ranks.get(word).copied())
+    };
+
+    let mut matches = vec![];
+
+    for start in 0..chars.len() {
+        for end in start + MIN_PATTERN_LENGTH..=chars.len() {
+            let word: String = lowercase[start..end].iter().collect();
+            let reversed: String = lowercase[start..end].iter().rev().collect();
+            let unleeted_word: String = unleeted[start..end].iter().collect();
+
+            let candidates = [
+                (rank(&word), 1.0),
+                (rank(&reversed), 2.0),
+                (
+                    rank(&unleeted_word),
+                    l33t_variations(&lowercase[start..end], &unleeted[start..end]),
+                ),
+            ];
+
+            let Some(guesses) = candidates
+                .iter()
+                .filter_map(|(rank, variations)| rank.map(|rank| to_f64(rank) * variations))
+                .reduce(f64::min)
+            else {
+                continue;
+            };
+
+            matches.push(Match {
+                start,
+                end,
+                guesses: guesses * uppercase_variations(&chars[start..end]),
+            });
+        }
+    }
+
+    matches
+}
+
+/// The longest pieces of the keyboard rows, in both directions.
+fn keyboard_matches(lowercase: &[char]) -> Vec<Match> {
+    let mut matches = vec![];
+
+    for start in 0..lowercase.len() {
+        let mut longest = None;
+
+        for end in start + MIN_KEYBOARD_PATTERN_LENGTH..=lowercase.len() {
+            let piece: String = lowercase[start..end].iter().collect();
+            let reversed: String = lowercase[start..end].iter().rev().collect();
+
+            if KEYBOARD_ROWS.iter().any(|row| row.contains(&piece)) {
+                longest = Some((end, 1.0));
+            } else if KEYBOARD_ROWS.iter().any(|row| row.contains(&reversed)) {
+                longest = Some((end, 2.0));
+            } else {
+                break;
+            }
+        }
+
+        if let Some((end, direction)) = longest {
+            matches.push(Match {
+                start,
+                end,
+                guesses: KEYBOARD_STARTING_POSITIONS * to_f64(end - start) * direction,
+            });
+        }
+    }
+
+    matches
+}
+
+/// Runs of consecutive characters, like `abc` or `987`.
+fn sequence_matches(chars: &[char]) -> Vec<Match> {
+    runs(chars, |previous, next| {
+        let delta = i64::from(u32::from(next)) - i64::from(u32::from(previous));
+        delta == 1 || delta == -1
+    })
+    .into_iter()
+    .map(|(start, end)| {
+        let first = chars[start];
+
+        let base = if matches!(first, 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+            4.0
+        } else if first.is_ascii_digit() {
+            10.0
+        } else {
+            26.0
+        };
+
+        let descending = if chars[start + 1] < first { 2.0 } else { 1.0 };
+
+        Match {
+            start,
+            end,
+            guesses: base * descending * to_f64(end - start),
+        }
+    })
+    .collect()
+}
+
+/// Runs of the same character, like `aaa`.
+fn repeat_matches(chars: &[char]) -> Vec<Match> {
+    runs(chars, |previous, next| previous == next)
+        .into_iter()
+        .map(|(start, end)| Match {
+            start,
+            end,
+            guesses: BRUTEFORCE_CARDINALITY * to_f64(end - start),
+        })
+        .collect()
+}
+
+/// Years from 1900 to 2099. The closer to the current year, the more
+/// guessable.
+fn year_matches(chars: &[char]) -> Vec<Match> {
+    let current_year = f64::from(Utc::now().year());
+
+    chars
+        .windows(4)
+        .enumerate()
+        .filter_map(|(start, window)| {
+            let year: String = window.iter().collect();
+            let year: u32 = year.parse().ok().filter(|year| (1900..=2099).contains(year))?;
+
+            Some(Match {
+                start,
+                end: start + 4,
+                guesses: (f64::from(year) - current_year).abs().max(MIN_YEAR_SPACE),
+            })
+        })
+        .collect()
+}
+
+/// The maximal runs of characters where each pair of consecutive characters
+/// satisfies the condition, with the minimum pattern length.
+fn runs(chars: &[char], condition: impl Fn(char, char) -> bool) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut start = 0;
+
+    for end in 1..=chars.len() {
+        let run_continues = end < chars.len() && condition(chars[end - 1], chars[end]);
+
+        // Sequences must keep the same direction.
+        let same_direction = end < 2 || end >= chars.len() || start + 1 >= end || {
+            (chars[end] > chars[end - 1]) == (chars[end - 1] > chars[end - 2])
+        };
+
+        if !run_continues || !same_direction {
+            if end - start >= MIN_PATTERN_LENGTH {
+                runs.push((start, end));
+            }
+            start = if run_continues { end - 1 } else { end };
+        }
+    }
+
+    runs
+}
+
+/// Common l33t substitutions.
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '8' => 'b',
+        '(' | '{' | '[' | '<' => 'c',
+        '3' => 'e',
+        '6' | '9' => 'g',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        '%' => 'x',
+        '2' => 'z',
+        _ => c,
+    }
+}
+
+/// Guesses multiplier for the substituted characters.
+fn l33t_variations(original: &[char], unleeted: &[char]) -> f64 {
+    let substitutions = original.iter().zip(unleeted).filter(|(a, b)| a != b).count();
+
+    2.0_f64.powi(i32::try_from(substitutions).unwrap_or(i32::MAX))
+}
+
+/// Guesses multiplier for the uppercase letters. Capitalized and all
+/// uppercase words are common, other combinations are counted.
+fn uppercase_variations(word: &[char]) -> f64 {
+    let uppercase = word.iter().filter(|c| c.is_uppercase()).count();
+    let lowercase = word.iter().filter(|c| c.is_lowercase()).count();
+
+    if uppercase == 0 {
+        return 1.0;
+    }
+
+    if lowercase == 0 || (uppercase == 1 && word[0].is_uppercase()) {
+        return 2.0;
+    }
+
+    (1..=uppercase.min(lowercase))
+        .map(|k| binomial(uppercase + lowercase, k))
+        .sum()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (1..=k).fold(1.0, |result, i| result * to_f64(n + 1 - i) / to_f64(i))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn to_f64(value: usize) -> f64 {
+    value as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::score;
+
+    #[test]
+    fn common_passwords_should_be_too_guessable() {
+        assert_eq!(score("password", &[]), 0);
+        assert_eq!(score("P@ssw0rd", &[]), 0);
+        assert_eq!(score("drowssap", &[]), 0);
+        assert_eq!(score("qwerty123", &[]), 0);
+    }
+
+    #[test]
+    fn keyboard_rows_sequences_and_repetitions_should_be_guessable() {
+        assert!(score("asdfghjkl", &[]) <= 1);
+        assert!(score("abcdefghij", &[]) <= 1);
+        assert!(score("zzzzzzzzzz", &[]) <= 1);
+    }
+
+    #[test]
+    fn passwords_with_the_user_inputs_should_be_guessable() {
+        assert!(score("indiana2024", &[]) >= 3);
+        assert!(score("indiana2024", &["indiana", "indiana@example.com"]) <= 1);
+    }
+
+    #[test]
+    fn long_random_passwords_should_be_very_unguessable() {
+        assert_eq!(score("x7#Kq9!vRm2$", &[]), 4);
+        assert_eq!(score("correct horse battery staple", &[]), 4);
+    }
+
+    #[test]
+    fn an_empty_password_should_be_too_guessable() {
+        assert_eq!(score("", &[]), 0);
+    }
+}
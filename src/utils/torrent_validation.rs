@@ -0,0 +1,367 @@
+//! Validation rules for uploaded torrent files.
+//!
+//! The meta-info file is checked in two steps:
+//!
+//! 1. [`use_utf8_alternatives`] works on the raw bencoded value, before it's
+//!    decoded into a [`Torrent`]. Names and paths must be valid UTF-8. When
+//!    they are not, the non-standard `name.utf-8` and `path.utf-8` keys some
+//!    clients add are used instead.
+//! 2. [`validate_torrent`] checks the decoded [`Torrent`]: piece length, piece
+//!    count, file lengths, path safety and limits on the number of files and
+//!    the path depth.
+//!
+//! Both functions return every rule the torrent violates, not only the first
+//! one, so uploaders can fix all the problems at once.
+use serde_bencode::value::Value;
+
+use super::parse_torrent::DecodeTorrentFileError;
+use crate::models::torrent_file::{Torrent, TorrentFile};
+
+/// The minimum piece length: 16 KiB.
+pub const MIN_PIECE_LENGTH: i64 = 16 * 1024;
+
+/// The maximum piece length: 256 MiB.
+pub const MAX_PIECE_LENGTH: i64 = 256 * 1024 * 1024;
+
+/// The maximum number of files in a torrent.
+pub const MAX_FILES: usize = 100_000;
+
+/// The maximum number of components in a file path.
+pub const MAX_PATH_DEPTH: usize = 32;
+
+/// The length of a v1 (SHA-1) piece hash in bytes.
+const PIECE_HASH_LENGTH: usize = 20;
+
+/// It replaces the `name` and `path` values that are not valid UTF-8 with
+/// their `name.utf-8` and `path.utf-8` alternatives, when they are valid.
+///
+/// It returns the violations for the names and paths that can't be fixed,
+/// including the keys of the BEP 52 `file tree`.
+pub fn use_utf8_alternatives(metainfo: &mut Value) -> Vec<DecodeTorrentFileError> {
+    let mut violations = vec![];
+
+    let Value::Dict(metainfo) = metainfo else {
+        return violations;
+    };
+
+    let Some(Value::Dict(info)) = metainfo.get_mut(b"info".as_slice()) else {
+        return violations;
+    };
+
+    if is_non_utf8_value(info.get(b"name".as_slice())) {
+        match info.get(b"name.utf-8".as_slice()) {
+            Some(alternative) if is_utf8_value(Some(alternative)) => {
+                let alternative = alternative.clone();
+                info.insert(b"name".to_vec(), alternative);
+            }
+            _ => add_violation(&mut violations, DecodeTorrentFileError::NameIsNotValidUtf8),
+        }
+    }
+
+    if let Some(Value::List(files)) = info.get_mut(b"files".as_slice()) {
+        for file in files {
+            let Value::Dict(file) = file else {
+                continue;
+            };
+
+            let Some(Value::List(path)) = file.get(b"path".as_slice()) else {
+                continue;
+            };
+
+            if !path.iter().any(|component| is_non_utf8_value(Some(component))) {
+                continue;
+            }
+
+            match file.get(b"path.utf-8".as_slice()) {
+                Some(alternative) if is_utf8_path(Some(alternative)) => {
+                    let alternative = alternative.clone();
+                    file.insert(b"path".to_vec(), alternative);
+                }
+                _ => add_violation(&mut violations, DecodeTorrentFileError::PathIsNotValidUtf8),
+            }
+        }
+    }
+
+    if let Some(file_tree) = info.get(b"file tree".as_slice()) {
+        if !has_utf8_keys(file_tree) {
+            add_violation(&mut violations, DecodeTorrentFileError::PathIsNotValidUtf8);
+        }
+    }
+
+    violations
+}
+
+/// It checks the decoded torrent against all the validation rules.
+///
+/// It returns all the violations found. The list is empty when the torrent is
+/// valid.
+#[must_use]
+pub fn validate_torrent(torrent: &Torrent) -> Vec<DecodeTorrentFileError> {
+    let mut violations = vec![];
+    let info = &torrent.info;
+
+    if !is_valid_piece_length(info.piece_length, info.is_v2()) {
+        add_violation(&mut violations, DecodeTorrentFileError::InvalidPieceLength);
+    }
+
+    if !is_safe_path_component(&info.name) {
+        add_violation(&mut violations, DecodeTorrentFileError::UnsafeName);
+    }
+
+    // BEP 52: v2 torrents describe their files in the `file tree` key
+    if info.is_v2() && info.file_tree.is_none() {
+        add_violation(&mut violations, DecodeTorrentFileError::InvalidInfoDictionary);
+    }
+
+    if info.is_v2() {
+        validate_files(&info.v2_files(), &mut violations);
+    }
+
+    if info.is_v2_only() {
+        return violations;
+    }
+
+    // For single-file torrents, the `name` is the file path.
+    let files = match (info.length, &info.files) {
+        (Some(length), None) => {
+            if length < 0 {
+                add_violation(&mut violations, DecodeTorrentFileError::NegativeFileLength);
+            }
+            vec![TorrentFile {
+                path: vec![info.name.clone()],
+                length,
+                md5sum: None,
+                attr: None,
+            }]
+        }
+        (None, Some(files)) if !files.is_empty() => {
+            validate_files(files, &mut violations);
+            files.clone()
+        }
+        _ => {
+            add_violation(&mut violations, DecodeTorrentFileError::InvalidFileLayout);
+            vec![]
+        }
+    };
+
+    if let Some(pieces) = &info.pieces {
+        if pieces.len() % PIECE_HASH_LENGTH == 0 {
+            if !files.is_empty() && info.piece_length > 0 && !has_expected_piece_count(&files, info.piece_length, pieces.len()) {
+                add_violation(&mut violations, DecodeTorrentFileError::PieceCountMismatch);
+            }
+        } else {
+            add_violation(&mut violations, DecodeTorrentFileError::InvalidTorrentPiecesLength);
+        }
+    } else if !info.is_bep_30() {
+        add_violation(&mut violations, DecodeTorrentFileError::InvalidInfoDictionary);
+    }
+
+    violations
+}
+
+fn validate_files(files: &[TorrentFile], violations: &mut Vec<DecodeTorrentFileError>) {
+    if files.len() > MAX_FILES {
+        add_violation(violations, DecodeTorrentFileError::TooManyFiles);
+    }
+
+    for file in files {
+        if file.length < 0 {
+            add_violation(violations, DecodeTorrentFileError::NegativeFileLength);
+        }
+
+        if file.path.is_empty() || !file.path.iter().all(|component| is_safe_path_component(component)) {
+            add_violation(violations, DecodeTorrentFileError::UnsafeFilePath);
+        }
+
+        if file.path.len() > MAX_PATH_DEPTH {
+            add_violation(violations, DecodeTorrentFileError::PathTooDeep);
+        }
+    }
+}
+
+/// The piece length must be within the limits. BEP 52 also requires a power
+/// of two for v2 torrents.
+fn is_valid_piece_length(piece_length: i64, is_v2: bool) -> bool {
+    (MIN_PIECE_LENGTH..=MAX_PIECE_LENGTH).contains(&piece_length) && (!is_v2 || piece_length.count_ones() == 1)
+}
+
+/// The number of pieces must be the total length, including padding files,
+/// divided by the piece length and rounded up.
+fn has_expected_piece_count(files: &[TorrentFile], piece_length: i64, pieces_bytes: usize) -> bool {
+    let Some(total_length) = files
+        .iter()
+        .try_fold(0i64, |total, file| total.checked_add(file.length.max(0)))
+    else {
+        return false;
+    };
+
+    let expected_pieces = (total_length + piece_length - 1) / piece_length;
+
+    i64::try_from(pieces_bytes / PIECE_HASH_LENGTH).ok() == Some(expected_pieces)
+}
+
+/// A name or a path component can't be empty, a relative directory or contain
+/// path separators. Otherwise, clients could write outside the download
+/// directory.
+fn is_safe_path_component(component: &str) -> bool {
+    !component.is_empty() && component != "." && component != ".." && !component.contains(['/', '\\', '\0'])
+}
+
+fn is_utf8_value(value: Option<&Value>) -> bool {
+    matches!(value, Some(Value::Bytes(bytes)) if std::str::from_utf8(bytes).is_ok())
+}
+
+fn is_non_utf8_value(value: Option<&Value>) -> bool {
+    matches!(value, Some(Value::Bytes(bytes)) if std::str::from_utf8(bytes).is_err())
+}
+
+fn is_utf8_path(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::List(components)) => components.iter().all(|component| is_utf8_value(Some(component))),
+        _ => false,
+    }
+}
+
+fn has_utf8_keys(value: &Value) -> bool {
+    match value {
+        Value::Dict(entries) => entries
+            .iter()
+            .all(|(key, child)| std::str::from_utf8(key).is_ok() && has_utf8_keys(child)),
+        _ => true,
+    }
+}
+
+fn add_violation(violations: &mut Vec<DecodeTorrentFileError>, violation: DecodeTorrentFileError) {
+    if !violations.contains(&violation) {
+        violations.push(violation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_bencode::value::Value;
+
+    use crate::models::torrent_file::Torrent;
+    use crate::utils::parse_torrent::{decode_and_validate_torrent_file, decode_torrent, DecodeTorrentFileError};
+    use crate::utils::torrent_validation::{use_utf8_alternatives, validate_torrent, MAX_PATH_DEPTH};
+
+    /// A single-file torrent with two pieces of 16 KiB.
+    fn single_file_torrent() -> Torrent {
+        // cspell:disable-next-line
+        decode_torrent(
+            b"d4:infod6:lengthi20000e4:name8:file.txt12:piece lengthi16384e6:pieces40:AAAAAAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBBBBBee",
+        )
+        .unwrap()
+    }
+
+    /// A multi-file torrent with one piece of 16 KiB.
+    fn multi_file_torrent() -> Torrent {
+        // cspell:disable-next-line
+        decode_torrent(b"d4:infod5:filesld6:lengthi1e4:pathl3:dir5:a.txteed6:lengthi2e4:pathl5:b.txteee4:name3:dir12:piece lengthi16384e6:pieces20:AAAAAAAAAAAAAAAAAAAAee")
+            .unwrap()
+    }
+
+    #[test]
+    fn it_should_accept_a_valid_torrent() {
+        assert!(validate_torrent(&single_file_torrent()).is_empty());
+        assert!(validate_torrent(&multi_file_torrent()).is_empty());
+    }
+
+    #[test]
+    fn it_should_fail_when_the_number_of_pieces_does_not_match_the_total_length() {
+        let mut torrent = single_file_torrent();
+        torrent.info.length = Some(100_000);
+
+        assert_eq!(validate_torrent(&torrent), vec![DecodeTorrentFileError::PieceCountMismatch]);
+    }
+
+    #[test]
+    fn it_should_fail_when_the_piece_length_is_out_of_limits() {
+        let mut torrent = single_file_torrent();
+        torrent.info.piece_length = 1;
+
+        assert!(validate_torrent(&torrent).contains(&DecodeTorrentFileError::InvalidPieceLength));
+    }
+
+    #[test]
+    fn it_should_fail_when_a_file_path_is_not_safe() {
+        for path in [vec![], vec![String::new()], vec!["..".to_string()], vec!["a/b".to_string()]] {
+            let mut torrent = multi_file_torrent();
+            torrent.info.files.as_mut().unwrap()[0].path = path;
+
+            assert_eq!(validate_torrent(&torrent), vec![DecodeTorrentFileError::UnsafeFilePath]);
+        }
+    }
+
+    #[test]
+    fn it_should_fail_when_the_name_is_not_safe() {
+        let mut torrent = single_file_torrent();
+        torrent.info.name = "../file.txt".to_string();
+
+        assert_eq!(validate_torrent(&torrent), vec![DecodeTorrentFileError::UnsafeName]);
+    }
+
+    #[test]
+    fn it_should_fail_when_a_file_path_is_too_deep() {
+        let mut torrent = multi_file_torrent();
+        torrent.info.files.as_mut().unwrap()[0].path = vec!["dir".to_string(); MAX_PATH_DEPTH + 1];
+
+        assert_eq!(validate_torrent(&torrent), vec![DecodeTorrentFileError::PathTooDeep]);
+    }
+
+    #[test]
+    fn it_should_list_every_violation() {
+        let mut torrent = multi_file_torrent();
+        torrent.info.name = String::new();
+        torrent.info.files.as_mut().unwrap()[0].length = -1;
+        torrent.info.files.as_mut().unwrap()[1].path = vec!["..".to_string()];
+
+        assert_eq!(
+            validate_torrent(&torrent),
+            vec![
+                DecodeTorrentFileError::UnsafeName,
+                DecodeTorrentFileError::NegativeFileLength,
+                DecodeTorrentFileError::UnsafeFilePath,
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_use_the_name_utf8_key_when_the_name_is_not_valid_utf8() {
+        // cspell:disable-next-line
+        let mut metainfo: Value = serde_bencode::from_bytes(b"d4:infod4:name4:\xff\xfe.a10:name.utf-86:\xc3\xa9.txtee").unwrap();
+
+        assert!(use_utf8_alternatives(&mut metainfo).is_empty());
+
+        let Value::Dict(metainfo) = metainfo else { panic!() };
+        let Some(Value::Dict(info)) = metainfo.get(b"info".as_slice()) else {
+            panic!()
+        };
+        assert_eq!(info.get(b"name".as_slice()), Some(&Value::Bytes("é.txt".as_bytes().to_vec())));
+    }
+
+    #[test]
+    fn it_should_fail_when_a_name_or_path_is_not_valid_utf8_and_there_is_no_alternative() {
+        let mut metainfo: Value =
+            // cspell:disable-next-line
+            serde_bencode::from_bytes(b"d4:infod5:filesld6:lengthi1e4:pathl2:\xff\xfeeee4:name2:\xff\xfeee").unwrap();
+
+        assert_eq!(
+            use_utf8_alternatives(&mut metainfo),
+            vec![
+                DecodeTorrentFileError::NameIsNotValidUtf8,
+                DecodeTorrentFileError::PathIsNotValidUtf8
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_decode_a_torrent_with_a_path_utf8_alternative() {
+        // cspell:disable-next-line
+        let bytes = b"d4:infod5:filesld6:lengthi1e4:pathl2:\xff\xfee10:path.utf-8l5:a.txteed6:lengthi2e4:pathl5:b.txteee4:name3:dir12:piece lengthi16384e6:pieces20:AAAAAAAAAAAAAAAAAAAAee";
+
+        let (torrent, _original_info_hash) = decode_and_validate_torrent_file(bytes).unwrap();
+
+        assert_eq!(torrent.info.files.unwrap()[0].path, vec!["a.txt".to_string()]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Minimal ZIP archive encoder.
+//!
+//! It only supports what the batch torrent download needs: stored (not
+//! compressed) entries and no ZIP64 extensions. `.torrent` files are mostly
+//! SHA-1 piece hashes, so compressing them barely reduces their size.
+//!
+//! Without ZIP64 extensions an archive can't have more than [`MAX_ENTRIES`]
+//! entries nor be larger than 4 GiB. The encoder returns an error instead of
+//! producing an archive readers can't open.
+//!
+//! The archive is produced entry by entry so it can be streamed to the client
+//! while the following entries are still being built:
+//!
+//! ```text
+//! [local header 1][data 1] ... [local header N][data N][central directory][end of central directory]
+//! ```
+//!
+//! Refer to the [ZIP specification](https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT)
+//! for the record layouts.
+use thiserror::Error;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+
+/// Version 2.0: the minimum for stored entries in directories.
+const VERSION: u16 = 20;
+
+/// General purpose flag bit 11: the entry name is UTF-8 encoded.
+const UTF8_NAME_FLAG: u16 = 1 << 11;
+
+const STORED: u16 = 0;
+
+/// Readers take the all-ones values of the counters and offsets in the end
+/// of central directory record as a sign of ZIP64 extensions, so they can't
+/// be used.
+const ZIP64_U16_MARKER: u16 = u16::MAX;
+const ZIP64_U32_MARKER: u32 = u32::MAX;
+
+/// The maximum number of entries in an archive.
+pub const MAX_ENTRIES: usize = ZIP64_U16_MARKER as usize - 1;
+
+/// Entries are dated 1980-01-01 00:00, the first representable MS-DOS date,
+/// so the same torrents always produce the same archive.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("The archive can't have more than {MAX_ENTRIES} entries without ZIP64 extensions.")]
+    TooManyEntries,
+
+    #[error("The archive can't be larger than 4 GiB without ZIP64 extensions.")]
+    TooLarge,
+
+    #[error("The entry name can't be longer than 65535 bytes.")]
+    NameTooLong,
+}
+
+struct CentralDirectoryEntry {
+    name: String,
+    crc32: u32,
+    size: u32,
+    local_header_offset: u32,
+}
+
+/// It encodes a ZIP archive incrementally.
+#[derive(Default)]
+pub struct Encoder {
+    entries: Vec<CentralDirectoryEntry>,
+    offset: u32,
+}
+
+impl Encoder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// It returns the bytes for a new entry: its local file header followed by
+    /// its data.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the name is too long or the archive would
+    /// exceed the non-ZIP64 limits.
+    pub fn entry(&mut self, name: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let name_length = u16::try_from(name.len()).map_err(|_| Error::NameTooLong)?;
+        let size = u32::try_from(data.len())
+            .ok()
+            .filter(|size| *size < ZIP64_U32_MARKER)
+            .ok_or(Error::TooLarge)?;
+
+        if self.entries.len() >= MAX_ENTRIES {
+            return Err(Error::TooManyEntries);
+        }
+
+        let crc32 = crc32fast::hash(data);
+
+        let mut bytes = Vec::with_capacity(30 + name.len() + data.len());
+
+        put_u32(&mut bytes, LOCAL_FILE_HEADER_SIGNATURE);
+        put_u16(&mut bytes, VERSION);
+        put_u16(&mut bytes, UTF8_NAME_FLAG);
+        put_u16(&mut bytes, STORED);
+        put_u16(&mut bytes, DOS_TIME);
+        put_u16(&mut bytes, DOS_DATE);
+        put_u32(&mut bytes, crc32);
+        put_u32(&mut bytes, size); // compressed size
+        put_u32(&mut bytes, size); // uncompressed size
+        put_u16(&mut bytes, name_length);
+        put_u16(&mut bytes, 0); // extra field length
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(data);
+
+        let local_header_offset = self.offset;
+
+        self.offset = u32::try_from(bytes.len())
+            .ok()
+            .and_then(|length| self.offset.checked_add(length))
+            .filter(|offset| *offset < ZIP64_U32_MARKER)
+            .ok_or(Error::TooLarge)?;
+
+        self.entries.push(CentralDirectoryEntry {
+            name: name.to_owned(),
+            crc32,
+            size,
+            local_header_offset,
+        });
+
+        Ok(bytes)
+    }
+
+    /// It returns the bytes closing the archive: the central directory and
+    /// the end of central directory record.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the archive would exceed the non-ZIP64 limits.
+    pub fn finish(self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+
+        for entry in &self.entries {
+            put_u32(&mut bytes, CENTRAL_DIRECTORY_HEADER_SIGNATURE);
+            put_u16(&mut bytes, VERSION); // version made by
+            put_u16(&mut bytes, VERSION); // version needed to extract
+            put_u16(&mut bytes, UTF8_NAME_FLAG);
+            put_u16(&mut bytes, STORED);
+            put_u16(&mut bytes, DOS_TIME);
+            put_u16(&mut bytes, DOS_DATE);
+            put_u32(&mut bytes, entry.crc32);
+            put_u32(&mut bytes, entry.size); // compressed size
+            put_u32(&mut bytes, entry.size); // uncompressed size
+            #[allow(clippy::cast_possible_truncation)]
+            put_u16(&mut bytes, entry.name.len() as u16); // checked when the entry was added
+            put_u16(&mut bytes, 0); // extra field length
+            put_u16(&mut bytes, 0); // file comment length
+            put_u16(&mut bytes, 0); // disk number start
+            put_u16(&mut bytes, 0); // internal file attributes
+            put_u32(&mut bytes, 0); // external file attributes
+            put_u32(&mut bytes, entry.local_header_offset);
+            bytes.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let central_directory_size = u32::try_from(bytes.len())
+            .ok()
+            .filter(|size| self.offset.checked_add(*size).is_some_and(|end| end < ZIP64_U32_MARKER))
+            .ok_or(Error::TooLarge)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let number_of_entries = self.entries.len() as u16; // checked when the entries were added
+
+        put_u32(&mut bytes, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        put_u16(&mut bytes, 0); // number of this disk
+        put_u16(&mut bytes, 0); // disk where the central directory starts
+        put_u16(&mut bytes, number_of_entries); // entries on this disk
+        put_u16(&mut bytes, number_of_entries); // total entries
+        put_u32(&mut bytes, central_directory_size);
+        put_u32(&mut bytes, self.offset); // central directory offset
+        put_u16(&mut bytes, 0); // comment length
+
+        Ok(bytes)
+    }
+}
+
+fn put_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::zip::{Encoder, Error, MAX_ENTRIES};
+
+    fn u16_at(bytes: &[u8], position: usize) -> u16 {
+        u16::from_le_bytes([bytes[position], bytes[position + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], position: usize) -> u32 {
+        u32::from_le_bytes([bytes[position], bytes[position + 1], bytes[position + 2], bytes[position + 3]])
+    }
+
+    #[test]
+    fn an_entry_should_start_with_its_local_header_followed_by_the_data() {
+        let mut encoder = Encoder::new();
+
+        let entry = encoder.entry("a.torrent", b"d4:infod4:name1:aee").unwrap();
+
+        assert_eq!(u32_at(&entry, 0), 0x0403_4b50);
+        assert_eq!(u32_at(&entry, 14), crc32fast::hash(b"d4:infod4:name1:aee"));
+        assert_eq!(u32_at(&entry, 18), 19);
+        assert_eq!(u32_at(&entry, 22), 19);
+        assert_eq!(&entry[30..39], b"a.torrent");
+        assert_eq!(&entry[39..], b"d4:infod4:name1:aee");
+    }
+
+    #[test]
+    fn the_end_of_central_directory_should_point_to_the_central_directory() {
+        let mut encoder = Encoder::new();
+
+        let first = encoder.entry("a.torrent", b"first").unwrap();
+        let second = encoder.entry("b.torrent", b"second").unwrap();
+        let end = encoder.finish().unwrap();
+
+        let central_directory_offset = first.len() + second.len();
+        let end_of_central_directory = &end[end.len() - 22..];
+
+        assert_eq!(u32_at(end_of_central_directory, 0), 0x0605_4b50);
+        assert_eq!(u16_at(end_of_central_directory, 10), 2);
+        assert_eq!(u32_at(end_of_central_directory, 12) as usize, end.len() - 22);
+        assert_eq!(u32_at(end_of_central_directory, 16) as usize, central_directory_offset);
+
+        // The second central directory header points to the second local header.
+        let second_header = 46 + "a.torrent".len();
+        assert_eq!(u32_at(&end, second_header), 0x0201_4b50);
+        assert_eq!(u32_at(&end, second_header + 42) as usize, first.len());
+    }
+
+    #[test]
+    fn an_empty_archive_should_only_have_the_end_of_central_directory() {
+        let end = Encoder::new().finish().unwrap();
+
+        assert_eq!(end.len(), 22);
+        assert_eq!(u16_at(&end, 10), 0);
+    }
+
+    #[test]
+    fn it_should_reject_more_entries_than_the_non_zip64_limit() {
+        let mut encoder = Encoder::new();
+
+        for i in 0..MAX_ENTRIES {
+            encoder.entry(&i.to_string(), b"").unwrap();
+        }
+
+        assert_eq!(encoder.entry("one-too-many", b""), Err(Error::TooManyEntries));
+
+        let end = encoder.finish().unwrap();
+        assert_eq!(u16_at(&end, end.len() - 12) as usize, MAX_ENTRIES);
+    }
+
+    #[test]
+    fn it_should_reject_archives_larger_than_4_gib() {
+        let mut encoder = Encoder::new();
+
+        // The previous entries fill the archive up to almost 4 GiB. An empty
+        // entry still fits but its central directory header does not.
+        encoder.offset = u32::MAX - 60;
+
+        assert_eq!(encoder.entry("a.torrent", &[0; 100]), Err(Error::TooLarge));
+        assert!(encoder.entry("a.torrent", b"").is_ok());
+        assert_eq!(encoder.finish(), Err(Error::TooLarge));
+    }
+
+    #[test]
+    fn it_should_reject_entry_names_longer_than_65535_bytes() {
+        let name = "a".repeat(65_536);
+
+        assert_eq!(Encoder::new().entry(&name, b""), Err(Error::NameTooLong));
+    }
+}
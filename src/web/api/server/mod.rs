@@ -71,7 +71,7 @@ async fn start_server(
     rx_halt: Receiver<Halted>,
     rust_tls_config: Option<RustlsConfig>,
 ) {
-    let router = router(app_data);
+    let router = router(app_data).await;
     let socket = std::net::TcpListener::bind(config_socket_addr).expect("Could not bind tcp_listener to address.");
     let address = socket.local_addr().expect("Could not get local_addr from tcp_listener.");
 
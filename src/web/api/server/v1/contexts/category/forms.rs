@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::category::TorrentFileFormat;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AddCategoryForm {
     pub name: String,
@@ -7,3 +9,9 @@ pub struct AddCategoryForm {
 }
 
 pub type DeleteCategoryForm = AddCategoryForm;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateCategoryForm {
+    pub name: String,
+    pub torrent_file_format: TorrentFileFormat,
+}
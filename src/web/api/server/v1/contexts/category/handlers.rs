@@ -5,8 +5,8 @@ use std::sync::Arc;
 use axum::extract::{self, State};
 use axum::response::{IntoResponse, Json, Response};
 
-use super::forms::{AddCategoryForm, DeleteCategoryForm};
-use super::responses::{added_category, deleted_category, Category};
+use super::forms::{AddCategoryForm, DeleteCategoryForm, UpdateCategoryForm};
+use super::responses::{added_category, deleted_category, updated_category, Category};
 use crate::common::AppData;
 use crate::web::api::server::v1::extractors::optional_user_id::ExtractOptionalLoggedInUser;
 use crate::web::api::server::v1::responses::{self};
@@ -89,3 +89,28 @@ pub async fn delete_handler(
         Err(error) => error.into_response(),
     }
 }
+
+/// It updates the torrent file format served for the torrents in a category.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user does not have permissions to update the category.
+/// - The category does not exist.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn update_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(category_form): extract::Json<UpdateCategoryForm>,
+) -> Response {
+    match app_data
+        .category_service
+        .update_torrent_file_format(&category_form.name, category_form.torrent_file_format, maybe_user_id)
+        .await
+    {
+        Ok(()) => updated_category(&category_form.name).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
@@ -13,6 +13,9 @@ pub struct Category {
     pub category_id: i64, // todo: remove when the Index GUI uses the new `id` field.
     pub name: String,
     pub num_torrents: i64,
+    /// The torrent file served for the torrents in the category: `canonical`
+    /// or `original`.
+    pub torrent_file_format: String,
 }
 
 /// Response after successfully creating a new category.
@@ -29,6 +32,13 @@ pub fn deleted_category(category_name: &str) -> Json<OkResponseData<String>> {
     })
 }
 
+/// Response after successfully updating a category.
+pub fn updated_category(category_name: &str) -> Json<OkResponseData<String>> {
+    Json(OkResponseData {
+        data: category_name.to_string(),
+    })
+}
+
 impl From<DatabaseCategory> for Category {
     fn from(db_category: DatabaseCategory) -> Self {
         Category {
@@ -36,6 +46,7 @@ impl From<DatabaseCategory> for Category {
             category_id: db_category.category_id,
             name: db_category.name,
             num_torrents: db_category.num_torrents,
+            torrent_file_format: db_category.torrent_file_format,
         }
     }
 }
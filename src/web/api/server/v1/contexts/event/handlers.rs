@@ -0,0 +1,41 @@
+//! API handlers for the the [`event`](crate::web::api::server::v1::contexts::event) API
+//! context.
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use futures::stream;
+
+use crate::common::AppData;
+use crate::services::event_stream::SubscriptionRequest;
+use crate::web::api::server::v1::extractors::optional_user_id::ExtractOptionalLoggedInUser;
+
+/// It subscribes the client to the stream of torrent events.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not allowed to list torrents.
+/// - A category or tag in the filter does not exist.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn subscribe_handler(
+    State(app_data): State<Arc<AppData>>,
+    Query(request): Query<SubscriptionRequest>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.event_stream_service.subscribe(&request, maybe_user_id).await {
+        Ok(subscription) => {
+            let events = stream::unfold(subscription, |mut subscription| async move {
+                let event = subscription.next().await?;
+
+                Some((Event::default().event(event.name()).json_data(&event), subscription))
+            });
+
+            Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+        }
+        Err(error) => error.into_response(),
+    }
+}
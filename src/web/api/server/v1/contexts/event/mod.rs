@@ -0,0 +1,72 @@
+//! API context: `event`.
+//!
+//! This API context provides a real-time stream of torrent events using
+//! [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+//! Frontends can use it to refresh the torrent listing without polling it.
+//!
+//! The stream requires the same permission as the torrent listing. Guests
+//! can subscribe when they are allowed to list torrents. Logged-in users have
+//! to send the `Authorization` header, so browsers need a client that
+//! supports custom headers instead of the native `EventSource`.
+//!
+//! Events:
+//!
+//! Name | Data
+//! ---|---
+//! `torrent.added` | `torrent_id`, `info_hash`, `title`, `category_id`, `tag_ids`
+//! `torrent.updated` | `torrent_id`, `info_hash`, `title`, `category_id`, `tag_ids`
+//! `torrent.deleted` | `torrent_id`, `info_hash`, `category_id`, `tag_ids`
+//! `torrent.stats_updated` | `torrent_id`, `info_hash`, `seeders`, `leechers`, `category_id`, `tag_ids`
+//!
+//! The `torrent.stats_updated` event is sent when the tracker statistics
+//! importer detects a change in the number of seeders or leechers.
+//!
+//! Events are not stored. Clients only receive the events that happen while
+//! they are connected.
+//!
+//! # Endpoints
+//!
+//! - [Subscribe to the event stream](#subscribe-to-the-event-stream)
+//!
+//! # Subscribe to the event stream
+//!
+//! `GET /v1/events`
+//!
+//! **Query parameters**
+//!
+//! Name | Type | Description | Required | Example
+//! ---|---|---|---|---
+//! `categories` | `Option<String>` | Comma-separated list of category names | No | `movies,music`
+//! `tags` | `Option<String>` | Comma-separated list of tag names | No | `Linux,Ubuntu`
+//!
+//! Like in the torrent listing, only events for torrents in any of the
+//! categories and with any of the tags are sent. The request fails with a
+//! `400` error when a category or tag does not exist.
+//!
+//! **Example request**
+//!
+//! ```bash
+//! curl \
+//!   --no-buffer \
+//!   --request GET \
+//!   "http://127.0.0.1:3001/v1/events?categories=movies"
+//! ```
+//!
+//! **Example response** `200`
+//!
+//! ```text
+//! event: torrent.added
+//! data: {"torrent_id":1,"info_hash":"443c7602b4fde83d1154d6d9da48808418b181b6","title":"Ubuntu 24.04","category_id":1,"tag_ids":[]}
+//!
+//! event: torrent.stats_updated
+//! data: {"torrent_id":1,"info_hash":"443c7602b4fde83d1154d6d9da48808418b181b6","seeders":1,"leechers":0,"category_id":1,"tag_ids":[]}
+//! ```
+//!
+//! Comments are sent periodically to keep the connection alive.
+//!
+//! **Resource**
+//!
+//! Refer to the [`Event`](crate::services::event_stream::Event) enum for more
+//! information about the event data.
+pub mod handlers;
+pub mod routes;
@@ -0,0 +1,51 @@
+//! API handlers for the the [`mail`](crate::web::api::server::v1::contexts::mail) API
+//! context.
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Json, Response};
+
+use crate::common::AppData;
+use crate::models::mail::MailId;
+use crate::web::api::server::v1::extractors::optional_user_id::ExtractOptionalLoggedInUser;
+use crate::web::api::server::v1::responses::OkResponseData;
+
+/// It returns the emails the Index gave up delivering.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn get_failed_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.mail_outbox_service.get_failed_mails(maybe_user_id).await {
+        Ok(mails) => Json(OkResponseData { data: mails }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It delivers a failed email again.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - There isn't a failed email with the ID.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn retry_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(mail_id): Path<MailId>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.mail_outbox_service.retry_failed_mail(mail_id, maybe_user_id).await {
+        Ok(()) => Json(OkResponseData { data: mail_id }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
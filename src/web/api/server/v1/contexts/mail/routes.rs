@@ -0,0 +1,17 @@
+//! API routes for the [`mail`](crate::web::api::server::v1::contexts::mail) API context.
+//!
+//! Refer to the [API endpoint documentation](crate::web::api::server::v1::contexts::mail).
+use std::sync::Arc;
+
+use axum::routing::{get, post};
+use axum::Router;
+
+use super::handlers::{get_failed_handler, retry_handler};
+use crate::common::AppData;
+
+/// Routes for the [`mail`](crate::web::api::server::v1::contexts::mail) API context.
+pub fn router(app_data: Arc<AppData>) -> Router {
+    Router::new()
+        .route("/failed", get(get_failed_handler).with_state(app_data.clone()))
+        .route("/:mail_id/retry", post(retry_handler).with_state(app_data))
+}
@@ -4,16 +4,22 @@
 //! ---|---|---
 //! `About` | Metadata about the API | [`v1`](crate::web::api::server::v1::contexts::about)
 //! `Category` | Torrent categories | [`v1`](crate::web::api::server::v1::contexts::category)
+//! `Event` | Real-time torrent events | [`v1`](crate::web::api::server::v1::contexts::event)
+//! `Mail` | Undelivered emails | [`v1`](crate::web::api::server::v1::contexts::mail)
 //! `Proxy` | Image proxy cache | [`v1`](crate::web::api::server::v1::contexts::proxy)
 //! `Settings` | Index settings | [`v1`](crate::web::api::server::v1::contexts::settings)
 //! `Tag` | Torrent tags | [`v1`](crate::web::api::server::v1::contexts::tag)
 //! `Torrent` | Indexed torrents | [`v1`](crate::web::api::server::v1::contexts::torrent)
 //! `User` | Users | [`v1`](crate::web::api::server::v1::contexts::user)
+//! `Webhook` | Outgoing webhooks | [`v1`](crate::web::api::server::v1::contexts::webhook)
 //!
 pub mod about;
 pub mod category;
+pub mod event;
+pub mod mail;
 pub mod proxy;
 pub mod settings;
 pub mod tag;
 pub mod torrent;
 pub mod user;
+pub mod webhook;
@@ -57,7 +57,54 @@
 //!       "user_claim_token_pepper": "***",
 //!       "password_constraints": {
 //!         "max_password_length": 64,
-//!         "min_password_length": 6
+//!         "min_password_length": 6,
+//!         "require_lowercase": false,
+//!         "require_uppercase": false,
+//!         "require_digit": false,
+//!         "require_special_character": false,
+//!         "min_strength_score": 0,
+//!         "reject_username_or_email": false,
+//!         "breached_passwords_path": null
+//!       },
+//!       "username_constraints": {
+//!         "reserved_usernames": [
+//!           "abuse",
+//!           "admin",
+//!           "administrator",
+//!           "anonymous",
+//!           "api",
+//!           "contact",
+//!           "guest",
+//!           "help",
+//!           "hostmaster",
+//!           "index",
+//!           "info",
+//!           "mail",
+//!           "mod",
+//!           "moderator",
+//!           "noreply",
+//!           "null",
+//!           "official",
+//!           "owner",
+//!           "postmaster",
+//!           "root",
+//!           "security",
+//!           "staff",
+//!           "support",
+//!           "sysadmin",
+//!           "system",
+//!           "torrust",
+//!           "tracker",
+//!           "undefined",
+//!           "webmaster",
+//!           "www"
+//!         ],
+//!         "profanity_words_path": null
+//!       },
+//!       "argon2": {
+//!         "memory_cost": 19456,
+//!         "time_cost": 2,
+//!         "parallelism": 1
 //!       }
 //!     },
 //!     "database": {
@@ -67,6 +114,14 @@
 //!       "email_verification_enabled": false,
 //!       "from": "example@email.com",
 //!       "reply_to": "noreply@email.com",
+//!       "transport": "smtp",
+//!       "templates_path": null,
+//!       "file": {
+//!         "path": "./storage/index/lib/mail"
+//!       },
+//!       "sendmail": {
+//!         "command": "sendmail"
+//!       },
 //!       "smtp": {
 //!         "port": 25,
 //!         "server": "",
@@ -85,7 +140,8 @@
 //!     },
 //!     "api": {
 //!       "default_torrent_page_size": 10,
-//!       "max_torrent_page_size": 30
+//!       "max_torrent_page_size": 30,
+//!       "max_torrents_per_batch_download": 50
 //!     },
 //!     "registration": {
 //!       "email": {
@@ -34,13 +34,34 @@ pub enum Request {
 
     #[display(fmt = "provided path param for Info-hash is not valid.")]
     InvalidInfoHashParam,
+
+    #[display(fmt = "torrent name bytes are nota valid UTF8 string.")]
+    NameIsNotValidUtf8,
+
+    #[display(fmt = "torrent piece length is missing or it is not a valid integer.")]
+    InvalidPieceLength,
+
+    #[display(fmt = "torrent manifest is not a valid JSON file manifest.")]
+    ManifestIsNotValidJson,
+
+    #[display(fmt = "the torrent content should be provided either as a `manifest` or as a `file`.")]
+    InvalidTorrentContent,
+
+    #[display(fmt = "one of the provided info-hashes is not valid.")]
+    InvalidInfoHash,
+
+    #[display(fmt = "the torrents to download should be provided either as `info_hashes` or as a listing `filter`.")]
+    InvalidBatchDownloadRequest,
 }
 
 impl IntoResponse for Request {
     fn into_response(self) -> Response {
         json_error_response(
             http_status_code_for_handler_error(&self),
-            &ErrorResponseData { error: self.to_string() },
+            &ErrorResponseData {
+                error: self.to_string(),
+                details: vec![],
+            },
         )
     }
 }
@@ -56,6 +77,12 @@ pub fn http_status_code_for_handler_error(error: &Request) -> StatusCode {
         Request::TagsArrayIsNotValidJson => StatusCode::BAD_REQUEST,
         Request::InvalidFileType => StatusCode::BAD_REQUEST,
         Request::InvalidInfoHashParam => StatusCode::BAD_REQUEST,
+        Request::NameIsNotValidUtf8 => StatusCode::BAD_REQUEST,
+        Request::InvalidPieceLength => StatusCode::BAD_REQUEST,
+        Request::ManifestIsNotValidJson => StatusCode::BAD_REQUEST,
+        Request::InvalidTorrentContent => StatusCode::BAD_REQUEST,
+        Request::InvalidInfoHash => StatusCode::BAD_REQUEST,
+        Request::InvalidBatchDownloadRequest => StatusCode::BAD_REQUEST,
         // Internal errors processing the request
         Request::CannotWriteChunkFromUploadedBinary => StatusCode::INTERNAL_SERVER_ERROR,
         Request::CannotReadChunkFromUploadedBinary => StatusCode::INTERNAL_SERVER_ERROR,
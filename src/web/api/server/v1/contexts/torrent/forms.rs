@@ -2,6 +2,7 @@ use serde::Deserialize;
 
 use crate::models::category::CategoryId;
 use crate::models::torrent_tag::TagId;
+use crate::services::torrent::ListingRequest;
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateTorrentInfoForm {
@@ -9,4 +10,27 @@ pub struct UpdateTorrentInfoForm {
     pub description: Option<String>,
     pub category: Option<CategoryId>,
     pub tags: Option<Vec<TagId>>,
+    /// It replaces the torrent web seeds (BEP 19 `url-list`).
+    pub web_seeds: Option<Vec<String>>,
+}
+
+/// A torrent listed from a magnet link, without its torrent file.
+#[derive(Debug, Deserialize)]
+pub struct AddMagnetTorrentForm {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// The category name.
+    pub category: String,
+    #[serde(default)]
+    pub tags: Vec<TagId>,
+    pub magnet_link: String,
+}
+
+/// The torrents to download in a ZIP archive. Either a list of info-hashes or
+/// a listing filter with the same parameters as the torrent list endpoint.
+#[derive(Debug, Deserialize)]
+pub struct DownloadTorrentsForm {
+    pub info_hashes: Option<Vec<String>>,
+    pub filter: Option<ListingRequest>,
 }
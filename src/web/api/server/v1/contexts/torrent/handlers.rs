@@ -1,26 +1,34 @@
 //! API handlers for the [`torrent`](crate::web::api::server::v1::contexts::torrent) API
 //! context.
+use std::collections::HashSet;
 use std::io::{Cursor, Write};
 use std::str::FromStr;
 use std::sync::Arc;
 
+use axum::body::Body;
+use axum::extract::multipart::Field;
 use axum::extract::{self, Multipart, Path, Query, State};
 use axum::response::{IntoResponse, Redirect, Response};
 use axum::Json;
+use bytes::Bytes;
 use serde::Deserialize;
-use tracing::debug;
+use tracing::{debug, error};
 use uuid::Uuid;
 
 use super::errors;
-use super::forms::UpdateTorrentInfoForm;
-use super::responses::{new_torrent_response, torrent_file_response};
+use super::forms::{AddMagnetTorrentForm, DownloadTorrentsForm, UpdateTorrentInfoForm};
+use super::responses::{new_torrent_response, replaced_torrent_response, torrent_file_response, torrents_zip_response};
 use crate::common::AppData;
 use crate::errors::ServiceError;
 use crate::models::info_hash::InfoHash;
 use crate::models::torrent_tag::TagId;
-use crate::services::torrent::{AddTorrentRequest, ListingRequest};
-use crate::services::torrent_file::generate_random_torrent;
-use crate::utils::parse_torrent;
+use crate::models::user::UserId;
+use crate::services::torrent::{
+    AddMagnetTorrentRequest, AddTorrentRequest, BatchDownloadRequest, CreateTorrentFromContentRequest, FileListingRequest,
+    ListingRequest,
+};
+use crate::services::torrent_file::{generate_random_torrent, TorrentContent, TorrentManifest};
+use crate::utils::{parse_torrent, zip};
 use crate::web::api::server::v1::extractors::optional_user_id::ExtractOptionalLoggedInUser;
 use crate::web::api::server::v1::extractors::user_id::ExtractLoggedInUser;
 use crate::web::api::server::v1::responses::OkResponseData;
@@ -51,6 +59,76 @@ pub async fn upload_torrent_handler(
     }
 }
 
+/// List a torrent from a magnet link, without its torrent file.
+///
+/// # Errors
+///
+/// This function will return an error if
+///
+/// - The user does not have permission to add torrents.
+/// - The magnet link is not valid.
+/// - The info-hash was already added to the Index.
+#[allow(clippy::unused_async)]
+pub async fn add_magnet_torrent_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(add_magnet_torrent_form): extract::Json<AddMagnetTorrentForm>,
+) -> Response {
+    let add_magnet_torrent_req = AddMagnetTorrentRequest {
+        title: add_magnet_torrent_form.title,
+        description: add_magnet_torrent_form.description,
+        category_name: add_magnet_torrent_form.category,
+        tags: add_magnet_torrent_form.tags,
+        magnet_link: add_magnet_torrent_form.magnet_link,
+    };
+
+    match app_data
+        .torrent_service
+        .add_magnet_torrent(add_magnet_torrent_req, maybe_user_id)
+        .await
+    {
+        Ok(response) => new_torrent_response(&response).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Create a new torrent from its content and add it to the Index.
+///
+/// It returns the generated torrent file as a byte stream
+/// `application/x-bittorrent`.
+///
+/// # Errors
+///
+/// This function will return an error if
+///
+/// - The user does not have permission to upload torrents.
+/// - The submitted manifest or file is not valid.
+/// - The torrent can't be added to the Index.
+#[allow(clippy::unused_async)]
+pub async fn create_torrent_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    multipart: Multipart,
+) -> Response {
+    let create_torrent_form = match build_create_torrent_request_from_payload(multipart).await {
+        Ok(torrent_request) => torrent_request,
+        Err(error) => return error.into_response(),
+    };
+
+    match app_data
+        .torrent_service
+        .create_torrent(create_torrent_form, maybe_user_id)
+        .await
+    {
+        Ok(torrent_file) => torrent_file_response(
+            torrent_file.bytes,
+            &format!("{}.torrent", torrent_file.name),
+            &torrent_file.info_hash,
+        ),
+        Err(error) => error.into_response(),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct InfoHashParam(pub String);
 
@@ -83,23 +161,154 @@ pub async fn download_torrent_handler(
         debug!("Redirecting to URL with canonical info-hash");
         redirect_response
     } else {
-        let torrent = match app_data.torrent_service.get_torrent(&info_hash, maybe_user_id).await {
-            Ok(torrent) => torrent,
+        let torrent_file = match app_data.torrent_service.get_torrent_file(&info_hash, maybe_user_id).await {
+            Ok(torrent_file) => torrent_file,
             Err(error) => return error.into_response(),
         };
 
-        let Ok(bytes) = parse_torrent::encode_torrent(&torrent) else {
-            return ServiceError::InternalServerError.into_response();
-        };
-
         torrent_file_response(
-            bytes,
-            &format!("{}.torrent", torrent.info.name),
-            &torrent.canonical_info_hash_hex(),
+            torrent_file.bytes,
+            &format!("{}.torrent", torrent_file.name),
+            &torrent_file.info_hash,
         )
     }
 }
 
+/// Returns several torrents as a ZIP archive `application/zip`.
+///
+/// Each torrent file is built like in the single torrent download. The
+/// archive is streamed while the torrent files are being built, so if building
+/// one of them fails after the response has started, the response is aborted.
+///
+/// # Errors
+///
+/// Returns an error if:
+///
+/// - The request does not contain either the info-hashes or the filter.
+/// - One of the info-hashes is invalid or the torrent does not exist.
+/// - There are more info-hashes than the maximum per batch download.
+/// - The user is not allowed to download torrents.
+#[allow(clippy::unused_async)]
+pub async fn download_torrents_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(download_torrents_form): extract::Json<DownloadTorrentsForm>,
+) -> Response {
+    let request = match (download_torrents_form.info_hashes, download_torrents_form.filter) {
+        (Some(info_hashes), None) => {
+            let Ok(info_hashes) = info_hashes
+                .iter()
+                .map(|info_hash| InfoHash::from_str(&info_hash.to_lowercase()))
+                .collect::<Result<Vec<_>, _>>()
+            else {
+                return errors::Request::InvalidInfoHash.into_response();
+            };
+
+            BatchDownloadRequest::InfoHashes(info_hashes)
+        }
+        (None, Some(listing_request)) => BatchDownloadRequest::Listing(listing_request),
+        _ => return errors::Request::InvalidBatchDownloadRequest.into_response(),
+    };
+
+    let info_hashes = match app_data
+        .torrent_service
+        .get_torrents_to_download(&request, maybe_user_id)
+        .await
+    {
+        Ok(info_hashes) => info_hashes,
+        Err(error) => return error.into_response(),
+    };
+
+    debug!("Downloading {} torrents in a ZIP archive", info_hashes.len());
+
+    torrents_zip_response(Body::from_stream(torrents_zip_stream(app_data, info_hashes, maybe_user_id)))
+}
+
+/// State of the ZIP archive stream for a batch download.
+struct TorrentsZipStream {
+    app_data: Arc<AppData>,
+    maybe_user_id: Option<UserId>,
+    pending_info_hashes: std::vec::IntoIter<InfoHash>,
+    entry_names: HashSet<String>,
+    /// It's `None` once the archive has been finished or has failed.
+    encoder: Option<zip::Encoder>,
+}
+
+/// It yields one ZIP entry per torrent and, finally, the ZIP central
+/// directory.
+fn torrents_zip_stream(
+    app_data: Arc<AppData>,
+    info_hashes: Vec<InfoHash>,
+    maybe_user_id: Option<UserId>,
+) -> impl futures::Stream<Item = Result<Bytes, ServiceError>> {
+    let state = TorrentsZipStream {
+        app_data,
+        maybe_user_id,
+        pending_info_hashes: info_hashes.into_iter(),
+        entry_names: HashSet::new(),
+        encoder: Some(zip::Encoder::new()),
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        let mut encoder = state.encoder.take()?;
+
+        let Some(info_hash) = state.pending_info_hashes.next() else {
+            return Some((encoder.finish().map(Bytes::from).map_err(|err| zip_error(&err)), state));
+        };
+
+        let result = match state
+            .app_data
+            .torrent_service
+            .get_torrent_file(&info_hash, state.maybe_user_id)
+            .await
+        {
+            Ok(torrent_file) => {
+                let entry_name = unique_zip_entry_name(&mut state.entry_names, &torrent_file.name, &torrent_file.info_hash);
+
+                encoder
+                    .entry(&entry_name, &torrent_file.bytes)
+                    .map(Bytes::from)
+                    .map_err(|err| zip_error(&err))
+            }
+            Err(err) => {
+                error!(
+                    "Failed to build torrent {} for a batch download: {err}",
+                    info_hash.to_hex_string()
+                );
+                Err(err)
+            }
+        };
+
+        if result.is_ok() {
+            state.encoder = Some(encoder);
+        }
+
+        Some((result, state))
+    })
+}
+
+fn zip_error(err: &zip::Error) -> ServiceError {
+    error!("Failed to build the ZIP archive for a batch download: {err}");
+    ServiceError::InternalServerError
+}
+
+/// It uses the torrent name as the file name, like the single torrent
+/// download, unless another torrent in the archive has the same name.
+fn unique_zip_entry_name(entry_names: &mut HashSet<String>, torrent_name: &str, info_hash: &str) -> String {
+    // Torrent names could contain path separators.
+    let torrent_name = torrent_name.replace(['/', '\\'], "_");
+
+    let mut entry_name = format!("{torrent_name}.torrent");
+
+    if entry_names.contains(&entry_name) {
+        entry_name = format!("{torrent_name} ({info_hash}).torrent");
+    }
+
+    entry_names.insert(entry_name.clone());
+
+    entry_name
+}
+
 async fn redirect_to_download_url_using_canonical_info_hash_if_needed(
     app_data: &Arc<AppData>,
     info_hash: &InfoHash,
@@ -235,6 +444,7 @@ pub async fn update_torrent_info_handler(
             &update_torrent_info_form.description,
             &update_torrent_info_form.category,
             &update_torrent_info_form.tags,
+            &update_torrent_info_form.web_seeds,
             &user_id,
         )
         .await
@@ -244,6 +454,99 @@ pub async fn update_torrent_info_handler(
     }
 }
 
+/// Replace the torrent file of an existing torrent.
+///
+/// The torrent keeps its listing data (title, description, category and tags)
+/// but gets a new info-hash. The previous torrent file is kept in the version
+/// history.
+///
+/// # Errors
+///
+/// This function will return an error if:
+///
+/// - The info-hash is not valid.
+/// - The submitted torrent file is not a valid torrent file.
+/// - The user does not have permission to replace the torrent file.
+#[allow(clippy::unused_async)]
+pub async fn replace_torrent_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    Path(info_hash): Path<InfoHashParam>,
+    multipart: Multipart,
+) -> Response {
+    let Ok(info_hash) = InfoHash::from_str(&info_hash.lowercase()) else {
+        return errors::Request::InvalidInfoHashParam.into_response();
+    };
+
+    let torrent_buffer = match build_replace_torrent_request_from_payload(multipart).await {
+        Ok(torrent_buffer) => torrent_buffer,
+        Err(error) => return error.into_response(),
+    };
+
+    match app_data
+        .torrent_service
+        .replace_torrent_file(&info_hash, &torrent_buffer, maybe_user_id)
+        .await
+    {
+        Ok(response) => replaced_torrent_response(&response).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Get the previous versions of the torrent file.
+///
+/// # Errors
+///
+/// This function returns an error if:
+///
+/// - The info-hash is not valid.
+/// - There was a problem getting the versions from the database.
+#[allow(clippy::unused_async)]
+pub async fn get_torrent_versions_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    Path(info_hash): Path<InfoHashParam>,
+) -> Response {
+    let Ok(info_hash) = InfoHash::from_str(&info_hash.lowercase()) else {
+        return errors::Request::InvalidInfoHashParam.into_response();
+    };
+
+    match app_data.torrent_service.get_torrent_versions(&info_hash, maybe_user_id).await {
+        Ok(versions) => Json(OkResponseData { data: versions }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// List the files in a torrent directory.
+///
+/// # Errors
+///
+/// This function returns an error if:
+///
+/// - The info-hash is not valid.
+/// - The directory does not exist in the torrent.
+/// - There was a problem getting the torrent files from the database.
+#[allow(clippy::unused_async)]
+pub async fn get_torrent_files_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    Path(info_hash): Path<InfoHashParam>,
+    Query(criteria): Query<FileListingRequest>,
+) -> Response {
+    let Ok(info_hash) = InfoHash::from_str(&info_hash.lowercase()) else {
+        return errors::Request::InvalidInfoHashParam.into_response();
+    };
+
+    match app_data
+        .torrent_service
+        .get_torrent_files(&info_hash, &criteria, maybe_user_id)
+        .await
+    {
+        Ok(files_response) => Json(OkResponseData { data: files_response }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
 /// Delete a torrent.
 ///
 /// # Errors
@@ -362,31 +665,168 @@ async fn build_add_torrent_request_from_payload(mut payload: Multipart) -> Resul
                 tags = serde_json::from_str(&string_data).map_err(|_| errors::Request::TagsArrayIsNotValidJson)?;
             }
             "torrent" => {
-                let content_type = field.content_type().unwrap();
+                write_torrent_file_field(&mut field, &mut torrent_cursor).await?;
+            }
+            _ => {}
+        }
+    }
 
-                if content_type != "application/x-bittorrent" && content_type != "application/octet-stream" {
-                    return Err(errors::Request::InvalidFileType);
+    Ok(AddTorrentRequest {
+        title,
+        description,
+        category_name: category,
+        tags,
+        torrent_buffer: torrent_cursor.into_inner(),
+    })
+}
+
+/// Extracts the [`CreateTorrentFromContentRequest`] from the multipart form
+/// payload.
+///
+/// The torrent content is either a JSON file manifest in the `manifest` field
+/// or a file in the `file` field. The `name` field overrides the uploaded file
+/// name.
+///
+/// # Errors
+///
+/// It will return an error if:
+///
+/// - The text fields do not contain a valid UTF8 string.
+/// - The piece length is missing or it is not an integer.
+/// - The manifest is not valid JSON.
+/// - There is no content, or both a manifest and a file were provided.
+/// - The multipart content is invalid.
+async fn build_create_torrent_request_from_payload(
+    mut payload: Multipart,
+) -> Result<CreateTorrentFromContentRequest, errors::Request> {
+    let mut title = String::new();
+    let mut description = String::new();
+    let mut category = String::new();
+    let mut tags: Vec<TagId> = vec![];
+    let mut piece_length: Option<i64> = None;
+    let mut name: Option<String> = None;
+    let mut manifest: Option<TorrentManifest> = None;
+    let mut file: Option<(String, Vec<u8>)> = None;
+
+    while let Some(mut field) = payload.next_field().await.unwrap() {
+        let field_name = field.name().unwrap().to_owned();
+
+        match field_name.as_str() {
+            "title" => {
+                let data = field.bytes().await.unwrap();
+                title = String::from_utf8(data.to_vec()).map_err(|_| errors::Request::TitleIsNotValidUtf8)?;
+            }
+            "description" => {
+                let data = field.bytes().await.unwrap();
+                description = String::from_utf8(data.to_vec()).map_err(|_| errors::Request::DescriptionIsNotValidUtf8)?;
+            }
+            "category" => {
+                let data = field.bytes().await.unwrap();
+                category = String::from_utf8(data.to_vec()).map_err(|_| errors::Request::CategoryIsNotValidUtf8)?;
+            }
+            "tags" => {
+                let data = field.bytes().await.unwrap();
+                if data.is_empty() {
+                    continue;
                 }
+                let string_data = String::from_utf8(data.to_vec()).map_err(|_| errors::Request::TagsArrayIsNotValidUtf8)?;
+                tags = serde_json::from_str(&string_data).map_err(|_| errors::Request::TagsArrayIsNotValidJson)?;
+            }
+            "piece_length" => {
+                let data = field.bytes().await.unwrap();
+                let string_data = String::from_utf8(data.to_vec()).map_err(|_| errors::Request::InvalidPieceLength)?;
+                piece_length = Some(string_data.trim().parse().map_err(|_| errors::Request::InvalidPieceLength)?);
+            }
+            "name" => {
+                let data = field.bytes().await.unwrap();
+                if data.is_empty() {
+                    continue;
+                }
+                name = Some(String::from_utf8(data.to_vec()).map_err(|_| errors::Request::NameIsNotValidUtf8)?);
+            }
+            "manifest" => {
+                let data = field.bytes().await.unwrap();
+                manifest = Some(serde_json::from_slice(&data).map_err(|_| errors::Request::ManifestIsNotValidJson)?);
+            }
+            "file" => {
+                let file_name = field.file_name().unwrap_or_default().to_owned();
+                let mut data = vec![];
 
                 while let Some(chunk) = field
                     .chunk()
                     .await
-                    .map_err(|_| (errors::Request::CannotReadChunkFromUploadedBinary))?
+                    .map_err(|_| errors::Request::CannotReadChunkFromUploadedBinary)?
                 {
-                    torrent_cursor
-                        .write_all(&chunk)
-                        .map_err(|_| (errors::Request::CannotWriteChunkFromUploadedBinary))?;
+                    data.extend_from_slice(&chunk);
                 }
+
+                file = Some((file_name, data));
             }
             _ => {}
         }
     }
 
-    Ok(AddTorrentRequest {
+    let piece_length = piece_length.ok_or(errors::Request::InvalidPieceLength)?;
+
+    let content = match (manifest, file) {
+        (Some(manifest), None) => TorrentContent::Manifest(manifest),
+        (None, Some((file_name, data))) => TorrentContent::File {
+            name: name.unwrap_or(file_name),
+            data,
+        },
+        _ => return Err(errors::Request::InvalidTorrentContent),
+    };
+
+    Ok(CreateTorrentFromContentRequest {
         title,
         description,
         category_name: category,
         tags,
-        torrent_buffer: torrent_cursor.into_inner(),
+        piece_length,
+        content,
     })
 }
+
+/// Extracts the torrent file bytes from the multipart form payload used to
+/// replace the torrent file of an existing torrent.
+///
+/// # Errors
+///
+/// It will return an error if:
+///
+/// - The content type is not `application/x-bittorrent` or `application/octet-stream`.
+/// - The multipart content is invalid.
+async fn build_replace_torrent_request_from_payload(mut payload: Multipart) -> Result<Vec<u8>, errors::Request> {
+    let torrent_buffer = vec![0u8];
+    let mut torrent_cursor = Cursor::new(torrent_buffer);
+
+    while let Some(mut field) = payload.next_field().await.unwrap() {
+        if field.name() == Some("torrent") {
+            write_torrent_file_field(&mut field, &mut torrent_cursor).await?;
+        }
+    }
+
+    Ok(torrent_cursor.into_inner())
+}
+
+/// Writes the uploaded torrent file from the multipart `torrent` field into
+/// the buffer.
+async fn write_torrent_file_field(field: &mut Field<'_>, torrent_cursor: &mut Cursor<Vec<u8>>) -> Result<(), errors::Request> {
+    let content_type = field.content_type().unwrap();
+
+    if content_type != "application/x-bittorrent" && content_type != "application/octet-stream" {
+        return Err(errors::Request::InvalidFileType);
+    }
+
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|_| errors::Request::CannotReadChunkFromUploadedBinary)?
+    {
+        torrent_cursor
+            .write_all(&chunk)
+            .map_err(|_| errors::Request::CannotWriteChunkFromUploadedBinary)?;
+    }
+
+    Ok(())
+}
@@ -1,10 +1,11 @@
+use axum::body::Body;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use hyper::{header, HeaderMap, StatusCode};
 use serde::{Deserialize, Serialize};
 
 use crate::models::torrent::TorrentId;
-use crate::services::torrent::AddTorrentResponse;
+use crate::services::torrent::{AddTorrentResponse, ReplaceTorrentResponse};
 use crate::web::api::server::v1::responses::OkResponseData;
 
 #[allow(clippy::module_name_repetitions)]
@@ -26,6 +27,31 @@ pub fn new_torrent_response(add_torrent_response: &AddTorrentResponse) -> Json<O
     })
 }
 
+#[allow(clippy::module_name_repetitions)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplacedTorrentResponseData {
+    pub torrent_id: TorrentId,
+    pub version: i64,
+    pub canonical_info_hash: String,
+    pub info_hash: String,
+    pub previous_info_hash: String,
+}
+
+/// Response after successfully replacing the torrent file of a torrent.
+pub fn replaced_torrent_response(
+    replace_torrent_response: &ReplaceTorrentResponse,
+) -> Json<OkResponseData<ReplacedTorrentResponseData>> {
+    Json(OkResponseData {
+        data: ReplacedTorrentResponseData {
+            torrent_id: replace_torrent_response.torrent_id,
+            version: replace_torrent_response.version,
+            canonical_info_hash: replace_torrent_response.canonical_info_hash.clone(),
+            info_hash: replace_torrent_response.info_hash.clone(),
+            previous_info_hash: replace_torrent_response.previous_info_hash.clone(),
+        },
+    })
+}
+
 /// Builds the binary response for a torrent file.
 ///
 /// # Panics
@@ -56,3 +82,26 @@ pub fn torrent_file_response(bytes: Vec<u8>, filename: &str, info_hash: &str) ->
 
     (StatusCode::OK, headers, bytes).into_response()
 }
+
+/// Response with a ZIP archive of torrent files. The body is streamed while
+/// the torrent files are being built.
+///
+/// # Panics
+///
+/// Panics if the headers are not valid header values.
+#[must_use]
+pub fn torrents_zip_response(body: Body) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        "application/zip".parse().expect("HTTP content type header should be valid"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        "attachment; filename=torrents.zip"
+            .parse()
+            .expect("ZIP filename should be a valid header value for the content disposition header"),
+    );
+
+    (StatusCode::OK, headers, body).into_response()
+}
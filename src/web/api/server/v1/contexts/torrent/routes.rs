@@ -7,8 +7,10 @@ use axum::routing::{delete, get, post, put};
 use axum::Router;
 
 use super::handlers::{
-    create_random_torrent_handler, delete_torrent_handler, download_torrent_handler, get_torrent_info_handler,
-    get_torrents_handler, update_torrent_info_handler, upload_torrent_handler,
+    add_magnet_torrent_handler, create_random_torrent_handler, create_torrent_handler, delete_torrent_handler,
+    download_torrent_handler, download_torrents_handler, get_torrent_files_handler, get_torrent_info_handler,
+    get_torrent_versions_handler, get_torrents_handler, replace_torrent_handler, update_torrent_info_handler,
+    upload_torrent_handler,
 };
 use crate::common::AppData;
 
@@ -17,10 +19,15 @@ pub fn router_for_single_resources(app_data: Arc<AppData>) -> Router {
     let torrent_info_routes = Router::new()
         .route("/", get(get_torrent_info_handler).with_state(app_data.clone()))
         .route("/", put(update_torrent_info_handler).with_state(app_data.clone()))
-        .route("/", delete(delete_torrent_handler).with_state(app_data.clone()));
+        .route("/", delete(delete_torrent_handler).with_state(app_data.clone()))
+        .route("/upload", post(replace_torrent_handler).with_state(app_data.clone()))
+        .route("/versions", get(get_torrent_versions_handler).with_state(app_data.clone()))
+        .route("/files", get(get_torrent_files_handler).with_state(app_data.clone()));
 
     Router::new()
         .route("/upload", post(upload_torrent_handler).with_state(app_data.clone()))
+        .route("/create", post(create_torrent_handler).with_state(app_data.clone()))
+        .route("/magnet", post(add_magnet_torrent_handler).with_state(app_data.clone()))
         .route(
             "/download/:info_hash",
             get(download_torrent_handler).with_state(app_data.clone()),
@@ -34,5 +41,7 @@ pub fn router_for_single_resources(app_data: Arc<AppData>) -> Router {
 
 /// Routes for the [`torrent`](crate::web::api::server::v1::contexts::torrent) API context for multiple resources.
 pub fn router_for_multiple_resources(app_data: Arc<AppData>) -> Router {
-    Router::new().route("/", get(get_torrents_handler).with_state(app_data))
+    Router::new()
+        .route("/", get(get_torrents_handler).with_state(app_data.clone()))
+        .route("/download", post(download_torrents_handler).with_state(app_data))
 }
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::category::CategoryId;
+use crate::models::notification::NotificationPreferences;
+use crate::models::torrent_tag::TagId;
+use crate::services::notification::UserNotificationSettings;
+
 // Registration
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -31,3 +36,39 @@ pub struct ChangePasswordForm {
     pub password: String,
     pub confirm_password: String,
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ChangeEmailForm {
+    pub current_password: String,
+    pub email: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RenameForm {
+    pub new_username: String,
+}
+
+// Notifications
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NotificationSettingsForm {
+    pub new_torrents: bool,
+    pub torrent_moderation: bool,
+    pub weekly_digest: bool,
+    pub followed_categories: Vec<CategoryId>,
+    pub followed_tags: Vec<TagId>,
+}
+
+impl From<NotificationSettingsForm> for UserNotificationSettings {
+    fn from(form: NotificationSettingsForm) -> Self {
+        Self {
+            preferences: NotificationPreferences {
+                new_torrents: form.new_torrents,
+                torrent_moderation: form.torrent_moderation,
+                weekly_digest: form.weekly_digest,
+            },
+            followed_categories: form.followed_categories,
+            followed_tags: form.followed_tags,
+        }
+    }
+}
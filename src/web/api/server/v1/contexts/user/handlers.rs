@@ -7,9 +7,12 @@ use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Deserialize;
 
-use super::forms::{ChangePasswordForm, JsonWebToken, LoginForm, RegistrationForm};
+use super::forms::{
+    ChangeEmailForm, ChangePasswordForm, JsonWebToken, LoginForm, NotificationSettingsForm, RegistrationForm, RenameForm,
+};
 use super::responses::{self};
 use crate::common::AppData;
+use crate::models::tracker_key::TrackerKey;
 use crate::web::api::server::v1::extractors::optional_user_id::ExtractOptionalLoggedInUser;
 use crate::web::api::server::v1::responses::OkResponseData;
 
@@ -150,6 +153,80 @@ pub async fn change_password_handler(
     }
 }
 
+/// It starts changing the user's email. A link to confirm the new address is
+/// sent to it.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not logged in.
+/// - The current password is not valid.
+/// - The new email is not valid or it's already used by an account.
+#[allow(clippy::unused_async)]
+pub async fn change_email_handler(
+    State(app_data): State<Arc<AppData>>,
+    Host(host_from_header): Host,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(change_email_form): extract::Json<ChangeEmailForm>,
+) -> Response {
+    let api_base_url = app_data
+        .cfg
+        .get_api_base_url()
+        .await
+        .unwrap_or(api_base_url(&host_from_header));
+
+    match app_data
+        .profile_service
+        .request_email_change(maybe_user_id, &change_email_form, &api_base_url)
+        .await
+    {
+        Ok(()) => Json(OkResponseData {
+            data: format!("Confirmation link sent to: {}", change_email_form.email.trim()),
+        })
+        .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It handles the confirmation of the new email with the token sent to it.
+#[allow(clippy::unused_async)]
+pub async fn email_change_confirmation_handler(State(app_data): State<Arc<AppData>>, Path(token): Path<TokenParam>) -> String {
+    match app_data.profile_service.confirm_email_change(&token.0).await {
+        Ok(()) => String::from("Email changed, you can close this page."),
+        Err(error) => error.to_string(),
+    }
+}
+
+/// It changes the username of a user. Only administrators can rename users.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an administrator.
+/// - The user to be renamed does not exist.
+/// - The new username breaks the username policy or it's already taken.
+#[allow(clippy::unused_async)]
+pub async fn rename_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(to_be_renamed_username): Path<UsernameParam>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(rename_form): extract::Json<RenameForm>,
+) -> Response {
+    match app_data
+        .profile_service
+        .rename_user(&to_be_renamed_username.0, &rename_form.new_username, maybe_user_id)
+        .await
+    {
+        Ok(new_username) => Json(OkResponseData {
+            data: format!("Renamed user: {} to {new_username}", to_be_renamed_username.0),
+        })
+        .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
 /// It bans a user from the index.
 ///
 /// # Errors
@@ -175,6 +252,156 @@ pub async fn ban_handler(
     }
 }
 
+/// It returns the user's current tracker key, the one embedded in their
+/// `.torrent` downloads.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not logged in.
+/// - The tracker could not issue a new key.
+#[allow(clippy::unused_async)]
+pub async fn get_tracker_key_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.tracker_key_service.get_tracker_key(maybe_user_id).await {
+        Ok(tracker_key) => tracker_key_response(&app_data, tracker_key).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It returns all the user's tracker keys that have not expired yet.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not logged in.
+/// - The keys could not be loaded.
+#[allow(clippy::unused_async)]
+pub async fn get_tracker_keys_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.tracker_key_service.get_tracker_keys(maybe_user_id).await {
+        Ok(tracker_keys) => Json(OkResponseData {
+            data: tracker_keys
+                .into_iter()
+                .map(|tracker_key| {
+                    let announce_url = app_data.tracker_service.announce_url_with_key(&tracker_key);
+                    responses::TrackerKeyResponse::from_tracker_key(tracker_key, &announce_url)
+                })
+                .collect::<Vec<_>>(),
+        })
+        .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It revokes all the user's tracker keys and returns a new one.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not logged in.
+/// - The old keys could not be revoked in the tracker.
+/// - The tracker could not issue a new key.
+#[allow(clippy::unused_async)]
+pub async fn rotate_tracker_key_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.tracker_key_service.rotate_tracker_key(maybe_user_id).await {
+        Ok(tracker_key) => tracker_key_response(&app_data, tracker_key).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It revokes all the tracker keys of a user.
+///
+/// # Errors
+///
+/// This function will return if:
+///
+/// - The user is not an admin.
+/// - The user does not exist.
+/// - The keys could not be revoked in the tracker.
+#[allow(clippy::unused_async)]
+pub async fn revoke_tracker_keys_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(username): Path<UsernameParam>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data
+        .tracker_key_service
+        .revoke_user_tracker_keys(&username.0, maybe_user_id)
+        .await
+    {
+        Ok(()) => Json(OkResponseData {
+            data: format!("Revoked tracker keys of user: {}", username.0),
+        })
+        .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+// Notifications
+
+/// It returns the notification settings of the logged-in user.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not logged in.
+/// - The settings could not be loaded.
+#[allow(clippy::unused_async)]
+pub async fn get_notification_settings_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.notification_service.get_settings(maybe_user_id).await {
+        Ok(settings) => Json(OkResponseData { data: settings }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It replaces the notification settings of the logged-in user.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not logged in.
+/// - A followed category or tag does not exist.
+/// - The settings could not be saved.
+#[allow(clippy::unused_async)]
+pub async fn update_notification_settings_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(notification_settings_form): extract::Json<NotificationSettingsForm>,
+) -> Response {
+    match app_data
+        .notification_service
+        .update_settings(&notification_settings_form.into(), maybe_user_id)
+        .await
+    {
+        Ok(settings) => Json(OkResponseData { data: settings }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+fn tracker_key_response(app_data: &Arc<AppData>, tracker_key: TrackerKey) -> Json<OkResponseData<responses::TrackerKeyResponse>> {
+    let announce_url = app_data.tracker_service.announce_url_with_key(&tracker_key);
+
+    Json(OkResponseData {
+        data: responses::TrackerKeyResponse::from_tracker_key(tracker_key, &announce_url),
+    })
+}
+
 /// It returns the base API URL without the port. For example: `http://localhost`.
 fn api_base_url(host: &str) -> String {
     // HTTPS is not supported yet.
@@ -1,6 +1,9 @@
 use axum::Json;
 use serde::{Deserialize, Serialize};
 
+use url::Url;
+
+use crate::models::tracker_key::TrackerKey;
 use crate::models::user::{UserCompact, UserId};
 use crate::web::api::server::v1::responses::OkResponseData;
 
@@ -48,3 +51,25 @@ pub fn renewed_token(token: String, user_compact: UserCompact) -> Json<OkRespons
         },
     })
 }
+
+// Tracker keys
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TrackerKeyResponse {
+    pub key: String,
+    /// Expiration time as a Unix timestamp in seconds.
+    pub valid_until: i64,
+    /// The announce URL with the key embedded in the user's `.torrent` files.
+    pub announce_url: String,
+}
+
+impl TrackerKeyResponse {
+    #[must_use]
+    pub fn from_tracker_key(tracker_key: TrackerKey, announce_url: &Url) -> Self {
+        Self {
+            key: tracker_key.key,
+            valid_until: tracker_key.valid_until,
+            announce_url: announce_url.to_string(),
+        }
+    }
+}
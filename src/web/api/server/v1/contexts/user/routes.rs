@@ -3,12 +3,14 @@
 //! Refer to the [API endpoint documentation](crate::web::api::server::v1::contexts::user).
 use std::sync::Arc;
 
-use axum::routing::{delete, get, post};
+use axum::routing::{delete, get, post, put};
 use axum::Router;
 
 use super::handlers::{
-    ban_handler, change_password_handler, email_verification_handler, login_handler, registration_handler, renew_token_handler,
-    verify_token_handler,
+    ban_handler, change_email_handler, change_password_handler, email_change_confirmation_handler, email_verification_handler,
+    get_notification_settings_handler, get_tracker_key_handler, get_tracker_keys_handler, login_handler, registration_handler,
+    rename_handler, renew_token_handler, revoke_tracker_keys_handler, rotate_tracker_key_handler,
+    update_notification_settings_handler, verify_token_handler,
 };
 use crate::common::AppData;
 
@@ -34,6 +36,32 @@ pub fn router(app_data: Arc<AppData>) -> Router {
             "/:user/change-password",
             post(change_password_handler).with_state(app_data.clone()),
         )
+        .route("/:user/change-email", post(change_email_handler).with_state(app_data.clone()))
+        .route(
+            "/email/change/:token",
+            get(email_change_confirmation_handler).with_state(app_data.clone()),
+        )
+        .route("/:user/rename", post(rename_handler).with_state(app_data.clone()))
+        // Tracker keys
+        .route("/tracker-key", get(get_tracker_key_handler).with_state(app_data.clone()))
+        .route("/tracker-keys", get(get_tracker_keys_handler).with_state(app_data.clone()))
+        .route(
+            "/tracker-key/rotate",
+            post(rotate_tracker_key_handler).with_state(app_data.clone()),
+        )
+        .route(
+            "/:user/tracker-keys",
+            delete(revoke_tracker_keys_handler).with_state(app_data.clone()),
+        )
+        // Notifications
+        .route(
+            "/notifications",
+            get(get_notification_settings_handler).with_state(app_data.clone()),
+        )
+        .route(
+            "/notifications",
+            put(update_notification_settings_handler).with_state(app_data.clone()),
+        )
         // User ban
         // code-review: should not this be a POST method? We add the user to the blacklist. We do not delete the user.
         .route("/ban/:user", delete(ban_handler).with_state(app_data))
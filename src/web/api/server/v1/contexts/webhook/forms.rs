@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::webhook::WebhookEvent;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddWebhookForm {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateWebhookForm {
+    pub url: String,
+    /// The current secret is kept when it's not provided.
+    pub secret: Option<String>,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+}
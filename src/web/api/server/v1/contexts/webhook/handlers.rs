@@ -0,0 +1,158 @@
+//! API handlers for the the [`webhook`](crate::web::api::server::v1::contexts::webhook) API
+//! context.
+use std::sync::Arc;
+
+use axum::extract::{self, Path, State};
+use axum::response::{IntoResponse, Json, Response};
+
+use super::forms::{AddWebhookForm, UpdateWebhookForm};
+use crate::common::AppData;
+use crate::models::webhook::{DeliveryId, WebhookId};
+use crate::web::api::server::v1::extractors::optional_user_id::ExtractOptionalLoggedInUser;
+use crate::web::api::server::v1::responses::OkResponseData;
+
+/// It returns all the webhooks.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn get_all_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.webhook_service.get_webhooks(maybe_user_id).await {
+        Ok(webhooks) => Json(OkResponseData { data: webhooks }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It adds a new webhook.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - The URL, the secret or the events are not valid.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn add_handler(
+    State(app_data): State<Arc<AppData>>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(add_webhook_form): extract::Json<AddWebhookForm>,
+) -> Response {
+    match app_data
+        .webhook_service
+        .add_webhook(
+            &add_webhook_form.url,
+            &add_webhook_form.secret,
+            add_webhook_form.events,
+            maybe_user_id,
+        )
+        .await
+    {
+        Ok(webhook) => Json(OkResponseData { data: webhook }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It updates a webhook.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - The webhook does not exist.
+/// - The URL, the secret or the events are not valid.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn update_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(webhook_id): Path<WebhookId>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+    extract::Json(update_webhook_form): extract::Json<UpdateWebhookForm>,
+) -> Response {
+    match app_data
+        .webhook_service
+        .update_webhook(
+            webhook_id,
+            &update_webhook_form.url,
+            update_webhook_form.secret.as_deref(),
+            update_webhook_form.events,
+            update_webhook_form.active,
+            maybe_user_id,
+        )
+        .await
+    {
+        Ok(webhook) => Json(OkResponseData { data: webhook }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It deletes a webhook and its delivery log.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - The webhook does not exist.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn delete_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(webhook_id): Path<WebhookId>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.webhook_service.delete_webhook(webhook_id, maybe_user_id).await {
+        Ok(()) => Json(OkResponseData { data: webhook_id }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It returns the latest deliveries of a webhook.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - The webhook does not exist.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn get_deliveries_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(webhook_id): Path<WebhookId>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.webhook_service.get_deliveries(webhook_id, maybe_user_id).await {
+        Ok(deliveries) => Json(OkResponseData { data: deliveries }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// It sends a delivery again.
+///
+/// # Errors
+///
+/// It returns an error if:
+///
+/// - The user is not an admin.
+/// - The delivery does not exist.
+/// - There is a database error.
+#[allow(clippy::unused_async)]
+pub async fn replay_delivery_handler(
+    State(app_data): State<Arc<AppData>>,
+    Path(delivery_id): Path<DeliveryId>,
+    ExtractOptionalLoggedInUser(maybe_user_id): ExtractOptionalLoggedInUser,
+) -> Response {
+    match app_data.webhook_service.replay_delivery(delivery_id, maybe_user_id).await {
+        Ok(()) => Json(OkResponseData { data: delivery_id }).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
@@ -0,0 +1,29 @@
+//! API routes for the [`webhook`](crate::web::api::server::v1::contexts::webhook) API context.
+//!
+//! Refer to the [API endpoint documentation](crate::web::api::server::v1::contexts::webhook).
+use std::sync::Arc;
+
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+
+use super::handlers::{
+    add_handler, delete_handler, get_all_handler, get_deliveries_handler, replay_delivery_handler, update_handler,
+};
+use crate::common::AppData;
+
+/// Routes for the [`webhook`](crate::web::api::server::v1::contexts::webhook) API context.
+pub fn router(app_data: Arc<AppData>) -> Router {
+    Router::new()
+        .route("/", get(get_all_handler).with_state(app_data.clone()))
+        .route("/", post(add_handler).with_state(app_data.clone()))
+        .route("/:webhook_id", put(update_handler).with_state(app_data.clone()))
+        .route("/:webhook_id", delete(delete_handler).with_state(app_data.clone()))
+        .route(
+            "/:webhook_id/deliveries",
+            get(get_deliveries_handler).with_state(app_data.clone()),
+        )
+        .route(
+            "/deliveries/:delivery_id/replay",
+            post(replay_delivery_handler).with_state(app_data),
+        )
+}
@@ -15,13 +15,26 @@ pub struct OkResponseData<T> {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ErrorResponseData {
     pub error: String,
+    /// A list of the specific problems found, when there is more than one
+    /// reason for the error. For example, all the rules an uploaded torrent
+    /// file violates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
 }
 
 impl IntoResponse for ServiceError {
     fn into_response(self) -> Response {
+        let details = match &self {
+            ServiceError::InvalidTorrentMetaInfo(violations) => violations.iter().map(ToString::to_string).collect(),
+            _ => vec![],
+        };
+
         json_error_response(
             http_status_code_for_service_error(&self),
-            &ErrorResponseData { error: self.to_string() },
+            &ErrorResponseData {
+                error: self.to_string(),
+                details,
+            },
         )
     }
 }
@@ -34,6 +47,7 @@ impl IntoResponse for database::Error {
             http_status_code_for_service_error(&service_error),
             &ErrorResponseData {
                 error: service_error.to_string(),
+                details: vec![],
             },
         )
     }
@@ -17,15 +17,29 @@ use tower_http::request_id::{MakeRequestUuid, SetRequestIdLayer};
 use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 use tracing::{Level, Span};
 
-use super::contexts::{about, category, proxy, settings, tag, torrent, user};
+use super::contexts::{about, category, event, mail, proxy, settings, tag, torrent, user, webhook};
 use crate::bootstrap::config::ENV_VAR_CORS_PERMISSIVE;
 use crate::common::AppData;
 
 pub const API_VERSION_URL_PREFIX: &str = "v1";
 
+/// The minimum request body size limit: 10 MiB.
+const MIN_BODY_LIMIT: usize = 10_485_760;
+
+/// Room for the other fields of the torrent upload form, besides the torrent
+/// file.
+const UPLOAD_FORM_FIELDS_SIZE: usize = 1_048_576;
+
 /// Add all API routes to the router.
 #[allow(clippy::needless_pass_by_value)]
-pub fn router(app_data: Arc<AppData>) -> Router {
+pub async fn router(app_data: Arc<AppData>) -> Router {
+    let max_torrent_file_size = app_data.cfg.settings.read().await.upload_policy.max_torrent_file_size;
+
+    // The body must fit the largest torrent file allowed by the upload policy.
+    let body_limit = usize::try_from(max_torrent_file_size)
+        .map_or(usize::MAX, |size| size.saturating_add(UPLOAD_FORM_FIELDS_SIZE))
+        .max(MIN_BODY_LIMIT);
+
     // code-review: should we use plural for the resource prefix: `users`, `categories`, `tags`?
     // Some endpoint are using plural (for instance, `get_categories`) and some singular.
     // See: https://stackoverflow.com/questions/6845772/should-i-use-singular-or-plural-name-convention-for-rest-resources
@@ -40,7 +54,10 @@ pub fn router(app_data: Arc<AppData>) -> Router {
         .nest("/settings", settings::routes::router(app_data.clone()))
         .nest("/torrent", torrent::routes::router_for_single_resources(app_data.clone()))
         .nest("/torrents", torrent::routes::router_for_multiple_resources(app_data.clone()))
-        .nest("/proxy", proxy::routes::router(app_data.clone()));
+        .nest("/proxy", proxy::routes::router(app_data.clone()))
+        .nest("/webhooks", webhook::routes::router(app_data.clone()))
+        .nest("/mails", mail::routes::router(app_data.clone()))
+        .nest("/events", event::routes::router(app_data.clone()));
 
     let router = Router::new()
         .route("/", get(redirect_to_about))
@@ -54,7 +71,7 @@ pub fn router(app_data: Arc<AppData>) -> Router {
     };
 
     router
-        .layer(DefaultBodyLimit::max(10_485_760))
+        .layer(DefaultBodyLimit::max(body_limit))
         .layer(CompressionLayer::new())
         .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(PropagateHeaderLayer::new(HeaderName::from_static("x-request-id")))
@@ -5,12 +5,15 @@ use serde::Serialize;
 
 use super::connection_info::ConnectionInfo;
 use super::contexts::category::forms::{AddCategoryForm, DeleteCategoryForm};
+use super::contexts::event::responses::EventStream;
 use super::contexts::tag::forms::{AddTagForm, DeleteTagForm};
-use super::contexts::torrent::forms::UpdateTorrentFrom;
+use super::contexts::torrent::forms::{AddMagnetTorrentForm, DownloadTorrentsForm, UpdateTorrentFrom};
 use super::contexts::torrent::requests::InfoHash;
 use super::contexts::user::forms::{
-    ChangePasswordForm, LoginForm, RegistrationForm, TokenRenewalForm, TokenVerificationForm, Username,
+    ChangeEmailForm, ChangePasswordForm, LoginForm, NotificationSettingsForm, RegistrationForm, RenameForm, TokenRenewalForm,
+    TokenVerificationForm, Username,
 };
+use super::contexts::webhook::forms::{AddWebhookForm, UpdateWebhookForm};
 use super::http::{Query, ReqwestQuery};
 use super::responses::{self, BinaryResponse, TextResponse};
 
@@ -84,6 +87,12 @@ impl Client {
         self.http_client.delete_with_body("/category", &delete_category_form).await
     }
 
+    // Context: event
+
+    pub async fn subscribe_to_events(&self, params: Query) -> EventStream {
+        EventStream::from(self.http_client.get_stream("/events", params).await)
+    }
+
     // Context: tag
 
     pub async fn get_tags(&self) -> TextResponse {
@@ -144,12 +153,38 @@ impl Client {
         self.http_client.post_multipart("/torrent/upload", form).await
     }
 
+    pub async fn add_magnet_torrent(&self, add_magnet_torrent_form: AddMagnetTorrentForm) -> TextResponse {
+        self.http_client.post("/torrent/magnet", &add_magnet_torrent_form).await
+    }
+
+    pub async fn replace_torrent(&self, info_hash: &InfoHash, form: multipart::Form) -> TextResponse {
+        self.http_client
+            .post_multipart(&format!("/torrent/{info_hash}/upload"), form)
+            .await
+    }
+
+    pub async fn get_torrent_versions(&self, info_hash: &InfoHash) -> TextResponse {
+        self.http_client
+            .get(&format!("/torrent/{info_hash}/versions"), Query::empty())
+            .await
+    }
+
+    pub async fn get_torrent_files(&self, info_hash: &InfoHash, params: Query) -> TextResponse {
+        self.http_client.get(&format!("/torrent/{info_hash}/files"), params).await
+    }
+
     pub async fn download_torrent(&self, info_hash: &InfoHash) -> responses::BinaryResponse {
         self.http_client
             .get_binary(&format!("/torrent/download/{info_hash}"), Query::empty())
             .await
     }
 
+    pub async fn download_torrents(&self, download_torrents_form: DownloadTorrentsForm) -> responses::BinaryResponse {
+        self.http_client
+            .post_binary("/torrents/download", &download_torrents_form)
+            .await
+    }
+
     // Context: user
 
     pub async fn register_user(&self, registration_form: RegistrationForm) -> TextResponse {
@@ -166,6 +201,18 @@ impl Client {
             .await
     }
 
+    pub async fn change_email(&self, username: Username, change_email_form: ChangeEmailForm) -> TextResponse {
+        self.http_client
+            .post(&format!("/user/{}/change-email", &username.value), &change_email_form)
+            .await
+    }
+
+    pub async fn confirm_email_change(&self, token: &str) -> TextResponse {
+        self.http_client
+            .get(&format!("/user/email/change/{token}"), Query::empty())
+            .await
+    }
+
     pub async fn verify_token(&self, token_verification_form: TokenVerificationForm) -> TextResponse {
         self.http_client.post("/user/token/verify", &token_verification_form).await
     }
@@ -174,9 +221,83 @@ impl Client {
         self.http_client.post("/user/token/renew", &token_verification_form).await
     }
 
+    pub async fn rename_user(&self, username: Username, rename_form: RenameForm) -> TextResponse {
+        self.http_client
+            .post(&format!("/user/{}/rename", &username.value), &rename_form)
+            .await
+    }
+
     pub async fn ban_user(&self, username: Username) -> TextResponse {
         self.http_client.delete(&format!("/user/ban/{}", &username.value)).await
     }
+
+    pub async fn get_tracker_key(&self) -> TextResponse {
+        self.http_client.get("/user/tracker-key", Query::empty()).await
+    }
+
+    pub async fn get_tracker_keys(&self) -> TextResponse {
+        self.http_client.get("/user/tracker-keys", Query::empty()).await
+    }
+
+    pub async fn rotate_tracker_key(&self) -> TextResponse {
+        self.http_client.post("/user/tracker-key/rotate", &()).await
+    }
+
+    pub async fn revoke_tracker_keys(&self, username: Username) -> TextResponse {
+        self.http_client
+            .delete(&format!("/user/{}/tracker-keys", &username.value))
+            .await
+    }
+
+    pub async fn get_notification_settings(&self) -> TextResponse {
+        self.http_client.get("/user/notifications", Query::empty()).await
+    }
+
+    pub async fn update_notification_settings(&self, notification_settings_form: NotificationSettingsForm) -> TextResponse {
+        self.http_client.put("/user/notifications", &notification_settings_form).await
+    }
+
+    // Context: webhook
+
+    pub async fn get_webhooks(&self) -> TextResponse {
+        self.http_client.get("/webhooks", Query::empty()).await
+    }
+
+    pub async fn add_webhook(&self, add_webhook_form: AddWebhookForm) -> TextResponse {
+        self.http_client.post("/webhooks", &add_webhook_form).await
+    }
+
+    pub async fn update_webhook(&self, webhook_id: i64, update_webhook_form: UpdateWebhookForm) -> TextResponse {
+        self.http_client
+            .put(&format!("/webhooks/{webhook_id}"), &update_webhook_form)
+            .await
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: i64) -> TextResponse {
+        self.http_client.delete(&format!("/webhooks/{webhook_id}")).await
+    }
+
+    pub async fn get_webhook_deliveries(&self, webhook_id: i64) -> TextResponse {
+        self.http_client
+            .get(&format!("/webhooks/{webhook_id}/deliveries"), Query::empty())
+            .await
+    }
+
+    pub async fn replay_webhook_delivery(&self, delivery_id: i64) -> TextResponse {
+        self.http_client
+            .post(&format!("/webhooks/deliveries/{delivery_id}/replay"), &())
+            .await
+    }
+
+    // Context: mail
+
+    pub async fn get_failed_mails(&self) -> TextResponse {
+        self.http_client.get("/mails/failed", Query::empty()).await
+    }
+
+    pub async fn retry_failed_mail(&self, mail_id: i64) -> TextResponse {
+        self.http_client.post(&format!("/mails/{mail_id}/retry"), &()).await
+    }
 }
 
 /// Generic HTTP Client
@@ -249,6 +370,20 @@ impl Http {
         BinaryResponse::from(response).await
     }
 
+    /// A `GET` request without timeout, for responses streamed indefinitely.
+    pub async fn get_stream(&self, path: &str, params: Query) -> reqwest::Response {
+        let request = reqwest::Client::new()
+            .get(self.base_url(path).clone())
+            .query(&ReqwestQuery::from(params));
+
+        let request = match &self.connection_info.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+
+        request.send().await.unwrap()
+    }
+
     pub async fn inner_get(&self, path: &str) -> Result<reqwest::Response, reqwest::Error> {
         reqwest::Client::builder()
             .timeout(self.timeout)
@@ -278,6 +413,25 @@ impl Http {
         TextResponse::from(response).await
     }
 
+    pub async fn post_binary<T: Serialize + ?Sized>(&self, path: &str, form: &T) -> BinaryResponse {
+        let response = match &self.connection_info.token {
+            Some(token) => reqwest::Client::new()
+                .post(self.base_url(path).clone())
+                .bearer_auth(token)
+                .json(&form)
+                .send()
+                .await
+                .unwrap(),
+            None => reqwest::Client::new()
+                .post(self.base_url(path).clone())
+                .json(&form)
+                .send()
+                .await
+                .unwrap(),
+        };
+        BinaryResponse::from(response).await
+    }
+
     pub async fn post_multipart(&self, path: &str, form: multipart::Form) -> TextResponse {
         let response = match &self.connection_info.token {
             Some(token) => reqwest::Client::builder()
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// An event received from the event stream.
+#[derive(Debug)]
+pub struct StreamedEvent {
+    pub event: String,
+    pub data: EventData,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct EventData {
+    pub torrent_id: i64,
+    pub info_hash: String,
+    pub title: Option<String>,
+    pub category_id: Option<i64>,
+    pub tag_ids: Vec<i64>,
+}
+
+/// A Server-Sent Events response.
+pub struct EventStream {
+    pub status: u16,
+    pub content_type: Option<String>,
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl EventStream {
+    pub fn from(response: reqwest::Response) -> Self {
+        Self {
+            status: response.status().as_u16(),
+            content_type: response
+                .headers()
+                .get("content-type")
+                .map(|content_type| content_type.to_str().unwrap().to_owned()),
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// It waits for the next event in the stream, skipping the keep-alive
+    /// comments.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the event does not arrive in time or the stream ends.
+    pub async fn next_event(&mut self) -> StreamedEvent {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let message: String = self.buffer.drain(..end + 2).collect();
+
+                if let Some(event) = parse_message(&message) {
+                    return event;
+                }
+
+                continue;
+            }
+
+            let chunk = tokio::time::timeout(Duration::from_secs(10), self.response.chunk())
+                .await
+                .expect("the event should arrive in time")
+                .unwrap()
+                .expect("the stream should not end");
+
+            self.buffer.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    }
+}
+
+fn parse_message(message: &str) -> Option<StreamedEvent> {
+    let mut event = None;
+    let mut data = None;
+
+    for line in message.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(serde_json::from_str(value.trim()).unwrap());
+        }
+    }
+
+    Some(StreamedEvent {
+        event: event?,
+        data: data?,
+    })
+}
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct FailedMailsResponse {
+    pub data: Vec<FailedMail>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct FailedMail {
+    pub mail_id: i64,
+    pub recipient: String,
+    pub subject: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub date_created: String,
+}
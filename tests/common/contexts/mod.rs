@@ -1,7 +1,10 @@
 pub mod about;
 pub mod category;
+pub mod event;
+pub mod mail;
 pub mod root;
 pub mod settings;
 pub mod tag;
 pub mod torrent;
 pub mod user;
+pub mod webhook;
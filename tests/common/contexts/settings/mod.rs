@@ -4,11 +4,12 @@ use std::net::SocketAddr;
 
 use serde::{Deserialize, Serialize};
 use torrust_index::config::{
-    Api as DomainApi, ApiToken, Auth as DomainAuth, Credentials as DomainCredentials, Database as DomainDatabase,
-    Email as DomainEmail, ImageCache as DomainImageCache, Logging as DomainLogging, Mail as DomainMail, Network as DomainNetwork,
-    PasswordConstraints as DomainPasswordConstraints, Registration as DomainRegistration, Settings as DomainSettings,
-    Smtp as DomainSmtp, Tracker as DomainTracker, TrackerStatisticsImporter as DomainTrackerStatisticsImporter,
-    Website as DomainWebsite,
+    Api as DomainApi, ApiToken, Argon2Params as DomainArgon2Params, Auth as DomainAuth, Credentials as DomainCredentials,
+    Database as DomainDatabase, Email as DomainEmail, FileTransport as DomainFileTransport, ImageCache as DomainImageCache,
+    Logging as DomainLogging, Mail as DomainMail, Network as DomainNetwork, PasswordConstraints as DomainPasswordConstraints,
+    Registration as DomainRegistration, Sendmail as DomainSendmail, Settings as DomainSettings, Smtp as DomainSmtp,
+    Tracker as DomainTracker, TrackerStatisticsImporter as DomainTrackerStatisticsImporter,
+    UsernameConstraints as DomainUsernameConstraints, Website as DomainWebsite,
 };
 use url::Url;
 
@@ -57,12 +58,35 @@ pub struct Network {
 pub struct Auth {
     pub user_claim_token_pepper: String,
     pub password_constraints: PasswordConstraints,
+    pub username_constraints: UsernameConstraints,
+    pub argon2: Argon2Params,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct PasswordConstraints {
     pub min_password_length: usize,
     pub max_password_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_special_character: bool,
+    pub min_strength_score: u8,
+    pub reject_username_or_email: bool,
+    pub breached_passwords_path: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct UsernameConstraints {
+    pub reserved_usernames: Vec<String>,
+    pub profanity_words_path: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Argon2Params {
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
@@ -74,9 +98,23 @@ pub struct Database {
 pub struct Mail {
     pub from: String,
     pub reply_to: String,
+    pub transport: String,
+    pub templates_path: Option<String>,
+    pub file: FileTransport,
+    pub sendmail: Sendmail,
     pub smtp: Smtp,
 }
 
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct FileTransport {
+    pub path: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Sendmail {
+    pub command: String,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct Smtp {
     pub server: String,
@@ -103,6 +141,7 @@ pub struct ImageCache {
 pub struct Api {
     pub default_torrent_page_size: u8,
     pub max_torrent_page_size: u8,
+    pub max_torrents_per_batch_download: u8,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
@@ -181,6 +220,8 @@ impl From<DomainAuth> for Auth {
         Self {
             user_claim_token_pepper: auth.user_claim_token_pepper.to_string(),
             password_constraints: auth.password_constraints.into(),
+            username_constraints: auth.username_constraints.into(),
+            argon2: auth.argon2.into(),
         }
     }
 }
@@ -190,6 +231,36 @@ impl From<DomainPasswordConstraints> for PasswordConstraints {
         Self {
             min_password_length: password_constraints.min_password_length,
             max_password_length: password_constraints.max_password_length,
+            require_lowercase: password_constraints.require_lowercase,
+            require_uppercase: password_constraints.require_uppercase,
+            require_digit: password_constraints.require_digit,
+            require_special_character: password_constraints.require_special_character,
+            min_strength_score: password_constraints.min_strength_score,
+            reject_username_or_email: password_constraints.reject_username_or_email,
+            breached_passwords_path: password_constraints
+                .breached_passwords_path
+                .map(|path| path.to_string_lossy().to_string()),
+        }
+    }
+}
+
+impl From<DomainUsernameConstraints> for UsernameConstraints {
+    fn from(username_constraints: DomainUsernameConstraints) -> Self {
+        Self {
+            reserved_usernames: username_constraints.reserved_usernames,
+            profanity_words_path: username_constraints
+                .profanity_words_path
+                .map(|path| path.to_string_lossy().to_string()),
+        }
+    }
+}
+
+impl From<DomainArgon2Params> for Argon2Params {
+    fn from(argon2: DomainArgon2Params) -> Self {
+        Self {
+            memory_cost: argon2.memory_cost,
+            time_cost: argon2.time_cost,
+            parallelism: argon2.parallelism,
         }
     }
 }
@@ -207,11 +278,31 @@ impl From<DomainMail> for Mail {
         Self {
             from: mail.from.to_string(),
             reply_to: mail.reply_to.to_string(),
+            transport: mail.transport.to_string(),
+            templates_path: mail.templates_path.map(|path| path.to_string_lossy().to_string()),
+            file: mail.file.into(),
+            sendmail: mail.sendmail.into(),
             smtp: mail.smtp.into(),
         }
     }
 }
 
+impl From<DomainFileTransport> for FileTransport {
+    fn from(file: DomainFileTransport) -> Self {
+        Self {
+            path: file.path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+impl From<DomainSendmail> for Sendmail {
+    fn from(sendmail: DomainSendmail) -> Self {
+        Self {
+            command: sendmail.command,
+        }
+    }
+}
+
 impl From<DomainSmtp> for Smtp {
     fn from(smtp: DomainSmtp) -> Self {
         Self {
@@ -248,6 +339,7 @@ impl From<DomainApi> for Api {
         Self {
             default_torrent_page_size: api.default_torrent_page_size,
             max_torrent_page_size: api.max_torrent_page_size,
+            max_torrents_per_batch_download: api.max_torrents_per_batch_download,
         }
     }
 }
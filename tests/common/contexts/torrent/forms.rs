@@ -11,6 +11,20 @@ pub struct UpdateTorrentFrom {
     pub tags: Option<Vec<i64>>,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct DownloadTorrentsForm {
+    pub info_hashes: Option<Vec<String>>,
+    pub filter: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AddMagnetTorrentForm {
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub magnet_link: String,
+}
+
 use reqwest::multipart::Form;
 
 pub struct UploadTorrentMultipartForm {
@@ -50,3 +64,19 @@ impl From<UploadTorrentMultipartForm> for Form {
             )
     }
 }
+
+pub struct ReplaceTorrentMultipartForm {
+    pub torrent_file: BinaryFile,
+}
+
+impl From<ReplaceTorrentMultipartForm> for Form {
+    fn from(form: ReplaceTorrentMultipartForm) -> Self {
+        Form::new().part(
+            "torrent",
+            reqwest::multipart::Part::bytes(form.torrent_file.contents)
+                .file_name(form.torrent_file.name)
+                .mime_str("application/x-bittorrent")
+                .unwrap(),
+        )
+    }
+}
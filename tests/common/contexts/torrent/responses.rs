@@ -5,6 +5,7 @@ pub type CategoryId = i64;
 pub type TagId = i64;
 pub type UtcDateTime = String; // %Y-%m-%d %H:%M:%S
 
+#[allow(dead_code)]
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct ErrorResponse {
     pub error: String,
@@ -123,3 +124,56 @@ pub struct UpdatedTorrentResponse {
 }
 
 pub type UpdatedTorrent = TorrentDetails;
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct ReplacedTorrentResponse {
+    pub data: ReplacedTorrent,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct ReplacedTorrent {
+    pub torrent_id: Id,
+    pub version: i64,
+    pub canonical_info_hash: String,
+    pub info_hash: String,
+    pub previous_info_hash: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct TorrentVersionsResponse {
+    pub data: Vec<TorrentVersion>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct TorrentVersion {
+    pub version: i64,
+    pub info_hash: String,
+    pub name: String,
+    pub size: i64,
+    pub replaced_by: Option<Id>,
+    pub date_replaced: UtcDateTime,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct TorrentFilesResponse {
+    pub data: TorrentFiles,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct TorrentFiles {
+    pub path: Vec<String>,
+    pub size: u64,
+    pub file_count: u64,
+    pub total: u32,
+    pub results: Vec<FileTreeEntry>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct FileTreeEntry {
+    pub kind: String,
+    pub name: String,
+    pub path: Vec<String>,
+    pub size: u64,
+    pub file_count: u64,
+    pub md5sum: Option<String>,
+}
@@ -42,3 +42,23 @@ pub struct ChangePasswordForm {
     pub password: String,
     pub confirm_password: String,
 }
+
+#[derive(Serialize)]
+pub struct ChangeEmailForm {
+    pub current_password: String,
+    pub email: String,
+}
+
+#[derive(Serialize)]
+pub struct RenameForm {
+    pub new_username: String,
+}
+
+#[derive(Serialize)]
+pub struct NotificationSettingsForm {
+    pub new_torrents: bool,
+    pub torrent_moderation: bool,
+    pub weekly_digest: bool,
+    pub followed_categories: Vec<i64>,
+    pub followed_tags: Vec<i64>,
+}
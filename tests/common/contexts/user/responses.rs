@@ -45,3 +45,35 @@ pub struct TokenRenewalData {
     pub username: String,
     pub admin: bool,
 }
+
+#[derive(Deserialize)]
+pub struct TrackerKeyResponse {
+    pub data: TrackerKeyData,
+}
+
+#[derive(Deserialize)]
+pub struct TrackerKeysResponse {
+    pub data: Vec<TrackerKeyData>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct TrackerKeyData {
+    pub key: String,
+    pub valid_until: i64,
+    pub announce_url: String,
+}
+
+#[derive(Deserialize)]
+pub struct NotificationSettingsResponse {
+    pub data: NotificationSettingsData,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct NotificationSettingsData {
+    pub new_torrents: bool,
+    pub torrent_moderation: bool,
+    pub weekly_digest: bool,
+    pub followed_categories: Vec<i64>,
+    pub followed_tags: Vec<i64>,
+}
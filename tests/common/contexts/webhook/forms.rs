@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct AddWebhookForm {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct UpdateWebhookForm {
+    pub url: String,
+    pub secret: Option<String>,
+    pub events: Vec<String>,
+    pub active: bool,
+}
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct AddedWebhookResponse {
+    pub data: Webhook,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ListResponse {
+    pub data: Vec<Webhook>,
+}
+
+#[allow(clippy::struct_field_names)]
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Webhook {
+    pub webhook_id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub date_created: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeliveriesResponse {
+    pub data: Vec<Delivery>,
+}
+
+#[allow(clippy::struct_field_names)]
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Delivery {
+    pub delivery_id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i64,
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub date_created: String,
+    pub date_delivered: Option<String>,
+}
@@ -0,0 +1,177 @@
+//! API contract for `event` context.
+
+use torrust_index::web::api;
+use uuid::Uuid;
+
+use crate::common::client::Client;
+use crate::common::contexts::event::responses::{EventStream, StreamedEvent};
+use crate::common::contexts::torrent::forms::AddMagnetTorrentForm;
+use crate::common::http::{Query, QueryParam};
+use crate::e2e::environment::TestEnv;
+use crate::e2e::web::api::v1::contexts::category::steps::add_random_category;
+use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_admin, new_logged_in_user};
+
+fn random_info_hash() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), &Uuid::new_v4().simple().to_string()[..8])
+}
+
+/// It adds a magnet-only torrent to the Index and returns its info-hash.
+async fn add_magnet_torrent(client: &Client, category: &str) -> String {
+    let info_hash = random_info_hash();
+
+    let response = client
+        .add_magnet_torrent(AddMagnetTorrentForm {
+            title: format!("title-{info_hash}"),
+            description: "description".to_string(),
+            category: category.to_string(),
+            magnet_link: format!("magnet:?xt=urn:btih:{info_hash}"),
+        })
+        .await;
+
+    assert!(response.is_json_and_ok());
+
+    info_hash
+}
+
+/// It skips the events of the torrents added by other tests running
+/// concurrently.
+async fn next_event_for(events: &mut EventStream, info_hash: &str) -> StreamedEvent {
+    loop {
+        let event = events.next_event().await;
+
+        if event.data.info_hash == info_hash {
+            return event;
+        }
+    }
+}
+
+#[tokio::test]
+async fn it_should_allow_guests_to_subscribe_to_the_event_stream() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+    let events = client.subscribe_to_events(Query::empty()).await;
+
+    assert_eq!(events.status, 200);
+    assert_eq!(events.content_type, Some("text/event-stream".to_string()));
+}
+
+#[tokio::test]
+async fn it_should_not_allow_subscribing_to_a_category_that_does_not_exist() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+    let unknown_category = format!("category-{}", Uuid::new_v4());
+
+    let events = client
+        .subscribe_to_events(Query::with_params(vec![QueryParam::new("categories", &unknown_category)]))
+        .await;
+
+    assert_eq!(events.status, 400);
+}
+
+#[tokio::test]
+async fn it_should_not_allow_subscribing_to_a_tag_that_does_not_exist() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+    let unknown_tag = format!("tag-{}", Uuid::new_v4());
+
+    let events = client
+        .subscribe_to_events(Query::with_params(vec![QueryParam::new("tags", &unknown_tag)]))
+        .await;
+
+    assert_eq!(events.status, 400);
+}
+
+#[tokio::test]
+async fn it_should_send_an_event_when_a_torrent_is_added() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    if !env.provides_a_tracker() {
+        println!("test skipped. It requires a tracker to be running.");
+        return;
+    }
+
+    let category = add_random_category(&env).await;
+
+    let mut events = Client::unauthenticated(&env.server_socket_addr().unwrap())
+        .subscribe_to_events(Query::empty())
+        .await;
+
+    let uploader = new_logged_in_user(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &uploader.token);
+
+    let info_hash = add_magnet_torrent(&client, &category).await;
+
+    let event = next_event_for(&mut events, &info_hash).await;
+
+    assert_eq!(event.event, "torrent.added");
+    assert_eq!(event.data.title, Some(format!("title-{info_hash}")));
+}
+
+#[tokio::test]
+async fn it_should_send_an_event_when_a_torrent_is_deleted() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    if !env.provides_a_tracker() {
+        println!("test skipped. It requires a tracker to be running.");
+        return;
+    }
+
+    let category = add_random_category(&env).await;
+
+    let admin = new_logged_in_admin(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &admin.token);
+
+    let info_hash = add_magnet_torrent(&client, &category).await;
+
+    let mut events = Client::unauthenticated(&env.server_socket_addr().unwrap())
+        .subscribe_to_events(Query::empty())
+        .await;
+
+    let response = client.delete_torrent(&info_hash).await;
+    assert_eq!(response.status, 200);
+
+    let event = next_event_for(&mut events, &info_hash).await;
+
+    assert_eq!(event.event, "torrent.deleted");
+}
+
+#[tokio::test]
+async fn it_should_only_send_the_events_for_the_torrents_in_the_subscribed_categories() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    if !env.provides_a_tracker() {
+        println!("test skipped. It requires a tracker to be running.");
+        return;
+    }
+
+    let subscribed_category = add_random_category(&env).await;
+    let other_category = add_random_category(&env).await;
+
+    let mut events = Client::unauthenticated(&env.server_socket_addr().unwrap())
+        .subscribe_to_events(Query::with_params(vec![QueryParam::new("categories", &subscribed_category)]))
+        .await;
+
+    let uploader = new_logged_in_user(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &uploader.token);
+
+    add_magnet_torrent(&client, &other_category).await;
+    let info_hash = add_magnet_torrent(&client, &subscribed_category).await;
+
+    // The first event is already the one for the subscribed category.
+    let event = events.next_event().await;
+
+    assert_eq!(event.event, "torrent.added");
+    assert_eq!(event.data.info_hash, info_hash);
+}
@@ -0,0 +1,2 @@
+//! API contract for `event` context.
+pub mod contract;
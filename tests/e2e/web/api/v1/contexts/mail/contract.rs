@@ -0,0 +1,101 @@
+//! API contract for `mail` context.
+use std::sync::Arc;
+
+use torrust_index::databases::database;
+use torrust_index::models::mail::Mail;
+use torrust_index::web::api;
+
+use crate::common::client::Client;
+use crate::common::contexts::mail::responses::{FailedMail, FailedMailsResponse};
+use crate::e2e::environment::TestEnv;
+use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_admin, new_logged_in_user};
+
+async fn database(env: &TestEnv) -> Arc<Box<dyn database::Database>> {
+    Arc::new(
+        database::connect(&env.database_connect_url().unwrap())
+            .await
+            .expect("Database error."),
+    )
+}
+
+/// It adds an email to the outbox the mailer gave up delivering.
+async fn add_failed_mail(env: &TestEnv) -> i64 {
+    let database = database(env).await;
+
+    let mail_id = database
+        .add_mail_to_outbox(&Mail {
+            recipient: "indiana@example.com".to_string(),
+            subject: "Torrust - Email verification".to_string(),
+            body_plain: "Welcome to Torrust".to_string(),
+            body_html: "<p>Welcome to Torrust</p>".to_string(),
+        })
+        .await
+        .unwrap();
+
+    database.fail_outbox_mail(mail_id, "Connection refused").await.unwrap();
+
+    mail_id
+}
+
+async fn failed_mails(client: &Client) -> Vec<FailedMail> {
+    let response = client.get_failed_mails().await;
+
+    assert_eq!(response.status, 200);
+
+    serde_json::from_str::<FailedMailsResponse>(&response.body).unwrap().data
+}
+
+#[tokio::test]
+async fn it_should_allow_admins_to_list_and_retry_the_failed_emails() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let mail_id = add_failed_mail(&env).await;
+
+    let logged_in_admin = new_logged_in_admin(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+    let failed_mail = failed_mails(&client)
+        .await
+        .into_iter()
+        .find(|mail| mail.mail_id == mail_id)
+        .unwrap();
+
+    assert_eq!(failed_mail.recipient, "indiana@example.com");
+    assert_eq!(failed_mail.last_error, Some("Connection refused".to_string()));
+
+    let response = client.retry_failed_mail(mail_id).await;
+
+    assert_eq!(response.status, 200);
+    assert!(!failed_mails(&client).await.iter().any(|mail| mail.mail_id == mail_id));
+
+    // It's not failed anymore.
+    let response = client.retry_failed_mail(mail_id).await;
+
+    assert_eq!(response.status, 404);
+}
+
+#[tokio::test]
+async fn it_should_not_allow_non_admins_to_list_or_retry_the_failed_emails() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let logged_in_user = new_logged_in_user(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+    assert_eq!(client.get_failed_mails().await.status, 403);
+    assert_eq!(client.retry_failed_mail(1).await.status, 403);
+}
+
+#[tokio::test]
+async fn it_should_not_retry_an_email_that_does_not_exist() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let logged_in_admin = new_logged_in_admin(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+    let response = client.retry_failed_mail(i64::MAX).await;
+
+    assert_eq!(response.status, 404);
+}
@@ -0,0 +1,2 @@
+//! API contract for `mail` context.
+pub mod contract;
@@ -1,8 +1,11 @@
 pub mod about;
 pub mod category;
+pub mod event;
+pub mod mail;
 pub mod proxy;
 pub mod root;
 pub mod settings;
 pub mod tag;
 pub mod torrent;
 pub mod user;
+pub mod webhook;
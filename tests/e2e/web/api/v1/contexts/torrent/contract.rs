@@ -26,7 +26,7 @@ mod for_guests {
     use crate::common::contexts::torrent::fixtures::TestTorrent;
     use crate::common::contexts::torrent::requests::InfoHash;
     use crate::common::contexts::torrent::responses::{
-        Category, File, TorrentDetails, TorrentDetailsResponse, TorrentListResponse,
+        Category, File, FileTreeEntry, TorrentDetails, TorrentDetailsResponse, TorrentFilesResponse, TorrentListResponse,
     };
     use crate::common::http::{Query, QueryParam};
     use crate::e2e::environment::TestEnv;
@@ -193,6 +193,99 @@ mod for_guests {
         assert!(response.is_json_and_ok());
     }
 
+    #[tokio::test]
+    async fn it_should_allow_guests_to_list_the_files_in_a_torrent() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.provides_a_tracker() {
+            println!("test skipped. It requires a tracker to be running.");
+            return;
+        }
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        let uploader = new_logged_in_user(&env).await;
+        let (test_torrent, _uploaded_torrent) = upload_random_torrent_to_index(&uploader, &env).await;
+
+        let response = client.get_torrent_files(&test_torrent.file_info_hash(), Query::empty()).await;
+
+        let torrent_files_response: TorrentFilesResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(torrent_files_response.data.size, test_torrent.file_info.content_size);
+        assert_eq!(torrent_files_response.data.file_count, 1);
+        assert_eq!(torrent_files_response.data.total, 1);
+        assert_eq!(
+            torrent_files_response.data.results,
+            vec![FileTreeEntry {
+                kind: "file".to_string(),
+                name: test_torrent.file_info.files[0].clone(),
+                path: vec![test_torrent.file_info.files[0].clone()],
+                size: test_torrent.file_info.content_size,
+                file_count: 1,
+                md5sum: None, // DevSkim: ignore DS126858
+            }]
+        );
+        assert!(response.is_json_and_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_return_a_not_found_error_listing_a_non_existing_torrent_directory() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.provides_a_tracker() {
+            println!("test skipped. It requires a tracker to be running.");
+            return;
+        }
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        let uploader = new_logged_in_user(&env).await;
+        let (test_torrent, _uploaded_torrent) = upload_random_torrent_to_index(&uploader, &env).await;
+
+        let response = client
+            .get_torrent_files(
+                &test_torrent.file_info_hash(),
+                Query::with_params([QueryParam::new("path", "non-existing-directory")].to_vec()),
+            )
+            .await;
+
+        assert_eq!(response.status, 404);
+    }
+
+    #[tokio::test]
+    async fn it_should_allow_guests_to_search_torrents_by_file_path() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.provides_a_tracker() {
+            println!("test skipped. It requires a tracker to be running.");
+            return;
+        }
+
+        let uploader = new_logged_in_user(&env).await;
+        let (test_torrent, _uploaded_torrent) = upload_random_torrent_to_index(&uploader, &env).await;
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        // The file name is not included in the torrent title
+        let response = client
+            .get_torrents(Query::with_params(
+                [QueryParam::new("search", &test_torrent.file_info.files[0])].to_vec(),
+            ))
+            .await;
+
+        let torrent_list_response: TorrentListResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(torrent_list_response.data.total, 1);
+        assert_eq!(
+            torrent_list_response.data.results[0].info_hash,
+            test_torrent.file_info_hash().to_lowercase()
+        );
+        assert!(response.is_json_and_ok());
+    }
+
     #[tokio::test]
     async fn it_should_allow_guests_to_find_torrent_details_using_a_non_canonical_info_hash() {
         let mut env = TestEnv::new();
@@ -410,7 +503,8 @@ mod for_guests {
 
         use crate::common::client::Client;
         use crate::common::contexts::torrent::fixtures::random_torrent;
-        use crate::common::contexts::torrent::forms::UploadTorrentMultipartForm;
+        use crate::common::contexts::torrent::forms::{BinaryFile, ReplaceTorrentMultipartForm, UploadTorrentMultipartForm};
+        use crate::common::contexts::torrent::requests::InfoHash;
         use crate::common::contexts::torrent::responses::TorrentListResponse;
         use crate::common::http::Query;
         use crate::e2e::environment::TestEnv;
@@ -433,6 +527,27 @@ mod for_guests {
             assert_eq!(response.status, 401);
         }
 
+        #[tokio::test]
+        async fn it_should_not_allow_guests_to_replace_torrent_files() {
+            let mut env = TestEnv::new();
+            env.start(api::Version::V1).await;
+
+            let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+            let info_hash: InfoHash = "443c7602b4fde83d1154d6d9da48808418b181b6".to_string();
+
+            let form = ReplaceTorrentMultipartForm {
+                torrent_file: BinaryFile {
+                    name: "replacement.torrent".to_string(),
+                    contents: vec![],
+                },
+            };
+
+            let response = client.replace_torrent(&info_hash, form.into()).await;
+
+            assert_eq!(response.status, 401);
+        }
+
         #[tokio::test]
         async fn it_should_not_allow_guests_to_delete_torrents() {
             let mut env = TestEnv::new();
@@ -613,7 +728,9 @@ mod for_authenticated_users {
 
         mod it_should_guard_that_the_torrent_file {
 
+            use torrust_index::utils::parse_torrent::DecodeTorrentFileError;
             use torrust_index::web::api;
+            use torrust_index::web::api::server::v1::responses::ErrorResponseData;
 
             use crate::common::client::Client;
             use crate::common::contexts::torrent::fixtures::random_torrent;
@@ -686,6 +803,37 @@ mod for_authenticated_users {
 
                 assert_eq!(response.status, 400);
             }
+
+            #[tokio::test]
+            async fn is_consistent_and_all_the_violated_rules_are_listed_in_the_response() {
+                let mut env = TestEnv::new();
+                env.start(api::Version::V1).await;
+
+                let uploader = new_logged_in_user(&env).await;
+                let client = Client::authenticated(&env.server_socket_addr().unwrap(), &uploader.token);
+
+                let mut test_torrent = random_torrent();
+
+                // Two files for only one piece, and a file path with a `..` component.
+                // cspell:disable-next-line
+                let inconsistent_torrent = b"d4:infod5:filesld6:lengthi20000e4:pathl2:..5:a.txteed6:lengthi1e4:pathl5:b.txteee4:name3:dir12:piece lengthi16384e6:pieces20:AAAAAAAAAAAAAAAAAAAAee";
+                test_torrent.index_info.torrent_file.contents = inconsistent_torrent.to_vec();
+
+                let form: UploadTorrentMultipartForm = test_torrent.index_info.into();
+
+                let response = client.upload_torrent(form.into()).await;
+
+                let error_response_data: ErrorResponseData = serde_json::from_str(&response.body).unwrap();
+
+                assert_eq!(response.status, 400);
+                assert_eq!(
+                    error_response_data.details,
+                    vec![
+                        DecodeTorrentFileError::UnsafeFilePath.to_string(),
+                        DecodeTorrentFileError::PieceCountMismatch.to_string()
+                    ]
+                );
+            }
         }
 
         #[tokio::test]
@@ -840,12 +988,130 @@ mod for_authenticated_users {
         }
     }
 
+    mod adding_a_magnet_link {
+
+        use torrust_index::web::api;
+
+        use crate::common::client::Client;
+        use crate::common::contexts::torrent::fixtures::random_torrent;
+        use crate::common::contexts::torrent::forms::AddMagnetTorrentForm;
+        use crate::common::contexts::torrent::responses::UploadedTorrentResponse;
+        use crate::e2e::environment::TestEnv;
+        use crate::e2e::web::api::v1::contexts::user::steps::new_logged_in_user;
+
+        #[tokio::test]
+        async fn it_should_list_the_torrent_without_its_torrent_file() {
+            let mut env = TestEnv::new();
+            env.start(api::Version::V1).await;
+
+            if !env.provides_a_tracker() {
+                println!("test skipped. It requires a tracker to be running.");
+                return;
+            }
+
+            let uploader = new_logged_in_user(&env).await;
+            let client = Client::authenticated(&env.server_socket_addr().unwrap(), &uploader.token);
+
+            let test_torrent = random_torrent();
+            let info_hash = test_torrent.file_info_hash().to_lowercase();
+
+            let response = client
+                .add_magnet_torrent(AddMagnetTorrentForm {
+                    title: test_torrent.index_info.title.clone(),
+                    description: test_torrent.index_info.description.clone(),
+                    category: test_torrent.index_info.category.clone(),
+                    magnet_link: format!("magnet:?xt=urn:btih:{info_hash}&dn=magnet-only"),
+                })
+                .await;
+
+            assert!(response.is_json_and_ok());
+
+            let added_torrent_response: UploadedTorrentResponse = serde_json::from_str(&response.body).unwrap();
+
+            assert_eq!(added_torrent_response.data.canonical_info_hash, info_hash);
+
+            let response = client.get_torrent(&info_hash).await;
+
+            let torrent_details: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+
+            assert_eq!(torrent_details["data"]["magnet_only"], true);
+
+            let response = client.download_torrent(&info_hash).await;
+
+            assert_eq!(response.status, 404);
+        }
+    }
+
+    mod downloading_several_torrents {
+
+        use torrust_index::web::api;
+
+        use crate::common::client::Client;
+        use crate::common::contexts::torrent::forms::DownloadTorrentsForm;
+        use crate::e2e::environment::TestEnv;
+        use crate::e2e::web::api::v1::contexts::torrent::steps::upload_random_torrent_to_index;
+        use crate::e2e::web::api::v1::contexts::user::steps::new_logged_in_user;
+
+        #[tokio::test]
+        async fn it_should_return_a_zip_archive_with_the_torrent_files() {
+            let mut env = TestEnv::new();
+            env.start(api::Version::V1).await;
+
+            if !env.provides_a_tracker() {
+                println!("test skipped. It requires a tracker to be running.");
+                return;
+            }
+
+            let uploader = new_logged_in_user(&env).await;
+            let client = Client::authenticated(&env.server_socket_addr().unwrap(), &uploader.token);
+
+            let (first_torrent, _) = upload_random_torrent_to_index(&uploader, &env).await;
+            let (second_torrent, _) = upload_random_torrent_to_index(&uploader, &env).await;
+
+            let response = client
+                .download_torrents(DownloadTorrentsForm {
+                    info_hashes: Some(vec![first_torrent.file_info_hash(), second_torrent.file_info_hash()]),
+                    filter: None,
+                })
+                .await;
+
+            assert_eq!(response.status, 200);
+            assert_eq!(response.content_type, Some("application/zip".to_string()));
+
+            // Each torrent file is stored uncompressed in the archive.
+            let single_download = client.download_torrent(&first_torrent.file_info_hash()).await;
+
+            assert!(response
+                .bytes
+                .windows(single_download.bytes.len())
+                .any(|window| window == single_download.bytes.as_slice()));
+        }
+
+        #[tokio::test]
+        async fn it_should_fail_when_the_torrents_to_download_are_not_provided() {
+            let mut env = TestEnv::new();
+            env.start(api::Version::V1).await;
+
+            let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+            let response = client
+                .download_torrents(DownloadTorrentsForm {
+                    info_hashes: None,
+                    filter: None,
+                })
+                .await;
+
+            assert_eq!(response.status, 400);
+        }
+    }
+
     mod and_non_admins {
 
         use torrust_index::web::api;
 
         use crate::common::client::Client;
-        use crate::common::contexts::torrent::forms::UpdateTorrentFrom;
+        use crate::common::contexts::torrent::fixtures::random_torrent;
+        use crate::common::contexts::torrent::forms::{ReplaceTorrentMultipartForm, UpdateTorrentFrom};
         use crate::e2e::environment::TestEnv;
         use crate::e2e::web::api::v1::contexts::torrent::steps::upload_random_torrent_to_index;
         use crate::e2e::web::api::v1::contexts::user::steps::new_logged_in_user;
@@ -905,6 +1171,33 @@ mod for_authenticated_users {
 
             assert_eq!(response.status, 403);
         }
+
+        #[tokio::test]
+        async fn it_should_not_allow_non_admin_users_to_replace_someone_elses_torrent_files() {
+            let mut env = TestEnv::new();
+            env.start(api::Version::V1).await;
+
+            if !env.provides_a_tracker() {
+                println!("test skipped. It requires a tracker to be running.");
+                return;
+            }
+
+            // Given a users uploads a torrent
+            let uploader = new_logged_in_user(&env).await;
+            let (test_torrent, _uploaded_torrent) = upload_random_torrent_to_index(&uploader, &env).await;
+
+            // Then another non admin user should not be able to replace the torrent file
+            let not_the_uploader = new_logged_in_user(&env).await;
+            let client = Client::authenticated(&env.server_socket_addr().unwrap(), &not_the_uploader.token);
+
+            let form = ReplaceTorrentMultipartForm {
+                torrent_file: random_torrent().index_info.torrent_file,
+            };
+
+            let response = client.replace_torrent(&test_torrent.file_info_hash(), form.into()).await;
+
+            assert_eq!(response.status, 403);
+        }
     }
 
     mod and_torrent_owners {
@@ -912,8 +1205,11 @@ mod for_authenticated_users {
         use torrust_index::web::api;
 
         use crate::common::client::Client;
-        use crate::common::contexts::torrent::forms::UpdateTorrentFrom;
-        use crate::common::contexts::torrent::responses::UpdatedTorrentResponse;
+        use crate::common::contexts::torrent::fixtures::random_torrent;
+        use crate::common::contexts::torrent::forms::{ReplaceTorrentMultipartForm, UpdateTorrentFrom};
+        use crate::common::contexts::torrent::responses::{
+            ReplacedTorrentResponse, TorrentVersionsResponse, UpdatedTorrentResponse,
+        };
         use crate::e2e::environment::TestEnv;
         use crate::e2e::web::api::v1::contexts::torrent::steps::upload_random_torrent_to_index;
         use crate::e2e::web::api::v1::contexts::user::steps::new_logged_in_user;
@@ -956,6 +1252,50 @@ mod for_authenticated_users {
             assert_eq!(torrent.description, new_description);
             assert!(response.is_json_and_ok());
         }
+
+        #[tokio::test]
+        async fn it_should_allow_torrent_owners_to_replace_their_torrent_files() {
+            let mut env = TestEnv::new();
+            env.start(api::Version::V1).await;
+
+            if !env.provides_a_tracker() {
+                println!("test skipped. It requires a tracker to be running.");
+                return;
+            }
+
+            let uploader = new_logged_in_user(&env).await;
+            let (test_torrent, uploaded_torrent) = upload_random_torrent_to_index(&uploader, &env).await;
+
+            let client = Client::authenticated(&env.server_socket_addr().unwrap(), &uploader.token);
+
+            let new_test_torrent = random_torrent();
+
+            let form = ReplaceTorrentMultipartForm {
+                torrent_file: new_test_torrent.index_info.torrent_file.clone(),
+            };
+
+            let response = client.replace_torrent(&test_torrent.file_info_hash(), form.into()).await;
+
+            let replaced_torrent_response: ReplacedTorrentResponse = serde_json::from_str(&response.body).unwrap();
+
+            assert_eq!(replaced_torrent_response.data.torrent_id, uploaded_torrent.torrent_id);
+            assert_eq!(replaced_torrent_response.data.version, 2);
+            assert_eq!(replaced_torrent_response.data.info_hash, new_test_torrent.file_info_hash());
+            assert_eq!(
+                replaced_torrent_response.data.previous_info_hash,
+                test_torrent.file_info_hash()
+            );
+            assert!(response.is_json_and_ok());
+
+            // The previous torrent file should be in the version history
+            let response = client.get_torrent_versions(&new_test_torrent.file_info_hash()).await;
+
+            let torrent_versions_response: TorrentVersionsResponse = serde_json::from_str(&response.body).unwrap();
+
+            assert_eq!(torrent_versions_response.data.len(), 1);
+            assert_eq!(torrent_versions_response.data[0].version, 1);
+            assert_eq!(torrent_versions_response.data[0].info_hash, test_torrent.file_info_hash());
+        }
     }
 
     mod and_admins {
@@ -33,7 +33,9 @@ mod registration {
     use crate::common::client::Client;
     use crate::common::contexts::user::asserts::assert_added_user_response;
     use crate::common::contexts::user::fixtures::random_user_registration_form;
+    use crate::common::contexts::user::forms::RegistrationForm;
     use crate::e2e::environment::TestEnv;
+    use crate::e2e::web::api::v1::contexts::user::steps::new_registered_user;
 
     #[tokio::test]
     async fn it_should_allow_a_guest_user_to_register() {
@@ -48,6 +50,44 @@ mod registration {
 
         assert_added_user_response(&response);
     }
+
+    #[tokio::test]
+    async fn it_should_not_allow_a_guest_user_to_register_a_reserved_username() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        let form = RegistrationForm {
+            username: "Ad_min".to_string(),
+            ..random_user_registration_form()
+        };
+
+        let response = client.register_user(form).await;
+
+        assert_eq!(response.status, 400);
+        assert!(response.body.contains("Username contains blacklisted words"));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_registering_a_username_that_only_differs_in_case_from_an_existing_one() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        let registered_user = new_registered_user(&env).await;
+
+        let form = RegistrationForm {
+            username: registered_user.username.to_uppercase(),
+            ..random_user_registration_form()
+        };
+
+        let response = client.register_user(form).await;
+
+        assert_eq!(response.status, 400);
+        assert!(response.body.contains("Username not available"));
+    }
 }
 
 mod authentication {
@@ -178,6 +218,279 @@ mod authentication {
     }
 }
 
+mod email_change {
+
+    use std::sync::Arc;
+
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use torrust_index::databases::database;
+    use torrust_index::mailer::VerifyClaims;
+    use torrust_index::utils::clock;
+    use torrust_index::web::api;
+
+    use crate::common::client::Client;
+    use crate::common::contexts::user::fixtures::DEFAULT_PASSWORD;
+    use crate::common::contexts::user::forms::{ChangeEmailForm, Username};
+    use crate::e2e::environment::TestEnv;
+    use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_user, new_registered_user};
+
+    /// The token in the link sent to the new email.
+    fn email_change_token(env: &TestEnv, user_id: i64, new_email: &str) -> String {
+        let claims = VerifyClaims {
+            iss: String::from("email-change"),
+            sub: user_id,
+            exp: clock::now() + 3600,
+            email: Some(new_email.to_string()),
+        };
+
+        let pepper = env.server_settings().unwrap().auth.user_claim_token_pepper;
+
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(pepper.as_bytes())).unwrap()
+    }
+
+    async fn database(env: &TestEnv) -> Arc<Box<dyn database::Database>> {
+        Arc::new(
+            database::connect(&env.database_connect_url().unwrap())
+                .await
+                .expect("Database error."),
+        )
+    }
+
+    #[tokio::test]
+    async fn it_should_allow_a_logged_in_user_to_change_their_email_after_confirming_the_new_one() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let database = database(&env).await;
+
+        let user_profile = database
+            .get_user_profile_from_username(&logged_in_user.username)
+            .await
+            .unwrap();
+
+        let new_email = format!("new_{}", user_profile.email);
+
+        let response = client
+            .change_email(
+                Username::new(logged_in_user.username.clone()),
+                ChangeEmailForm {
+                    current_password: DEFAULT_PASSWORD.to_string(),
+                    email: new_email.clone(),
+                },
+            )
+            .await;
+
+        assert_eq!(response.status, 200);
+
+        // The email does not change until the new one is confirmed
+        let pending_user_profile = database
+            .get_user_profile_from_username(&logged_in_user.username)
+            .await
+            .unwrap();
+        assert_eq!(pending_user_profile.email, user_profile.email);
+
+        let response = client
+            .confirm_email_change(&email_change_token(&env, user_profile.user_id, &new_email))
+            .await;
+
+        assert_eq!(response.body, "Email changed, you can close this page.");
+
+        let changed_user_profile = database
+            .get_user_profile_from_username(&logged_in_user.username)
+            .await
+            .unwrap();
+        assert_eq!(changed_user_profile.email, new_email);
+        assert!(changed_user_profile.email_verified);
+    }
+
+    #[tokio::test]
+    async fn it_should_only_accept_the_link_of_the_last_requested_email_change() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let database = database(&env).await;
+
+        let user_profile = database
+            .get_user_profile_from_username(&logged_in_user.username)
+            .await
+            .unwrap();
+
+        let first_email = format!("first_{}", user_profile.email);
+        let second_email = format!("second_{}", user_profile.email);
+
+        for email in [&first_email, &second_email] {
+            let response = client
+                .change_email(
+                    Username::new(logged_in_user.username.clone()),
+                    ChangeEmailForm {
+                        current_password: DEFAULT_PASSWORD.to_string(),
+                        email: email.clone(),
+                    },
+                )
+                .await;
+
+            assert_eq!(response.status, 200);
+        }
+
+        let response = client
+            .confirm_email_change(&email_change_token(&env, user_profile.user_id, &first_email))
+            .await;
+
+        assert!(response.body.contains("This email change is not pending anymore"));
+
+        let unchanged_user_profile = database
+            .get_user_profile_from_username(&logged_in_user.username)
+            .await
+            .unwrap();
+        assert_eq!(unchanged_user_profile.email, user_profile.email);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_changing_the_email_to_one_used_by_another_account() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let other_user = new_registered_user(&env).await;
+
+        let response = client
+            .change_email(
+                Username::new(logged_in_user.username.clone()),
+                ChangeEmailForm {
+                    current_password: DEFAULT_PASSWORD.to_string(),
+                    email: other_user.email.unwrap(),
+                },
+            )
+            .await;
+
+        assert_eq!(response.status, 400);
+        assert!(response.body.contains("Email not available"));
+    }
+
+    #[tokio::test]
+    async fn it_should_require_the_current_password_to_change_the_email() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = client
+            .change_email(
+                Username::new(logged_in_user.username.clone()),
+                ChangeEmailForm {
+                    current_password: "wrong password".to_string(),
+                    email: "new_email@email.com".to_string(),
+                },
+            )
+            .await;
+
+        assert_eq!(response.status, 403);
+    }
+}
+
+mod user_rename {
+
+    use torrust_index::web::api;
+
+    use crate::common::client::Client;
+    use crate::common::contexts::user::forms::{LoginForm, RenameForm, Username};
+    use crate::e2e::environment::TestEnv;
+    use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_admin, new_logged_in_user, new_registered_user};
+
+    #[tokio::test]
+    async fn it_should_allow_an_admin_to_rename_a_user() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_admin = new_logged_in_admin(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+        let registered_user = new_registered_user(&env).await;
+
+        let new_username = registered_user.username.replace("username", "renamed");
+
+        let response = client
+            .rename_user(
+                Username::new(registered_user.username.clone()),
+                RenameForm {
+                    new_username: new_username.clone(),
+                },
+            )
+            .await;
+
+        assert_eq!(response.status, 200);
+
+        let response = Client::unauthenticated(&env.server_socket_addr().unwrap())
+            .login_user(LoginForm {
+                login: new_username,
+                password: registered_user.password.clone(),
+            })
+            .await;
+
+        assert_eq!(response.status, 200);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_an_admin_to_rename_a_user_to_a_reserved_username() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_admin = new_logged_in_admin(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+        let registered_user = new_registered_user(&env).await;
+
+        let response = client
+            .rename_user(
+                Username::new(registered_user.username.clone()),
+                RenameForm {
+                    new_username: "Support".to_string(),
+                },
+            )
+            .await;
+
+        assert_eq!(response.status, 400);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_a_non_admin_to_rename_a_user() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_non_admin = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_non_admin.token);
+
+        let registered_user = new_registered_user(&env).await;
+
+        let response = client
+            .rename_user(
+                Username::new(registered_user.username.clone()),
+                RenameForm {
+                    new_username: registered_user.username.replace("username", "renamed"),
+                },
+            )
+            .await;
+
+        assert_eq!(response.status, 403);
+    }
+}
+
 mod banned_user_list {
 
     use torrust_index::web::api;
@@ -234,3 +547,264 @@ mod banned_user_list {
         assert_eq!(response.status, 401);
     }
 }
+
+mod tracker_keys {
+
+    use torrust_index::web::api;
+
+    use crate::common::client::Client;
+    use crate::common::contexts::user::forms::Username;
+    use crate::common::contexts::user::responses::{TrackerKeyResponse, TrackerKeysResponse};
+    use crate::e2e::environment::TestEnv;
+    use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_admin, new_logged_in_user, new_registered_user};
+
+    #[tokio::test]
+    async fn it_should_allow_a_logged_in_user_to_rotate_their_tracker_key() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.provides_a_tracker() {
+            println!("test skipped. It requires a tracker to be running.");
+            return;
+        }
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = client.get_tracker_key().await;
+        assert_eq!(response.status, 200);
+        let old_key = serde_json::from_str::<TrackerKeyResponse>(&response.body).unwrap().data;
+
+        let response = client.rotate_tracker_key().await;
+        assert_eq!(response.status, 200);
+        let new_key = serde_json::from_str::<TrackerKeyResponse>(&response.body).unwrap().data;
+
+        assert_ne!(old_key.key, new_key.key);
+        assert!(new_key.announce_url.ends_with(&new_key.key));
+
+        let response = client.get_tracker_keys().await;
+        assert_eq!(response.status, 200);
+        let active_keys = serde_json::from_str::<TrackerKeysResponse>(&response.body).unwrap().data;
+
+        assert_eq!(
+            active_keys
+                .iter()
+                .map(|tracker_key| tracker_key.key.clone())
+                .collect::<Vec<_>>(),
+            vec![new_key.key]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_a_guest_to_get_a_tracker_key() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        let response = client.get_tracker_key().await;
+
+        assert_eq!(response.status, 401);
+    }
+
+    #[tokio::test]
+    async fn it_should_allow_an_admin_to_revoke_the_tracker_keys_of_a_user() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.provides_a_tracker() {
+            println!("test skipped. It requires a tracker to be running.");
+            return;
+        }
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let user_client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = user_client.get_tracker_key().await;
+        assert_eq!(response.status, 200);
+
+        let logged_in_admin = new_logged_in_admin(&env).await;
+
+        let admin_client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+        let response = admin_client
+            .revoke_tracker_keys(Username::new(logged_in_user.username.clone()))
+            .await;
+        assert_eq!(response.status, 200);
+
+        let response = user_client.get_tracker_keys().await;
+        let active_keys = serde_json::from_str::<TrackerKeysResponse>(&response.body).unwrap().data;
+
+        assert!(active_keys.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_a_non_admin_to_revoke_the_tracker_keys_of_a_user() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_non_admin = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_non_admin.token);
+
+        let registered_user = new_registered_user(&env).await;
+
+        let response = client
+            .revoke_tracker_keys(Username::new(registered_user.username.clone()))
+            .await;
+
+        assert_eq!(response.status, 403);
+    }
+}
+
+mod notification_settings {
+
+    use torrust_index::web::api;
+
+    use crate::common::client::Client;
+    use crate::common::contexts::category::responses::ListResponse;
+    use crate::common::contexts::user::forms::NotificationSettingsForm;
+    use crate::common::contexts::user::responses::{NotificationSettingsData, NotificationSettingsResponse};
+    use crate::e2e::environment::TestEnv;
+    use crate::e2e::web::api::v1::contexts::user::steps::new_logged_in_user;
+
+    #[tokio::test]
+    async fn it_should_disable_all_notifications_by_default() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = client.get_notification_settings().await;
+
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            serde_json::from_str::<NotificationSettingsResponse>(&response.body)
+                .unwrap()
+                .data,
+            NotificationSettingsData {
+                new_torrents: false,
+                torrent_moderation: false,
+                weekly_digest: false,
+                followed_categories: vec![],
+                followed_tags: vec![],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_allow_a_logged_in_user_to_update_their_notification_settings() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = client.get_categories().await;
+        let category_id = serde_json::from_str::<ListResponse>(&response.body).unwrap().data[0].category_id;
+
+        let response = client
+            .update_notification_settings(NotificationSettingsForm {
+                new_torrents: true,
+                torrent_moderation: true,
+                weekly_digest: false,
+                followed_categories: vec![category_id, category_id],
+                followed_tags: vec![],
+            })
+            .await;
+        assert_eq!(response.status, 200);
+
+        let response = client.get_notification_settings().await;
+
+        assert_eq!(
+            serde_json::from_str::<NotificationSettingsResponse>(&response.body)
+                .unwrap()
+                .data,
+            NotificationSettingsData {
+                new_torrents: true,
+                torrent_moderation: true,
+                weekly_digest: false,
+                followed_categories: vec![category_id],
+                followed_tags: vec![],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_following_a_non_existing_category() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = client
+            .update_notification_settings(NotificationSettingsForm {
+                new_torrents: true,
+                torrent_moderation: false,
+                weekly_digest: false,
+                followed_categories: vec![i64::MAX],
+                followed_tags: vec![],
+            })
+            .await;
+
+        assert_eq!(response.status, 400);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_change_any_notification_setting_when_the_update_fails() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let logged_in_user = new_logged_in_user(&env).await;
+
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+        let response = client.get_categories().await;
+        let category_id = serde_json::from_str::<ListResponse>(&response.body).unwrap().data[0].category_id;
+
+        let response = client
+            .update_notification_settings(NotificationSettingsForm {
+                new_torrents: true,
+                torrent_moderation: true,
+                weekly_digest: true,
+                followed_categories: vec![category_id],
+                followed_tags: vec![i64::MAX],
+            })
+            .await;
+        assert_eq!(response.status, 400);
+
+        let response = client.get_notification_settings().await;
+
+        assert_eq!(
+            serde_json::from_str::<NotificationSettingsResponse>(&response.body)
+                .unwrap()
+                .data,
+            NotificationSettingsData {
+                new_torrents: false,
+                torrent_moderation: false,
+                weekly_digest: false,
+                followed_categories: vec![],
+                followed_tags: vec![],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_not_allow_a_guest_to_get_notification_settings() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        let client = Client::unauthenticated(&env.server_socket_addr().unwrap());
+
+        let response = client.get_notification_settings().await;
+
+        assert_eq!(response.status, 401);
+    }
+}
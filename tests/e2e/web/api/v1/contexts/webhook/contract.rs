@@ -0,0 +1,238 @@
+//! API contract for `webhook` context.
+
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use torrust_index::web::api;
+
+use crate::common::client::Client;
+use crate::common::contexts::webhook::forms::{AddWebhookForm, UpdateWebhookForm};
+use crate::common::contexts::webhook::responses::{AddedWebhookResponse, DeliveriesResponse, Delivery, ListResponse};
+use crate::e2e::environment::TestEnv;
+use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_admin, new_logged_in_user};
+
+const SECRET: &str = "webhook-secret";
+
+fn add_webhook_form(url: &str) -> AddWebhookForm {
+    AddWebhookForm {
+        url: url.to_string(),
+        secret: SECRET.to_string(),
+        events: vec!["user.registered".to_string()],
+    }
+}
+
+fn signature(body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn wait_for_delivery(client: &Client, webhook_id: i64, condition: impl Fn(&Delivery) -> bool) -> Delivery {
+    for _ in 0..100 {
+        let response = client.get_webhook_deliveries(webhook_id).await;
+        let res: DeliveriesResponse = serde_json::from_str(&response.body).unwrap();
+
+        if let Some(delivery) = res.data.into_iter().find(|delivery| condition(delivery)) {
+            return delivery;
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("the delivery was not logged in time");
+}
+
+#[tokio::test]
+async fn it_should_allow_admins_to_add_a_webhook() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let logged_in_admin = new_logged_in_admin(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+    let response = client.add_webhook(add_webhook_form("http://example.com/hook")).await;
+
+    assert_eq!(response.status, 200);
+    let added = serde_json::from_str::<AddedWebhookResponse>(&response.body).unwrap().data;
+    assert_eq!(added.url, "http://example.com/hook");
+    assert_eq!(added.events, vec!["user.registered".to_string()]);
+    assert!(added.active);
+    assert!(!response.body.contains(SECRET));
+
+    let response = client.get_webhooks().await;
+
+    let res: ListResponse = serde_json::from_str(&response.body).unwrap();
+    assert!(res.data.contains(&added));
+}
+
+#[tokio::test]
+async fn it_should_not_allow_non_admins_to_add_webhooks() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let logged_in_user = new_logged_in_user(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_user.token);
+
+    let response = client.add_webhook(add_webhook_form("http://example.com/hook")).await;
+
+    assert_eq!(response.status, 403);
+}
+
+#[tokio::test]
+async fn it_should_not_allow_adding_a_webhook_with_an_invalid_url() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let logged_in_admin = new_logged_in_admin(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+    let response = client.add_webhook(add_webhook_form("ftp://example.com/hook")).await;
+
+    assert_eq!(response.status, 400);
+}
+
+#[tokio::test]
+async fn it_should_allow_admins_to_update_and_delete_a_webhook() {
+    let mut env = TestEnv::new();
+    env.start(api::Version::V1).await;
+
+    let logged_in_admin = new_logged_in_admin(&env).await;
+    let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+    let response = client.add_webhook(add_webhook_form("http://example.com/hook")).await;
+    let added = serde_json::from_str::<AddedWebhookResponse>(&response.body).unwrap().data;
+
+    let response = client
+        .update_webhook(
+            added.webhook_id,
+            UpdateWebhookForm {
+                url: "https://example.com/new-hook".to_string(),
+                secret: None,
+                events: vec!["torrent.deleted".to_string(), "torrent.uploaded".to_string()],
+                active: false,
+            },
+        )
+        .await;
+
+    assert_eq!(response.status, 200);
+    let updated = serde_json::from_str::<AddedWebhookResponse>(&response.body).unwrap().data;
+    assert_eq!(updated.url, "https://example.com/new-hook");
+    assert_eq!(
+        updated.events,
+        vec!["torrent.uploaded".to_string(), "torrent.deleted".to_string()]
+    );
+    assert!(!updated.active);
+
+    let response = client.delete_webhook(added.webhook_id).await;
+    assert_eq!(response.status, 200);
+
+    let response = client.delete_webhook(added.webhook_id).await;
+    assert_eq!(response.status, 404);
+}
+
+mod for_isolated_environments {
+    use axum::http::StatusCode;
+    use torrust_index::web::api;
+
+    use super::{add_webhook_form, signature, wait_for_delivery};
+    use crate::common::client::Client;
+    use crate::common::contexts::webhook::responses::AddedWebhookResponse;
+    use crate::e2e::environment::TestEnv;
+    use crate::e2e::web::api::v1::contexts::user::steps::{new_logged_in_admin, new_registered_user};
+    use crate::e2e::web::api::v1::contexts::webhook::receiver::Receiver;
+
+    #[tokio::test]
+    async fn it_should_send_a_signed_payload_when_a_subscribed_event_happens() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.is_isolated() {
+            println!("test skipped. It requires the Index to reach a local webhook receiver.");
+            return;
+        }
+
+        let receiver = Receiver::start(StatusCode::OK).await;
+
+        let logged_in_admin = new_logged_in_admin(&env).await;
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+        let response = client.add_webhook(add_webhook_form(&receiver.url)).await;
+        let webhook = serde_json::from_str::<AddedWebhookResponse>(&response.body).unwrap().data;
+
+        let registered_user = new_registered_user(&env).await;
+
+        let requests = receiver.wait_for_requests(1).await;
+        let request = &requests[0];
+
+        assert_eq!(request.event, "user.registered");
+        assert_eq!(request.signature, signature(&request.body));
+
+        let payload: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(payload["event"], "user.registered");
+        assert_eq!(payload["data"]["username"], registered_user.username);
+
+        let delivery = wait_for_delivery(&client, webhook.webhook_id, |delivery| delivery.status == "delivered").await;
+        assert_eq!(delivery.delivery_id.to_string(), request.delivery_id);
+        assert_eq!(delivery.response_status, Some(200));
+        assert_eq!(delivery.payload, request.body);
+    }
+
+    #[tokio::test]
+    async fn it_should_allow_admins_to_replay_a_delivery() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.is_isolated() {
+            println!("test skipped. It requires the Index to reach a local webhook receiver.");
+            return;
+        }
+
+        let receiver = Receiver::start(StatusCode::OK).await;
+
+        let logged_in_admin = new_logged_in_admin(&env).await;
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+        let response = client.add_webhook(add_webhook_form(&receiver.url)).await;
+        let webhook = serde_json::from_str::<AddedWebhookResponse>(&response.body).unwrap().data;
+
+        new_registered_user(&env).await;
+
+        let delivery = wait_for_delivery(&client, webhook.webhook_id, |delivery| delivery.status == "delivered").await;
+
+        let response = client.replay_webhook_delivery(delivery.delivery_id).await;
+        assert_eq!(response.status, 200);
+
+        let requests = receiver.wait_for_requests(2).await;
+
+        assert_eq!(requests[1].delivery_id, delivery.delivery_id.to_string());
+        assert_eq!(requests[1].body, requests[0].body);
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_a_failed_delivery_pending_to_retry_it_later() {
+        let mut env = TestEnv::new();
+        env.start(api::Version::V1).await;
+
+        if !env.is_isolated() {
+            println!("test skipped. It requires the Index to reach a local webhook receiver.");
+            return;
+        }
+
+        let receiver = Receiver::start(StatusCode::INTERNAL_SERVER_ERROR).await;
+
+        let logged_in_admin = new_logged_in_admin(&env).await;
+        let client = Client::authenticated(&env.server_socket_addr().unwrap(), &logged_in_admin.token);
+
+        let response = client.add_webhook(add_webhook_form(&receiver.url)).await;
+        let webhook = serde_json::from_str::<AddedWebhookResponse>(&response.body).unwrap().data;
+
+        new_registered_user(&env).await;
+
+        let delivery = wait_for_delivery(&client, webhook.webhook_id, |delivery| delivery.attempts == 1).await;
+
+        assert_eq!(delivery.status, "pending");
+        assert_eq!(delivery.response_status, Some(500));
+        assert!(delivery.date_delivered.is_none());
+    }
+}
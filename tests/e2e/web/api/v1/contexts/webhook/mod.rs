@@ -0,0 +1,3 @@
+//! API contract for `webhook` context.
+pub mod contract;
+pub mod receiver;
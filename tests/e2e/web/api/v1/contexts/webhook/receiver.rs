@@ -0,0 +1,95 @@
+//! HTTP server used as webhook endpoint in the tests.
+//!
+//! It records every request it receives and replies with a fixed status code.
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub event: String,
+    pub delivery_id: String,
+    pub signature: String,
+    pub body: String,
+}
+
+#[derive(Clone)]
+struct ReceiverState {
+    status: StatusCode,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+pub struct Receiver {
+    pub url: String,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl Receiver {
+    /// It starts a receiver replying with the given status code.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if it can't bind to a local port.
+    pub async fn start(status: StatusCode) -> Self {
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let router = Router::new().route("/hook", post(receive)).with_state(ReceiverState {
+            status,
+            requests: requests.clone(),
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("it should bind to a local port");
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self { url, requests }
+    }
+
+    /// It waits until the receiver has received at least `count` requests.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the requests don't arrive in time.
+    pub async fn wait_for_requests(&self, count: usize) -> Vec<ReceivedRequest> {
+        for _ in 0..100 {
+            let requests = self.requests.lock().unwrap().clone();
+
+            if requests.len() >= count {
+                return requests;
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        panic!("the receiver did not get {count} requests in time");
+    }
+}
+
+async fn receive(State(state): State<ReceiverState>, headers: HeaderMap, body: String) -> StatusCode {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    state.requests.lock().unwrap().push(ReceivedRequest {
+        event: header("X-Torrust-Event"),
+        delivery_id: header("X-Torrust-Delivery"),
+        signature: header("X-Torrust-Signature-256"),
+        body,
+    });
+
+    state.status
+}
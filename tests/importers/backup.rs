@@ -0,0 +1,132 @@
+//! Tests for the `export_index` and `import_index` commands, exporting the
+//! whole Index into a backup archive and restoring it into a new database.
+//!
+//! You can run them with:
+//!
+//! ```text
+//! cargo test importers::backup -- --nocapture
+//! ```
+use std::fs;
+use std::str::FromStr;
+
+use tempfile::TempDir;
+use torrust_index::console::commands::backup::{export, import};
+use torrust_index::databases::database::{self, Database};
+use torrust_index::models::info_hash::InfoHash;
+use torrust_index::models::magnet_link::MagnetLink;
+use torrust_index::models::torrent::Metadata;
+use torrust_index::utils::parse_torrent::decode_torrent;
+use url::Url;
+
+const TORRENT_FIXTURE: &str = "./tests/fixtures/torrents/MC_GRID.zip-3cd18ff2d3eec881207dcc5ca5a2c3a2a3afe462.torrent";
+/// The canonical info-hash of the fixture, the one stored by the Index.
+const TORRENT_INFO_HASH: &str = "eec5a52e7661cdb55a91a8335cbff1b9dd2bc0c3";
+const MAGNET_INFO_HASH: &str = "6c6a0d3f4f4d3f0e6dd2b7b7d2d8c2f4e1e5b6a7";
+const PASSWORD_HASH: &str = "$argon2id$v=19$m=4096,t=3,p=1$c2FsdHNhbHQ$hqrnM4pZ2THLWUbTVu3nuzCNQ9E6eqbtq2jvp+aFqw0";
+
+async fn new_index_database(dir: &TempDir, name: &str) -> Box<dyn Database> {
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join(name).display());
+
+    database::connect(&url).await.expect("Can't create the Index database.")
+}
+
+/// It adds a torrent with meta-info and another one listed from a magnet link.
+async fn populate(database: &dyn Database) {
+    let uploader_id = database
+        .insert_user_and_get_id("alice", "alice@example.com", PASSWORD_HASH)
+        .await
+        .unwrap();
+
+    // The `software` category is created by the Index migrations.
+    let category_id = database.get_category_from_name("software").await.unwrap().category_id;
+
+    let torrent = decode_torrent(&fs::read(TORRENT_FIXTURE).unwrap()).unwrap();
+
+    database
+        .insert_torrent_and_get_id(
+            &torrent.canonical_info_hash(),
+            &torrent,
+            uploader_id,
+            &Metadata {
+                title: "MC Grid".to_string(),
+                description: "A Minecraft map".to_string(),
+                category_id,
+                tags: vec![],
+            },
+        )
+        .await
+        .unwrap();
+
+    let magnet_link = MagnetLink::from_str(&format!(
+        "magnet:?xt=urn:btih:{MAGNET_INFO_HASH}&dn=Mandelbrot%20Set&tr=udp%3A%2F%2Ftracker.example.com%3A6969&ws=https%3A%2F%2Fexample.com%2Fseed"
+    ))
+    .unwrap();
+
+    database
+        .insert_magnet_torrent_and_get_id(
+            &magnet_link,
+            uploader_id,
+            &Metadata {
+                title: "Mandelbrot Set".to_string(),
+                description: String::new(),
+                category_id,
+                tags: vec![],
+            },
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn it_should_restore_an_exported_index_including_the_torrents_listed_from_a_magnet_link() {
+    let dir = tempfile::tempdir().unwrap();
+    let backup_dir = dir.path().join("backup");
+    fs::create_dir_all(backup_dir.join("torrents")).unwrap();
+
+    let source = new_index_database(&dir, "source.db").await;
+    populate(source.as_ref()).await;
+
+    let manifest = export::export(source.as_ref(), &backup_dir).await.unwrap();
+
+    assert_eq!(manifest.torrents, 2);
+
+    let target = new_index_database(&dir, "target.db").await;
+
+    let report = import::import(target.as_ref(), &backup_dir, &Url::parse("udp://localhost:6969").unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(report.users, 1);
+    assert_eq!(report.torrents, 2);
+    assert_eq!(report.skipped_torrents, 0);
+
+    let torrent = target
+        .get_torrent_listing_from_info_hash(&InfoHash::from_str(TORRENT_INFO_HASH).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(torrent.title, "MC Grid");
+    assert!(!torrent.magnet_only);
+    assert!(target.get_torrent_from_id(torrent.torrent_id).await.is_ok());
+
+    let magnet_only = target
+        .get_torrent_listing_from_info_hash(&InfoHash::from_str(MAGNET_INFO_HASH).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(magnet_only.title, "Mandelbrot Set");
+    assert_eq!(magnet_only.name, "Mandelbrot Set");
+    assert!(magnet_only.magnet_only);
+    assert_eq!(
+        target
+            .get_torrent_announce_urls_from_id(magnet_only.torrent_id)
+            .await
+            .unwrap(),
+        vec![vec!["udp://tracker.example.com:6969".to_string()]]
+    );
+    assert_eq!(
+        target
+            .get_torrent_web_seed_urls_from_id(magnet_only.torrent_id)
+            .await
+            .unwrap(),
+        vec!["https://example.com/seed".to_string()]
+    );
+}
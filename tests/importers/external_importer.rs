@@ -0,0 +1,216 @@
+//! Tests for the `import` command, importing the data of other torrent index
+//! applications into a new Index database.
+//!
+//! You can run them with:
+//!
+//! ```text
+//! cargo test importers::external_importer -- --nocapture
+//! ```
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use sqlx::sqlite::SqlitePoolOptions;
+use tempfile::TempDir;
+use torrust_index::config::Configuration;
+use torrust_index::console::commands::external_importer::adapters::{interchange, unit3d};
+use torrust_index::console::commands::external_importer::app::import;
+use torrust_index::console::commands::external_importer::transferrers::Report;
+use torrust_index::databases::database::{self, Database};
+use torrust_index::models::info_hash::InfoHash;
+use torrust_index::services::authentication::{self, DbUserAuthenticationRepository, JsonWebToken};
+use torrust_index::services::user::{DbUserProfileRepository, DbUserRepository, Repository};
+
+const TORRENT_FIXTURE: &str = "./tests/fixtures/torrents/MC_GRID.zip-3cd18ff2d3eec881207dcc5ca5a2c3a2a3afe462.torrent";
+/// The canonical info-hash of the fixture, the one stored by the Index.
+const TORRENT_INFO_HASH: &str = "eec5a52e7661cdb55a91a8335cbff1b9dd2bc0c3";
+const BCRYPT_PASSWORD_HASH: &str = "$2y$04$1U4CN9stPtUhunkoEulh9ugA3va6Jrs9KBFDvdPlBNNjnU8fXcn62";
+const PASSWORD_HASH: &str = "$argon2id$v=19$m=4096,t=3,p=1$c2FsdHNhbHQ$hqrnM4pZ2THLWUbTVu3nuzCNQ9E6eqbtq2jvp+aFqw0";
+
+async fn new_index_database(dir: &TempDir) -> Box<dyn Database> {
+    let url = format!("sqlite://{}?mode=rwc", dir.path().join("index.db").display());
+
+    database::connect(&url).await.expect("Can't create the Index database.")
+}
+
+fn write_interchange_dir(dir: &Path) {
+    fs::create_dir(dir.join("torrents")).unwrap();
+    fs::copy(TORRENT_FIXTURE, dir.join("torrents/mc_grid.torrent")).unwrap();
+
+    fs::write(
+        dir.join("users.json"),
+        format!(
+            r#"[
+                {{"username": "alice", "email": "alice@example.com", "email_verified": true, "administrator": true, "password_hash": "{PASSWORD_HASH}"}},
+                {{"username": "bob", "email": "bob@example.com", "password_hash": "5f4dcc3b5aa765d61d8327deb882cf99"}}
+            ]"#
+        ),
+    )
+    .unwrap();
+
+    // The `software` category is created by the Index migrations.
+    fs::write(dir.join("categories.csv"), "name\nsoftware\nminecraft\n").unwrap();
+
+    fs::write(dir.join("tags.csv"), "name\nmaps\n").unwrap();
+
+    fs::write(
+        dir.join("torrents.csv"),
+        "file,uploader,title,description,category,tags,date_uploaded\n\
+         torrents/mc_grid.torrent,alice,MC Grid,A Minecraft map,software,maps,2023-04-01T10:35:15Z\n\
+         torrents/missing.torrent,alice,Missing,,software,,\n",
+    )
+    .unwrap();
+}
+
+async fn write_unit3d_database(dir: &Path) -> String {
+    let torrents_dir = dir.join("torrents");
+
+    fs::create_dir(&torrents_dir).unwrap();
+    fs::copy(TORRENT_FIXTURE, torrents_dir.join("mc_grid.torrent")).unwrap();
+
+    let url = format!("sqlite://{}?mode=rwc", dir.join("unit3d.db").display());
+
+    let pool = SqlitePoolOptions::new().connect(&url).await.unwrap();
+
+    for statement in [
+        "CREATE TABLE `groups` (id INTEGER PRIMARY KEY, is_admin INTEGER, is_owner INTEGER)",
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, username TEXT, email TEXT, password TEXT, group_id INTEGER, active INTEGER, created_at TEXT)",
+        "CREATE TABLE categories (id INTEGER PRIMARY KEY, name TEXT)",
+        "CREATE TABLE torrents (id INTEGER PRIMARY KEY, name TEXT, description TEXT, file_name TEXT, user_id INTEGER, category_id INTEGER, created_at TEXT)",
+        "CREATE TABLE keywords (id INTEGER PRIMARY KEY, torrent_id INTEGER, name TEXT)",
+        "INSERT INTO `groups` VALUES (1, 0, 0), (2, 1, 0)",
+        "INSERT INTO categories VALUES (1, 'Games')",
+        "INSERT INTO torrents VALUES (1, 'MC Grid', '[b]A Minecraft map[/b]', 'mc_grid.torrent', 1, 1, '2023-04-02 08:00:00')",
+        "INSERT INTO keywords VALUES (1, 1, 'minecraft'), (2, 1, 'maps')",
+    ] {
+        sqlx::query(statement).execute(&pool).await.unwrap();
+    }
+
+    sqlx::query("INSERT INTO users VALUES (1, 'alice', 'alice@example.com', ?, 2, 1, '2023-04-01 10:35:15')")
+        .bind(BCRYPT_PASSWORD_HASH)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    url
+}
+
+#[tokio::test]
+async fn it_should_import_the_data_in_the_interchange_format() {
+    let dir = tempfile::tempdir().unwrap();
+    write_interchange_dir(dir.path());
+    let database = new_index_database(&dir).await;
+
+    let report = import(&interchange::Adapter::new(dir.path()), database.as_ref())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report,
+        Report {
+            users: 1,
+            skipped_users: 0,
+            users_with_unsupported_password_hash: 1,
+            categories: 1,
+            tags: 1,
+            torrents: 1,
+            skipped_torrents: 1,
+        }
+    );
+
+    let alice = database.get_user_profile_from_username("alice").await.unwrap();
+    assert!(alice.email_verified);
+
+    // Bob's password hash can't be verified, so he would not be able to log in.
+    assert!(database.get_user_profile_from_username("bob").await.is_err());
+
+    let torrent = database
+        .get_torrent_listing_from_info_hash(&InfoHash::from_str(TORRENT_INFO_HASH).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(torrent.title, "MC Grid");
+    assert_eq!(torrent.uploader, "alice");
+    assert_eq!(torrent.date_uploaded, "2023-04-01 10:35:15");
+
+    let tags = database.get_tags_for_torrent_id(torrent.torrent_id).await.unwrap();
+    assert_eq!(tags.len(), 1);
+}
+
+#[tokio::test]
+async fn it_should_skip_the_records_that_already_exist_when_importing_again() {
+    let dir = tempfile::tempdir().unwrap();
+    write_interchange_dir(dir.path());
+    let database = new_index_database(&dir).await;
+    let adapter = interchange::Adapter::new(dir.path());
+
+    import(&adapter, database.as_ref()).await.unwrap();
+    let report = import(&adapter, database.as_ref()).await.unwrap();
+
+    assert_eq!(report.users, 0);
+    assert_eq!(report.skipped_users, 1);
+    assert_eq!(report.categories, 0);
+    assert_eq!(report.torrents, 0);
+}
+
+#[tokio::test]
+async fn it_should_import_a_unit3d_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let source_database_url = write_unit3d_database(dir.path()).await;
+    let database = new_index_database(&dir).await;
+
+    let adapter = unit3d::Adapter::new(&source_database_url, &dir.path().join("torrents"))
+        .await
+        .unwrap();
+
+    let report = import(&adapter, database.as_ref()).await.unwrap();
+
+    assert_eq!(report.users, 1);
+    assert_eq!(report.users_with_unsupported_password_hash, 0);
+    assert_eq!(report.categories, 1);
+    assert_eq!(report.tags, 2);
+    assert_eq!(report.torrents, 1);
+
+    let torrent = database
+        .get_torrent_listing_from_info_hash(&InfoHash::from_str(TORRENT_INFO_HASH).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(torrent.description, Some("[b]A Minecraft map[/b]".to_string()));
+    assert_eq!(torrent.date_uploaded, "2023-04-02 08:00:00");
+
+    let administrator = database.get_user_profile_from_username("alice").await.unwrap();
+    let authentication = database.get_user_authentication_from_id(administrator.user_id).await.unwrap();
+    assert_eq!(authentication.password_hash, BCRYPT_PASSWORD_HASH);
+}
+
+#[tokio::test]
+async fn imported_bcrypt_passwords_should_be_rehashed_when_the_users_log_in() {
+    let dir = tempfile::tempdir().unwrap();
+    let source_database_url = write_unit3d_database(dir.path()).await;
+    let database = Arc::new(new_index_database(&dir).await);
+
+    let adapter = unit3d::Adapter::new(&source_database_url, &dir.path().join("torrents"))
+        .await
+        .unwrap();
+
+    import(&adapter, database.as_ref().as_ref()).await.unwrap();
+
+    let configuration = Arc::new(Configuration::default());
+    let user_repository: Arc<Box<dyn Repository>> = Arc::new(Box::new(DbUserRepository::new(database.clone())));
+    let authentication_service = authentication::Service::new(
+        configuration.clone(),
+        Arc::new(JsonWebToken::new(configuration)),
+        user_repository,
+        Arc::new(DbUserProfileRepository::new(database.clone())),
+        Arc::new(DbUserAuthenticationRepository::new(database.clone())),
+    );
+
+    authentication_service.login("alice", "87654321").await.unwrap();
+
+    let alice = database.get_user_profile_from_username("alice").await.unwrap();
+    let authentication = database.get_user_authentication_from_id(alice.user_id).await.unwrap();
+    assert!(authentication.password_hash.starts_with("$argon2id$"));
+
+    // The new hash is verified in the next login
+    authentication_service.login("alice", "87654321").await.unwrap();
+}
@@ -1,4 +1,5 @@
 mod common;
 mod e2e;
 pub mod environments;
+mod importers;
 mod upgrades;
@@ -1 +1,2 @@
 pub mod from_v1_0_0_to_v2_0_0;
+pub mod upgrader;
@@ -0,0 +1,181 @@
+//! Tests for the `upgrade` command, chaining all the upgrade steps.
+//!
+//! You can run them with:
+//!
+//! ```text
+//! cargo test upgrades::upgrader -- --nocapture
+//! ```
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use torrust_index::upgrades::database::Connection;
+use torrust_index::upgrades::from_v1_0_0_to_v2_0_0::upgrader::datetime_iso_8601;
+use torrust_index::upgrades::report::VerificationReport;
+use torrust_index::upgrades::upgrader::{upgrade, Options};
+use torrust_index::upgrades::version::Version;
+
+use crate::upgrades::from_v1_0_0_to_v2_0_0::sqlite_v1_0_0::SqliteDatabaseV1_0_0;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::sqlite_v2_0_0::SqliteDatabaseV2_0_0;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrer_testers::category_transferrer_tester::CategoryTester;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrer_testers::torrent_transferrer_tester::TorrentTester;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrer_testers::tracker_key_transferrer_tester::TrackerKeyTester;
+use crate::upgrades::from_v1_0_0_to_v2_0_0::transferrer_testers::user_transferrer_tester::UserTester;
+
+const FIXTURES_DIR: &str = "./tests/upgrades/from_v1_0_0_to_v2_0_0/fixtures/";
+const OUTPUT_DIR: &str = "./tests/upgrades/from_v1_0_0_to_v2_0_0/output/";
+
+struct TestDatabases {
+    source_database_file: String,
+    target_database_file: String,
+    upload_path: String,
+}
+
+impl TestDatabases {
+    /// It creates a `v1.0.0` source database with test data. Each test uses
+    /// its own files so they can run in parallel.
+    async fn new(name: &str) -> Self {
+        let databases = Self {
+            source_database_file: format!("{OUTPUT_DIR}{name}_source.db"),
+            target_database_file: format!("{OUTPUT_DIR}{name}_target.db"),
+            upload_path: format!("{FIXTURES_DIR}uploads/"),
+        };
+
+        for file in [&databases.source_database_file, &databases.target_database_file] {
+            if Path::new(file).exists() {
+                fs::remove_file(file).expect("Can't remove the DB file.");
+            }
+        }
+
+        let source_db = Arc::new(SqliteDatabaseV1_0_0::db_connection(&databases.source_database_file).await);
+        source_db.migrate(FIXTURES_DIR).await;
+
+        // The testers only use the target database to check the upgraded data.
+        let target_db = Arc::new(SqliteDatabaseV2_0_0::db_connection(&databases.target_database_file).await);
+
+        let category_tester = CategoryTester::new(source_db.clone(), target_db.clone());
+        let user_tester = UserTester::new(source_db.clone(), target_db.clone(), &datetime_iso_8601());
+        let tracker_key_tester = TrackerKeyTester::new(source_db.clone(), target_db.clone(), user_tester.test_data.user.user_id);
+        let torrent_tester = TorrentTester::new(
+            source_db.clone(),
+            target_db.clone(),
+            &user_tester.test_data.user,
+            category_tester.get_valid_category_id(),
+        );
+
+        category_tester.load_data_into_source_db().await;
+        user_tester.load_data_into_source_db().await;
+        tracker_key_tester.load_data_into_source_db().await;
+        torrent_tester.load_data_into_source_db().await;
+
+        databases
+    }
+
+    fn options(&self) -> Options {
+        Options {
+            source: self.source_database_file.clone(),
+            target: Some(self.target_database_file.clone()),
+            upload_dir: Some(self.upload_path.clone()),
+            to: None,
+            dry_run: false,
+            resume: false,
+        }
+    }
+
+    async fn target(&self) -> Connection {
+        Connection::connect(&format!("sqlite://{}?mode=ro", self.target_database_file))
+            .await
+            .unwrap()
+    }
+}
+
+#[tokio::test]
+async fn it_should_upgrade_a_v1_0_0_database_to_the_latest_version_and_verify_the_data() {
+    let databases = TestDatabases::new("latest").await;
+
+    let reports = upgrade(&databases.options(), &datetime_iso_8601()).await.unwrap();
+
+    assert_eq!(
+        reports.iter().map(|report| report.step.clone()).collect::<Vec<_>>(),
+        vec!["v1.0.0-to-v2.0.0", "v2.0.0-to-latest"]
+    );
+    assert!(reports.iter().all(VerificationReport::is_ok));
+
+    let target = databases.target().await;
+
+    assert_eq!(Version::detect(&target).await.unwrap(), Version::Latest);
+    assert!(!target
+        .tables()
+        .await
+        .unwrap()
+        .contains(&"torrust_upgrade_checkpoints".to_string()));
+}
+
+#[tokio::test]
+async fn it_should_not_change_the_target_database_in_a_dry_run() {
+    let databases = TestDatabases::new("dry_run").await;
+
+    let reports = upgrade(
+        &Options {
+            dry_run: true,
+            ..databases.options()
+        },
+        &datetime_iso_8601(),
+    )
+    .await
+    .unwrap();
+
+    assert!(reports.is_empty());
+    assert!(databases.target().await.tables().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn it_should_resume_a_failed_upgrade() {
+    let databases = TestDatabases::new("resume").await;
+
+    // The torrent files are missing, so the upgrade fails when it transfers
+    // the torrents, after transferring the users.
+    let options = Options {
+        upload_dir: Some(OUTPUT_DIR.to_string()),
+        ..databases.options()
+    };
+    let date_imported = datetime_iso_8601();
+    let failed_upgrade = tokio::spawn(async move { upgrade(&options, &date_imported).await.map(|_| ()) }).await;
+
+    assert!(failed_upgrade.is_err());
+    assert!(databases
+        .target()
+        .await
+        .completed_phases("v1.0.0-to-v2.0.0")
+        .await
+        .unwrap()
+        .contains(&"users".to_string()));
+
+    let reports = upgrade(
+        &Options {
+            resume: true,
+            ..databases.options()
+        },
+        &datetime_iso_8601(),
+    )
+    .await
+    .unwrap();
+
+    assert!(reports.iter().all(VerificationReport::is_ok));
+}
+
+#[tokio::test]
+async fn it_should_require_a_target_database_to_upgrade_from_v1_0_0() {
+    let databases = TestDatabases::new("no_target").await;
+
+    let result = upgrade(
+        &Options {
+            target: None,
+            ..databases.options()
+        },
+        &datetime_iso_8601(),
+    )
+    .await;
+
+    assert!(result.is_err());
+}